@@ -0,0 +1,140 @@
+//! Benchmarks of building, reading and deserializing configurations
+//!
+//! Run with `cargo bench`, or `cargo bench -- <filter>` to only run some of them. Criterion
+//! compares each run with the previous one, so run the benchmarks on the base branch first to
+//! catch a regression.
+
+use std::collections::BTreeMap;
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use serde::Deserialize;
+
+use config::{Config, Environment, File, FileFormat, Map};
+
+/// A TOML file setting `keys` keys, of which every fourth is shared with the other files
+fn toml_source(index: usize, keys: usize) -> String {
+    let mut text = format!("[source_{index}]\n");
+    for key in 0..keys {
+        text.push_str(&format!("key_{key} = \"value {index} {key}\"\n"));
+    }
+    text.push_str("[shared]\n");
+    for key in (0..keys).step_by(4) {
+        text.push_str(&format!("key_{key} = {index}\n"));
+    }
+    text
+}
+
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    for sources in [1, 10, 100] {
+        let texts: Vec<String> = (0..sources).map(|index| toml_source(index, 50)).collect();
+        let builder = texts.iter().fold(Config::builder(), |builder, text| {
+            builder.add_source(File::from_str(text, FileFormat::Toml))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("sources", sources),
+            &builder,
+            |b, builder| {
+                b.iter(|| builder.build_cloned().unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+fn get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get");
+    let mut key = String::from("root");
+    let mut builder = Config::builder();
+    for depth in 1..=16 {
+        key.push_str(&format!(".level_{depth}"));
+        builder = builder
+            .set_default(format!("{key}.items"), vec![1, 2, 3, 4])
+            .unwrap();
+    }
+    let config = builder.build().unwrap();
+
+    for depth in [1, 4, 16] {
+        let key: Vec<String> = (1..=depth).map(|depth| format!("level_{depth}")).collect();
+        let key = format!("root.{}.items[3]", key.join("."));
+        group.bench_with_input(BenchmarkId::new("depth", depth), &key, |b, key| {
+            b.iter(|| config.get_int(black_box(key)).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn env(c: &mut Criterion) {
+    let mut group = c.benchmark_group("env");
+    for vars in [100, 10_000] {
+        let mut source = Map::new();
+        for var in 0..vars {
+            // One variable in ten has the prefix, like in a busy environment
+            let name = match var % 10 {
+                0 => format!("APP__SECTION_{}__KEY_{var}", var % 7),
+                _ => format!("OTHER_VARIABLE_{var}"),
+            };
+            source.insert(name, format!("value {var}"));
+        }
+        let environment = Environment::with_prefix("APP")
+            .prefix_separator("__")
+            .separator("__")
+            .source(Some(source));
+        group.bench_with_input(BenchmarkId::new("vars", vars), &environment, |b, env| {
+            b.iter(|| Config::builder().add_source(env.clone()).build().unwrap());
+        });
+    }
+    group.finish();
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Settings {
+    name: String,
+    debug: bool,
+    services: Vec<Service>,
+    limits: BTreeMap<String, u64>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Service {
+    host: String,
+    port: u16,
+    timeout: f64,
+    tags: Vec<String>,
+    proxy: Option<String>,
+}
+
+fn deserialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("try_deserialize");
+    for services in [10, 1000] {
+        let mut text = String::from("name = \"bench\"\ndebug = true\n[limits]\n");
+        for limit in 0..100 {
+            text.push_str(&format!("limit_{limit} = {limit}\n"));
+        }
+        for service in 0..services {
+            text.push_str(&format!(
+                "[[services]]\nhost = \"host-{service}\"\nport = {}\ntimeout = 2.5\n\
+                 tags = [\"a\", \"b\", \"c\"]\n",
+                service % 65_536
+            ));
+        }
+        let config = Config::builder()
+            .add_source(File::from_str(&text, FileFormat::Toml))
+            .build()
+            .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("services", services),
+            &config,
+            |b, config| {
+                b.iter(|| config.clone().try_deserialize::<Settings>().unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, build, get, env, deserialize);
+criterion_main!(benches);
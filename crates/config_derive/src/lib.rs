@@ -0,0 +1,240 @@
+//! Derive macro for [`config::ConfigStruct`](https://docs.rs/config/latest/config/trait.ConfigStruct.html)
+//!
+//! Use it through the `derive` feature of `config`, which re-exports it.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Expr, Fields, Lit, LitStr, Meta, Token, parse_macro_input};
+
+/// Implement `ConfigStruct` from `#[config(...)]` attributes on the fields of a struct
+///
+/// Each field is read at the key named after it, and accepts:
+///
+/// - `key = "name"`: read the field at another key of the table, moved to the name the field is
+///   deserialized from, its own or that of `#[serde(rename = "...")]`, before deserializing
+/// - `default = expr`: a default value, given to `ConfigBuilder::set_default`
+/// - `env = "VAR"`: override the value with the environment variable `VAR` when it is set,
+///   which requires the `env` feature of `config`
+/// - `validate = range(expr)`: check that the value is within a range, like `1..65535`
+/// - `validate = path`: check the value with a function taking a reference to it and returning
+///   a `Result<(), E>` where `E` implements `Display`
+/// - `nested`: the field is a struct implementing `ConfigStruct` itself, configured and
+///   validated under the key of the field
+///
+/// `validate` can be repeated.
+///
+/// This also implements `TryFrom<Config>`, deserializing and validating an already built `Config`.
+#[proc_macro_derive(ConfigStruct, attributes(config))]
+pub fn derive_config_struct(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    key: Option<LitStr>,
+    default: Option<Expr>,
+    env: Option<LitStr>,
+    validators: Vec<Expr>,
+    nested: bool,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "ConfigStruct requires a struct with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "ConfigStruct can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut configure = Vec::new();
+    let mut validate = Vec::new();
+    let mut rename = Vec::new();
+    for field in fields {
+        let attrs = field_attrs(field)?;
+        let ident = field.ident.as_ref().expect("named fields have identifiers");
+        let name = serde_rename(field)
+            .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_owned());
+        let key = match attrs.key {
+            Some(key) => key.value(),
+            None => name.clone(),
+        };
+        if key != name {
+            rename.push(quote! {
+                ::config::__private::rename(table, #key, #name);
+            });
+        }
+
+        if let Some(default) = attrs.default {
+            configure.push(quote! {
+                let builder = builder.set_default(
+                    ::config::__private::key(prefix, #key),
+                    #default,
+                )?;
+            });
+        }
+        if let Some(env) = attrs.env {
+            configure.push(quote! {
+                let builder = ::config::__private::env_override(
+                    builder,
+                    ::config::__private::key(prefix, #key),
+                    #env,
+                )?;
+            });
+        }
+        if attrs.nested {
+            let ty = &field.ty;
+            configure.push(quote! {
+                let builder = <#ty as ::config::ConfigStruct>::configure(
+                    builder,
+                    &::config::__private::key(prefix, #key),
+                )?;
+            });
+            validate.push(quote! {
+                ::config::ConfigStruct::validate(&self.#ident)
+                    .map_err(|error| ::config::__private::nested(error, #key))?;
+            });
+            rename.push(quote! {
+                ::config::__private::rename_nested::<#ty>(table, #name);
+            });
+        }
+
+        for validator in attrs.validators {
+            validate.push(validator_check(ident, &key, validator));
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::config::ConfigStruct for #name #ty_generics #where_clause {
+            fn configure(
+                builder: ::config::builder::ConfigBuilder<::config::builder::DefaultState>,
+                prefix: &str,
+            ) -> ::std::result::Result<
+                ::config::builder::ConfigBuilder<::config::builder::DefaultState>,
+                ::config::ConfigError,
+            > {
+                #(#configure)*
+                ::std::result::Result::Ok(builder)
+            }
+
+            fn validate(&self) -> ::std::result::Result<(), ::config::ConfigError> {
+                #(#validate)*
+                ::std::result::Result::Ok(())
+            }
+
+            #[allow(unused_variables)]
+            fn rename_keys(table: &mut ::config::Map<::std::string::String, ::config::Value>) {
+                #(#rename)*
+            }
+        }
+
+        impl #impl_generics ::std::convert::TryFrom<::config::Config> for #name #ty_generics #where_clause {
+            type Error = ::config::ConfigError;
+
+            fn try_from(config: ::config::Config) -> ::std::result::Result<Self, Self::Error> {
+                let value: Self = ::config::__private::deserialize(config)?;
+                ::config::ConfigStruct::validate(&value)?;
+                ::std::result::Result::Ok(value)
+            }
+        }
+    })
+}
+
+fn field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("config"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                attrs.key = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("env") {
+                attrs.env = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("validate") {
+                attrs.validators.push(meta.value()?.parse()?);
+            } else if meta.path.is_ident("nested") {
+                attrs.nested = true;
+            } else {
+                return Err(meta.error("unsupported config attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// The name given by `#[serde(rename = "...")]` on `field`, if any
+fn serde_rename(field: &syn::Field) -> Option<String> {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .find_map(|meta| match meta {
+            Meta::NameValue(meta) if meta.path.is_ident("rename") => match meta.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(name),
+                    ..
+                }) => Some(name.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+fn validator_check(ident: &syn::Ident, key: &str, validator: Expr) -> TokenStream2 {
+    match validator {
+        Expr::Call(call)
+            if matches!(*call.func, Expr::Path(ref path) if path.path.is_ident("range"))
+                && call.args.len() == 1 =>
+        {
+            let range = &call.args[0];
+            quote! {
+                if !(#range).contains(&self.#ident) {
+                    return ::std::result::Result::Err(::config::__private::invalid(
+                        #key,
+                        ::std::format_args!(
+                            "{:?} is out of range {:?}",
+                            self.#ident,
+                            #range,
+                        ),
+                    ));
+                }
+            }
+        }
+        check => {
+            let span = check.span();
+            quote::quote_spanned! {span=>
+                if let ::std::result::Result::Err(error) = (#check)(&self.#ident) {
+                    return ::std::result::Result::Err(::config::__private::invalid(#key, error));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Print the configuration merged from files and environment variables, with where each value
+//! was set.
+//!
+//! ```text
+//! cargo run --example config-inspect -- [--env PREFIX] [--separator SEP] FILE...
+//! ```
+//!
+//! Files are merged in order, followed by the variables starting with `PREFIX`, if given.
+
+use std::process::ExitCode;
+
+use config::{Config, Environment, File, Map, ParseOptions, Source, Value, ValueKind};
+
+#[derive(Default)]
+struct Args {
+    files: Vec<String>,
+    env_prefix: Option<String>,
+    separator: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            eprintln!("usage: config-inspect [--env PREFIX] [--separator SEP] FILE...");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match inspect(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--env" => parsed.env_prefix = Some(args.next().ok_or("--env requires a prefix")?),
+            "--separator" => {
+                parsed.separator = Some(args.next().ok_or("--separator requires a value")?);
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+            _ => parsed.files.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn environment(args: &Args) -> Option<Environment> {
+    let prefix = args.env_prefix.as_deref()?;
+    let env = Environment::with_prefix(prefix);
+    Some(match args.separator {
+        Some(ref separator) => env.separator(separator),
+        None => env,
+    })
+}
+
+fn inspect(args: &Args) -> Result<(), config::ConfigError> {
+    let mut builder = Config::builder();
+    for path in &args.files {
+        builder = builder.add_source(
+            File::with_name(path).parse_options(ParseOptions::default().record_spans(true)),
+        );
+    }
+    if let Some(env) = environment(args) {
+        builder = builder.add_source(env);
+    }
+    let config = builder.build()?;
+
+    println!("# Sources");
+    for source in config.sources() {
+        println!("{}", source.uri().unwrap_or("<unnamed>"));
+    }
+
+    println!();
+    println!("# Values");
+    print_leaves(&config.cache, &mut String::new());
+
+    if let Some(prefix) = args.env_prefix.as_deref() {
+        println!();
+        println!("# Environment variables");
+        for (name, value) in std::env::vars() {
+            if !name.to_lowercase().starts_with(&prefix.to_lowercase()) {
+                continue;
+            }
+            // Collect each variable on its own to find the key it sets
+            let single = Map::from_iter([(name.clone(), value)]);
+            let env = environment(args)
+                .expect("a prefix is given")
+                .source(Some(single));
+            for key in env.collect()?.keys() {
+                println!("{name} -> {key}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the scalars, empty tables and empty arrays under `value`, with their origin
+fn print_leaves(value: &Value, key: &mut String) {
+    let len = key.len();
+    match value.kind {
+        ValueKind::Table(ref table) if !table.is_empty() => {
+            let mut entries: Vec<_> = table.iter().collect();
+            entries.sort_by_key(|(name, _)| *name);
+            for (name, child) in entries {
+                if !key.is_empty() {
+                    key.push('.');
+                }
+                key.push_str(name);
+                print_leaves(child, key);
+                key.truncate(len);
+            }
+        }
+        ValueKind::Array(ref array) if !array.is_empty() => {
+            for (index, child) in array.iter().enumerate() {
+                key.push_str(&format!("[{index}]"));
+                print_leaves(child, key);
+                key.truncate(len);
+            }
+        }
+        _ => {
+            let origin = match (value.origin(), value.span()) {
+                (Some(origin), Some(span)) => format!("{origin}:{}", span.start()),
+                (Some(origin), None) => origin.to_owned(),
+                (None, _) => "default".to_owned(),
+            };
+            println!("{key} = {value}  # {origin}");
+        }
+    }
+}
@@ -0,0 +1,219 @@
+#[cfg(feature = "env")]
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Result;
+use crate::map::Map;
+use crate::path::{self, Expression};
+use crate::source::{Source, set_value};
+use crate::value::{Value, ValueKind};
+
+/// A [`Source`] mounted under a table, created by [`Source::with_prefix`]
+#[derive(Debug, Clone)]
+pub struct Prefixed<S> {
+    source: S,
+    prefix: String,
+}
+
+/// A [`Source`] keeping some of its keys, created by [`Source::filter_keys`]
+#[derive(Clone)]
+pub struct FilterKeys<S, F> {
+    source: S,
+    filter: F,
+}
+
+/// A [`Source`] with renamed keys, created by [`Source::map_keys`]
+#[derive(Clone)]
+pub struct MapKeys<S, F> {
+    source: S,
+    map: F,
+}
+
+/// A [`Source`] expanding references to environment variables in its strings, created by
+/// [`Source::expand_env_vars`]
+#[cfg(feature = "env")]
+#[derive(Debug, Clone)]
+pub struct ExpandEnvVars<S> {
+    source: S,
+}
+
+impl<S> Prefixed<S> {
+    pub(crate) fn new(source: S, prefix: &str) -> Self {
+        Self {
+            source,
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl<S, F> FilterKeys<S, F> {
+    pub(crate) fn new(source: S, filter: F) -> Self {
+        Self { source, filter }
+    }
+}
+
+impl<S, F> MapKeys<S, F> {
+    pub(crate) fn new(source: S, map: F) -> Self {
+        Self { source, map }
+    }
+}
+
+#[cfg(feature = "env")]
+impl<S> ExpandEnvVars<S> {
+    pub(crate) fn new(source: S) -> Self {
+        Self { source }
+    }
+}
+
+impl<S> Source for Prefixed<S>
+where
+    S: Source + Clone + Send + Sync + 'static,
+{
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn keeps_existing_types(&self) -> bool {
+        self.source.keeps_existing_types()
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        let mut cache = Value::from(Map::<String, Value>::new());
+        Expression::from_str(&self.prefix)?.set(&mut cache, collect_tree(&self.source)?);
+        Ok(into_table(cache))
+    }
+}
+
+impl<S, F> Source for FilterKeys<S, F>
+where
+    S: Source + Clone + Send + Sync + 'static,
+    F: Fn(&str) -> bool + Clone + Send + Sync + 'static,
+{
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn keeps_existing_types(&self) -> bool {
+        self.source.keeps_existing_types()
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        let tree = collect_tree(&self.source)?;
+        let mut cache = Value::from(Map::<String, Value>::new());
+        path::visit_leaves(&tree, &mut |key, value| {
+            if (self.filter)(key) {
+                set_value(&mut cache, key.to_owned(), value.clone());
+            }
+        });
+        Ok(into_table(cache))
+    }
+}
+
+impl<S, F> Source for MapKeys<S, F>
+where
+    S: Source + Clone + Send + Sync + 'static,
+    F: Fn(&str) -> String + Clone + Send + Sync + 'static,
+{
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn keeps_existing_types(&self) -> bool {
+        self.source.keeps_existing_types()
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        let tree = collect_tree(&self.source)?;
+        let mut cache = Value::from(Map::<String, Value>::new());
+        path::visit_leaves(&tree, &mut |key, value| {
+            set_value(&mut cache, (self.map)(key), value.clone());
+        });
+        Ok(into_table(cache))
+    }
+}
+
+#[cfg(feature = "env")]
+impl<S> Source for ExpandEnvVars<S>
+where
+    S: Source + Clone + Send + Sync + 'static,
+{
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn keeps_existing_types(&self) -> bool {
+        self.source.keeps_existing_types()
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        let mut tree = collect_tree(&self.source)?;
+        let vars = crate::env::vars();
+        let vars: HashMap<&str, &str> = vars
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_str()?, value.to_str()?)))
+            .collect();
+        expand_strings(&mut tree, &vars, &mut String::new())?;
+        Ok(into_table(tree))
+    }
+}
+
+impl<S: fmt::Debug, F> fmt::Debug for FilterKeys<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterKeys")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: fmt::Debug, F> fmt::Debug for MapKeys<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapKeys")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Collect `source` into a tree, so that keys are paths whether the source nests tables or not
+fn collect_tree(source: &dyn Source) -> Result<Value> {
+    let mut cache = Value::from(Map::<String, Value>::new());
+    source.collect_to(&mut cache)?;
+    Ok(cache)
+}
+
+/// Expand the references to environment variables in the strings of `value`, at the path `key`
+#[cfg(feature = "env")]
+fn expand_strings(value: &mut Value, vars: &HashMap<&str, &str>, key: &mut String) -> Result<()> {
+    let len = key.len();
+    match value.kind {
+        ValueKind::String(ref mut text) if text.contains('$') => {
+            *text = crate::expand::expand(text, vars).map_err(|e| e.extend_with_key(key))?;
+        }
+        ValueKind::Table(ref mut table) => {
+            for (name, child) in table.iter_mut() {
+                if !key.is_empty() {
+                    key.push('.');
+                }
+                key.push_str(name);
+                expand_strings(child, vars, key)?;
+                key.truncate(len);
+            }
+        }
+        ValueKind::Array(ref mut array) => {
+            for (index, child) in array.iter_mut().enumerate() {
+                key.push_str(&format!("[{index}]"));
+                expand_strings(child, vars, key)?;
+                key.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn into_table(cache: Value) -> Map<String, Value> {
+    match cache.kind {
+        ValueKind::Table(table) => table,
+        _ => unreachable!("the cache is a table"),
+    }
+}
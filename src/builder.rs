@@ -1,10 +1,31 @@
+use std::any::TypeId;
+use std::panic::resume_unwind;
+#[cfg(feature = "async")]
+use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::Poll;
+#[cfg(feature = "tokio")]
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use futures_util::Stream;
 
-use crate::error::Result;
+use serde_core::ser::Serialize;
+
+use crate::error::{ConfigError, Result};
 use crate::map::Map;
+use crate::normalize::KeyNormalizer;
+use crate::path::{self, Expression};
+use crate::perf::{self, Counter};
 #[cfg(feature = "async")]
 use crate::source::AsyncSource;
-use crate::{config::Config, path::Expression, source::Source, value::Value};
+#[cfg(feature = "tokio")]
+use crate::source::BlockingSource;
+use crate::source::{SourceReport, track_loaded};
+use crate::value::ValueKind;
+use crate::{config::Config, source::Source, value::Value};
 
 /// A configuration builder
 ///
@@ -96,7 +117,9 @@ use crate::{config::Config, path::Expression, source::Source, value::Value};
 #[must_use]
 pub struct ConfigBuilder<St: BuilderState> {
     defaults: Map<Expression, Value>,
+    lazy_defaults: Vec<(Expression, LazyValue)>,
     overrides: Map<Expression, Value>,
+    merge: MergeOptions,
     state: St,
 }
 
@@ -106,25 +129,98 @@ pub trait BuilderState {}
 /// Represents data specific to builder in default, synchronous state, without support for async.
 #[derive(Debug, Default, Clone)]
 pub struct DefaultState {
-    sources: Vec<Box<dyn Source + Send + Sync>>,
+    sources: Vec<SyncSource>,
 }
 
 /// Represents data specific to builder in asynchronous state, with support for async.
 #[derive(Debug, Default, Clone)]
 pub struct AsyncState {
-    sources: Vec<SourceType>,
+    /// Sources sorted by priority, along with it
+    sources: Vec<(i32, SourceType)>,
+}
+
+/// A [`Source`] along with the type and the priority it was registered with
+#[derive(Debug, Clone)]
+struct SyncSource {
+    type_id: TypeId,
+    priority: i32,
+    source: Box<dyn Source + Send + Sync>,
 }
 
 #[derive(Debug, Clone)]
 enum SourceType {
-    Sync(Box<dyn Source + Send + Sync>),
+    Sync(TypeId, Box<dyn Source + Send + Sync>),
     #[cfg(feature = "async")]
-    Async(Box<dyn AsyncSource + Send + Sync>),
+    Async(Arc<dyn AsyncSource + Send + Sync>),
 }
 
 impl BuilderState for DefaultState {}
 impl BuilderState for AsyncState {}
 
+/// A reusable set of defaults, overrides or sources, applied with [`ConfigBuilder::with_layer`]
+///
+/// This lets a library publish the configuration it expects, e.g. the defaults of its telemetry
+/// keys, for applications to compose with their own.
+///
+/// ```rust
+/// # use config::*;
+/// use config::builder::{BuilderState, ConfigLayer};
+///
+/// struct Telemetry;
+///
+/// impl<St: BuilderState> ConfigLayer<St> for Telemetry {
+///     fn apply(&self, builder: ConfigBuilder<St>) -> Result<ConfigBuilder<St>, ConfigError> {
+///         builder
+///             .set_default("telemetry.enabled", true)?
+///             .set_default("telemetry.sample_rate", 0.1)
+///     }
+/// }
+///
+/// let config = Config::builder()
+///     .with_layer(Telemetry)?
+///     .with_layer(|builder: ConfigBuilder<_>| builder.set_override("telemetry.sample_rate", 1.0))?
+///     .build()?;
+///
+/// assert!(config.get_bool("telemetry.enabled")?);
+/// assert_eq!(config.get_float("telemetry.sample_rate")?, 1.0);
+/// # Ok::<(), ConfigError>(())
+/// ```
+///
+/// Implement it for [`DefaultState`] or [`AsyncState`] only to register sources, which are added
+/// differently in each state. Closures taking and returning a builder are layers too.
+pub trait ConfigLayer<St: BuilderState = DefaultState> {
+    /// Register the defaults, overrides or sources of this layer in `builder`
+    fn apply(&self, builder: ConfigBuilder<St>) -> Result<ConfigBuilder<St>>;
+}
+
+impl<St, F> ConfigLayer<St> for F
+where
+    St: BuilderState,
+    F: Fn(ConfigBuilder<St>) -> Result<ConfigBuilder<St>>,
+{
+    fn apply(&self, builder: ConfigBuilder<St>) -> Result<ConfigBuilder<St>> {
+        self(builder)
+    }
+}
+
+/// Resolves values written as URIs of a scheme, registered with
+/// [`ConfigBuilder::register_resolver`]
+///
+/// Closures taking the location and returning a [`Value`] are resolvers too.
+pub trait Resolver: Send + Sync {
+    /// The value at `location`, the part of the URI after `scheme://`
+    fn resolve(&self, location: &str) -> Result<Value>;
+}
+
+impl<F> Resolver for F
+where
+    F: Fn(&str) -> Result<Value> + Send + Sync,
+{
+    fn resolve(&self, location: &str) -> Result<Value> {
+        self(location)
+    }
+}
+
 /// Operations allowed in any state
 impl<St: BuilderState> ConfigBuilder<St> {
     /// Set a default `value` at `key`
@@ -139,8 +235,95 @@ impl<St: BuilderState> ConfigBuilder<St> {
         S: AsRef<str>,
         T: Into<Value>,
     {
-        self.defaults
-            .insert(Expression::from_str(key.as_ref())?, value.into());
+        self.insert_default(key.as_ref(), value.into())?;
+        Ok(self)
+    }
+
+    /// Sets a default `value` at `key` if it is Some(_)
+    ///
+    /// If None is passed, this function does nothing, e.g. for an optional command line flag.
+    /// The value can be overwritten by any [`Source`], [`AsyncSource`] or override.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `Expression::from_str(key)` fails.
+    pub fn set_default_option<S, T>(mut self, key: S, value: Option<T>) -> Result<Self>
+    where
+        S: AsRef<str>,
+        T: Into<Value>,
+    {
+        if let Some(value) = value {
+            self.insert_default(key.as_ref(), value.into())?;
+        }
+        Ok(self)
+    }
+
+    /// Set every field of `defaults` as a default value
+    ///
+    /// `defaults` is serialized like with [`Config::try_from`], and each of its values can be
+    /// overwritten by any [`Source`], [`AsyncSource`] or override, e.g. a file setting only
+    /// `server.port` keeps the other fields of a `server` default. Tables are set key by key,
+    /// keeping the defaults set at their other keys, e.g. by [`set_default`](Self::set_default),
+    /// while arrays are set whole.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct Server {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// let config = Config::builder()
+    ///     .set_defaults(&Server { host: "localhost".into(), port: 8080 })?
+    ///     .set_override("port", 9090)?
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_string("host")?, "localhost");
+    /// assert_eq!(config.get_int("port")?, 9090);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if `defaults` can't be serialized into a table, e.g. if it is not a struct or a map.
+    pub fn set_defaults<T: Serialize>(mut self, defaults: &T) -> Result<Self> {
+        self.insert_defaults(defaults)?;
+        Ok(self)
+    }
+
+    /// Set a default at `key`, computed by `value` only when building finds no other value there
+    ///
+    /// Unlike [`set_default`](Self::set_default), `value` is not called when any default,
+    /// [`Source`], [`AsyncSource`] or override provides `key`, which suits expensive defaults like
+    /// probing the host. It is called on each build otherwise.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .set_default_with("workers", || {
+    ///         std::thread::available_parallelism().map_or(1, |n| n.get() as u64)
+    ///     })?
+    ///     .set_default_with("cache.dir", || -> String { unreachable!() })?
+    ///     .set_override("cache.dir", "/tmp/cache")?
+    ///     .build()?;
+    ///
+    /// assert!(config.get_int("workers")? >= 1);
+    /// assert_eq!(config.get_string("cache.dir")?, "/tmp/cache");
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if `Expression::from_str(key)` fails.
+    pub fn set_default_with<S, F, T>(mut self, key: S, value: F) -> Result<Self>
+    where
+        S: AsRef<str>,
+        F: Fn() -> T + Send + Sync + 'static,
+        T: Into<Value>,
+    {
+        self.insert_lazy_default(key.as_ref(), LazyValue(Arc::new(move || value().into())))?;
         Ok(self)
     }
 
@@ -148,6 +331,33 @@ impl<St: BuilderState> ConfigBuilder<St> {
     ///
     /// This function sets an overwrite value. It will not be altered by any default, [`Source`] nor [`AsyncSource`]
     ///
+    /// A key ending with `[+]` appends the value to the array collected from the sources, in the
+    /// order the overrides were set, rather than replacing an element at a given index:
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "json")]
+    /// # {
+    /// let config = Config::builder()
+    ///     .add_source(File::from_str(r#"{"plugins": ["auth"]}"#, FileFormat::Json))
+    ///     .set_override("plugins[+]", "metrics")?
+    ///     .set_override("plugins[+]", "tracing")?
+    ///     .build()?;
+    ///
+    /// let plugins: Vec<String> = config.get("plugins")?;
+    /// assert_eq!(plugins, ["auth", "metrics", "tracing"]);
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// A key ending with `[++]` appends each element of an array value instead, so
+    /// `set_override("plugins[++]", vec!["metrics", "tracing"])` is equivalent to the above.
+    ///
+    /// Every key containing `[+]` appends its own element, even when the keys only differ after
+    /// it: `servers[+].host` and `servers[+].port` add two servers, with a host and a port each.
+    /// To append one table with several keys, set a [`Map`](crate::Map) of them at
+    /// `servers[+]`.
+    ///
     /// # Errors
     ///
     /// Fails if `Expression::from_str(key)` fails.
@@ -156,8 +366,7 @@ impl<St: BuilderState> ConfigBuilder<St> {
         S: AsRef<str>,
         T: Into<Value>,
     {
-        self.overrides
-            .insert(Expression::from_str(key.as_ref())?, value.into());
+        self.insert_override(key.as_ref(), value.into())?;
         Ok(self)
     }
 
@@ -175,181 +384,1844 @@ impl<St: BuilderState> ConfigBuilder<St> {
         T: Into<Value>,
     {
         if let Some(value) = value {
-            self.overrides
-                .insert(Expression::from_str(key.as_ref())?, value.into());
+            self.insert_override(key.as_ref(), value.into())?;
         }
         Ok(self)
     }
-}
 
-/// Operations allowed in sync state
-impl ConfigBuilder<DefaultState> {
-    /// Registers new [`Source`] in this builder.
+    /// Set an override for each `(key, value)` pair
     ///
-    /// Calling this method does not invoke any I/O. [`Source`] is only saved in internal register for later use.
-    pub fn add_source<T>(mut self, source: T) -> Self
+    /// Unlike [`Environment`](crate::Environment), every key is parsed as a full path expression,
+    /// making this convenient for table-driven tests.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .with_overrides_from_pairs([("server.port", 8080), ("workers[0].threads", 4)])?
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_int("server.port")?, 8080);
+    /// assert_eq!(config.get_int("workers[0].threads")?, 4);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if `Expression::from_str(key)` fails for any key.
+    pub fn with_overrides_from_pairs<I, S, T>(mut self, pairs: I) -> Result<Self>
     where
-        T: Source + Send + Sync + 'static,
+        I: IntoIterator<Item = (S, T)>,
+        S: AsRef<str>,
+        T: Into<Value>,
     {
-        self.state.sources.push(Box::new(source));
-        self
+        for (key, value) in pairs {
+            path::insert(
+                &mut self.overrides,
+                Expression::from_str(key.as_ref())?,
+                value.into(),
+            );
+        }
+        Ok(self)
     }
 
-    /// Registers new [`AsyncSource`] in this builder and forces transition to [`AsyncState`].
+    /// Rewrite every key with `normalize`, e.g. to compare keys case-insensitively.
     ///
-    /// Calling this method does not invoke any I/O. [`AsyncSource`] is only saved in internal register for later use.
-    #[cfg(feature = "async")]
-    pub fn add_async_source<T>(self, source: T) -> ConfigBuilder<AsyncState>
+    /// Keys are normalized consistently: in defaults, overrides and each source while building,
+    /// down to the keys of the tables set as defaults or overrides, as well as in paths looked
+    /// up on the built [`Config`]. Keys that are equal once normalized
+    /// are merged in the usual priority order.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .normalize_keys(|key| key.to_lowercase())
+    ///     .set_default("Server.Port", 8080)?
+    ///     .set_override("SERVER.port", 9090)?
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_int("server.PORT")?, 9090);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn normalize_keys<F>(mut self, normalize: F) -> Self
     where
-        T: AsyncSource + Send + Sync + 'static,
+        F: Fn(&str) -> String + Send + Sync + 'static,
     {
-        let async_state = ConfigBuilder {
-            state: AsyncState {
-                sources: self
-                    .state
-                    .sources
-                    .into_iter()
-                    .map(SourceType::Sync)
-                    .collect(),
-            },
-            defaults: self.defaults,
-            overrides: self.overrides,
-        };
+        self.merge.normalizer = Some(KeyNormalizer::new(normalize));
+        self
+    }
+
+    /// Convert every key to the case `case`, e.g. to access `camelCase` files as `snake_case`.
+    ///
+    /// This is [`normalize_keys`](Self::normalize_keys) with [`Casing::to_case`], so keys are
+    /// converted in every source as well as in lookups, and the converted keys are the ones seen
+    /// when deserializing or serializing the built [`Config`].
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .convert_case(Case::Snake)
+    ///     .set_default("maxConnections", 8)?
+    ///     .set_override("max-connections", 16)?
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_int("maxConnections")?, 16);
+    /// assert_eq!(config.get_int("max_connections")?, 16);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// [`Casing::to_case`]: convert_case::Casing::to_case
+    #[cfg(feature = "convert-case")]
+    pub fn convert_case(self, case: convert_case::Case) -> Self {
+        use convert_case::Casing as _;
 
-        async_state.add_async_source(source)
+        self.normalize_keys(move |key| key.to_case(case))
     }
 
-    /// Reads all registered [`Source`]s.
+    /// Fail the build with [`ConfigError::MergeConflict`] when a source changes the shape of a
+    /// value set by a default or an earlier source.
     ///
-    /// This is the method that invokes all I/O operations.
-    /// For a non consuming alternative see [`build_cloned`](Self::build_cloned)
+    /// By default the last write wins, so a source defining `server` as a string silently
+    /// replaces a `server` table from an earlier source. With this enabled, replacing a table,
+    /// an array or a scalar by a value of another of those kinds is an error. Scalars of
+    /// different types, like a string overriding an integer, and overrides are still accepted.
     ///
-    /// # Errors
-    /// If source collection fails, be it technical reasons or related to inability to read data as `Config` for different reasons,
-    /// this method returns error.
-    pub fn build(self) -> Result<Config> {
-        Self::build_internal(self.defaults, self.overrides, &self.state.sources)
+    /// [`ConfigError::MergeConflict`]: crate::ConfigError::MergeConflict
+    pub fn detect_merge_conflicts(mut self, detect: bool) -> Self {
+        self.merge.detect_conflicts = detect;
+        self
     }
 
-    /// Reads all registered [`Source`]s.
+    /// Whether all-digit key segments of paths are array indices rather than table keys.
     ///
-    /// Similar to [`build`](Self::build), but it does not take ownership of `ConfigBuilder` to allow later reuse.
-    /// Internally it clones data to achieve it.
+    /// Disabled by default, so `divisors.1` is the `1` key of the `divisors` table. When
+    /// enabled, such segments are array indices instead, and `list.0.name` is `list[0].name`,
+    /// like the keys of an [`Environment`](crate::Environment) with
+    /// [`numeric_keys_as_indices(true)`](crate::Environment::numeric_keys_as_indices). This applies to
+    /// the keys of defaults and overrides, and to the keys looked up in the built [`Config`].
     ///
-    /// # Errors
-    /// If source collection fails, be it technical reasons or related to inability to read data as `Config` for different reasons,
-    /// this method returns error.
-    pub fn build_cloned(&self) -> Result<Config> {
-        Self::build_internal(
-            self.defaults.clone(),
-            self.overrides.clone(),
-            &self.state.sources,
-        )
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .numeric_keys_as_indices(true)
+    ///     .set_override("list.0.name", "first")?
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_string("list[0].name")?, "first");
+    /// assert_eq!(config.get_string("list.0.name")?, "first");
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn numeric_keys_as_indices(mut self, enabled: bool) -> Self {
+        self.merge.numeric_keys_as_index = enabled;
+        self
     }
 
-    fn build_internal(
-        defaults: Map<Expression, Value>,
-        overrides: Map<Expression, Value>,
-        sources: &[Box<dyn Source + Send + Sync>],
-    ) -> Result<Config> {
-        let mut cache: Value = Map::<String, Value>::new().into();
+    /// Deserialize empty tables as `None` into `Option` fields.
+    ///
+    /// By default an empty table, like a `[section]` header without keys, deserializes as
+    /// `Some` of a struct built from no keys, which fails if the struct has required fields.
+    /// With this enabled, it is treated like a missing value. Use `#[serde(default)]` on the
+    /// struct of a non-`Option` field to get its `Default` instead.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// #[derive(serde::Deserialize)]
+    /// struct Settings {
+    ///     tls: Option<Tls>,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Tls {
+    ///     cert: String,
+    /// }
+    ///
+    /// let settings: Settings = Config::builder()
+    ///     .add_source(File::from_str("[tls]", FileFormat::Toml))
+    ///     .empty_tables_as_none(true)
+    ///     .build()?
+    ///     .try_deserialize()?;
+    ///
+    /// assert!(settings.tls.is_none());
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn empty_tables_as_none(mut self, enabled: bool) -> Self {
+        self.merge.empty_tables_as_none = enabled;
+        self
+    }
 
-        // Add defaults
-        for (key, val) in defaults {
-            key.set(&mut cache, val);
-        }
+    /// Collect the sources concurrently when building, then merge them in the order they were
+    /// added.
+    ///
+    /// This speeds up building from many files or remote sources. In the sync state, [`Source`]s
+    /// are collected on scoped threads, at most one per available core, each taking the next
+    /// source not yet collected. In the async state, [`AsyncSource`]s are awaited
+    /// concurrently on the current task, whatever the runtime. Each source is collected into a
+    /// table of its own, merged over the previous ones in the order they were added, and the
+    /// first error in source order is returned after all sources are collected.
+    pub fn collect_in_parallel(mut self, enabled: bool) -> Self {
+        self.merge.parallel = enabled;
+        self
+    }
 
-        // Add sources
-        sources.collect_to(&mut cache)?;
+    /// Keep the sources in the built [`Config`], along with what each of them collected, so
+    /// that [`Config::refresh_source`] can collect some of them again.
+    ///
+    /// Disabled by default, as the configuration then holds a copy of the values of each source
+    /// for its whole lifetime. Each source is collected into a table of its own, merged over the
+    /// previous ones in the order they were added. Only [`Source`]s are collected again, the
+    /// values of [`AsyncSource`]s are kept as they were when building.
+    pub fn refreshable(mut self, enabled: bool) -> Self {
+        self.merge.refreshable = enabled;
+        self
+    }
 
-        // Add overrides
-        for (key, val) in overrides {
-            key.set(&mut cache, val);
-        }
+    /// Order the keys of tables lexically, so that they don't depend on the order of the
+    /// sources or on the randomized hashing of [`Map`].
+    ///
+    /// Deserializing visits the keys of tables in lexical order, so maps filled in visiting
+    /// order, like a `Vec` of entries or an `IndexMap`, and the first of several errors, are the
+    /// same from one run to the next. [`Config::sections`] is sorted as well.
+    ///
+    /// With the `preserve_order` feature, the tables of the built [`Config`] are sorted too, so
+    /// that iterating them, e.g. the table returned by [`Config::get_table`], and
+    /// [rendering](Config::get_rendered) them follow the same order. Without it, tables are hash
+    /// maps that can't be ordered, so iterating them stays unordered; rendering is then always
+    /// sorted, as are [`Config::keys_with_prefix`] and
+    /// [`Value::into_nested_json`](crate::Value::into_nested_json).
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// #[derive(serde::Deserialize)]
+    /// struct Settings {
+    ///     #[serde(with = "entries")]
+    ///     servers: Vec<(String, u16)>,
+    /// }
+    ///
+    /// mod entries {
+    ///     pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+    ///         deserializer: D,
+    ///     ) -> Result<Vec<(String, u16)>, D::Error> {
+    ///         struct Entries;
+    ///         impl<'de> serde::de::Visitor<'de> for Entries {
+    ///             type Value = Vec<(String, u16)>;
+    ///             fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///                 f.write_str("a map")
+    ///             }
+    ///             fn visit_map<A: serde::de::MapAccess<'de>>(
+    ///                 self,
+    ///                 mut map: A,
+    ///             ) -> Result<Self::Value, A::Error> {
+    ///                 let mut entries = Vec::new();
+    ///                 while let Some(entry) = map.next_entry()? {
+    ///                     entries.push(entry);
+    ///                 }
+    ///                 Ok(entries)
+    ///             }
+    ///         }
+    ///         deserializer.deserialize_map(Entries)
+    ///     }
+    /// }
+    ///
+    /// let settings: Settings = Config::builder()
+    ///     .set_override("servers.gamma", 3)?
+    ///     .set_override("servers.alpha", 1)?
+    ///     .set_override("servers.beta", 2)?
+    ///     .sort_keys(true)
+    ///     .build()?
+    ///     .try_deserialize()?;
+    ///
+    /// let names: Vec<_> = settings.servers.iter().map(|(name, _)| name.as_str()).collect();
+    /// assert_eq!(names, ["alpha", "beta", "gamma"]);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn sort_keys(mut self, enabled: bool) -> Self {
+        self.merge.sort_keys = enabled;
+        self
+    }
 
-        Ok(Config::new(cache))
+    /// Deserialize the strings that read as a boolean or a number, like those of environment
+    /// variables, as such where serde doesn't say which type it expects.
+    ///
+    /// Serde reads the fields of internally tagged and untagged enums before knowing their types,
+    /// and then no longer converts strings to the type of a field. With this option, a string
+    /// that reads back the same as a boolean or a number, e.g. `8080` but not `08080`, is given
+    /// as such to these enums and to the other types that deserialize any value, like
+    /// `serde_json::Value` or the values of a `#[serde(flatten)]` field. Disabled by default, in
+    /// which case [`helpers::coerced`](crate::helpers::coerced) converts single fields.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// #[derive(Debug, PartialEq, serde::Deserialize)]
+    /// #[serde(tag = "type", rename_all = "lowercase")]
+    /// enum Listener {
+    ///     Tcp { port: u16, tls: bool },
+    /// }
+    ///
+    /// let listener: Listener = Config::builder()
+    ///     .set_default("type", "tcp")?
+    ///     .set_default("port", "8080")?
+    ///     .set_default("tls", "true")?
+    ///     .coerce_strings(true)
+    ///     .build()?
+    ///     .try_deserialize()?;
+    ///
+    /// assert_eq!(listener, Listener::Tcp { port: 8080, tls: true });
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn coerce_strings(mut self, enabled: bool) -> Self {
+        self.merge.coerce_strings = enabled;
+        self
     }
-}
 
-/// Operations allowed in async state
-impl ConfigBuilder<AsyncState> {
-    /// Registers new [`Source`] in this builder.
+    /// Parse files with `extension`, like `conf`, as `format` when they are given without a
+    /// format, as with [`File::with_name`](crate::File::with_name).
     ///
-    /// Calling this method does not invoke any I/O. [`Source`] is only saved in internal register for later use.
-    pub fn add_source<T>(mut self, source: T) -> Self
-    where
-        T: Source + Send + Sync + 'static,
-    {
-        self.state.sources.push(SourceType::Sync(Box::new(source)));
+    /// This takes precedence over the extensions of [`FileFormat`](crate::FileFormat), so it can
+    /// also remap one of them, e.g. `ini` to TOML. A name without extension is also looked up
+    /// with `extension`, after the built-in ones. Registering the same extension again replaces
+    /// it.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # let dir = std::env::temp_dir().join("config-register-extension-doc");
+    /// # std::fs::create_dir_all(&dir).unwrap();
+    /// # std::fs::write(dir.join("app.conf"), "port = 8080").unwrap();
+    /// # let name = dir.join("app");
+    /// # let name = name.to_str().unwrap();
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// let config = Config::builder()
+    ///     .register_extension("conf", FileFormat::Toml)
+    ///     .add_source(File::with_name(name))
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_int("port")?, 8080);
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn register_extension(mut self, extension: &str, format: crate::FileFormat) -> Self {
+        self.merge.extensions.set(extension, Some(format));
         self
     }
 
-    /// Registers new [`AsyncSource`] in this builder.
+    /// Refuse files with `extension`, like `yml`, when they are given without a format.
     ///
-    /// Calling this method does not invoke any I/O. [`AsyncSource`] is only saved in internal register for later use.
-    #[cfg(feature = "async")]
-    pub fn add_async_source<T>(mut self, source: T) -> Self
+    /// A name without extension is not looked up with `extension`, and building fails for a
+    /// file with it, even when the file is not [required](crate::File::required).
+    #[cfg(feature = "fs")]
+    pub fn forbid_extension(mut self, extension: &str) -> Self {
+        self.merge.extensions.set(extension, None);
+        self
+    }
+
+    /// Resolve string values that are URIs of `scheme`, like `secret://vault/db#password`, with
+    /// `resolver` when building.
+    ///
+    /// Values are resolved once defaults, sources and overrides are merged, so a source can
+    /// reference a secret that a later one overrides with a plain value, and are resolved again
+    /// when [refreshing](Config::refresh_source) a source. The resolver is given the location
+    /// after `scheme://` and its value keeps the origin of the URI. Registering another resolver
+    /// for the same scheme replaces it.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// let config = Config::builder()
+    ///     .add_source(File::from_str(
+    ///         r#"password = "secret://db/password""#,
+    ///         FileFormat::Toml,
+    ///     ))
+    ///     .register_resolver("secret", |location: &str| match location {
+    ///         "db/password" => Ok(Value::from("hunter2")),
+    ///         _ => Err(ConfigError::Message(format!("no secret at {location}"))),
+    ///     })
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_string("password")?, "hunter2");
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Building fails with the error of the resolver, naming the key of the value.
+    pub fn register_resolver<R>(mut self, scheme: &str, resolver: R) -> Self
     where
-        T: AsyncSource + Send + Sync + 'static,
+        R: Resolver + 'static,
     {
-        self.state.sources.push(SourceType::Async(Box::new(source)));
+        let prefix = format!("{scheme}://");
+        self.merge
+            .resolvers
+            .0
+            .retain(|(existing, _)| *existing != prefix);
+        self.merge.resolvers.0.push((prefix, Arc::new(resolver)));
         self
     }
 
-    /// Reads all registered defaults, [`Source`]s, [`AsyncSource`]s and overrides.
+    /// Replace the value at `key` with the result of `transform` when building, e.g. to trim
+    /// whitespace or lowercase a hostname before deserializing it.
     ///
-    /// This is the method that invokes all I/O operations.
-    /// For a non consuming alternative see [`build_cloned`](Self::build_cloned)
+    /// Transforms run once defaults, sources and overrides are merged and
+    /// [resolvers](Self::register_resolver) have run, in the order they are registered, and run
+    /// again when [refreshing](Config::refresh_source) a source. They are skipped when nothing
+    /// is set at `key`. The transformed value keeps the origin of the original one.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .set_default("server.host", "  Example.COM ")?
+    ///     .map_key("server.host", |value: Value| {
+    ///         Ok(Value::from(value.into_string()?.trim().to_lowercase()))
+    ///     })?
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_string("server.host")?, "example.com");
+    /// # Ok::<(), ConfigError>(())
+    /// ```
     ///
     /// # Errors
-    /// If source collection fails, be it technical reasons or related to inability to read data as `Config` for different reasons,
-    /// this method returns error.
-    pub async fn build(self) -> Result<Config> {
-        Self::build_internal(self.defaults, self.overrides, &self.state.sources).await
+    ///
+    /// Fails if `Expression::from_str(key)` fails. Building fails with the error of `transform`,
+    /// naming the key.
+    pub fn map_key<S, F>(mut self, key: S, transform: F) -> Result<Self>
+    where
+        S: AsRef<str>,
+        F: Fn(Value) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.insert_transform(key.as_ref(), Transform(Arc::new(transform)))?;
+        Ok(self)
     }
 
-    /// Reads all registered defaults, [`Source`]s, [`AsyncSource`]s and overrides.
+    /// Apply a [`ConfigLayer`] to this builder.
     ///
-    /// Similar to [`build`](Self::build), but it does not take ownership of `ConfigBuilder` to allow later reuse.
-    /// Internally it clones data to achieve it.
+    /// Layers are applied right away, so their defaults, overrides and sources take their place
+    /// among those registered before and after.
     ///
     /// # Errors
-    /// If source collection fails, be it technical reasons or related to inability to read data as `Config` for different reasons,
-    /// this method returns error.
-    pub async fn build_cloned(&self) -> Result<Config> {
-        Self::build_internal(
-            self.defaults.clone(),
-            self.overrides.clone(),
-            &self.state.sources,
-        )
-        .await
+    ///
+    /// Fails if the layer fails, e.g. when it sets a default at an invalid key.
+    pub fn with_layer<L>(self, layer: L) -> Result<Self>
+    where
+        L: ConfigLayer<St>,
+    {
+        layer.apply(self)
     }
 
-    async fn build_internal(
-        defaults: Map<Expression, Value>,
-        overrides: Map<Expression, Value>,
-        sources: &[SourceType],
-    ) -> Result<Config> {
-        let mut cache: Value = Map::<String, Value>::new().into();
-
-        // Add defaults
-        for (key, val) in defaults {
-            key.set(&mut cache, val);
+    /// Record the errors of the fallible methods that follow instead of returning them, to
+    /// report them all at once when building.
+    ///
+    /// See [`Deferred`].
+    pub fn defer_errors(self) -> Deferred<St> {
+        Deferred {
+            builder: self,
+            errors: Vec::new(),
         }
+    }
+
+    /// Snapshot the defaults, overrides, sources and options registered so far, to
+    /// [roll back](Self::reset_to) to them later.
+    ///
+    /// This lets an interactive tool layer a source tentatively and drop it if building fails.
+    /// Sources are cloned, not collected, so taking a checkpoint does no I/O.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// let mut builder = Config::builder().set_default("port", 80)?;
+    ///
+    /// let checkpoint = builder.checkpoint();
+    /// builder = builder.add_source(File::from_str("port = ", FileFormat::Toml));
+    /// if builder.build_cloned().is_err() {
+    ///     builder = builder.reset_to(&checkpoint);
+    /// }
+    ///
+    /// assert_eq!(builder.build()?.get_int("port")?, 80);
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn checkpoint(&self) -> Checkpoint<St>
+    where
+        St: Clone,
+    {
+        Checkpoint(self.clone())
+    }
+
+    /// Discard everything registered since `checkpoint` was taken, from this builder or another
+    /// one in the same state.
+    ///
+    /// The checkpoint is kept, so the builder can be rolled back to it again.
+    pub fn reset_to(self, checkpoint: &Checkpoint<St>) -> Self
+    where
+        St: Clone,
+    {
+        checkpoint.0.clone()
+    }
+}
+
+/// The fallible steps of the builder methods, shared with [`Deferred`], which change nothing
+/// when they fail
+impl<St: BuilderState> ConfigBuilder<St> {
+    fn insert_default(&mut self, key: &str, value: Value) -> Result<()> {
+        path::insert(&mut self.defaults, Expression::from_str(key)?, value);
+        Ok(())
+    }
 
-        for source in sources.iter() {
-            match source {
-                SourceType::Sync(source) => source.collect_to(&mut cache)?,
-                #[cfg(feature = "async")]
-                SourceType::Async(source) => source.collect_to(&mut cache).await?,
+    fn insert_defaults<T: Serialize>(&mut self, defaults: &T) -> Result<()> {
+        fn insert_leaves(defaults: &mut Map<Expression, Value>, key: Expression, value: Value) {
+            match value.kind {
+                ValueKind::Table(table) if !table.is_empty() => {
+                    for (child, value) in table {
+                        insert_leaves(defaults, key.clone().child(child), value);
+                    }
+                }
+                _ => path::insert(defaults, key, value),
             }
         }
 
-        // Add overrides
-        for (key, val) in overrides {
-            key.set(&mut cache, val);
+        let table = Config::try_from(defaults)?.cache.into_table()?;
+        for (key, value) in table {
+            insert_leaves(&mut self.defaults, Expression::root(key), value);
         }
+        Ok(())
+    }
+
+    fn insert_lazy_default(&mut self, key: &str, value: LazyValue) -> Result<()> {
+        self.lazy_defaults.push((Expression::from_str(key)?, value));
+        Ok(())
+    }
+
+    fn insert_override(&mut self, key: &str, value: Value) -> Result<()> {
+        path::insert(&mut self.overrides, Expression::from_str(key)?, value);
+        Ok(())
+    }
 
-        Ok(Config::new(cache))
+    fn insert_transform(&mut self, key: &str, transform: Transform) -> Result<()> {
+        self.merge
+            .transforms
+            .push((Expression::from_str(key)?, transform));
+        Ok(())
     }
 }
+
+/// A snapshot of a [`ConfigBuilder`], taken with [`ConfigBuilder::checkpoint`]
+#[derive(Debug, Clone)]
+pub struct Checkpoint<St: BuilderState = DefaultState>(ConfigBuilder<St>);
+
+/// A [`ConfigBuilder`] recording the errors of its fallible methods, returned by
+/// [`ConfigBuilder::defer_errors`]
+///
+/// Methods that would fail, like [`set_default`](Self::set_default) with an invalid key, are
+/// skipped and their errors reported all at once by [`finish`](Self::finish) or `build`. Methods
+/// that can't fail are reached with [`map`](Self::map).
+///
+/// ```rust
+/// # use config::*;
+/// let error = Config::builder()
+///     .defer_errors()
+///     .set_default("server.port", 80)
+///     .set_default("server..host", "localhost")
+///     .set_override("workers[", 4)
+///     .map(|builder| builder.sort_keys(true))
+///     .build()
+///     .unwrap_err();
+///
+/// assert!(error.to_string().starts_with("2 configuration errors"));
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[derive(Debug)]
+#[must_use]
+pub struct Deferred<St: BuilderState = DefaultState> {
+    builder: ConfigBuilder<St>,
+    errors: Vec<ConfigError>,
+}
+
+impl<St: BuilderState> Deferred<St> {
+    /// Apply a fallible step to the builder, recording its error
+    fn record<F>(mut self, apply: F) -> Self
+    where
+        F: FnOnce(&mut ConfigBuilder<St>) -> Result<()>,
+    {
+        if let Err(error) = apply(&mut self.builder) {
+            self.errors.push(error);
+        }
+        self
+    }
+
+    /// See [`ConfigBuilder::set_default`]
+    pub fn set_default<S, T>(self, key: S, value: T) -> Self
+    where
+        S: AsRef<str>,
+        T: Into<Value>,
+    {
+        self.set_default_option(key, Some(value))
+    }
+
+    /// See [`ConfigBuilder::set_default_option`]
+    pub fn set_default_option<S, T>(self, key: S, value: Option<T>) -> Self
+    where
+        S: AsRef<str>,
+        T: Into<Value>,
+    {
+        match value {
+            Some(value) => {
+                self.record(|builder| builder.insert_default(key.as_ref(), value.into()))
+            }
+            None => self,
+        }
+    }
+
+    /// See [`ConfigBuilder::set_defaults`]
+    pub fn set_defaults<T: Serialize>(self, defaults: &T) -> Self {
+        self.record(|builder| builder.insert_defaults(defaults))
+    }
+
+    /// See [`ConfigBuilder::set_default_with`]
+    pub fn set_default_with<S, F, T>(self, key: S, value: F) -> Self
+    where
+        S: AsRef<str>,
+        F: Fn() -> T + Send + Sync + 'static,
+        T: Into<Value>,
+    {
+        let value = LazyValue(Arc::new(move || value().into()));
+        self.record(|builder| builder.insert_lazy_default(key.as_ref(), value))
+    }
+
+    /// See [`ConfigBuilder::set_override`]
+    pub fn set_override<S, T>(self, key: S, value: T) -> Self
+    where
+        S: AsRef<str>,
+        T: Into<Value>,
+    {
+        self.set_override_option(key, Some(value))
+    }
+
+    /// See [`ConfigBuilder::set_override_option`]
+    pub fn set_override_option<S, T>(self, key: S, value: Option<T>) -> Self
+    where
+        S: AsRef<str>,
+        T: Into<Value>,
+    {
+        match value {
+            Some(value) => {
+                self.record(|builder| builder.insert_override(key.as_ref(), value.into()))
+            }
+            None => self,
+        }
+    }
+
+    /// See [`ConfigBuilder::with_overrides_from_pairs`]
+    ///
+    /// The pairs with a valid key are set, even if others are not.
+    pub fn with_overrides_from_pairs<I, S, T>(mut self, pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (S, T)>,
+        S: AsRef<str>,
+        T: Into<Value>,
+    {
+        for (key, value) in pairs {
+            self = self.set_override(key, value);
+        }
+        self
+    }
+
+    /// See [`ConfigBuilder::map_key`]
+    pub fn map_key<S, F>(self, key: S, transform: F) -> Self
+    where
+        S: AsRef<str>,
+        F: Fn(Value) -> Result<Value> + Send + Sync + 'static,
+    {
+        let transform = Transform(Arc::new(transform));
+        self.record(|builder| builder.insert_transform(key.as_ref(), transform))
+    }
+
+    /// See [`ConfigBuilder::with_layer`]
+    ///
+    /// A layer that fails is not applied at all.
+    pub fn with_layer<L>(mut self, layer: L) -> Self
+    where
+        L: ConfigLayer<St>,
+        St: Clone,
+    {
+        match layer.apply(self.builder.clone()) {
+            Ok(builder) => self.builder = builder,
+            Err(error) => self.errors.push(error),
+        }
+        self
+    }
+
+    /// Call one of the methods of the underlying [`ConfigBuilder`] that can't fail
+    pub fn map<F>(mut self, apply: F) -> Self
+    where
+        F: FnOnce(ConfigBuilder<St>) -> ConfigBuilder<St>,
+    {
+        self.builder = apply(self.builder);
+        self
+    }
+
+    /// The underlying [`ConfigBuilder`], if no error was recorded
+    ///
+    /// # Errors
+    ///
+    /// Fails with the recorded error, or with [`ConfigError::Multiple`] holding all of them in
+    /// the order they occurred if there are several.
+    pub fn finish(mut self) -> Result<ConfigBuilder<St>> {
+        match self.errors.len() {
+            0 => Ok(self.builder),
+            1 => Err(self.errors.remove(0)),
+            _ => Err(ConfigError::Multiple(self.errors)),
+        }
+    }
+}
+
+impl Deferred<DefaultState> {
+    /// See [`ConfigBuilder::add_source`]
+    pub fn add_source<T>(self, source: T) -> Self
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        self.map(|builder| builder.add_source(source))
+    }
+
+    /// [Finish](Self::finish) and [build](ConfigBuilder::build)
+    ///
+    /// # Errors
+    ///
+    /// Fails with the recorded errors, if any, before reading any source.
+    pub fn build(self) -> Result<Config> {
+        self.finish()?.build()
+    }
+}
+
+impl Deferred<AsyncState> {
+    /// See [`ConfigBuilder::add_source`]
+    pub fn add_source<T>(self, source: T) -> Self
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        self.map(|builder| builder.add_source(source))
+    }
+
+    /// See [`ConfigBuilder::add_async_source`]
+    #[cfg(feature = "async")]
+    pub fn add_async_source<T>(self, source: T) -> Self
+    where
+        T: AsyncSource + Send + Sync + 'static,
+    {
+        self.map(|builder| builder.add_async_source(source))
+    }
+
+    /// [Finish](Self::finish) and [build](ConfigBuilder::build)
+    ///
+    /// # Errors
+    ///
+    /// Fails with the recorded errors, if any, before reading any source.
+    pub async fn build(self) -> Result<Config> {
+        self.finish()?.build().await
+    }
+}
+
+/// Operations allowed in sync state
+impl ConfigBuilder<DefaultState> {
+    /// Registers new [`Source`] in this builder.
+    ///
+    /// Calling this method does not invoke any I/O. [`Source`] is only saved in internal register for later use.
+    pub fn add_source<T>(self, source: T) -> Self
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        self.add_source_with_priority(source, 0)
+    }
+
+    /// Registers new [`Source`] in this builder, merged according to `priority`.
+    ///
+    /// Sources are merged by increasing priority, so values from a source override those of
+    /// sources with a lower priority regardless of the order they were added in. Sources with the
+    /// same priority are merged in the order they were added. [`add_source`](Self::add_source)
+    /// uses a priority of 0.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "json")]
+    /// # {
+    /// let config = Config::builder()
+    ///     .add_source_with_priority(File::from_str(r#"{"port": 9090}"#, FileFormat::Json), 10)
+    ///     .add_source(File::from_str(r#"{"port": 8080, "host": "localhost"}"#, FileFormat::Json))
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_int("port")?, 9090);
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn add_source_with_priority<T>(mut self, source: T, priority: i32) -> Self
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        self.insert_source(TypeId::of::<T>(), Box::new(source), priority);
+        self
+    }
+
+    /// Registers each of `sources` in this builder, in order, like [`add_source`](Self::add_source).
+    ///
+    /// Meant for source lists assembled at runtime, e.g. from a registry of plugins. As their
+    /// types are erased, they are refreshed all at once with
+    /// `refresh_source::<Vec<Box<dyn Source + Send + Sync>>>()`.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "json")]
+    /// # {
+    /// let plugins: Vec<Box<dyn Source + Send + Sync>> = vec![
+    ///     Box::new(File::from_str(r#"{"cache": {"size": 64}}"#, FileFormat::Json)),
+    ///     Box::new(File::from_str(r#"{"metrics": {"port": 9100}}"#, FileFormat::Json)),
+    /// ];
+    /// let config = Config::builder().with_sources(plugins).build()?;
+    ///
+    /// assert_eq!(config.get_int("cache.size")?, 64);
+    /// assert_eq!(config.get_int("metrics.port")?, 9100);
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn with_sources(mut self, sources: Vec<Box<dyn Source + Send + Sync>>) -> Self {
+        for source in sources {
+            self.insert_source(
+                TypeId::of::<Vec<Box<dyn Source + Send + Sync>>>(),
+                source,
+                0,
+            );
+        }
+        self
+    }
+
+    fn insert_source(
+        &mut self,
+        type_id: TypeId,
+        source: Box<dyn Source + Send + Sync>,
+        priority: i32,
+    ) {
+        let sources = &mut self.state.sources;
+        let index = sources.partition_point(|sync| sync.priority <= priority);
+        sources.insert(
+            index,
+            SyncSource {
+                type_id,
+                priority,
+                source,
+            },
+        );
+    }
+
+    /// The registered [`Source`]s, in the order they are merged
+    pub fn sources(&self) -> impl ExactSizeIterator<Item = &(dyn Source + Send + Sync)> {
+        self.state.sources.iter().map(|sync| &*sync.source)
+    }
+
+    /// Unregisters the [`Source`]s for which `keep` returns `false`.
+    ///
+    /// Lets frameworks drop sources registered earlier, e.g. by a plugin that was disabled since.
+    pub fn retain_sources<F>(mut self, mut keep: F) -> Self
+    where
+        F: FnMut(&(dyn Source + Send + Sync)) -> bool,
+    {
+        self.state.sources.retain(|sync| keep(&*sync.source));
+        self
+    }
+
+    /// Unregisters the [`Source`]s of type `T`, e.g. the [`Environment`](crate::Environment) in
+    /// tests.
+    pub fn remove_sources<T: Source + 'static>(mut self) -> Self {
+        let type_id = TypeId::of::<T>();
+        self.state.sources.retain(|sync| sync.type_id != type_id);
+        self
+    }
+
+    /// Registers new [`AsyncSource`] in this builder and forces transition to [`AsyncState`].
+    ///
+    /// Calling this method does not invoke any I/O. [`AsyncSource`] is only saved in internal register for later use.
+    #[cfg(feature = "async")]
+    pub fn add_async_source<T>(self, source: T) -> ConfigBuilder<AsyncState>
+    where
+        T: AsyncSource + Send + Sync + 'static,
+    {
+        self.add_async_source_with_priority(source, 0)
+    }
+
+    /// Registers new [`AsyncSource`] in this builder, merged according to `priority`, and forces
+    /// transition to [`AsyncState`].
+    ///
+    /// See [`add_source_with_priority`](Self::add_source_with_priority) for how priorities apply.
+    #[cfg(feature = "async")]
+    pub fn add_async_source_with_priority<T>(
+        self,
+        source: T,
+        priority: i32,
+    ) -> ConfigBuilder<AsyncState>
+    where
+        T: AsyncSource + Send + Sync + 'static,
+    {
+        let async_state = ConfigBuilder {
+            state: AsyncState {
+                sources: self
+                    .state
+                    .sources
+                    .into_iter()
+                    .map(|sync| (sync.priority, SourceType::Sync(sync.type_id, sync.source)))
+                    .collect(),
+            },
+            defaults: self.defaults,
+            lazy_defaults: self.lazy_defaults,
+            overrides: self.overrides,
+            merge: self.merge,
+        };
+
+        async_state.add_async_source_with_priority(source, priority)
+    }
+
+    /// Registers new [`Source`] in this builder to be collected on a blocking thread and forces
+    /// transition to [`AsyncState`].
+    ///
+    /// Calling this method does not invoke any I/O. See [`BlockingSource`] for details.
+    #[cfg(feature = "tokio")]
+    pub fn add_source_blocking<T>(self, source: T) -> ConfigBuilder<AsyncState>
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        self.add_async_source(BlockingSource::new(source))
+    }
+
+    /// Reads all registered [`Source`]s.
+    ///
+    /// This is the method that invokes all I/O operations.
+    /// For a non consuming alternative see [`build_cloned`](Self::build_cloned)
+    ///
+    /// # Errors
+    /// If source collection fails, be it technical reasons or related to inability to read data as `Config` for different reasons,
+    /// this method returns error.
+    pub fn build(self) -> Result<Config> {
+        Self::build_internal(
+            self.defaults,
+            &self.lazy_defaults,
+            self.overrides,
+            &self.state.sources,
+            self.merge,
+        )
+    }
+
+    /// Reads all registered [`Source`]s.
+    ///
+    /// Similar to [`build`](Self::build), but it does not take ownership of `ConfigBuilder` to allow later reuse.
+    /// Internally it clones data to achieve it.
+    ///
+    /// # Errors
+    /// If source collection fails, be it technical reasons or related to inability to read data as `Config` for different reasons,
+    /// this method returns error.
+    pub fn build_cloned(&self) -> Result<Config> {
+        Self::build_internal(
+            self.defaults.clone(),
+            &self.lazy_defaults,
+            self.overrides.clone(),
+            &self.state.sources,
+            self.merge.clone(),
+        )
+    }
+
+    fn build_internal(
+        defaults: Map<Expression, Value>,
+        lazy_defaults: &[(Expression, LazyValue)],
+        overrides: Map<Expression, Value>,
+        sources: &[SyncSource],
+        merge: MergeOptions,
+    ) -> Result<Config> {
+        let mut assembly = Assembly::new(&merge, &defaults);
+        share_environment(|| collect_sources(sources, &mut assembly))?;
+        let Assembly {
+            cache,
+            report,
+            collected,
+            ..
+        } = assembly.finish(&overrides, lazy_defaults)?;
+
+        let config = Config::new(cache)
+            .with_normalizer(merge.normalizer.clone())
+            .with_empty_tables_as_none(merge.empty_tables_as_none)
+            .with_numeric_keys_as_index(merge.numeric_keys_as_index)
+            .with_sort_keys(merge.sort_keys)
+            .with_coerce_strings(merge.coerce_strings)
+            .with_report(report);
+        if !merge.refreshable {
+            return Ok(config);
+        }
+
+        let sources = sources
+            .iter()
+            .map(|sync| SourceType::Sync(sync.type_id, sync.source.clone()))
+            .zip(collected)
+            .map(|(source, (layer, read))| (source, layer, read))
+            .collect();
+        Ok(config.with_recipe(Recipe {
+            defaults,
+            lazy_defaults: lazy_defaults.to_vec(),
+            overrides,
+            sources,
+            merge,
+        }))
+    }
+}
+
+/// Operations allowed in async state
+impl ConfigBuilder<AsyncState> {
+    /// Registers new [`Source`] in this builder.
+    ///
+    /// Calling this method does not invoke any I/O. [`Source`] is only saved in internal register for later use.
+    pub fn add_source<T>(self, source: T) -> Self
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        self.add_source_with_priority(source, 0)
+    }
+
+    /// Registers new [`Source`] in this builder, merged according to `priority`.
+    ///
+    /// Sources are merged by increasing priority, and in the order they were added for equal
+    /// priorities. [`add_source`](Self::add_source) uses a priority of 0.
+    pub fn add_source_with_priority<T>(self, source: T, priority: i32) -> Self
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        self.insert_source(
+            SourceType::Sync(TypeId::of::<T>(), Box::new(source)),
+            priority,
+        )
+    }
+
+    /// Registers new [`AsyncSource`] in this builder.
+    ///
+    /// Calling this method does not invoke any I/O. [`AsyncSource`] is only saved in internal register for later use.
+    #[cfg(feature = "async")]
+    pub fn add_async_source<T>(self, source: T) -> Self
+    where
+        T: AsyncSource + Send + Sync + 'static,
+    {
+        self.add_async_source_with_priority(source, 0)
+    }
+
+    /// Registers new [`AsyncSource`] in this builder, merged according to `priority`.
+    ///
+    /// Sources are merged by increasing priority, and in the order they were added for equal
+    /// priorities. [`add_async_source`](Self::add_async_source) uses a priority of 0.
+    #[cfg(feature = "async")]
+    pub fn add_async_source_with_priority<T>(self, source: T, priority: i32) -> Self
+    where
+        T: AsyncSource + Send + Sync + 'static,
+    {
+        self.insert_source(SourceType::Async(Arc::new(source)), priority)
+    }
+
+    /// Registers each of `sources` in this builder, in order, like [`add_source`](Self::add_source).
+    pub fn with_sources(self, sources: Vec<Box<dyn Source + Send + Sync>>) -> Self {
+        sources.into_iter().fold(self, |builder, source| {
+            builder.insert_source(
+                SourceType::Sync(TypeId::of::<Vec<Box<dyn Source + Send + Sync>>>(), source),
+                0,
+            )
+        })
+    }
+
+    fn insert_source(mut self, source: SourceType, priority: i32) -> Self {
+        let sources = &mut self.state.sources;
+        let index = sources.partition_point(|&(other, _)| other <= priority);
+        sources.insert(index, (priority, source));
+        self
+    }
+
+    /// Registers new [`Source`] in this builder to be collected on a blocking thread.
+    ///
+    /// Calling this method does not invoke any I/O. See [`BlockingSource`] for details.
+    #[cfg(feature = "tokio")]
+    pub fn add_source_blocking<T>(self, source: T) -> Self
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        self.add_async_source(BlockingSource::new(source))
+    }
+
+    /// Reads all registered defaults, [`Source`]s, [`AsyncSource`]s and overrides.
+    ///
+    /// This is the method that invokes all I/O operations.
+    /// For a non consuming alternative see [`build_cloned`](Self::build_cloned)
+    ///
+    /// # Errors
+    /// If source collection fails, be it technical reasons or related to inability to read data as `Config` for different reasons,
+    /// this method returns error.
+    pub async fn build(self) -> Result<Config> {
+        Self::build_internal(
+            self.defaults,
+            &self.lazy_defaults,
+            self.overrides,
+            &self.state.sources,
+            self.merge,
+        )
+        .await
+    }
+
+    /// Reads all registered defaults, [`Source`]s, [`AsyncSource`]s and overrides.
+    ///
+    /// Similar to [`build`](Self::build), but it does not take ownership of `ConfigBuilder` to allow later reuse.
+    /// Internally it clones data to achieve it.
+    ///
+    /// # Errors
+    /// If source collection fails, be it technical reasons or related to inability to read data as `Config` for different reasons,
+    /// this method returns error.
+    pub async fn build_cloned(&self) -> Result<Config> {
+        Self::build_internal(
+            self.defaults.clone(),
+            &self.lazy_defaults,
+            self.overrides.clone(),
+            &self.state.sources,
+            self.merge.clone(),
+        )
+        .await
+    }
+
+    /// Rebuilds the configuration every `period` and yields each [`Config`] that differs from the
+    /// previously yielded one.
+    ///
+    /// The first item is always the initial build. Build errors are yielded as they occur and do
+    /// not end the stream, so a source that is temporarily unavailable does not stop the watch.
+    /// Must be polled within a Tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn watch(self, period: Duration) -> impl Stream<Item = Result<Config>> + Send {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        futures_util::stream::unfold(
+            (self, interval, None::<Value>),
+            |(builder, mut interval, mut last)| async move {
+                loop {
+                    interval.tick().await;
+
+                    match builder.build_cloned().await {
+                        Ok(config) if last.as_ref() == Some(&config.cache) => continue,
+                        Ok(config) => {
+                            last = Some(config.cache.clone());
+                            return Some((Ok(config), (builder, interval, last)));
+                        }
+                        Err(error) => return Some((Err(error), (builder, interval, last))),
+                    }
+                }
+            },
+        )
+    }
+
+    async fn build_internal(
+        defaults: Map<Expression, Value>,
+        lazy_defaults: &[(Expression, LazyValue)],
+        overrides: Map<Expression, Value>,
+        sources: &[(i32, SourceType)],
+        merge: MergeOptions,
+    ) -> Result<Config> {
+        let mut assembly = Assembly::new(&merge, &defaults);
+
+        let mut collected = match merge.parallel {
+            true => Some(
+                join_all(
+                    sources
+                        .iter()
+                        .enumerate()
+                        .map(|(index, (_, source))| source.collect_layer(index, &merge)),
+                )
+                .await,
+            ),
+            false => None,
+        }
+        .map(Vec::into_iter);
+
+        for (index, (_, source)) in sources.iter().enumerate() {
+            let keep_types = source.keeps_existing_types();
+            if let Some((layer, read)) = collected.as_mut().and_then(Iterator::next) {
+                assembly.add(Some(layer?), keep_types, read)?;
+                continue;
+            }
+
+            let layered = merge.is_layered(keep_types);
+            let mut layer: Value = Map::<String, Value>::new().into();
+            let target = match layered {
+                true => &mut layer,
+                false => &mut assembly.cache,
+            };
+            let (collected, read) = source.collect_to(index, &merge, target).await;
+            collected?;
+            assembly.add(layered.then_some(layer), keep_types, read)?;
+        }
+
+        let Assembly {
+            cache,
+            report,
+            collected,
+            ..
+        } = assembly.finish(&overrides, lazy_defaults)?;
+
+        let config = Config::new(cache)
+            .with_normalizer(merge.normalizer.clone())
+            .with_empty_tables_as_none(merge.empty_tables_as_none)
+            .with_numeric_keys_as_index(merge.numeric_keys_as_index)
+            .with_sort_keys(merge.sort_keys)
+            .with_coerce_strings(merge.coerce_strings)
+            .with_report(report);
+        if !merge.refreshable {
+            return Ok(config);
+        }
+
+        let sources = sources
+            .iter()
+            .map(|(_, source)| source.clone())
+            .zip(collected)
+            .map(|(source, (layer, read))| (source, layer, read))
+            .collect();
+        Ok(config.with_recipe(Recipe {
+            defaults,
+            lazy_defaults: lazy_defaults.to_vec(),
+            overrides,
+            sources,
+            merge,
+        }))
+    }
+}
+
+impl SourceType {
+    fn keeps_existing_types(&self) -> bool {
+        match self {
+            Self::Sync(_, source) => source.keeps_existing_types(),
+            #[cfg(feature = "async")]
+            Self::Async(_) => false,
+        }
+    }
+
+    /// Collect this source, the `index`-th of a build, into `target`, along with what it read
+    async fn collect_to(
+        &self,
+        index: usize,
+        merge: &MergeOptions,
+        target: &mut Value,
+    ) -> (Result<()>, SourceReport) {
+        match self {
+            Self::Sync(_, source) => merge.collect_source(index, || source.collect_to(target)),
+            #[cfg(feature = "async")]
+            Self::Async(source) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(source = index, "collecting async source");
+                perf::add(Counter::Sources, 1);
+                let (collected, read) = merge.collect_async(source.collect_to(target)).await;
+                let collected = collected.map_err(|err| match source.uri() {
+                    Some(uri) => err.with_uri(uri),
+                    None => err,
+                });
+                (collected, read)
+            }
+        }
+    }
+
+    /// Collect this source, the `index`-th of a build, into a layer of its own
+    async fn collect_layer(
+        &self,
+        index: usize,
+        merge: &MergeOptions,
+    ) -> (Result<Value>, SourceReport) {
+        let mut layer: Value = Map::<String, Value>::new().into();
+        let (collected, read) = self.collect_to(index, merge, &mut layer).await;
+        (collected.map(|()| layer), read)
+    }
+}
+
+/// Poll `futures` concurrently, returning their outputs in order
+async fn join_all<F: Future>(futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+    let mut futures: Vec<_> = futures.into_iter().map(Box::pin).collect();
+    let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+    std::future::poll_fn(|cx| {
+        let mut ready = true;
+        for (future, output) in futures.iter_mut().zip(&mut outputs) {
+            if output.is_none() {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(value) => *output = Some(value),
+                    Poll::Pending => ready = false,
+                }
+            }
+        }
+        match ready {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    })
+    .await;
+
+    outputs
+        .into_iter()
+        .map(|output| output.expect("futures were polled to completion"))
+        .collect()
+}
+
+/// Collect each of `sources` into `assembly`, on a pool of at most one thread per available core
+/// if `merge.parallel`
+fn collect_sources(sources: &[SyncSource], assembly: &mut Assembly<'_>) -> Result<()> {
+    let merge = assembly.merge;
+    let workers = std::thread::available_parallelism()
+        .map_or(1, usize::from)
+        .min(sources.len());
+    if !merge.parallel || workers <= 1 {
+        for (index, sync) in sources.iter().enumerate() {
+            let keep_types = sync.source.keeps_existing_types();
+            let layered = merge.is_layered(keep_types);
+            let mut layer: Value = Map::<String, Value>::new().into();
+            let target = match layered {
+                true => &mut layer,
+                false => &mut assembly.cache,
+            };
+            let (collected, read) = merge.collect_source(index, || sync.source.collect_to(target));
+            collected?;
+            assembly.add(layered.then_some(layer), keep_types, read)?;
+        }
+        return Ok(());
+    }
+
+    let collect = |index, sync: &SyncSource| {
+        let mut layer: Value = Map::<String, Value>::new().into();
+        let (collected, read) = merge.collect_source(index, || sync.source.collect_to(&mut layer));
+        (collected.map(|()| layer), read)
+    };
+    let environment = &SharedEnvironment::current();
+    let collect = &collect;
+    let next = &AtomicUsize::new(0);
+    let mut collected: Vec<_> = std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(move || {
+                    environment.share(|| {
+                        let mut collected = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(sync) = sources.get(index) else {
+                                return collected;
+                            };
+                            collected.push((index, collect(index, sync)));
+                        }
+                    })
+                })
+            })
+            .collect();
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap_or_else(|panic| resume_unwind(panic)))
+            .collect()
+    });
+    collected.sort_by_key(|&(index, _)| index);
+
+    for (sync, (_, (layer, read))) in sources.iter().zip(collected) {
+        assembly.add(Some(layer?), sync.source.keeps_existing_types(), read)?;
+    }
+    Ok(())
+}
+
+/// The process environment read by the sources of a build, shared with the threads collecting
+/// them
+struct SharedEnvironment {
+    #[cfg(feature = "env")]
+    vars: crate::env::Snapshot,
+}
+
+impl SharedEnvironment {
+    fn current() -> Self {
+        Self {
+            #[cfg(feature = "env")]
+            vars: crate::env::vars(),
+        }
+    }
+
+    fn share<T>(&self, collect: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "env")]
+        {
+            crate::env::with_snapshot(self.vars.clone(), collect)
+        }
+        #[cfg(not(feature = "env"))]
+        {
+            collect()
+        }
+    }
+}
+
+/// Run `collect`, reading the process environment once for all environment sources
+fn share_environment<T>(collect: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "env")]
+    {
+        crate::env::share_snapshot(collect)
+    }
+    #[cfg(not(feature = "env"))]
+    {
+        collect()
+    }
+}
+
+/// The cache of a [`Config`] being built, along with what its sources read and, when it is
+/// [refreshable](ConfigBuilder::refreshable), what each of them collected
+struct Assembly<'a> {
+    merge: &'a MergeOptions,
+    cache: Value,
+    report: SourceReport,
+    collected: Vec<(Value, SourceReport)>,
+}
+
+impl<'a> Assembly<'a> {
+    /// Start from `defaults`
+    fn new(merge: &'a MergeOptions, defaults: &Map<Expression, Value>) -> Self {
+        let mut cache: Value = Map::<String, Value>::new().into();
+        for (key, val) in path::ordered(defaults) {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(key = %key, "applying default");
+            merge
+                .key(key.clone())
+                .set(&mut cache, merge.value(val.clone()));
+        }
+
+        Self {
+            merge,
+            cache,
+            report: SourceReport::default(),
+            collected: Vec::new(),
+        }
+    }
+
+    /// Add the next source, which collected `layer` on its own, or straight into the cache if
+    /// `None`, and `read` what it collected
+    fn add(&mut self, layer: Option<Value>, keep_types: bool, read: SourceReport) -> Result<()> {
+        if let Some(layer) = layer {
+            if self.merge.refreshable {
+                self.collected.push((layer.clone(), read.clone()));
+            }
+            self.merge.layer(&mut self.cache, layer, keep_types)?;
+        }
+        self.report.append(read);
+        Ok(())
+    }
+
+    /// Apply `overrides` and the `lazy_defaults` nothing else provided, and finish the cache
+    fn finish(
+        mut self,
+        overrides: &Map<Expression, Value>,
+        lazy_defaults: &[(Expression, LazyValue)],
+    ) -> Result<Self> {
+        let merge = self.merge;
+
+        for (key, val) in path::ordered(overrides) {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(key = %key, "applying override");
+            merge
+                .key(key.clone())
+                .set(&mut self.cache, merge.value(val.clone()));
+        }
+
+        for (key, value) in lazy_defaults {
+            let key = merge.key(key.clone());
+            if key.is_vacant(&self.cache) {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(key = %key, "applying lazy default");
+                key.set(&mut self.cache, merge.value((value.0)()));
+            }
+        }
+
+        merge.finish(&mut self.cache)?;
+
+        Ok(self)
+    }
+}
+
+/// What a refreshable [`Config`] was built from, to rebuild it when some of its sources change
+#[derive(Debug, Clone)]
+pub(crate) struct Recipe {
+    defaults: Map<Expression, Value>,
+    lazy_defaults: Vec<(Expression, LazyValue)>,
+    overrides: Map<Expression, Value>,
+    /// Each source, with the layer it last collected and what it read to collect it
+    sources: Vec<(SourceType, Value, SourceReport)>,
+    merge: MergeOptions,
+}
+
+impl Recipe {
+    /// Collect again the [`Source`]s registered as `type_id` and rebuild the cache
+    pub(crate) fn refresh(&mut self, type_id: TypeId) -> Result<Value> {
+        let merge = &self.merge;
+        let collected = share_environment(|| {
+            self.sources
+                .iter()
+                .enumerate()
+                .map(|(index, (source, _, _))| match *source {
+                    SourceType::Sync(id, ref source) if id == type_id => {
+                        let mut layer: Value = Map::<String, Value>::new().into();
+                        let (collected, read) =
+                            merge.collect_source(index, || source.collect_to(&mut layer));
+                        collected.map(|()| Some((layer, read)))
+                    }
+                    _ => Ok(None),
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        for ((_, layer, read), collected) in self.sources.iter_mut().zip(collected) {
+            if let Some(collected) = collected {
+                (*layer, *read) = collected;
+            }
+        }
+
+        self.cache()
+    }
+
+    /// What the sources reported, in the order they were added
+    pub(crate) fn report(&self) -> SourceReport {
+        let mut report = SourceReport::default();
+        for (_, _, read) in &self.sources {
+            report.append(read.clone());
+        }
+        report
+    }
+
+    fn cache(&self) -> Result<Value> {
+        let mut assembly = Assembly::new(&self.merge, &self.defaults);
+        for (source, layer, _) in &self.sources {
+            self.merge.layer(
+                &mut assembly.cache,
+                layer.clone(),
+                source.keeps_existing_types(),
+            )?;
+        }
+        Ok(assembly.finish(&self.overrides, &self.lazy_defaults)?.cache)
+    }
+}
+
+/// A default value computed on demand
+#[derive(Clone)]
+struct LazyValue(Arc<dyn Fn() -> Value + Send + Sync>);
+
+impl std::fmt::Debug for LazyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LazyValue")
+    }
+}
+
+/// How values from successive sources are collected and combined, and read from the built
+/// [`Config`]
+#[derive(Debug, Clone, Default)]
+struct MergeOptions {
+    normalizer: Option<KeyNormalizer>,
+    detect_conflicts: bool,
+    empty_tables_as_none: bool,
+    numeric_keys_as_index: bool,
+    sort_keys: bool,
+    coerce_strings: bool,
+    parallel: bool,
+    refreshable: bool,
+    resolvers: Resolvers,
+    transforms: Vec<(Expression, Transform)>,
+    #[cfg(feature = "fs")]
+    extensions: crate::file::Extensions,
+}
+
+impl MergeOptions {
+    /// Collect a source, along with what it read
+    fn collect<R>(&self, collect: impl FnOnce() -> R) -> (R, SourceReport) {
+        #[cfg(feature = "fs")]
+        {
+            self.extensions.scope(|| track_loaded(collect))
+        }
+        #[cfg(not(feature = "fs"))]
+        {
+            track_loaded(collect)
+        }
+    }
+
+    /// Await `future`, collecting an async source, along with what it read
+    ///
+    /// The source may run on other threads between its polls, so each of them is a collection of
+    /// its own, and sources collected on blocking threads report back to the poll awaiting them.
+    #[cfg(feature = "async")]
+    async fn collect_async<F>(&self, mut future: F) -> (F::Output, SourceReport)
+    where
+        F: Future + Unpin,
+    {
+        let mut report = SourceReport::default();
+        let collected = std::future::poll_fn(|cx| {
+            let (poll, read) = self.collect(|| Pin::new(&mut future).poll(cx));
+            report.append(read);
+            poll
+        })
+        .await;
+        (collected, report)
+    }
+
+    /// Collect the `index`-th source of a build, along with what it read
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn collect_source<T>(
+        &self,
+        index: usize,
+        collect: impl FnOnce() -> Result<T>,
+    ) -> (Result<T>, SourceReport) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(source = index, "collecting source");
+        perf::add(Counter::Sources, 1);
+        let (collected, report) = perf::timed(Counter::CollectNanos, || self.collect(collect));
+        #[cfg(feature = "tracing")]
+        match collected {
+            Ok(_) => {
+                let loaded: Vec<_> = report
+                    .loaded()
+                    .iter()
+                    .filter_map(|info| info.uri())
+                    .collect();
+                let skipped = report.skipped();
+                tracing::debug!(source = index, ?loaded, ?skipped, "collected source");
+            }
+            Err(ref err) => {
+                tracing::debug!(source = index, error = %err, "failed to collect source");
+            }
+        }
+        (collected, report)
+    }
+
+    fn key(&self, mut key: Expression) -> Expression {
+        if self.numeric_keys_as_index {
+            key = key.numeric_keys_as_indices();
+        }
+        match self.normalizer {
+            Some(ref normalizer) => key.normalize(normalizer),
+            None => key,
+        }
+    }
+
+    /// `value` with the keys of its tables [normalized](ConfigBuilder::normalize_keys), for a
+    /// default or an override set at a normalized key
+    fn value(&self, mut value: Value) -> Value {
+        if let Some(ref normalizer) = self.normalizer {
+            normalizer.value(&mut value);
+        }
+        value
+    }
+
+    /// Whether each source, or one that [keeps existing types](Source::keeps_existing_types),
+    /// is collected on its own before being merged with [`Self::layer`]
+    fn is_layered(&self, keep_types: bool) -> bool {
+        self.normalizer.is_some()
+            || self.detect_conflicts
+            || self.parallel
+            || self.refreshable
+            || keep_types
+    }
+
+    /// Resolve, transform and sort the values of `cache` once all of them are merged
+    fn finish(&self, cache: &mut Value) -> Result<()> {
+        self.resolvers.resolve(cache, &mut String::new())?;
+        self.transform(cache)?;
+        #[cfg(feature = "preserve_order")]
+        if self.sort_keys {
+            sort_tables(cache);
+        }
+        perf::add(Counter::Builds, 1);
+
+        #[cfg(feature = "tracing")]
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            let mut keys = 0;
+            path::visit_leaves(cache, &mut |_, _| keys += 1);
+            tracing::debug!(keys, "built configuration");
+        }
+        Ok(())
+    }
+
+    /// Apply the transforms to the values of `cache` they are registered for
+    fn transform(&self, cache: &mut Value) -> Result<()> {
+        for (key, transform) in &self.transforms {
+            let key = self.key(key.clone());
+            let name = key.to_string();
+            if let Some(value) = key.get_mut(cache) {
+                let transformed =
+                    (transform.0)(value.clone()).map_err(|e| e.extend_with_key(&name))?;
+                value.kind = transformed.kind;
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge the values collected from one source into `cache`
+    fn layer(&self, cache: &mut Value, mut layer: Value, keep_types: bool) -> Result<()> {
+        if let Some(ref normalizer) = self.normalizer {
+            normalizer.value(&mut layer);
+        }
+        if keep_types {
+            keep_existing_types(cache, &mut layer, &mut String::new())?;
+        }
+        if self.detect_conflicts {
+            check_conflicts(cache, &layer, &mut String::new())?;
+        }
+        if let ValueKind::Table(table) = layer.kind {
+            for (key, val) in table {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(key, origin = val.origin(), "merging layered key");
+                Expression::root(key).set(cache, val);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sort the keys of the tables of `value`, which only keep an order with `preserve_order`
+#[cfg(feature = "preserve_order")]
+fn sort_tables(value: &mut Value) {
+    match value.kind {
+        ValueKind::Table(ref mut table) => {
+            table.sort_keys();
+            table.values_mut().for_each(sort_tables);
+        }
+        ValueKind::Array(ref mut array) => array.iter_mut().for_each(sort_tables),
+        _ => {}
+    }
+}
+
+/// A transform of the value at a key, registered with [`ConfigBuilder::map_key`]
+#[derive(Clone)]
+struct Transform(Arc<dyn Fn(Value) -> Result<Value> + Send + Sync>);
+
+impl std::fmt::Debug for Transform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Transform")
+    }
+}
+
+/// Resolvers of the URIs starting with `scheme://`
+#[derive(Clone, Default)]
+struct Resolvers(Vec<(String, Arc<dyn Resolver>)>);
+
+impl Resolvers {
+    /// Replace the strings of `value` that are URIs of a registered scheme by their value
+    fn resolve(&self, value: &mut Value, key: &mut String) -> Result<()> {
+        let len = key.len();
+        match value.kind {
+            ValueKind::String(ref uri) => {
+                let resolved = self.0.iter().find_map(|(prefix, resolver)| {
+                    let location = uri.strip_prefix(prefix.as_str())?;
+                    Some(resolver.resolve(location))
+                });
+                if let Some(resolved) = resolved {
+                    value.kind = resolved.map_err(|e| e.extend_with_key(key))?.kind;
+                }
+            }
+            ValueKind::Table(ref mut table) => {
+                for (name, child) in table.iter_mut() {
+                    if !key.is_empty() {
+                        key.push('.');
+                    }
+                    key.push_str(name);
+                    self.resolve(child, key)?;
+                    key.truncate(len);
+                }
+            }
+            ValueKind::Array(ref mut array) => {
+                for (index, child) in array.iter_mut().enumerate() {
+                    key.push_str(&format!("[{index}]"));
+                    self.resolve(child, key)?;
+                    key.truncate(len);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Resolvers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(prefix, _)| prefix))
+            .finish()
+    }
+}
+
+/// Convert the strings of `incoming` to the type of the scalars of `existing` they replace
+fn keep_existing_types(existing: &Value, incoming: &mut Value, key: &mut String) -> Result<()> {
+    let (ValueKind::Table(existing), ValueKind::Table(incoming)) =
+        (&existing.kind, &mut incoming.kind)
+    else {
+        return Ok(());
+    };
+
+    for (name, incoming) in incoming.iter_mut() {
+        let Some(existing) = existing.get(name) else {
+            continue;
+        };
+
+        let len = key.len();
+        if !key.is_empty() {
+            key.push('.');
+        }
+        key.push_str(name);
+
+        if let ValueKind::String(_) = incoming.kind {
+            let string = incoming.clone();
+            let converted = match existing.kind {
+                ValueKind::Boolean(_) => string.into_bool().map(ValueKind::from),
+                ValueKind::I64(_) => string.into_int().map(ValueKind::from),
+                ValueKind::I128(_) => string.into_int128().map(ValueKind::from),
+                ValueKind::U64(_) => string.into_uint().map(ValueKind::from),
+                ValueKind::U128(_) => string.into_uint128().map(ValueKind::from),
+                ValueKind::Float(_) => string.into_float().map(ValueKind::from),
+                _ => Ok(string.kind),
+            };
+            incoming.kind = converted.map_err(|e| e.extend_with_key(key))?;
+        } else {
+            keep_existing_types(existing, incoming, key)?;
+        }
+
+        key.truncate(len);
+    }
+
+    Ok(())
+}
+
+/// Find values of `incoming` that would replace a value of `existing` of another shape
+fn check_conflicts(existing: &Value, incoming: &Value, key: &mut String) -> Result<()> {
+    let (ValueKind::Table(existing), ValueKind::Table(incoming)) = (&existing.kind, &incoming.kind)
+    else {
+        return Ok(());
+    };
+
+    for (name, incoming) in incoming {
+        let Some(existing) = existing.get(name) else {
+            continue;
+        };
+
+        let len = key.len();
+        if !key.is_empty() {
+            key.push('.');
+        }
+        key.push_str(name);
+
+        match (&existing.kind, &incoming.kind) {
+            (ValueKind::Table(_), ValueKind::Table(_)) => check_conflicts(existing, incoming, key)?,
+            (ValueKind::Nil, _) | (_, ValueKind::Nil) => {}
+            (ValueKind::Array(_), ValueKind::Array(_)) => {}
+            (ValueKind::Table(_) | ValueKind::Array(_), _)
+            | (_, ValueKind::Table(_) | ValueKind::Array(_)) => {
+                return Err(ConfigError::MergeConflict {
+                    key: key.clone(),
+                    existing_origin: existing.origin().map(Into::into),
+                    existing_type: existing.kind.type_name(),
+                    incoming_origin: incoming.origin().map(Into::into),
+                    incoming_type: incoming.kind.type_name(),
+                });
+            }
+            _ => {}
+        }
+
+        key.truncate(len);
+    }
+
+    Ok(())
+}
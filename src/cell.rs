@@ -0,0 +1,175 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::config::Config;
+#[cfg(feature = "tokio")]
+use crate::error::{ConfigError, Result};
+
+/// A shared, reloadable [`Config`] with a generation counter
+///
+/// Readers [`load`](Self::load) the current [`Config`] and keep the [`Arc`] as long as they
+/// need a consistent view, while a reloader, e.g. fed by `ConfigBuilder::watch`, replaces it
+/// with [`swap`](Self::swap). Each swap increments the [`generation`](Self::generation), which
+/// readers can compare with the one they loaded to detect a stale [`Config`] without locking.
+///
+/// ```rust
+/// # use config::*;
+/// let cell = ConfigCell::new(Config::builder().set_default("workers", 4)?.build()?);
+/// let (config, generation) = cell.load_with_generation();
+/// assert_eq!(config.get_int("workers")?, 4);
+///
+/// cell.swap(Config::builder().set_default("workers", 8)?.build()?);
+/// assert_ne!(cell.generation(), generation);
+/// assert_eq!(config.get_int("workers")?, 4);
+/// assert_eq!(cell.load().get_int("workers")?, 8);
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct ConfigCell {
+    config: RwLock<Arc<Config>>,
+    generation: AtomicU64,
+}
+
+impl ConfigCell {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config: RwLock::new(Arc::new(config)),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// The current [`Config`]
+    pub fn load(&self) -> Arc<Config> {
+        self.load_with_generation().0
+    }
+
+    /// The current [`Config`] along with its generation
+    pub fn load_with_generation(&self) -> (Arc<Config>, u64) {
+        let config = self.config.read().unwrap_or_else(PoisonError::into_inner);
+        // Swaps increment the generation while holding the lock for writing
+        (config.clone(), self.generation.load(Ordering::Acquire))
+    }
+
+    /// The number of times the [`Config`] was swapped
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Replace the [`Config`], returning the previous one
+    ///
+    /// [`Arc`]s loaded before keep the previous values.
+    pub fn swap(&self, config: Config) -> Arc<Config> {
+        let mut current = self.config.write().unwrap_or_else(PoisonError::into_inner);
+        let previous = std::mem::replace(&mut *current, Arc::new(config));
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        previous
+    }
+}
+
+impl From<Config> for ConfigCell {
+    fn from(config: Config) -> Self {
+        Self::new(config)
+    }
+}
+
+/// A shared, reloadable [`Config`] whose updates async tasks can await
+///
+/// This is the async counterpart of [`ConfigCell`], built on [`tokio::sync::watch`]. Tasks
+/// [`subscribe`](Self::subscribe) to get a receiver, and await
+/// [`changed`](tokio::sync::watch::Receiver::changed) on it to be woken when the [`Config`] is
+/// [`swap`](Self::swap)ped. [`follow`](Self::follow) swaps in each [`Config`] of a stream like
+/// the one of [`ConfigBuilder::watch`](crate::ConfigBuilder::watch).
+///
+/// Clones share the same [`Config`].
+///
+/// ```rust
+/// # use config::*;
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), ConfigError> {
+/// let cell = AsyncConfigCell::new(Config::builder().set_default("workers", 4)?.build()?);
+/// let mut updates = cell.subscribe();
+///
+/// let worker = tokio::spawn(async move {
+///     updates.changed().await.unwrap();
+///     updates.borrow_and_update().get_int("workers")
+/// });
+///
+/// cell.swap(Config::builder().set_default("workers", 8)?.build()?);
+/// assert_eq!(worker.await.unwrap()?, 8);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone)]
+pub struct AsyncConfigCell {
+    sender: tokio::sync::watch::Sender<Arc<Config>>,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncConfigCell {
+    pub fn new(config: Config) -> Self {
+        Self {
+            sender: tokio::sync::watch::Sender::new(Arc::new(config)),
+        }
+    }
+
+    /// The current [`Config`]
+    pub fn load(&self) -> Arc<Config> {
+        self.sender.borrow().clone()
+    }
+
+    /// A receiver notified of each [`Config`] swapped in from now on
+    ///
+    /// The current [`Config`] is marked as seen, so [`changed`](tokio::sync::watch::Receiver::changed)
+    /// waits for the next swap.
+    pub fn subscribe(&self) -> tokio::sync::watch::Receiver<Arc<Config>> {
+        self.sender.subscribe()
+    }
+
+    /// Replace the [`Config`] and wake the subscribers, returning the previous one
+    ///
+    /// [`Arc`]s loaded before keep the previous values.
+    pub fn swap(&self, config: Config) -> Arc<Config> {
+        self.sender.send_replace(Arc::new(config))
+    }
+
+    /// Swap in each [`Config`] yielded by `updates`, until it ends
+    ///
+    /// Errors are passed to `on_error` and leave the current [`Config`] in place, so a reload
+    /// that fails, e.g. while a file is being written, does not interrupt the others. This is
+    /// meant to be spawned as a task:
+    ///
+    /// ```rust,no_run
+    /// # use config::{*, builder::AsyncState};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), ConfigError> {
+    /// let builder = ConfigBuilder::<AsyncState>::default().add_source(File::with_name("settings"));
+    /// let cell = AsyncConfigCell::new(builder.build_cloned().await?);
+    ///
+    /// let reloader = cell.clone();
+    /// tokio::spawn(async move {
+    ///     let updates = builder.watch(Duration::from_secs(5));
+    ///     reloader.follow(updates, |error| log::warn!("reload failed: {error}")).await;
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn follow<S, F>(&self, updates: S, mut on_error: F)
+    where
+        S: futures_util::Stream<Item = Result<Config>>,
+        F: FnMut(ConfigError),
+    {
+        use futures_util::StreamExt as _;
+
+        let mut updates = std::pin::pin!(updates);
+        while let Some(update) = updates.next().await {
+            match update {
+                Ok(config) => {
+                    self.swap(config);
+                }
+                Err(error) => on_error(error),
+            }
+        }
+    }
+}
@@ -1,25 +1,72 @@
+use std::any::TypeId;
 use std::fmt::Debug;
+use std::sync::Arc;
 
 use serde_core::de::Deserialize;
 use serde_core::ser::Serialize;
 
-use crate::builder::{ConfigBuilder, DefaultState};
+use crate::builder::{ConfigBuilder, DefaultState, Recipe};
+use crate::de;
 use crate::error::{ConfigError, Result};
 use crate::map::Map;
+use crate::normalize::KeyNormalizer;
 use crate::path;
+use crate::perf::{self, Counter};
+use crate::redact::{self, RedactionRules};
+use crate::render::{self, RenderFormat};
 use crate::ser::ConfigSerializer;
-use crate::source::Source;
-use crate::value::{Table, Value};
+use crate::source::{Source, SourceInfo, SourceReport, set_value};
+use crate::stats::ConfigStats;
+use crate::value::{Table, Value, ValueKind};
 
 /// A prioritized configuration repository.
 ///
 /// It maintains a set of configuration sources, fetches values to populate those, and provides
 /// them according to the source's priority.
+///
+/// # Composing configurations
+///
+/// A built [`Config`] is a [`Source`] too, which lets configurations be built in stages, e.g. a
+/// base built once and layered under the sources of each service or tenant. Its values keep
+/// their origin. Building copies its values into the new configuration, like those of any other
+/// source; add it as a [`SharedConfig`] so that the builders holding it, and their clones, share
+/// one copy of it until then:
+///
+/// ```rust
+/// # use config::*;
+/// let base = SharedConfig::from(
+///     Config::builder()
+///         .set_default("server.port", 80)?
+///         .set_default("server.host", "localhost")?
+///         .build()?,
+/// );
+///
+/// let api = Config::builder()
+///     .add_source(base.clone())
+///     .set_override("server.port", 8080)?
+///     .build()?;
+/// let admin = Config::builder().add_source(base).build()?;
+///
+/// assert_eq!(api.get_int("server.port")?, 8080);
+/// assert_eq!(api.get_string("server.host")?, "localhost");
+/// assert_eq!(admin.get_int("server.port")?, 80);
+/// # Ok::<(), ConfigError>(())
+/// ```
 #[derive(Clone, Debug)]
 pub struct Config {
     defaults: Map<path::Expression, Value>,
     overrides: Map<path::Expression, Value>,
     sources: Vec<Box<dyn Source + Send + Sync>>,
+    normalizer: Option<KeyNormalizer>,
+    empty_tables_as_none: bool,
+    numeric_keys_as_index: bool,
+    sort_keys: bool,
+    coerce_strings: bool,
+    recipe: Option<Arc<Recipe>>,
+    report: SourceReport,
+
+    /// Subtrees made read-only by [`freeze_subtree`](Self::freeze_subtree), with their value
+    frozen: Vec<(path::Expression, Option<Value>)>,
 
     /// Root of the cached configuration.
     pub cache: Value,
@@ -31,6 +78,14 @@ impl Default for Config {
             defaults: Default::default(),
             overrides: Default::default(),
             sources: Default::default(),
+            normalizer: None,
+            empty_tables_as_none: false,
+            numeric_keys_as_index: false,
+            sort_keys: false,
+            coerce_strings: false,
+            recipe: None,
+            report: SourceReport::default(),
+            frozen: Vec::new(),
             cache: Value::new(None, Table::new()),
         }
     }
@@ -44,22 +99,131 @@ impl Config {
         }
     }
 
+    pub(crate) fn with_normalizer(mut self, normalizer: Option<KeyNormalizer>) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    pub(crate) fn with_empty_tables_as_none(mut self, enabled: bool) -> Self {
+        self.empty_tables_as_none = enabled;
+        self
+    }
+
+    pub(crate) fn with_numeric_keys_as_index(mut self, enabled: bool) -> Self {
+        self.numeric_keys_as_index = enabled;
+        self
+    }
+
+    pub(crate) fn with_sort_keys(mut self, enabled: bool) -> Self {
+        self.sort_keys = enabled;
+        self
+    }
+
+    pub(crate) fn with_coerce_strings(mut self, enabled: bool) -> Self {
+        self.coerce_strings = enabled;
+        self
+    }
+
+    /// The options the values of this config are deserialized with
+    pub(crate) fn deserialize_options(&self) -> de::Options {
+        de::Options {
+            empty_tables_as_none: self.empty_tables_as_none,
+            sort_keys: self.sort_keys,
+            coerce_strings: self.coerce_strings,
+            ..de::Options::default()
+        }
+    }
+
+    /// A deserializer of `value`, found in this config, with `options`
+    fn deserializer(value: Value, options: de::Options) -> de::ValueDeserializer {
+        perf::add(Counter::Deserializations, 1);
+        de::ValueDeserializer::new(value, options)
+    }
+
+    pub(crate) fn with_report(mut self, report: SourceReport) -> Self {
+        self.report = report;
+        self
+    }
+
+    pub(crate) fn with_recipe(mut self, recipe: Recipe) -> Self {
+        self.recipe = Some(Arc::new(recipe));
+        self
+    }
+
+    /// Parse `key`, normalizing it like the keys of the cache
+    fn parse_key(&self, key: &str) -> Result<path::Expression> {
+        let mut expr: path::Expression = key.parse()?;
+        if self.numeric_keys_as_index {
+            expr = expr.numeric_keys_as_indices();
+        }
+        Ok(match self.normalizer {
+            Some(ref normalizer) => expr.normalize(normalizer),
+            None => expr,
+        })
+    }
+
     /// Creates new [`ConfigBuilder`] instance
     pub fn builder() -> ConfigBuilder<DefaultState> {
         ConfigBuilder::<DefaultState>::default()
     }
 
+    /// Build the configuration of the application `app_name` following common conventions, for
+    /// applications without specific needs.
+    ///
+    /// Merges, in order:
+    ///
+    /// 1. `config/default`,
+    /// 2. `config/{RUN_MODE}`, if the `RUN_MODE` environment variable is set, e.g. to `production`,
+    /// 3. `config/local`, for overrides kept out of version control,
+    /// 4. the environment variables prefixed with `app_name` in upper case, with `-` replaced by
+    ///    `_` and `__` separating nested keys, like `MY_APP__SERVER__PORT` for `my-app`.
+    ///
+    /// Files are looked up in the working directory, with the extension of any enabled format,
+    /// like `config/default.toml` or `config/default.yaml`, and are optional.
+    ///
+    /// ```rust,no_run
+    /// # use config::*;
+    /// let config = Config::auto(env!("CARGO_PKG_NAME"))?;
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if `RUN_MODE` is not a single path component, or if building fails, e.g. on a file
+    /// that does not parse.
+    #[cfg(all(feature = "fs", feature = "env"))]
+    pub fn auto(app_name: &str) -> Result<Self> {
+        use crate::{Environment, File};
+
+        let mut builder =
+            Self::builder().add_source(File::with_name("config/default").required(false));
+        if let Some(mode) = std::env::var("RUN_MODE")
+            .ok()
+            .filter(|mode| !mode.is_empty())
+        {
+            let vars = Map::from_iter([("mode".to_owned(), mode)]);
+            builder =
+                builder.add_source(File::with_name_tpl("config/{mode}", &vars)?.required(false));
+        }
+
+        let prefix = app_name.to_uppercase().replace('-', "_");
+        builder
+            .add_source(File::with_name("config/local").required(false))
+            .add_source(Environment::with_prefix(&prefix).separator("__"))
+            .build()
+    }
+
     /// Refresh the configuration cache with fresh
     /// data from added sources.
     ///
     /// Configuration is automatically refreshed after a mutation
     /// operation (`set`, `merge`, `set_default`, etc.).
     fn refresh(&mut self) -> Result<&mut Self> {
-        self.cache = {
+        let cache = {
             let mut cache: Value = Map::<String, Value>::new().into();
 
             // Add defaults
-            for (key, val) in &self.defaults {
+            for (key, val) in path::ordered(&self.defaults) {
                 key.set(&mut cache, val.clone());
             }
 
@@ -67,16 +231,115 @@ impl Config {
             self.sources.collect_to(&mut cache)?;
 
             // Add overrides
-            for (key, val) in &self.overrides {
+            for (key, val) in path::ordered(&self.overrides) {
                 key.set(&mut cache, val.clone());
             }
 
             cache
         };
+        self.check_frozen(&cache)?;
+        self.cache = cache;
 
         Ok(self)
     }
 
+    /// Collect again the sources of type `S` and update the configuration with their values.
+    ///
+    /// Other sources are not read again, their previously collected values are merged in the
+    /// same order as when building. This suits values that change at runtime, like feature flags
+    /// passed through the [`Environment`](crate::Environment), where rebuilding would read every
+    /// file again.
+    ///
+    /// Only configurations built with [`ConfigBuilder::refreshable`] keep their sources. The
+    /// [`Source`]s of an async build are collected again as well, on the current thread.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "env")]
+    /// # {
+    /// let mut config = Config::builder()
+    ///     .set_default("flags.beta", false)?
+    ///     .add_source(Environment::with_prefix("REFRESH").separator("__"))
+    ///     .refreshable(true)
+    ///     .build()?;
+    ///
+    /// # unsafe { std::env::set_var("REFRESH__FLAGS__BETA", "true") };
+    /// // ... REFRESH__FLAGS__BETA=true is set by an orchestrator
+    /// config.refresh_source::<Environment>()?;
+    /// assert!(config.get_bool("flags.beta")?);
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if the configuration was not built [refreshable](ConfigBuilder::refreshable), if
+    /// collecting a source or merging its values fails, or if the values of a
+    /// [frozen subtree](Self::freeze_subtree) would change, leaving the configuration unchanged.
+    pub fn refresh_source<S: Source + 'static>(&mut self) -> Result<&mut Self> {
+        let Some(ref recipe) = self.recipe else {
+            return Err(ConfigError::Message(
+                "configuration keeps no sources to refresh, build it with `refreshable(true)`"
+                    .to_owned(),
+            ));
+        };
+
+        let mut refreshed = Recipe::clone(recipe);
+        let cache = refreshed.refresh(TypeId::of::<S>())?;
+        self.check_frozen(&cache)?;
+        self.cache = cache;
+        self.report = refreshed.report();
+        self.recipe = Some(Arc::new(refreshed));
+        Ok(self)
+    }
+
+    /// Make the values at `key` and below read-only, while the rest of the configuration can
+    /// still change.
+    ///
+    /// Later mutations changing them, like [`refresh_source`](Self::refresh_source) collecting
+    /// another value from the environment, fail with [`ConfigError::Frozen`] and leave the
+    /// configuration unchanged. This guards security-critical settings against code paths
+    /// reloading the configuration at runtime. A missing key is frozen as missing.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "env")]
+    /// # {
+    /// let mut config = Config::builder()
+    ///     .set_default("security.tls", true)?
+    ///     .add_source(Environment::with_prefix("FROZEN").separator("__"))
+    ///     .refreshable(true)
+    ///     .build()?;
+    /// config.freeze_subtree("security")?;
+    ///
+    /// # unsafe { std::env::set_var("FROZEN__SECURITY__TLS", "false") };
+    /// // ... FROZEN__SECURITY__TLS=false is set by some other code
+    /// assert!(config.refresh_source::<Environment>().is_err());
+    /// assert!(config.get_bool("security.tls")?);
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if `key` is not a valid path.
+    pub fn freeze_subtree(&mut self, key: &str) -> Result<&mut Self> {
+        let expr = self.parse_key(key)?;
+        let value = expr.clone().get(&self.cache).cloned();
+        self.frozen.push((expr, value));
+        Ok(self)
+    }
+
+    /// Fail if `cache` changes the values of a frozen subtree
+    fn check_frozen(&self, cache: &Value) -> Result<()> {
+        for (expr, value) in &self.frozen {
+            if expr.clone().get(cache) != value.as_ref() {
+                return Err(ConfigError::Frozen.extend_with_key(&expr.to_string()));
+            }
+        }
+        Ok(())
+    }
+
     /// Set an overwrite
     ///
     /// This function sets an overwrite value.
@@ -84,31 +347,64 @@ impl Config {
     ///
     /// # Warning
     ///
-    /// Errors if config is frozen
+    /// Errors if the value of a frozen subtree would change
     pub(crate) fn set<T>(&mut self, key: &str, value: T) -> Result<&mut Self>
     where
         T: Into<Value>,
     {
-        self.overrides.insert(key.parse()?, value.into());
+        let key = self.parse_key(key)?;
+        let overrides = self.overrides.clone();
+        path::insert(&mut self.overrides, key, value.into());
 
-        self.refresh()
+        if let Err(error) = self.refresh() {
+            self.overrides = overrides;
+            return Err(error);
+        }
+        Ok(self)
     }
 
     fn get_value(&self, key: &str) -> Result<Value> {
+        perf::add(Counter::Lookups, 1);
         // Parse the key into a path expression
-        let expr: path::Expression = key.parse()?;
+        let expr = self.parse_key(key)?;
 
         // Traverse the cache using the path to (possibly) retrieve a value
-        let value = expr.get(&self.cache).cloned();
+        let value = expr.clone().get(&self.cache).cloned();
 
-        value.ok_or_else(|| ConfigError::NotFound(key.into()))
+        value.ok_or_else(|| ConfigError::not_found_similar(key, expr.suggest(&self.cache)))
     }
 
     pub fn get<'de, T: Deserialize<'de>>(&self, key: &str) -> Result<T> {
-        self.get_value(key).and_then(|value| {
-            // Deserialize the received value into the requested type
+        self.get_with(key, self.deserialize_options())
+    }
+
+    /// Like [`get`](Self::get), except that every field of the structs deserialized must be set,
+    /// see [`try_deserialize_strict`](Self::try_deserialize_strict).
+    pub fn get_strict<'de, T: Deserialize<'de>>(&self, key: &str) -> Result<T> {
+        let options = de::Options {
+            strict: true,
+            ..self.deserialize_options()
+        };
+        self.get_with(key, options)
+    }
+
+    fn get_with<'de, T: Deserialize<'de>>(&self, key: &str, options: de::Options) -> Result<T> {
+        let value = Self::deserializer(self.get_value(key)?, options);
+        // Deserialize the received value into the requested type
+        #[cfg(not(feature = "path-to-error"))]
+        {
             T::deserialize(value).map_err(|e| e.extend_with_key(key))
-        })
+        }
+        // Keep the path below `key` to the value that failed
+        #[cfg(feature = "path-to-error")]
+        {
+            de::deserialize_tracked(value).map_err(|e| match e {
+                ConfigError::NotFound(_) | ConfigError::NotFoundWithSuggestions { .. } => {
+                    e.extend_with_key(key)
+                }
+                e => e.prepend_key(key),
+            })
+        }
     }
 
     pub fn get_string(&self, key: &str) -> Result<String> {
@@ -121,6 +417,21 @@ impl Config {
             .and_then(|value| value.into_int().map_err(|e| e.extend_with_key(key)))
     }
 
+    pub fn get_int128(&self, key: &str) -> Result<i128> {
+        self.get_value(key)
+            .and_then(|value| value.into_int128().map_err(|e| e.extend_with_key(key)))
+    }
+
+    pub fn get_uint(&self, key: &str) -> Result<u64> {
+        self.get_value(key)
+            .and_then(|value| value.into_uint().map_err(|e| e.extend_with_key(key)))
+    }
+
+    pub fn get_uint128(&self, key: &str) -> Result<u128> {
+        self.get_value(key)
+            .and_then(|value| value.into_uint128().map_err(|e| e.extend_with_key(key)))
+    }
+
     pub fn get_float(&self, key: &str) -> Result<f64> {
         self.get_value(key)
             .and_then(|value| value.into_float().map_err(|e| e.extend_with_key(key)))
@@ -141,9 +452,330 @@ impl Config {
             .and_then(|value| value.into_array().map_err(|e| e.extend_with_key(key)))
     }
 
+    /// The value at `key` rendered as text in `format`, whatever its type, e.g. to log a table
+    /// or fill a template with it.
+    ///
+    /// Unlike [`get_string`](Self::get_string), tables and arrays are rendered rather than
+    /// rejected, and strings are quoted as the format requires.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .set_default("server.hosts", vec!["a", "b"])?
+    ///     .set_default("server.port", 8080)?
+    ///     .build()?;
+    ///
+    /// assert_eq!(
+    ///     config.get_rendered("server", RenderFormat::Json)?,
+    ///     r#"{"hosts":["a","b"],"port":8080}"#
+    /// );
+    /// assert_eq!(config.get_rendered("server.port", RenderFormat::Json)?, "8080");
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if `key` is invalid or missing.
+    pub fn get_rendered(&self, key: &str, format: RenderFormat) -> Result<String> {
+        self.get_value(key)
+            .map(|value| render::render(&value, format))
+    }
+
+    /// Attempt to deserialize the section at `key` into the requested type.
+    ///
+    /// A section that is missing or explicitly nil yields `None` rather than an error, so
+    /// independent subsystems can own optional parts of the configuration. Unlike
+    /// [`get`](Self::get), keys in deserialization errors are reported relative to the root
+    /// of the configuration, e.g. `server.port` rather than `port`.
+    pub fn get_section<'de, T: Deserialize<'de>>(&self, key: &str) -> Result<Option<T>> {
+        perf::add(Counter::Lookups, 1);
+        let expr = self.parse_key(key)?;
+
+        match expr.get(&self.cache) {
+            Some(value) if value.kind != ValueKind::Nil => {
+                let value = Self::deserializer(value.clone(), self.deserialize_options());
+                de::deserialize_tracked(value)
+                    .map(Some)
+                    .map_err(|e| e.prepend_key(key))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The table at `key` as a standalone [`Config`].
+    ///
+    /// Values keep their origins, and keys of the returned config are relative to `key`. This is
+    /// useful to hand a section of the configuration to a library that takes a [`Config`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `key` is missing or does not hold a table.
+    pub fn sub_config(&self, key: &str) -> Result<Config> {
+        let table = self.get_table(key)?;
+        Ok(Config::new(Value::new(None, table))
+            .with_normalizer(self.normalizer.clone())
+            .with_empty_tables_as_none(self.empty_tables_as_none)
+            .with_numeric_keys_as_index(self.numeric_keys_as_index)
+            .with_sort_keys(self.sort_keys)
+            .with_coerce_strings(self.coerce_strings))
+    }
+
+    /// Whether a value is set at `key`, including a nil one.
+    ///
+    /// Unlike [`get`](Self::get), a missing key is not an error, and the value is not
+    /// deserialized. An invalid key is never set.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.parse_key(key)
+            .is_ok_and(|expr| expr.get(&self.cache).is_some())
+    }
+
+    /// Paths of the values under `prefix`, sorted, or of every value if `prefix` is empty.
+    ///
+    /// Paths are relative to the root, like `server.port` or `server.hosts[0]`, and can be passed
+    /// to [`get`](Self::get). Scalars, empty tables and empty arrays have a path, tables and
+    /// arrays holding values only have those of their values. A missing or invalid `prefix`
+    /// yields no paths, and a `prefix` holding a scalar yields itself.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .set_default("server.host", "localhost")?
+    ///     .set_default("server.ports", vec![80, 443])?
+    ///     .set_default("debug", false)?
+    ///     .build()?;
+    ///
+    /// assert_eq!(
+    ///     config.keys_with_prefix("server"),
+    ///     ["server.host", "server.ports[0]", "server.ports[1]"],
+    /// );
+    /// assert!(config.keys_with_prefix("client").is_empty());
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let value = if prefix.is_empty() {
+            Some(&self.cache)
+        } else {
+            self.parse_key(prefix)
+                .ok()
+                .and_then(|expr| expr.get(&self.cache))
+        };
+
+        let mut keys = Vec::new();
+        if let Some(value) = value {
+            path::visit_leaves(value, &mut |path, _| {
+                keys.push(match (prefix, path) {
+                    (prefix, "") => prefix.to_owned(),
+                    ("", path) => path.to_owned(),
+                    (prefix, path) if path.starts_with('[') => format!("{prefix}{path}"),
+                    (prefix, path) => format!("{prefix}.{path}"),
+                });
+            });
+        }
+        // The root has no path of its own
+        keys.retain(|key| !key.is_empty());
+        keys
+    }
+
+    /// Names of the top-level keys holding a table.
+    ///
+    /// Each of them can be loaded with [`get_section`](Self::get_section). They are sorted if
+    /// the configuration was built with
+    /// [`sort_keys`](crate::builder::ConfigBuilder::sort_keys).
+    pub fn sections(&self) -> Vec<&str> {
+        let mut sections: Vec<&str> = match self.cache.kind {
+            ValueKind::Table(ref table) => table
+                .iter()
+                .filter(|(_, value)| matches!(value.kind, ValueKind::Table(_)))
+                .map(|(key, _)| key.as_str())
+                .collect(),
+            _ => Vec::new(),
+        };
+        if self.sort_keys {
+            sections.sort_unstable();
+        }
+        sections
+    }
+
+    /// What the sources read when the configuration was built, in the order they were added.
+    ///
+    /// Compare the [modification time](SourceInfo::modified) or the
+    /// [hash of the contents](SourceInfo::content_hash) of each file with what is on disk to
+    /// decide whether to rebuild, or log where the configuration was loaded from.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// let config = Config::builder()
+    ///     .add_source(File::from_str("port = 8080", FileFormat::Toml))
+    ///     .build()?;
+    ///
+    /// for source in config.sources() {
+    ///     println!("loaded {:?}, modified at {:?}", source.uri(), source.modified());
+    /// }
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn sources(&self) -> &[SourceInfo] {
+        self.report.loaded()
+    }
+
+    /// What the sources read, and the files they skipped or failed to load without failing the
+    /// build, when the configuration was built.
+    pub fn source_report(&self) -> &SourceReport {
+        &self.report
+    }
+
+    /// Render every value as a `key = value` line, masking those selected by `rules`.
+    ///
+    /// Keys are sorted so the output is stable, making it suitable for logging the resolved
+    /// configuration at startup.
+    pub fn to_redacted_string(&self, rules: &RedactionRules) -> String {
+        redact::render(&self.cache, rules)
+    }
+
+    /// The number of keys, the nesting depth and the approximate memory of the values.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .set_default("server.host", "localhost")?
+    ///     .set_default("server.ports", vec![80, 443])?
+    ///     .set_default("debug", false)?
+    ///     .build()?;
+    ///
+    /// let stats = config.stats();
+    /// assert_eq!(stats.keys(), 4);
+    /// assert_eq!(stats.depth(), 2);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn stats(&self) -> ConfigStats {
+        crate::stats::stats(&self.cache)
+    }
+
+    /// Release the capacity of the strings, tables and arrays of the values beyond their length.
+    ///
+    /// Merging sources leaves tables larger than needed, which adds up when holding many
+    /// configurations, like one per tenant, for the lifetime of a process.
+    pub fn shrink_to_fit(&mut self) {
+        crate::stats::shrink_to_fit(&mut self.cache);
+    }
+
+    /// A SHA-256 fingerprint of the configuration values, as 64 lowercase hex digits.
+    ///
+    /// The fingerprint is stable across runs, platforms and crate features: it does not depend on
+    /// the order keys were set in nor on where values came from, so it can be logged to compare
+    /// the configuration of deployments and detect drift. Integers of different widths hash the
+    /// same, while an integer and a string holding its digits do not.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let a = Config::builder()
+    ///     .set_default("port", 8080)?
+    ///     .set_default("host", "localhost")?
+    ///     .build()?;
+    /// let b = Config::builder()
+    ///     .set_override("host", "localhost")?
+    ///     .set_override("port", 8080u16)?
+    ///     .build()?;
+    ///
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    #[cfg(feature = "fingerprint")]
+    pub fn fingerprint(&self) -> String {
+        crate::fingerprint::fingerprint(&self.cache)
+    }
+
     /// Attempt to deserialize the entire configuration into the requested type.
+    ///
+    /// With the `path-to-error` feature, errors the crate cannot attribute to a key itself, like
+    /// those raised by a custom [`Deserialize`] impl or while deserializing a map key, name the
+    /// path to the value that failed as tracked by [`serde_path_to_error`].
+    ///
+    /// [`serde_path_to_error`]: https://docs.rs/serde_path_to_error
     pub fn try_deserialize<'de, T: Deserialize<'de>>(self) -> Result<T> {
-        T::deserialize(self)
+        let options = self.deserialize_options();
+        de::deserialize_tracked(Self::deserializer(self.cache, options))
+    }
+
+    /// Attempt to deserialize the entire configuration, coercing values to the shape of the fields.
+    ///
+    /// Like [`try_deserialize`](Self::try_deserialize), except that a single value deserializes
+    /// into a sequence of one element and a one-element array into a single value. A `hosts`
+    /// field of type `Vec<String>` accepts `hosts = "a"` as well as `hosts = ["a"]`.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// #[derive(serde::Deserialize)]
+    /// struct Settings {
+    ///     hosts: Vec<String>,
+    ///     port: u16,
+    /// }
+    ///
+    /// let settings: Settings = Config::builder()
+    ///     .set_default("hosts", "localhost")?
+    ///     .set_default("port", vec![8080])?
+    ///     .build()?
+    ///     .try_deserialize_lenient()?;
+    ///
+    /// assert_eq!(settings.hosts, ["localhost"]);
+    /// assert_eq!(settings.port, 8080);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn try_deserialize_lenient<'de, T: Deserialize<'de>>(self) -> Result<T> {
+        let options = de::Options {
+            lenient: true,
+            ..self.deserialize_options()
+        };
+        de::deserialize_tracked(Self::deserializer(self.cache, options))
+    }
+
+    /// Attempt to deserialize the entire configuration, requiring every field to be set.
+    ///
+    /// By default a field missing from the configuration falls back like serde does: an
+    /// `Option` field is `None` and a field marked `#[serde(default)]` gets its default. Here
+    /// every field of the structs deserialized must be set, so a key dropped from a file is an
+    /// error rather than silently defaulted. An explicit null, like `null` in JSON and YAML or
+    /// `None` in RON, still deserializes as `None`.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// #[derive(Debug, serde::Deserialize)]
+    /// struct Settings {
+    ///     port: u16,
+    ///     proxy: Option<String>,
+    /// }
+    ///
+    /// let config = Config::builder()
+    ///     .set_default("port", 80)?
+    ///     .set_default("proxy", None::<String>)?
+    ///     .build()?;
+    /// let settings: Settings = config.try_deserialize_strict()?;
+    /// assert_eq!(settings.proxy, None);
+    ///
+    /// let config = Config::builder().set_default("port", 80)?.build()?;
+    /// let error = config.try_deserialize_strict::<Settings>().unwrap_err();
+    /// assert_eq!(error.to_string(), "missing configuration field \"proxy\"");
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn try_deserialize_strict<'de, T: Deserialize<'de>>(self) -> Result<T> {
+        let options = de::Options {
+            strict: true,
+            ..self.deserialize_options()
+        };
+        de::deserialize_tracked(Self::deserializer(self.cache, options))
+    }
+
+    /// Attempt to deserialize the entire configuration, also returning the keys it ignored.
+    ///
+    /// A key is reported when it belongs to a table deserialized into a struct that has no
+    /// matching field, e.g. a misspelled option. Keys are rendered as paths like `server.prot`
+    /// and sorted. Tables deserialized into maps, including `#[serde(flatten)]` fields, consume
+    /// all of their keys.
+    pub fn try_deserialize_with_unused<'de, T: Deserialize<'de>>(self) -> Result<(T, Vec<String>)> {
+        let options = self.deserialize_options();
+        de::deserialize_with_unused(Self::deserializer(self.cache, options))
     }
 
     /// Attempt to serialize the entire configuration from the given type.
@@ -152,14 +784,93 @@ impl Config {
         from.serialize(&mut serializer)?;
         Ok(serializer.output)
     }
+
+    /// Collect key/value pairs into a [`Config`], parsing keys as paths like `server.ports[0]`.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::try_from_iter([("server.host", "localhost"), ("server.ports[0]", "80")])?;
+    /// assert_eq!(config.get_string("server.host")?, "localhost");
+    /// assert_eq!(config.get_int("server.ports[0]")?, 80);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if a key is not a valid path.
+    pub fn try_from_iter<I, K, V>(pairs: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<Value>,
+    {
+        let mut cache = Value::new(None, Table::new());
+        for (key, value) in pairs {
+            let expr: path::Expression = key.as_ref().parse()?;
+            expr.set(&mut cache, value.into());
+        }
+        Ok(Self::new(cache))
+    }
 }
 
 impl Source for Config {
+    /// Clone the values of this [`Config`] into a [`SharedConfig`], so that further clones, e.g.
+    /// by [`ConfigBuilder::build_cloned`], share them
     fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
-        Box::new((*self).clone())
+        Box::new(SharedConfig::from(Config::new(self.cache.clone())))
     }
 
     fn collect(&self) -> Result<Map<String, Value>> {
         self.cache.clone().into_table()
     }
+
+    /// Copy the values of this [`Config`] into `cache` one key at a time, without collecting
+    /// them into a table first
+    fn collect_to(&self, cache: &mut Value) -> Result<()> {
+        let ValueKind::Table(ref table) = self.cache.kind else {
+            return self.collect().map(drop);
+        };
+        for (key, value) in table {
+            set_value(cache, key.clone(), value.clone());
+        }
+        Ok(())
+    }
+}
+
+/// A [`Config`] used as a [`Source`], shared by its clones rather than copied
+///
+/// Use it to layer a [`Config`] under the sources of several builders, see
+/// [composing configurations](Config#composing-configurations). It is also created from the
+/// [`Arc`] loaded from a [`ConfigCell`](crate::ConfigCell), to build on the current
+/// configuration.
+///
+/// Only the source is shared: each build still copies its values into the built [`Config`],
+/// which owns them.
+#[derive(Clone, Debug)]
+pub struct SharedConfig(Arc<Config>);
+
+impl From<Config> for SharedConfig {
+    fn from(config: Config) -> Self {
+        Self(Arc::new(config))
+    }
+}
+
+impl From<Arc<Config>> for SharedConfig {
+    fn from(config: Arc<Config>) -> Self {
+        Self(config)
+    }
+}
+
+impl Source for SharedConfig {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        self.0.collect()
+    }
+
+    fn collect_to(&self, cache: &mut Value) -> Result<()> {
+        self.0.collect_to(cache)
+    }
 }
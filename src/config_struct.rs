@@ -0,0 +1,142 @@
+use std::fmt::Display;
+
+use serde_core::de::DeserializeOwned;
+
+use crate::builder::{ConfigBuilder, DefaultState};
+use crate::config::Config;
+use crate::error::{ConfigError, Result};
+use crate::map::Map;
+use crate::value::{Value, ValueKind};
+
+/// A struct loaded with its defaults, environment variables and validators
+///
+/// Usually derived with `#[derive(ConfigStruct)]` and `#[config(...)]` attributes on the fields,
+/// with the `derive` feature, rather than wiring each field into the builder by hand:
+///
+/// ```rust
+/// # #[cfg(all(feature = "derive", feature = "env"))] {
+/// use config::{Config, ConfigStruct};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, ConfigStruct)]
+/// struct Settings {
+///     #[config(default = "localhost")]
+///     host: String,
+///     #[config(env = "APP_PORT", default = 8080, validate = range(1..65535))]
+///     port: u16,
+///     #[config(nested)]
+///     database: Database,
+/// }
+///
+/// #[derive(Debug, Deserialize, ConfigStruct)]
+/// struct Database {
+///     #[config(default = 4, validate = range(1..=64))]
+///     pool_size: u8,
+/// }
+///
+/// let settings = Settings::load(Config::builder().set_override("database.pool_size", 8)?)?;
+/// assert_eq!(settings.host, "localhost");
+/// assert_eq!(settings.database.pool_size, 8);
+///
+/// let error = Settings::load(Config::builder().set_override("port", 0)?).unwrap_err();
+/// assert_eq!(error.to_string(), "0 is out of range 1..65535 for key `port`");
+/// # }
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+///
+/// The derive also implements `TryFrom<Config>`, so that an already built [`Config`] converts
+/// with `config.try_into()?`. Validators run, but defaults and environment overrides are not
+/// applied as the sources were already collected.
+pub trait ConfigStruct: DeserializeOwned {
+    /// Register the defaults and environment overrides of the fields in `builder`
+    ///
+    /// Keys are relative to the table at `prefix`, or to the root if it is empty.
+    fn configure(
+        builder: ConfigBuilder<DefaultState>,
+        prefix: &str,
+    ) -> Result<ConfigBuilder<DefaultState>>;
+
+    /// Check the values of the fields
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Move the values of `table` read at another key than the name of their field to that name
+    #[doc(hidden)]
+    fn rename_keys(table: &mut Map<String, Value>) {
+        let _ = table;
+    }
+
+    /// Build the configuration from `builder` and deserialize it, then [validate](Self::validate) it
+    fn load(builder: ConfigBuilder<DefaultState>) -> Result<Self> {
+        let value: Self = __private::deserialize(Self::configure(builder, "")?.build()?)?;
+        value.validate()?;
+        Ok(value)
+    }
+}
+
+/// Helpers for the code generated by `#[derive(ConfigStruct)]`, not part of the public API
+#[doc(hidden)]
+pub mod __private {
+    use super::{Config, ConfigError, ConfigStruct, Display, Map, Result, Value, ValueKind};
+    #[cfg(feature = "env")]
+    use super::{ConfigBuilder, DefaultState};
+
+    pub fn key(prefix: &str, key: &str) -> String {
+        match prefix {
+            "" => key.to_owned(),
+            prefix => format!("{prefix}.{key}"),
+        }
+    }
+
+    #[cfg(feature = "env")]
+    pub fn env_override(
+        builder: ConfigBuilder<DefaultState>,
+        key: String,
+        var: &str,
+    ) -> Result<ConfigBuilder<DefaultState>> {
+        match std::env::var(var) {
+            Ok(value) => builder.set_override(key, value),
+            Err(std::env::VarError::NotPresent) => Ok(builder),
+            Err(std::env::VarError::NotUnicode(value)) => Err(ConfigError::Message(format!(
+                "env variable {var:?} contains non-Unicode data: {value:?}"
+            ))),
+        }
+    }
+
+    /// Deserialize `config` into `T`, once the values of its fields are at their names
+    pub fn deserialize<T: ConfigStruct>(mut config: Config) -> Result<T> {
+        if let ValueKind::Table(ref mut table) = config.cache.kind {
+            T::rename_keys(table);
+        }
+        config.try_deserialize()
+    }
+
+    /// Move the value at `key` in `table` to `name`
+    pub fn rename(table: &mut Map<String, Value>, key: &str, name: &str) {
+        #[cfg(not(feature = "preserve_order"))]
+        let value = table.remove(key);
+        #[cfg(feature = "preserve_order")]
+        let value = table.shift_remove(key);
+        if let Some(value) = value {
+            table.insert(name.to_owned(), value);
+        }
+    }
+
+    /// Move the values of the nested struct `T` at `name` in `table`
+    pub fn rename_nested<T: ConfigStruct>(table: &mut Map<String, Value>, name: &str) {
+        if let Some(value) = table.get_mut(name) {
+            if let ValueKind::Table(ref mut table) = value.kind {
+                T::rename_keys(table);
+            }
+        }
+    }
+
+    pub fn invalid(key: &str, message: impl Display) -> ConfigError {
+        ConfigError::Message(message.to_string()).extend_with_key(key)
+    }
+
+    pub fn nested(error: ConfigError, key: &str) -> ConfigError {
+        error.prepend_key(key)
+    }
+}
@@ -1,6 +1,8 @@
+use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::convert::TryInto;
 use std::iter::Enumerate;
+use std::rc::Rc;
 
 use serde_core::de;
 
@@ -33,7 +35,295 @@ macro_rules! try_convert_number {
     }};
 }
 
-impl<'de> de::Deserializer<'de> for Value {
+/// How values are deserialized, as set on the [`Config`] they come from
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Options {
+    /// Whether empty tables deserialize as `None` into options
+    pub(crate) empty_tables_as_none: bool,
+
+    /// Whether the keys of tables are visited in lexical order
+    pub(crate) sort_keys: bool,
+
+    /// Whether single values deserialize into one-element sequences and one-element arrays into
+    /// single values
+    pub(crate) lenient: bool,
+
+    /// Whether fields missing from a table deserialized into a struct fail, even those of type
+    /// `Option` or with a default, while nil still deserializes as `None`
+    pub(crate) strict: bool,
+
+    /// Whether strings that read as a boolean or a number are given as such to the types that
+    /// deserialize any value
+    pub(crate) coerce_strings: bool,
+}
+
+/// What a deserialization keeps track of while visiting the values below the one it started from
+#[derive(Default)]
+struct State {
+    /// Path of the value being deserialized, while tracking unused keys
+    path: String,
+
+    /// Keys of the tables deserialized that no struct field consumed, when tracked
+    unused: Option<Vec<String>>,
+
+    /// Depth of the tables and arrays being deserialized as any value, which serde buffers for
+    /// internally tagged and untagged enums
+    buffering: usize,
+
+    /// Paths of the leaves deserialized as any value, relative to the value being deserialized,
+    /// along with how serde reports them in errors, recorded while `locating` runs
+    buffered: Option<Vec<(String, String)>>,
+}
+
+/// Deserializes a [`Value`] with the [`Options`] of the [`Config`] it comes from
+///
+/// The values below it are deserialized with the same options, sharing the [`State`] of the
+/// deserialization.
+pub(crate) struct ValueDeserializer {
+    value: Value,
+    options: Options,
+    state: Rc<RefCell<State>>,
+}
+
+impl ValueDeserializer {
+    pub(crate) fn new(value: Value, options: Options) -> Self {
+        Self {
+            value,
+            options,
+            state: Rc::default(),
+        }
+    }
+
+    /// Deserialize `value`, below the value of this deserializer
+    fn child(options: Options, state: &Rc<RefCell<State>>, value: Value) -> Self {
+        Self {
+            value,
+            options,
+            state: Rc::clone(state),
+        }
+    }
+
+    /// The element of a one-element array when lenient, the value otherwise
+    fn unwrap_singleton(self) -> Value {
+        match self.value.kind {
+            ValueKind::Array(mut values) if values.len() == 1 && self.options.lenient => {
+                values.remove(0)
+            }
+            _ => self.value,
+        }
+    }
+}
+
+/// `text` as the boolean or number it reads as, if it reads back the same, e.g. `8080` but not
+/// `08080` or `1.10`
+fn coerce(text: &str) -> Option<ValueKind> {
+    match text {
+        "true" => return Some(ValueKind::Boolean(true)),
+        "false" => return Some(ValueKind::Boolean(false)),
+        _ => {}
+    }
+    if let Ok(value) = text.parse::<i64>() {
+        return (value.to_string() == text).then_some(ValueKind::I64(value));
+    }
+    if let Ok(value) = text.parse::<u64>() {
+        return (value.to_string() == text).then_some(ValueKind::U64(value));
+    }
+    match text.parse::<f64>() {
+        Ok(value) if value.is_finite() && text.contains('.') && value.to_string() == text => {
+            Some(ValueKind::Float(value))
+        }
+        _ => None,
+    }
+}
+
+/// How serde reports the leaf `kind` once buffered, e.g. ``integer `8080` ``
+fn buffered_unexpected(kind: &ValueKind) -> Option<String> {
+    let unexpected = match *kind {
+        ValueKind::Boolean(value) => de::Unexpected::Bool(value),
+        ValueKind::I64(value) => de::Unexpected::Signed(value),
+        ValueKind::U64(value) => de::Unexpected::Unsigned(value),
+        ValueKind::Float(value) => de::Unexpected::Float(value),
+        ValueKind::String(ref value) => de::Unexpected::Str(value),
+        _ => return None,
+    };
+    Some(unexpected.to_string())
+}
+
+/// Run `deserialize` for the child of the current value found at `segment`, naming in the error
+/// of an enum the leaf it failed on
+///
+/// serde replays the values it buffered for internally tagged and untagged enums without this
+/// deserializer, so their errors name no key. The leaves deserialized as any value while running
+/// `deserialize` are matched against its error, or passed on to the parent if it may still
+/// replay them.
+fn locating<R>(
+    state: &RefCell<State>,
+    segment: Option<&str>,
+    deserialize: impl FnOnce() -> Result<R>,
+) -> Result<R> {
+    let outer = state.borrow_mut().buffered.replace(Vec::new());
+    let result = deserialize();
+    let mut state = state.borrow_mut();
+    let leaves = std::mem::replace(&mut state.buffered, outer).unwrap_or_default();
+
+    let error = match result {
+        Ok(value) => {
+            // Serde may replay what it buffered, and fail on it, once the parent is deserialized
+            if let (true, Some(segment), Some(buffered)) =
+                (state.buffering > 0, segment, state.buffered.as_mut())
+            {
+                buffered.extend(leaves.into_iter().map(|(path, unexpected)| {
+                    let path = match path.as_bytes().first() {
+                        None => segment.to_owned(),
+                        Some(b'[') => format!("{segment}{path}"),
+                        Some(_) => format!("{segment}.{path}"),
+                    };
+                    (path, unexpected)
+                }));
+            }
+            return Ok(value);
+        }
+        Err(error) => error,
+    };
+
+    let ConfigError::Message(ref message) = error else {
+        return Err(error);
+    };
+    let mut matching = leaves.iter().filter(|(_, unexpected)| {
+        message.starts_with(&format!("invalid type: {unexpected},"))
+            || message.starts_with(&format!("invalid value: {unexpected},"))
+    });
+    match (matching.next(), matching.next()) {
+        (Some((path, _)), None) if !path.is_empty() => Err(error.extend_with_key(path)),
+        _ => Err(error),
+    }
+}
+
+/// Deserialize `T`, collecting the paths of the keys that no struct field consumed
+pub(crate) fn deserialize_with_unused<'de, T>(
+    deserializer: ValueDeserializer,
+) -> Result<(T, Vec<String>)>
+where
+    T: de::Deserialize<'de>,
+{
+    let state = Rc::clone(&deserializer.state);
+    state.borrow_mut().unused = Some(Vec::new());
+    let value = deserialize_tracked(deserializer);
+
+    let mut keys = state.borrow_mut().unused.take().unwrap_or_default();
+    keys.sort();
+    value.map(|value| (value, keys))
+}
+
+/// Deserialize `T`, with the `path-to-error` feature naming the path to the value that failed as
+/// tracked by `serde_path_to_error`, which also sees through enum variants and custom impls
+pub(crate) fn deserialize_tracked<'de, T>(deserializer: ValueDeserializer) -> Result<T>
+where
+    T: de::Deserialize<'de>,
+{
+    let state = Rc::clone(&deserializer.state);
+    #[cfg(not(feature = "path-to-error"))]
+    {
+        locating(&state, None, || T::deserialize(deserializer))
+    }
+    #[cfg(feature = "path-to-error")]
+    {
+        use serde_path_to_error::Segment;
+
+        locating(&state, None, || {
+            serde_path_to_error::deserialize(deserializer).map_err(|error| {
+                let mut path = String::new();
+                for segment in error.path() {
+                    match segment {
+                        Segment::Seq { index } => path.push_str(&format!("[{index}]")),
+                        Segment::Map { key } | Segment::Enum { variant: key } => {
+                            if !path.is_empty() {
+                                path.push('.');
+                            }
+                            path.push_str(key);
+                        }
+                        // A map key that failed to deserialize
+                        Segment::Unknown => break,
+                    }
+                }
+
+                match error.into_inner() {
+                    // Already names the missing field, below this path
+                    error @ (ConfigError::NotFound(_)
+                    | ConfigError::NotFoundWithSuggestions { .. }) => error,
+                    error if path.is_empty() => error,
+                    // Already names the leaf of an enum it failed on, below this path
+                    error
+                        if error.key().is_some_and(|key| {
+                            key.len() > path.len() && key.starts_with(&path)
+                        }) =>
+                    {
+                        error
+                    }
+                    error => error.extend_with_key(&path),
+                }
+            })
+        })
+    }
+}
+
+/// Run `deserialize` for a table or array deserialized as any value, which serde may buffer to
+/// replay it later
+fn buffering<R>(state: &RefCell<State>, deserialize: impl FnOnce() -> R) -> R {
+    state.borrow_mut().buffering += 1;
+    let result = deserialize();
+    state.borrow_mut().buffering -= 1;
+    result
+}
+
+/// Run `deserialize` for the child of the current value found at `key` or `index`
+fn within<R>(
+    state: &RefCell<State>,
+    key: Option<&str>,
+    index: usize,
+    deserialize: impl FnOnce() -> R,
+) -> R {
+    let len = {
+        let mut state = state.borrow_mut();
+        let state = &mut *state;
+        state.unused.as_ref().map(|_| {
+            let len = state.path.len();
+            match key {
+                Some(key) if state.path.is_empty() => state.path.push_str(key),
+                Some(key) => {
+                    state.path.push('.');
+                    state.path.push_str(key);
+                }
+                None => state.path.push_str(&format!("[{index}]")),
+            }
+            len
+        })
+    };
+
+    let result = deserialize();
+
+    if let Some(len) = len {
+        state.borrow_mut().path.truncate(len);
+    }
+    result
+}
+
+fn record_unused<'a>(state: &RefCell<State>, keys: impl Iterator<Item = &'a String>) {
+    let mut state = state.borrow_mut();
+    let state = &mut *state;
+    if let Some(unused) = state.unused.as_mut() {
+        for key in keys {
+            let path = if state.path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{key}", state.path)
+            };
+            unused.push(path);
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
     type Error = ConfigError;
 
     #[inline]
@@ -41,8 +331,22 @@ impl<'de> de::Deserializer<'de> for Value {
     where
         V: de::Visitor<'de>,
     {
+        let mut kind = self.value.kind;
+        if let ValueKind::String(ref text) = kind {
+            if self.options.coerce_strings {
+                if let Some(coerced) = coerce(text) {
+                    kind = coerced;
+                }
+            }
+        }
+        if let Some(unexpected) = buffered_unexpected(&kind) {
+            if let Some(buffered) = self.state.borrow_mut().buffered.as_mut() {
+                buffered.push((String::new(), unexpected));
+            }
+        }
+
         // Deserialize based on the underlying type
-        match self.kind {
+        match kind {
             ValueKind::Nil => visitor.visit_unit(),
             ValueKind::I64(i) => visitor.visit_i64(i),
             ValueKind::I128(i) => visitor.visit_i128(i),
@@ -51,82 +355,99 @@ impl<'de> de::Deserializer<'de> for Value {
             ValueKind::Boolean(b) => visitor.visit_bool(b),
             ValueKind::Float(f) => visitor.visit_f64(f),
             ValueKind::String(s) => visitor.visit_string(s),
-            ValueKind::Array(values) => visitor.visit_seq(SeqAccess::new(values)),
-            ValueKind::Table(map) => visitor.visit_map(MapAccess::new(map)),
+            ValueKind::Array(values) => buffering(&self.state, || {
+                visitor.visit_seq(SeqAccess::new(values, self.options, &self.state))
+            }),
+            ValueKind::Table(map) => buffering(&self.state, || {
+                visitor.visit_map(MapAccess::new(map, self.options, &self.state))
+            }),
         }
     }
 
     #[inline]
     fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_bool(self.into_bool()?)
+        let value = self.unwrap_singleton();
+        visitor.visit_bool(value.into_bool()?)
     }
 
     #[inline]
     fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let num = try_convert_number!(signed, self, "8");
+        let value = self.unwrap_singleton();
+        let num = try_convert_number!(signed, value, "8");
         visitor.visit_i8(num)
     }
 
     #[inline]
     fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let num = try_convert_number!(signed, self, "16");
+        let value = self.unwrap_singleton();
+        let num = try_convert_number!(signed, value, "16");
         visitor.visit_i16(num)
     }
 
     #[inline]
     fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let num = try_convert_number!(signed, self, "32");
+        let value = self.unwrap_singleton();
+        let num = try_convert_number!(signed, value, "32");
         visitor.visit_i32(num)
     }
 
     #[inline]
     fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let num = try_convert_number!(signed, self, "64");
+        let value = self.unwrap_singleton();
+        let num = try_convert_number!(signed, value, "64");
         visitor.visit_i64(num)
     }
 
     #[inline]
     fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let num = try_convert_number!(unsigned, self, "8");
+        let value = self.unwrap_singleton();
+        let num = try_convert_number!(unsigned, value, "8");
         visitor.visit_u8(num)
     }
 
     #[inline]
     fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let num = try_convert_number!(unsigned, self, "16");
+        let value = self.unwrap_singleton();
+        let num = try_convert_number!(unsigned, value, "16");
         visitor.visit_u16(num)
     }
 
     #[inline]
     fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let num = try_convert_number!(unsigned, self, "32");
+        let value = self.unwrap_singleton();
+        let num = try_convert_number!(unsigned, value, "32");
         visitor.visit_u32(num)
     }
 
     #[inline]
     fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let num = try_convert_number!(unsigned, self, "u64");
+        let value = self.unwrap_singleton();
+        let num = try_convert_number!(unsigned, value, "u64");
         visitor.visit_u64(num)
     }
 
     #[inline]
     fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_f32(self.into_float()? as f32)
+        let value = self.unwrap_singleton();
+        visitor.visit_f32(value.into_float()? as f32)
     }
 
     #[inline]
     fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_f64(self.into_float()?)
+        let value = self.unwrap_singleton();
+        visitor.visit_f64(value.into_float()?)
     }
 
     #[inline]
     fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_string(self.into_string()?)
+        let value = self.unwrap_singleton();
+        visitor.visit_string(value.into_string()?)
     }
 
     #[inline]
     fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_string(self.into_string()?)
+        let value = self.unwrap_singleton();
+        visitor.visit_string(value.into_string()?)
     }
 
     #[inline]
@@ -135,8 +456,13 @@ impl<'de> de::Deserializer<'de> for Value {
         V: de::Visitor<'de>,
     {
         // Match an explicit nil as None and everything else as Some
-        match self.kind {
+        match self.value.kind {
             ValueKind::Nil => visitor.visit_none(),
+            ValueKind::Table(ref table)
+                if table.is_empty() && self.options.empty_tables_as_none =>
+            {
+                visitor.visit_none()
+            }
             _ => visitor.visit_some(self),
         }
     }
@@ -158,15 +484,76 @@ impl<'de> de::Deserializer<'de> for Value {
         V: de::Visitor<'de>,
     {
         visitor.visit_enum(EnumAccess {
-            value: self,
+            value: self.value,
+            options: self.options,
+            state: self.state,
             name,
             variants,
         })
     }
 
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value.kind {
+            // `std::time::Duration`, written as text
+            ValueKind::String(ref text) if name == "Duration" && fields == ["secs", "nanos"] => {
+                let duration = crate::time::parse_duration(text).ok_or_else(|| {
+                    ConfigError::invalid_type(
+                        self.value.origin().map(Into::into),
+                        Unexpected::Str(text.clone()),
+                        "a duration like `30s` or `PT30S`",
+                    )
+                })?;
+
+                let mut map = Map::new();
+                map.insert("secs".to_owned(), duration.as_secs().into());
+                map.insert("nanos".to_owned(), duration.subsec_nanos().into());
+                visitor.visit_map(MapAccess::new(map, self.options, &self.state))
+            }
+            ValueKind::Table(map) => {
+                // Keep unknown keys around to suggest them for missing fields
+                let unknown: Vec<String> = map
+                    .keys()
+                    .filter(|key| !fields.contains(&key.as_str()))
+                    .cloned()
+                    .collect();
+                record_unused(&self.state, unknown.iter());
+
+                if self.options.strict {
+                    if let Some(field) = fields.iter().find(|field| !map.contains_key(**field)) {
+                        return Err(ConfigError::not_found(field)
+                            .suggest_from(unknown.iter().map(String::as_str)));
+                    }
+                }
+
+                visitor
+                    .visit_map(MapAccess::new(map, self.options, &self.state))
+                    .map_err(|e| e.suggest_from(unknown.iter().map(String::as_str)))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value.kind {
+            ValueKind::Nil | ValueKind::Array(_) => self.deserialize_any(visitor),
+            _ if self.options.lenient => {
+                visitor.visit_seq(SeqAccess::new(vec![self.value], self.options, &self.state))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     serde_core::forward_to_deserialize_any! {
-        char seq
-        bytes byte_buf map struct unit
+        char
+        bytes byte_buf map unit
         identifier ignored_any unit_struct tuple_struct tuple
     }
 }
@@ -190,12 +577,16 @@ impl<'de> de::Deserializer<'de> for StrDeserializer<'_> {
 
 struct SeqAccess {
     elements: Enumerate<::std::vec::IntoIter<Value>>,
+    options: Options,
+    state: Rc<RefCell<State>>,
 }
 
 impl SeqAccess {
-    fn new(elements: Vec<Value>) -> Self {
+    fn new(elements: Vec<Value>, options: Options, state: &Rc<RefCell<State>>) -> Self {
         Self {
             elements: elements.into_iter().enumerate(),
+            options,
+            state: Rc::clone(state),
         }
     }
 }
@@ -208,10 +599,16 @@ impl<'de> de::SeqAccess<'de> for SeqAccess {
         T: de::DeserializeSeed<'de>,
     {
         match self.elements.next() {
-            Some((idx, value)) => seed
-                .deserialize(value)
+            Some((idx, value)) => {
+                let segment = format!("[{idx}]");
+                let value = ValueDeserializer::child(self.options, &self.state, value);
+                let state = &self.state;
+                locating(state, Some(&segment), || {
+                    within(state, None, idx, || seed.deserialize(value))
+                })
                 .map(Some)
-                .map_err(|e| e.prepend_index(idx)),
+                .map_err(|e| e.prepend_index(idx))
+            }
             None => Ok(None),
         }
     }
@@ -226,12 +623,22 @@ impl<'de> de::SeqAccess<'de> for SeqAccess {
 
 struct MapAccess {
     elements: VecDeque<(String, Value)>,
+    options: Options,
+    state: Rc<RefCell<State>>,
 }
 
 impl MapAccess {
-    fn new(table: Map<String, Value>) -> Self {
+    fn new(table: Map<String, Value>, options: Options, state: &Rc<RefCell<State>>) -> Self {
+        let mut elements: VecDeque<_> = table.into_iter().collect();
+        if options.sort_keys {
+            elements
+                .make_contiguous()
+                .sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        }
         Self {
-            elements: table.into_iter().collect(),
+            elements,
+            options,
+            state: Rc::clone(state),
         }
     }
 }
@@ -244,8 +651,8 @@ impl<'de> de::MapAccess<'de> for MapAccess {
         K: de::DeserializeSeed<'de>,
     {
         if let Some((key_s, _)) = self.elements.front() {
-            let key_de = Value::new(None, key_s as &str);
-            let key = de::DeserializeSeed::deserialize(seed, key_de)?;
+            let key = de::DeserializeSeed::deserialize(seed, KeyDeserializer(key_s))
+                .map_err(|e| ConfigError::Message(format!("invalid map key {key_s:?}: {e}")))?;
 
             Ok(Some(key))
         } else {
@@ -258,12 +665,107 @@ impl<'de> de::MapAccess<'de> for MapAccess {
         V: de::DeserializeSeed<'de>,
     {
         let (key, value) = self.elements.pop_front().unwrap();
-        de::DeserializeSeed::deserialize(seed, value).map_err(|e| e.prepend_key(&key))
+        let value = ValueDeserializer::child(self.options, &self.state, value);
+        let state = &self.state;
+        locating(state, Some(&key), || {
+            within(state, Some(&key), 0, || {
+                de::DeserializeSeed::deserialize(seed, value)
+            })
+        })
+        .map_err(|e| e.prepend_key(&key))
+    }
+}
+
+/// Define `$method`s parsing the key with [`FromStr`](std::str::FromStr)
+macro_rules! deserialize_parsed_key { { $(
+    $method:ident => $visit:ident, $expected:literal;
+)* } => { $(
+    #[inline]
+    fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0.parse() {
+            Ok(value) => visitor.$visit(value),
+            Err(_) => Err(ConfigError::Message(concat!("expected ", $expected).to_owned())),
+        }
+    }
+)* } }
+
+/// Deserializes a table key into the key type of a map, like `bool` or `IpAddr`
+///
+/// Keys are always strings, so numbers and booleans are parsed from them, and other types are
+/// deserialized from the string.
+struct KeyDeserializer<'a>(&'a str);
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'_> {
+    type Error = ConfigError;
+
+    #[inline]
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_str(self.0)
+    }
+
+    #[inline]
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // Accept the same spellings as boolean values
+        match Value::new(None, self.0).into_bool() {
+            Ok(value) => visitor.visit_bool(value),
+            Err(_) => Err(ConfigError::Message("expected a boolean".to_owned())),
+        }
+    }
+
+    deserialize_parsed_key! {
+        deserialize_i8 => visit_i8, "a signed 8 bit integer";
+        deserialize_i16 => visit_i16, "a signed 16 bit integer";
+        deserialize_i32 => visit_i32, "a signed 32 bit integer";
+        deserialize_i64 => visit_i64, "a signed 64 bit integer";
+        deserialize_i128 => visit_i128, "a signed 128 bit integer";
+        deserialize_u8 => visit_u8, "an unsigned 8 bit integer";
+        deserialize_u16 => visit_u16, "an unsigned 16 bit integer";
+        deserialize_u32 => visit_u32, "an unsigned 32 bit integer";
+        deserialize_u64 => visit_u64, "an unsigned 64 bit integer";
+        deserialize_u128 => visit_u128, "an unsigned 128 bit integer";
+        deserialize_f32 => visit_f32, "a float";
+        deserialize_f64 => visit_f64, "a float";
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_enum(EnumAccess {
+            value: Value::new(None, self.0),
+            options: Options::default(),
+            state: Rc::default(),
+            name,
+            variants,
+        })
+    }
+
+    serde_core::forward_to_deserialize_any! {
+        char str string seq bytes byte_buf map struct unit
+        identifier ignored_any unit_struct tuple_struct tuple
     }
 }
 
 struct EnumAccess {
     value: Value,
+    options: Options,
+    state: Rc<RefCell<State>>,
     name: &'static str,
     variants: &'static [&'static str],
 }
@@ -286,9 +788,37 @@ impl EnumAccess {
     }
 
     fn no_constructor_error(&self, supposed_variant: &str) -> ConfigError {
-        ConfigError::Message(format!(
+        let mut message = format!(
             "enum {} does not have variant constructor {}",
             self.name, supposed_variant
+        );
+        if !self.variants.is_empty() {
+            let variants: Vec<_> = self.variants.iter().map(|v| format!("`{v}`")).collect();
+            message.push_str(&format!(", expected one of {}", variants.join(", ")));
+        }
+        // The most common mistake is writing the variant in the case of the rest of the file
+        if let Some(variant) = self
+            .variants
+            .iter()
+            .find(|v| v.eq_ignore_ascii_case(supposed_variant))
+        {
+            message.push_str(&format!(
+                " (variants are case-sensitive, did you mean `{variant}`?)"
+            ));
+        }
+        ConfigError::Message(message)
+    }
+
+    /// The error of a variant holding data written as a string, like `Brightness` for
+    /// `Brightness(i32)`, which has no data to deserialize
+    fn missing_data_error(&self) -> ConfigError {
+        let variant = match self.value.kind {
+            ValueKind::String(ref variant) => variant.as_str(),
+            _ => "",
+        };
+        ConfigError::Message(format!(
+            "variant {variant} of enum {} holds data, so it should be represented by a table with {variant} as its only key",
+            self.name
         ))
     }
 
@@ -333,8 +863,12 @@ impl<'de> de::VariantAccess<'de> for EnumAccess {
         T: de::DeserializeSeed<'de>,
     {
         match self.value.kind {
-            ValueKind::Table(t) => seed.deserialize(t.into_iter().next().unwrap().1),
-            _ => unreachable!(),
+            ValueKind::Table(t) => {
+                let (variant, value) = t.into_iter().next().unwrap();
+                let value = ValueDeserializer::child(self.options, &self.state, value);
+                within(&self.state, Some(&variant), 0, || seed.deserialize(value))
+            }
+            _ => Err(self.missing_data_error()),
         }
     }
 
@@ -344,29 +878,38 @@ impl<'de> de::VariantAccess<'de> for EnumAccess {
     {
         match self.value.kind {
             ValueKind::Table(t) => {
-                de::Deserializer::deserialize_seq(t.into_iter().next().unwrap().1, visitor)
+                let (variant, value) = t.into_iter().next().unwrap();
+                let value = ValueDeserializer::child(self.options, &self.state, value);
+                within(&self.state, Some(&variant), 0, || {
+                    de::Deserializer::deserialize_seq(value, visitor)
+                })
             }
-            _ => unreachable!(),
+            _ => Err(self.missing_data_error()),
         }
     }
 
-    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
         match self.value.kind {
             ValueKind::Table(t) => {
-                de::Deserializer::deserialize_map(t.into_iter().next().unwrap().1, visitor)
+                let (variant, value) = t.into_iter().next().unwrap();
+                let value = ValueDeserializer::child(self.options, &self.state, value);
+                within(&self.state, Some(&variant), 0, || {
+                    de::Deserializer::deserialize_struct(value, self.name, fields, visitor)
+                })
             }
-            _ => unreachable!(),
+            _ => Err(self.missing_data_error()),
         }
     }
 }
 
-/// Define `$method`s, `deserialize_foo`, by forwarding to `Value`
+/// Define `$method`s, `deserialize_foo`, by forwarding to the [`ValueDeserializer`] returned by
+/// `$deserializer`
 ///
 /// `($arg: $argtype, ...)`, if supplied, are the formal arguments
-macro_rules! config_deserialize_via_value { { $(
+macro_rules! deserialize_via_value_deserializer { { $deserializer:expr; $(
     $method:ident $( ( $( $arg:ident: $argtype:ty ),* ) )? ;
 )* } => { $(
     #[inline]
@@ -375,14 +918,60 @@ macro_rules! config_deserialize_via_value { { $(
       $( $( $arg: $argtype, )* )?
             visitor: V,
         ) -> Result<V::Value> {
-        self.cache.$method( $( $( $arg, )* )? visitor)
+        let deserializer: fn(Self) -> ValueDeserializer = $deserializer;
+        deserializer(self).$method( $( $( $arg, )* )? visitor)
     }
 )* } }
 
+/// Deserializes a [`Value`] with the default [`Options`]
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = ConfigError;
+
+    deserialize_via_value_deserializer! {
+        |value| ValueDeserializer::new(value, Options::default());
+        deserialize_any;
+        deserialize_bool;
+        deserialize_i8;
+        deserialize_i16;
+        deserialize_i32;
+        deserialize_i64;
+        deserialize_u8;
+        deserialize_u16;
+        deserialize_u32;
+        deserialize_u64;
+        deserialize_f32;
+        deserialize_f64;
+        deserialize_str;
+        deserialize_string;
+        deserialize_option;
+
+        deserialize_char;
+        deserialize_seq;
+        deserialize_bytes;
+        deserialize_byte_buf;
+        deserialize_map;
+        deserialize_unit;
+        deserialize_identifier;
+        deserialize_ignored_any;
+
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_tuple(n: usize);
+        deserialize_tuple_struct(name: &'static str, n: usize);
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+    }
+}
+
+/// Deserializes the values of a [`Config`] with its [`Options`]
 impl<'de> de::Deserializer<'de> for Config {
     type Error = ConfigError;
 
-    config_deserialize_via_value! {
+    deserialize_via_value_deserializer! {
+        |config| {
+            let options = config.deserialize_options();
+            ValueDeserializer::new(config.cache, options)
+        };
         deserialize_any;
         deserialize_bool;
         deserialize_i8;
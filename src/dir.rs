@@ -0,0 +1,153 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::{ConfigError, Result};
+use crate::file::{Extensions, File, ParseOptions, uri};
+use crate::map::Map;
+use crate::redact::glob_match;
+use crate::source::{Source, record_failed, record_skipped};
+use crate::value::Value;
+
+/// A [`Source`] merging the files of a directory, like `/etc/app/conf.d`, in the order of their
+/// names.
+///
+/// Each file is parsed in the format registered for its extension, and files with another
+/// extension are skipped. Subdirectories are not read. Later files override the values of
+/// earlier ones, so files are usually named with a numeric prefix, like `10-logging.toml`.
+///
+/// ```rust
+/// # use config::*;
+/// # let dir = std::env::temp_dir().join("config-directory-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # std::fs::write(dir.join("10-server.toml"), "port = 80").unwrap();
+/// # std::fs::write(dir.join("20-local.json"), r#"{ "port": 8080 }"#).unwrap();
+/// # std::fs::write(dir.join("README.md"), "# Settings").unwrap();
+/// # #[cfg(all(feature = "toml", feature = "json"))]
+/// # {
+/// let config = Config::builder()
+///     .add_source(Directory::new(&dir))
+///     .build()?;
+///
+/// assert_eq!(config.get_int("port")?, 8080);
+/// assert!(config.source_report().skipped()[0].ends_with("README.md"));
+/// # }
+/// # Ok::<(), ConfigError>(())
+/// ```
+///
+/// Files that are skipped, or that fail to load when the directory is not
+/// [required](Self::required), are listed in the
+/// [`Config::source_report`](crate::Config::source_report).
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Directory {
+    path: PathBuf,
+
+    /// Glob the names of the files must match
+    pattern: Option<String>,
+
+    /// A required directory will error if it cannot be read or one of its files cannot be loaded
+    required: bool,
+
+    /// Options passed to the formats when parsing
+    options: ParseOptions,
+}
+
+impl Directory {
+    /// Read the files of the directory at `path`
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            pattern: None,
+            required: true,
+            options: ParseOptions::default(),
+        }
+    }
+
+    /// Only read the files whose name matches the glob `pattern`, like `*.toml`, where `*`
+    /// matches any sequence of characters and `?` a single character.
+    ///
+    /// Files that do not match are not candidates, so they are not reported as skipped.
+    pub fn pattern(mut self, pattern: &str) -> Self {
+        self.pattern = Some(pattern.to_owned());
+        self
+    }
+
+    /// Whether the directory must exist and each of its files must load.
+    ///
+    /// Enabled by default. When disabled, a missing directory yields no values and the files
+    /// that fail to load are left out, as reported by
+    /// [`SourceReport::failed`](crate::SourceReport::failed).
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Set the options used when parsing the files.
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The files of the directory matching the pattern, sorted by name
+    fn files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let matches = match self.pattern {
+                Some(ref pattern) => glob_match(pattern, &name),
+                None => true,
+            };
+            if matches && path.is_file() {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+impl Source for Directory {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        let files = match self.files() {
+            Ok(files) => files,
+            Err(err) if !self.required && err.kind() == io::ErrorKind::NotFound => {
+                return Ok(Map::new());
+            }
+            Err(err) => {
+                return Err(ConfigError::SourceRead {
+                    origin: "configuration directory".to_owned(),
+                    uri: Some(uri(&self.path)),
+                    cause: Box::new(err),
+                });
+            }
+        };
+
+        let extensions = Extensions::current();
+        let mut cache = Value::new(None, Map::<String, Value>::new());
+        for path in files {
+            let extension = path.extension().unwrap_or_default().to_string_lossy();
+            let Some(format) = extensions.format(&extension) else {
+                record_skipped(uri(&path));
+                continue;
+            };
+
+            let file = File::from(path.as_path())
+                .format(format)
+                .parse_options(self.options.clone());
+            if let Err(error) = file.collect_to(&mut cache) {
+                if self.required {
+                    return Err(error);
+                }
+                record_failed(uri(&path), error.to_string());
+            }
+        }
+
+        cache.into_table()
+    }
+}
@@ -0,0 +1,207 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::error::{ConfigError, Result};
+use crate::file::{File, FileFormat, ParseOptions, uri};
+use crate::map::Map;
+use crate::source::{Source, record_report, set_value, track_loaded};
+use crate::value::Value;
+
+/// How a [`HierarchicalFile`] combines the files it finds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Discovery {
+    /// Merge all the files found, those of nested directories overriding those of their parents,
+    /// like Cargo does with `.cargo/config.toml`
+    #[default]
+    Merge,
+
+    /// Only read the file found closest to the starting directory, like a `.nvmrc`
+    Nearest,
+}
+
+/// A [`Source`] looking for a file in a directory and each of its parents, up to the root of
+/// the file system, like `.cargo/config.toml` or `.editorconfig`
+///
+/// The name is looked up like by [`File::with_name`], so it may be given without extension and
+/// may include directories. The search starts in the current directory, unless
+/// [set](Self::start) otherwise. By default, all the files found are
+/// [merged](Discovery::Merge), letting a project override the settings of a user-wide file.
+///
+/// ```rust
+/// # use config::*;
+/// # let root = std::env::temp_dir().join("config-hierarchical-doc");
+/// # let project = root.join("project");
+/// # std::fs::create_dir_all(project.join("src")).unwrap();
+/// # std::fs::write(root.join(".app.toml"), "jobs = 4\ncolor = true").unwrap();
+/// # std::fs::write(project.join(".app.toml"), "jobs = 8").unwrap();
+/// # #[cfg(feature = "toml")]
+/// # {
+/// let config = Config::builder()
+///     .add_source(
+///         HierarchicalFile::new(".app")
+///             .start(project.join("src"))
+///             .stop_at(&root),
+///     )
+///     .build()?;
+///
+/// assert_eq!(config.get_int("jobs")?, 8);
+/// assert!(config.get_bool("color")?);
+/// # }
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct HierarchicalFile {
+    name: PathBuf,
+
+    /// Directory the search starts from, the current directory if unset
+    start: Option<PathBuf>,
+
+    /// Last directory searched, the root if unset
+    stop_at: Option<PathBuf>,
+
+    discovery: Discovery,
+
+    /// A required file will error if it is not found in any directory
+    required: bool,
+
+    format: Option<FileFormat>,
+
+    /// Options passed to the format when parsing
+    options: ParseOptions,
+}
+
+impl HierarchicalFile {
+    /// Look for the file at `name` relative to each directory, like `.app` or
+    /// `.cargo/config.toml`
+    pub fn new(name: impl AsRef<Path>) -> Self {
+        Self {
+            name: name.as_ref().to_path_buf(),
+            start: None,
+            stop_at: None,
+            discovery: Discovery::default(),
+            required: false,
+            format: None,
+            options: ParseOptions::default(),
+        }
+    }
+
+    /// Start the search in `dir` instead of the current directory
+    pub fn start(mut self, dir: impl AsRef<Path>) -> Self {
+        self.start = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Stop the search at `dir`, e.g. the home directory or the root of a repository, after
+    /// looking for the file in it
+    ///
+    /// The search goes up to the root if the starting directory is not within `dir`.
+    pub fn stop_at(mut self, dir: impl AsRef<Path>) -> Self {
+        self.stop_at = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Whether to merge all the files found or to only read the closest one
+    pub fn discovery(mut self, discovery: Discovery) -> Self {
+        self.discovery = discovery;
+        self
+    }
+
+    /// Whether the file must be found in at least one directory.
+    ///
+    /// Disabled by default.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Parse the files as `format`, e.g. for a name without a registered extension
+    pub fn format(mut self, format: FileFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Set the options used when parsing the files.
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The directories to search, starting directory first
+    fn dirs(&self) -> Result<Vec<PathBuf>> {
+        let current = || {
+            env::current_dir().map_err(|err| ConfigError::SourceRead {
+                origin: "current directory".to_owned(),
+                uri: None,
+                cause: Box::new(err),
+            })
+        };
+        let absolute = |dir: &Path| -> Result<PathBuf> {
+            match dir.is_absolute() {
+                true => Ok(dir.to_path_buf()),
+                false => Ok(current()?.join(dir)),
+            }
+        };
+
+        let start = match self.start {
+            Some(ref start) => absolute(start)?,
+            None => current()?,
+        };
+        let stop_at = self.stop_at.as_deref().map(absolute).transpose()?;
+
+        let mut dirs = Vec::new();
+        for dir in start.ancestors() {
+            dirs.push(dir.to_path_buf());
+            if stop_at.as_deref() == Some(dir) {
+                break;
+            }
+        }
+        Ok(dirs)
+    }
+}
+
+impl Source for HierarchicalFile {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        let dirs = self.dirs()?;
+        let mut found = Vec::new();
+        for dir in &dirs {
+            let mut file = File::from(dir.join(&self.name))
+                .required(false)
+                .parse_options(self.options.clone());
+            if let Some(format) = self.format {
+                file = file.format(format);
+            }
+
+            let (values, report) = track_loaded(|| file.collect());
+            let loaded = !report.loaded().is_empty();
+            record_report(report);
+            let values = values?;
+            if loaded {
+                found.push(values);
+                if self.discovery == Discovery::Nearest {
+                    break;
+                }
+            }
+        }
+
+        if found.is_empty() && self.required {
+            return Err(ConfigError::Message(format!(
+                "configuration file {:?} not found in {:?} or any of its parents",
+                self.name.to_string_lossy(),
+                uri(&dirs[0])
+            )));
+        }
+
+        let mut cache = Value::new(None, Map::<String, Value>::new());
+        for values in found.into_iter().rev() {
+            for (key, value) in values {
+                set_value(&mut cache, key, value);
+            }
+        }
+        cache.into_table()
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::env::Environment;
+use crate::error::{ConfigError, Result};
+use crate::file::{FileFormat, uri};
+use crate::map::Map;
+use crate::source::{Source, SourceInfo, record_loaded};
+use crate::value::Value;
+
+/// A [`Source`] reading variables from the `.env` files of a directory, with the precedence of
+/// dotenv tooling
+///
+/// The files are read in this order, each overriding the variables of the previous ones:
+///
+/// 1. `.env`, shared defaults
+/// 2. `.env.local`, local overrides, except in the `test` [mode](Self::mode) so tests behave the
+///    same everywhere
+/// 3. `.env.<mode>`, defaults of the mode, like `.env.production`
+/// 4. `.env.<mode>.local`, local overrides of the mode
+///
+/// Missing files are skipped. The files are parsed as [`FileFormat::Dotenv`], and the variables
+/// then mapped to keys by an [`Environment`], as if they were set in the process environment,
+/// and their values keep the file they were read from as their origin.
+///
+/// ```rust
+/// # use config::*;
+/// # let dir = std::env::temp_dir().join("config-dotenv-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # std::fs::write(dir.join(".env"), "APP_PORT=80\nAPP_DATABASE__HOST=localhost\n").unwrap();
+/// # std::fs::write(dir.join(".env.production"), "export APP_PORT=8080 # behind a proxy\n").unwrap();
+/// let config = Config::builder()
+///     .add_source(
+///         DotenvStack::new(&dir)
+///             .mode("production")
+///             .environment(
+///                 Environment::with_prefix("APP")
+///                     .prefix_separator("_")
+///                     .separator("__"),
+///             ),
+///     )
+///     .build()?;
+///
+/// assert_eq!(config.get_string("port")?, "8080");
+/// assert_eq!(config.get_string("database.host")?, "localhost");
+/// # Ok::<(), ConfigError>(())
+/// ```
+///
+/// Variables already set in the process environment take precedence in dotenv tooling: add the
+/// same [`Environment`] after this source to get that.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct DotenvStack {
+    dir: PathBuf,
+    mode: Option<String>,
+    environment: Environment,
+}
+
+impl DotenvStack {
+    /// Read the `.env` files of the directory at `dir`
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            mode: None,
+            environment: Environment::default(),
+        }
+    }
+
+    /// Also read the files of `mode`, like `production` or `test`
+    pub fn mode(mut self, mode: &str) -> Self {
+        self.mode = Some(mode.to_owned());
+        self
+    }
+
+    /// Map the variables to keys with `environment`, e.g. to strip a prefix or nest keys
+    ///
+    /// Only the way variables are mapped is used: `environment` is not read from.
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Names of the files to read, lowest precedence first
+    fn files(&self) -> Vec<String> {
+        let mut files = vec![".env".to_owned()];
+        if self.mode.as_deref() != Some("test") {
+            files.push(".env.local".to_owned());
+        }
+        if let Some(ref mode) = self.mode {
+            files.push(format!(".env.{mode}"));
+            files.push(format!(".env.{mode}.local"));
+        }
+        files
+    }
+}
+
+impl Source for DotenvStack {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn keeps_existing_types(&self) -> bool {
+        self.environment.keeps_existing_types()
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        let mut vars = Map::new();
+        for name in self.files() {
+            let path = self.dir.join(name);
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(ConfigError::SourceRead {
+                        origin: "dotenv file".to_owned(),
+                        uri: Some(uri(&path)),
+                        cause: Box::new(err),
+                    });
+                }
+            };
+
+            let uri = uri(&path);
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+            record_loaded(SourceInfo::new(Some(uri.clone()), modified, &content));
+            let parsed = FileFormat::Dotenv
+                .parse(Some(&uri), &content)
+                .map_err(|cause| ConfigError::FileParse {
+                    uri: Some(uri.clone()),
+                    cause,
+                })?;
+
+            let origin: Arc<str> = uri.into();
+            for (name, value) in parsed {
+                vars.insert(name, (value.into_string()?, origin.clone()));
+            }
+        }
+
+        self.environment.clone().source_with_origins(vars).collect()
+    }
+}
@@ -1,5 +1,8 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::env;
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
+use std::sync::Arc;
 
 #[cfg(feature = "convert-case")]
 use convert_case::{Case, Casing};
@@ -7,8 +10,78 @@ use convert_case::{Case, Casing};
 use crate::ConfigError;
 use crate::error::Result;
 use crate::map::Map;
+use crate::path::Expression;
 use crate::source::Source;
-use crate::value::{Value, ValueKind};
+use crate::value::{Notations, Value, ValueKind};
+
+pub(crate) type Snapshot = Arc<[(OsString, OsString)]>;
+
+thread_local! {
+    /// Variables shared by the [`Environment`] sources collected within [`share_snapshot`], read
+    /// when the first of them is collected
+    static SNAPSHOT: RefCell<Option<Option<Snapshot>>> = const { RefCell::new(None) };
+}
+
+/// Run `collect`, reading the process environment at most once for all [`Environment`] sources
+pub(crate) fn share_snapshot<R>(collect: impl FnOnce() -> R) -> R {
+    let outer = SNAPSHOT.with(|snapshot| {
+        let mut snapshot = snapshot.borrow_mut();
+        let outer = snapshot.take();
+        *snapshot = Some(outer.clone().flatten());
+        outer
+    });
+    let result = collect();
+    SNAPSHOT.with(|snapshot| *snapshot.borrow_mut() = outer);
+    result
+}
+
+/// Run `collect` with `snapshot` shared by all [`Environment`] sources, e.g. in a thread
+/// collecting a source for a [`share_snapshot`] call of another thread
+pub(crate) fn with_snapshot<R>(snapshot: Snapshot, collect: impl FnOnce() -> R) -> R {
+    let outer = SNAPSHOT.with(|shared| shared.borrow_mut().replace(Some(snapshot)));
+    let result = collect();
+    SNAPSHOT.with(|shared| *shared.borrow_mut() = outer);
+    result
+}
+
+/// The process environment, from the shared snapshot if any
+pub(crate) fn vars() -> Snapshot {
+    SNAPSHOT.with(|snapshot| match *snapshot.borrow_mut() {
+        Some(ref mut shared) => shared
+            .get_or_insert_with(|| env::vars_os().collect())
+            .clone(),
+        None => env::vars_os().collect(),
+    })
+}
+
+/// Strip the lowercase `prefix` from `key`, ignoring the case of `key`
+///
+/// This avoids lowercasing every variable in the environment only to discard most of them.
+fn strip_prefix_ignore_case<'a>(key: &'a str, prefix: &str) -> Option<&'a str> {
+    let mut prefix = prefix.chars();
+    for (index, c) in key.char_indices() {
+        if prefix.as_str().is_empty() {
+            return Some(&key[index..]);
+        }
+        for c in c.to_lowercase() {
+            if prefix.next() != Some(c) {
+                return None;
+            }
+        }
+    }
+    prefix.as_str().is_empty().then_some("")
+}
+
+/// Whether `name` starts with the lowercase `prefix`, ignoring its case, even if the rest of
+/// `name` is not valid Unicode
+fn os_str_has_prefix(name: &OsStr, prefix: &str) -> bool {
+    let bytes = name.as_encoded_bytes();
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(valid) => valid,
+        Err(err) => std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap_or_default(),
+    };
+    strip_prefix_ignore_case(valid, prefix).is_some()
+}
 
 /// An environment source collects a dictionary of environment variables values into a hierarchical
 /// config Value type. We have to be aware how the config tree is created from the environment
@@ -55,15 +128,40 @@ pub struct Environment {
     /// A list of keys which should always be parsed as a list. If not set you can have only `Vec<String>` or `String` (not both) in one environment.
     list_parse_keys: Option<Vec<String>>,
 
+    /// Parsers of the values of the variables setting each key, instead of the standard parsing.
+    parsers: Vec<(String, Parser)>,
+
     /// Ignore empty env values (treat as unset).
     ignore_empty: bool,
 
     /// Parses booleans, integers and floats if they're detected (can be safely parsed).
     try_parsing: bool,
 
+    /// Number notations recognized when `try_parsing`, beyond plain decimals.
+    notations: Notations,
+
+    /// Convert values to the type of the values they override when building.
+    keep_existing_types: bool,
+
     // Preserve the prefix while parsing
     keep_prefix: bool,
 
+    /// Treat all-digit key segments as array indices instead of table keys.
+    numeric_keys_as_index: bool,
+
+    /// Read the value of `KEY_FILE` variables from the file they point to.
+    #[cfg(feature = "fs")]
+    file_indirection: bool,
+
+    /// What to do when a variable sets a key that others nest values under.
+    key_collision: KeyCollision,
+
+    /// Names of the variables that must be set, after the prefix.
+    required_keys: Vec<String>,
+
+    /// Last key segment, lowercased, of the variables appending to an array.
+    append_suffix: Option<String>,
+
     /// Alternate source for the environment. This can be used when you want to test your own code
     /// using this source, without the need to change the actual system environment variables.
     ///
@@ -99,9 +197,105 @@ pub struct Environment {
     /// }
     /// ```
     source: Option<Map<String, String>>,
+
+    /// Where the variables of `source` were read from, instead of the environment
+    origins: Map<String, Arc<str>>,
+
+    /// Variables captured when the source was created, read instead of the process environment.
+    snapshot: Option<Snapshot>,
 }
 
 impl Environment {
+    /// Capture the process environment now, instead of when the source is collected.
+    ///
+    /// Variables set or removed between creating the source and building the configuration are
+    /// ignored, e.g. by tests running in parallel in the same process.
+    pub fn snapshot() -> Self {
+        Self::from_closure(env::vars_os)
+    }
+
+    /// Capture the variables returned by `vars` now, like [`snapshot`](Self::snapshot).
+    ///
+    /// `vars` is called once, right away, e.g. to read the environment while holding a lock
+    /// shared with tests that modify it, or to add variables to it.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let env = Environment::from_closure(|| {
+    ///     std::env::vars().chain([("APP_PORT".to_owned(), "8080".to_owned())])
+    /// })
+    /// .prefix("APP");
+    ///
+    /// let config = Config::builder().add_source(env).build()?;
+    /// assert_eq!(config.get_string("port")?, "8080");
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn from_closure<F, I, K, V>(vars: F) -> Self
+    where
+        F: FnOnce() -> I,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        Self {
+            snapshot: Some(
+                vars()
+                    .into_iter()
+                    .map(|(key, value)| (key.into(), value.into()))
+                    .collect(),
+            ),
+            ..Self::default()
+        }
+    }
+
+    /// Read the variables `vars` instead of the process environment, like
+    /// [`source`](Self::source) but with names and values that need not be valid Unicode.
+    ///
+    /// The prefix is matched on the names as they are, so the variables of another prefix are
+    /// skipped whatever their encoding, like those of the process environment. A variable that
+    /// has the prefix but a name or value that is not valid Unicode is an error. Without a
+    /// prefix, only the variables whose name is not valid Unicode are skipped.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # use std::ffi::OsString;
+    /// let vars: Vec<(OsString, OsString)> = vec![
+    ///     ("APP_PORT".into(), "8080".into()),
+    ///     ("HOME".into(), "/home/user".into()),
+    /// ];
+    ///
+    /// let config = Config::builder()
+    ///     .add_source(Environment::with_source_os(vars).prefix("APP"))
+    ///     .build()?;
+    /// assert_eq!(config.get_string("port")?, "8080");
+    /// assert!(config.get_string("home").is_err());
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn with_source_os<I, K, V>(vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        Self::default().source_os(vars)
+    }
+
+    /// See [`Environment::with_source_os`]
+    pub fn source_os<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        self.source = None;
+        self.snapshot = Some(
+            vars.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        );
+        self
+    }
+
     /// Optional prefix that will limit access to the environment to only keys that
     /// begin with the defined prefix.
     ///
@@ -171,6 +365,51 @@ impl Environment {
         self
     }
 
+    /// Parse the value of the variable setting `key` with `parser`, e.g. a list of CIDR blocks
+    /// or `name=value` pairs, instead of the standard parsing.
+    ///
+    /// `key` is matched like by [`with_list_parse_key`](Self::with_list_parse_key), after the
+    /// prefix is removed and the separators are replaced. The parsed value applies whether or not
+    /// [`try_parsing`](Self::try_parsing) is set, while the values of other keys keep the
+    /// standard parsing. Registering a parser for a key again replaces the previous one.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let env = Environment::with_prefix("APP")
+    ///     .separator("__")
+    ///     .try_parsing(true)
+    ///     .with_parser("server.labels", |text| {
+    ///         let labels: Map<String, String> = text
+    ///             .split(',')
+    ///             .map(|pair| match pair.split_once('=') {
+    ///                 Some((name, value)) => Ok((name.to_owned(), value.to_owned())),
+    ///                 None => Err(ConfigError::Message(format!("expected name=value, got {pair:?}"))),
+    ///             })
+    ///             .collect::<Result<_, _>>()?;
+    ///         Ok(Value::from(labels))
+    ///     })
+    ///     .source(Some(Map::from_iter([
+    ///         ("APP__SERVER__LABELS".to_owned(), "team=core,tier=1".to_owned()),
+    ///         ("APP__SERVER__PORT".to_owned(), "8080".to_owned()),
+    ///     ])));
+    ///
+    /// let config = Config::builder().add_source(env).build()?;
+    /// assert_eq!(config.get_string("server.labels.team")?, "core");
+    /// assert_eq!(config.get_int("server.port")?, 8080);
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    ///
+    /// Collecting fails with the error of `parser`, naming the variable.
+    pub fn with_parser<F>(mut self, key: &str, parser: F) -> Self
+    where
+        F: Fn(&str) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.parsers.retain(|(existing, _)| existing != key);
+        self.parsers
+            .push((key.to_owned(), Parser(Arc::new(parser))));
+        self
+    }
+
     /// Ignore empty env values (treat as unset).
     pub fn ignore_empty(mut self, ignore: bool) -> Self {
         self.ignore_empty = ignore;
@@ -184,12 +423,182 @@ impl Environment {
         self
     }
 
+    /// Parse hexadecimal integers like `0x1F` when `try_parsing`.
+    pub fn parse_hex(mut self, enabled: bool) -> Self {
+        self.notations.hex = enabled;
+        self
+    }
+
+    /// Parse octal integers like `0o755` when `try_parsing`.
+    pub fn parse_octal(mut self, enabled: bool) -> Self {
+        self.notations.octal = enabled;
+        self
+    }
+
+    /// Accept `_` between digits of numbers, like `1_000_000`, when `try_parsing`.
+    pub fn parse_underscores(mut self, enabled: bool) -> Self {
+        self.notations.underscores = enabled;
+        self
+    }
+
+    /// Parse floats in scientific notation like `1.5e3` when `try_parsing`.
+    ///
+    /// Enabled by default. When disabled, such values are kept as strings.
+    pub fn parse_scientific(mut self, enabled: bool) -> Self {
+        self.notations.no_scientific = !enabled;
+        self
+    }
+
+    /// Keep values like `007` or `+4912345` as strings when `try_parsing`.
+    ///
+    /// Parsing them as integers would drop the leading zeros of zip codes or the `+` of phone
+    /// numbers. Numbers starting with a `+` sign, or with a `0` followed by another digit, are
+    /// then kept as strings, while `0`, `0.5` or `-12` are still parsed. Disabled by default.
+    pub fn preserve_leading_zeros(mut self, enabled: bool) -> Self {
+        self.notations.preserve_leading_zeros = enabled;
+        self
+    }
+
+    /// Convert each value to the type of the value it overrides when building, e.g. to an
+    /// integer for a port a file already set.
+    ///
+    /// Building then fails on a value that does not convert, naming its key and the
+    /// environment, rather than when deserializing. Values overriding a string, a table or an
+    /// array, and values of keys nothing set before, are kept as they are collected.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let env = Environment::with_prefix("APP")
+    ///     .keep_existing_types(true)
+    ///     .source(Some(Map::from_iter([("APP_PORT".to_owned(), "http".to_owned())])));
+    ///
+    /// let error = Config::builder()
+    ///     .set_default("port", 8080)?
+    ///     .add_source(env)
+    ///     .build()
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "invalid type: string \"http\", expected an integer for key `port` in the environment"
+    /// );
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn keep_existing_types(mut self, keep: bool) -> Self {
+        self.keep_existing_types = keep;
+        self
+    }
+
     // Preserve the prefix while parsing
     pub fn keep_prefix(mut self, keep: bool) -> Self {
         self.keep_prefix = keep;
         self
     }
 
+    /// Whether all-digit key segments are array indices rather than table keys.
+    ///
+    /// Disabled by default, so `DIVISORS_1` sets the `1` key of the `divisors` table.
+    /// When enabled, such segments are array indices instead and `LIST_0_NAME` sets
+    /// `list[0].name`. Only segments after the first one are considered.
+    pub fn numeric_keys_as_indices(mut self, enabled: bool) -> Self {
+        self.numeric_keys_as_index = enabled;
+        self
+    }
+
+    /// Read the value of variables suffixed with `_FILE` from the file they point to.
+    ///
+    /// Following the convention used to pass secrets to containers,
+    /// `APP_DB_PASSWORD_FILE=/run/secrets/db` sets the `db_password` key, split by the
+    /// [`separator`](Self::separator) as usual, to the contents of `/run/secrets/db` with trailing
+    /// newlines removed. It takes precedence over an `APP_DB_PASSWORD` variable. Collecting fails
+    /// if the file can't be read.
+    #[cfg(feature = "fs")]
+    pub fn file_indirection(mut self, enabled: bool) -> Self {
+        self.file_indirection = enabled;
+        self
+    }
+
+    /// What to do when a variable sets a key that others nest values under, like `APP_LOG` and
+    /// `APP_LOG_LEVEL` with a separator of `_`.
+    ///
+    /// Defaults to [`KeyCollision::PreferNested`].
+    pub fn key_collision(mut self, policy: KeyCollision) -> Self {
+        self.key_collision = policy;
+        self
+    }
+
+    /// Append the value of variables whose last key segment is `suffix` to an array, rather
+    /// than setting a key.
+    ///
+    /// With a [separator](Self::separator) of `__` and a suffix of `append`,
+    /// `APP__PLUGINS__APPEND=metrics` adds `metrics` after the elements of `plugins` set by
+    /// earlier sources, like a file. With a [`list_separator`](Self::list_separator), each
+    /// element of the list is appended, and [`with_list_parse_key`](Self::with_list_parse_key)
+    /// names the key of the array, `plugins`. The suffix is matched ignoring case.
+    ///
+    /// To replace an element instead, give its index as a key segment, like
+    /// `APP__PLUGINS__0=auth`, with [`numeric_keys_as_indices(true)`](Self::numeric_keys_as_indices).
+    ///
+    /// Appends extend the array merged from earlier sources, except when keys are
+    /// [normalized](crate::ConfigBuilder::normalize_keys) or
+    /// [merge conflicts are detected](crate::ConfigBuilder::detect_merge_conflicts): each source is
+    /// then collected on its own, and the array of the environment replaces the earlier one.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "json")] {
+    /// let env = Environment::with_prefix("APP")
+    ///     .separator("__")
+    ///     .append_suffix("append")
+    ///     .try_parsing(true)
+    ///     .list_separator(",")
+    ///     .source(Some(Map::from_iter([(
+    ///         "APP__PLUGINS__APPEND".to_owned(),
+    ///         "metrics,tracing".to_owned(),
+    ///     )])));
+    ///
+    /// let config = Config::builder()
+    ///     .add_source(File::from_str(r#"{"plugins": ["auth"]}"#, FileFormat::Json))
+    ///     .add_source(env)
+    ///     .build()?;
+    ///
+    /// let plugins: Vec<String> = config.get("plugins")?;
+    /// assert_eq!(plugins, ["auth", "metrics", "tracing"]);
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn append_suffix(mut self, suffix: &str) -> Self {
+        self.append_suffix = Some(suffix.to_lowercase());
+        self
+    }
+
+    /// Fail collecting when any of the variables named `keys`, after the prefix, is unset.
+    ///
+    /// Names are matched ignoring case, so with a prefix of `APP`, `DATABASE_URL` requires
+    /// `APP_DATABASE_URL`. The error lists every missing variable at once. A variable ignored
+    /// because it is [empty](Self::ignore_empty) counts as missing, and one read from a
+    /// [file](Self::file_indirection) as set.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let env = Environment::with_prefix("APP")
+    ///     .require_keys(["DATABASE_URL", "PORT", "LOG_LEVEL"])
+    ///     .source(Some(Map::from_iter([("APP_PORT".to_owned(), "8080".to_owned())])));
+    ///
+    /// let error = Config::builder().add_source(env).build().unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "missing required environment variables: APP_DATABASE_URL, APP_LOG_LEVEL"
+    /// );
+    /// ```
+    pub fn require_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required_keys.extend(keys.into_iter().map(Into::into));
+        self
+    }
+
     /// Alternate source for the environment. This can be used when you want to test your own code
     /// using this source, without the need to change the actual system environment variables.
     ///
@@ -228,6 +637,19 @@ impl Environment {
         self.source = source;
         self
     }
+
+    /// Read `vars` instead of the environment, each along with the origin of its value, e.g. the
+    /// file it was read from
+    #[cfg(all(feature = "fs", feature = "dotenv"))]
+    pub(crate) fn source_with_origins(mut self, vars: Map<String, (String, Arc<str>)>) -> Self {
+        let (source, origins) = vars
+            .into_iter()
+            .map(|(name, (value, origin))| ((name.clone(), value), (name, origin)))
+            .unzip();
+        self.source = Some(source);
+        self.origins = origins;
+        self
+    }
 }
 
 impl Source for Environment {
@@ -235,9 +657,15 @@ impl Source for Environment {
         Box::new((*self).clone())
     }
 
+    fn keeps_existing_types(&self) -> bool {
+        self.keep_existing_types
+    }
+
     fn collect(&self) -> Result<Map<String, Value>> {
         let mut m = Map::new();
-        let uri: String = "the environment".into();
+        // Values read from files, which take precedence
+        let mut indirect_values = Map::new();
+        let uri: Arc<str> = "the environment".into();
 
         let separator = self.separator.as_deref().unwrap_or("");
         #[cfg(feature = "convert-case")]
@@ -253,42 +681,86 @@ impl Source for Environment {
             .prefix
             .as_ref()
             .map(|prefix| format!("{prefix}{prefix_separator}").to_lowercase());
-
-        let collector = |(key, value): (OsString, OsString)| {
-            let key = match key.into_string() {
-                Ok(key) => key,
+        // Names of the variables set, after the prefix, to check the required ones
+        let mut present = HashSet::new();
+
+        let mut collector = |(name, value): (&OsString, &OsString)| {
+            let name = match (name.to_str(), &prefix_pattern) {
+                (Some(name), _) => name,
+                // Key is not valid unicode but has the prefix, so it was meant to be read
+                (None, Some(prefix_pattern)) if os_str_has_prefix(name, prefix_pattern) => {
+                    return Err(ConfigError::Message(format!(
+                        "env variable {name:?} has a name that is not valid Unicode"
+                    )));
+                }
                 // Key is not valid unicode, skip it
-                Err(_) => return Ok(()),
+                (None, _) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(?name, "skipping env variable with a non-Unicode name");
+                    return Ok(());
+                }
             };
+            let uri = self.origins.get(name).unwrap_or(&uri);
 
             // Treat empty environment variables as unset
             if self.ignore_empty && value.is_empty() {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(name, "skipping empty env variable");
                 return Ok(());
             }
 
-            let mut key = key.to_lowercase();
-
-            // Check for prefix
-            if let Some(ref prefix_pattern) = prefix_pattern {
-                if key.starts_with(prefix_pattern) {
-                    if !self.keep_prefix {
-                        // Remove this prefix from the key
-                        key = key[prefix_pattern.len()..].to_string();
-                    }
-                } else {
+            // Check for prefix, skipping other keys before lowercasing them
+            let mut key = match prefix_pattern {
+                Some(ref prefix_pattern) => match strip_prefix_ignore_case(name, prefix_pattern) {
+                    // Remove this prefix from the key
+                    Some(rest) if !self.keep_prefix => rest.to_lowercase(),
+                    Some(_) => name.to_lowercase(),
                     // Skip this key
-                    return Ok(());
-                }
-            }
+                    None => {
+                        #[cfg(feature = "tracing")]
+                        tracing::trace!(
+                            name,
+                            prefix = prefix_pattern,
+                            "skipping env variable without the prefix"
+                        );
+                        return Ok(());
+                    }
+                },
+                None => name.to_lowercase(),
+            };
 
             // At this point, we don't know if the key is required or not.
             // Therefore if the value is not a valid unicode string, we error out.
-            let value = value.into_string().map_err(|os_string| {
+            let value = value.to_str().map(str::to_owned).ok_or_else(|| {
                 ConfigError::Message(format!(
-                    "env variable {key:?} contains non-Unicode data: {os_string:?}"
+                    "env variable {key:?} contains non-Unicode data: {value:?}"
                 ))
             })?;
 
+            #[cfg(feature = "fs")]
+            let indirect = self.file_indirection && key.ends_with("_file");
+            #[cfg(not(feature = "fs"))]
+            let indirect = false;
+            #[cfg(feature = "fs")]
+            let value = if indirect {
+                key.truncate(key.len() - "_file".len());
+                read_indirect_value(name, &value)?
+            } else {
+                value
+            };
+
+            if !self.required_keys.is_empty() {
+                let mut rest = prefix_pattern
+                    .as_ref()
+                    .and_then(|prefix_pattern| strip_prefix_ignore_case(name, prefix_pattern))
+                    .unwrap_or(name)
+                    .to_lowercase();
+                if indirect {
+                    rest.truncate(rest.len() - "_file".len());
+                }
+                present.insert(rest);
+            }
+
             // If separator is given replace with `.`
             if !separator.is_empty() {
                 key = key.replace(separator, ".");
@@ -299,20 +771,35 @@ impl Source for Environment {
                 key = key.to_case(*convert_case);
             }
 
-            let value = if self.try_parsing {
-                // convert to lowercase because bool parsing expects all lowercase
-                if let Ok(parsed) = value.to_lowercase().parse::<bool>() {
-                    ValueKind::Boolean(parsed)
-                } else if let Ok(parsed) = value.parse::<i64>() {
-                    ValueKind::I64(parsed)
-                } else if let Ok(parsed) = value.parse::<f64>() {
-                    ValueKind::Float(parsed)
+            // Append to the array at the rest of the key
+            let append_len = self.append_suffix.as_ref().and_then(|suffix| {
+                let array = key.strip_suffix(suffix.as_str())?.strip_suffix('.')?;
+                (!array.is_empty()).then_some(array.len())
+            });
+            if let Some(len) = append_len {
+                key.truncate(len);
+            }
+
+            let parser = self.parsers.iter().find(|(parsed, _)| *parsed == key);
+            let value = if let Some((_, Parser(parser))) = parser {
+                parser(&value)
+                    .map_err(|err| {
+                        ConfigError::Message(format!(
+                            "invalid value for env variable {name:?}: {err}"
+                        ))
+                    })?
+                    .kind
+            } else if self.try_parsing {
+                if let Some(parsed) = self.notations.parse(&value) {
+                    parsed
                 } else if let Some(separator) = &self.list_separator {
                     if let Some(keys) = &self.list_parse_keys {
                         if keys.contains(&key) {
                             let v: Vec<Value> = value
                                 .split(separator)
-                                .map(|s| Value::new(Some(&uri), ValueKind::String(s.to_owned())))
+                                .map(|s| {
+                                    Value::with_origin(Some(uri), ValueKind::String(s.to_owned()))
+                                })
                                 .collect();
                             ValueKind::Array(v)
                         } else {
@@ -321,7 +808,7 @@ impl Source for Environment {
                     } else {
                         let v: Vec<Value> = value
                             .split(separator)
-                            .map(|s| Value::new(Some(&uri), ValueKind::String(s.to_owned())))
+                            .map(|s| Value::with_origin(Some(uri), ValueKind::String(s.to_owned())))
                             .collect();
                         ValueKind::Array(v)
                     }
@@ -332,20 +819,142 @@ impl Source for Environment {
                 ValueKind::String(value)
             };
 
-            m.insert(key, Value::new(Some(&uri), value));
+            if self.numeric_keys_as_index {
+                if let Ok(expr) = key.parse::<Expression>() {
+                    key = expr.numeric_keys_as_indices().to_string();
+                }
+            }
+            if append_len.is_some() {
+                key.push_str("[++]");
+            }
+
+            let value = (name.to_owned(), Value::with_origin(Some(uri), value));
+            if indirect {
+                indirect_values.insert(key, value);
+            } else {
+                m.insert(key, value);
+            }
 
             Ok(())
         };
 
         match &self.source {
             Some(source) => source
+                .iter()
+                .map(|(key, value)| (OsString::from(key), OsString::from(value)))
+                .try_for_each(|(key, value)| collector((&key, &value))),
+            None => self
+                .snapshot
                 .clone()
-                .into_iter()
-                .map(|(key, value)| (key.into(), value.into()))
+                .unwrap_or_else(vars)
+                .iter()
+                .map(|(key, value)| (key, value))
                 .try_for_each(collector),
-            None => env::vars_os().try_for_each(collector),
         }?;
 
-        Ok(m)
+        let missing: Vec<String> = self
+            .required_keys
+            .iter()
+            .filter(|key| !present.contains(&key.to_lowercase()))
+            .map(|key| match self.prefix {
+                Some(ref prefix) => format!("{prefix}{prefix_separator}{key}"),
+                None => key.clone(),
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(ConfigError::Message(format!(
+                "missing required environment variables: {}",
+                missing.join(", ")
+            )));
+        }
+
+        m.extend(indirect_values);
+        resolve_collisions(m, self.key_collision)
+    }
+}
+
+/// A parser of the values of a key, registered with [`Environment::with_parser`]
+#[derive(Clone)]
+struct Parser(Arc<ParseFn>);
+
+type ParseFn = dyn Fn(&str) -> Result<Value> + Send + Sync;
+
+impl std::fmt::Debug for Parser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Parser")
+    }
+}
+
+/// How an [`Environment`] resolves a variable setting a key that others nest values under
+///
+/// With a separator of `_`, `APP_LOG=debug` sets `log` to a string while `APP_LOG_LEVEL=info`
+/// sets `log.level`, so `log` can't be both. Which one is kept doesn't depend on the order the
+/// variables are read in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyCollision {
+    /// Keep the nested values, ignoring the variable that sets the key they are nested under
+    #[default]
+    PreferNested,
+    /// Keep the value of the key, ignoring the variables nested under it
+    PreferOuter,
+    /// Fail collecting, naming the colliding variables
+    Error,
+}
+
+/// Apply `policy` to the keys of `vars` that other keys nest values under, like `a` and `a.b`
+fn resolve_collisions(
+    vars: Map<String, (String, Value)>,
+    policy: KeyCollision,
+) -> Result<Map<String, Value>> {
+    let mut collisions: Vec<(&str, &str)> = Vec::new();
+    for key in vars.keys() {
+        for (end, _) in key.match_indices(['.', '[']) {
+            if let Some((outer, _)) = vars.get_key_value(&key[..end]) {
+                collisions.push((outer, key));
+            }
+        }
+    }
+    collisions.sort_unstable();
+
+    let removed: HashSet<String> = match (policy, collisions.first()) {
+        (_, None) => HashSet::new(),
+        (KeyCollision::Error, Some(&(outer, nested))) => {
+            return Err(ConfigError::Message(format!(
+                "env variable {:?} sets key {outer:?}, colliding with {nested:?} set by {:?}",
+                vars[outer].0, vars[nested].0
+            )));
+        }
+        (KeyCollision::PreferNested, _) => collisions
+            .iter()
+            .map(|(outer, _)| (*outer).to_owned())
+            .collect(),
+        (KeyCollision::PreferOuter, _) => collisions
+            .iter()
+            .map(|(_, nested)| (*nested).to_owned())
+            .collect(),
+    };
+
+    Ok(vars
+        .into_iter()
+        .filter(|(key, _)| !removed.contains(key))
+        .map(|(key, (_, value))| (key, value))
+        .collect())
+}
+
+/// Read the file at `path` given by the variable `name`, without trailing newlines
+#[cfg(feature = "fs")]
+fn read_indirect_value(name: &str, path: &str) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(mut value) => {
+            let len = value.trim_end_matches(['\n', '\r']).len();
+            value.truncate(len);
+            Ok(value)
+        }
+        Err(err) => Err(ConfigError::SourceRead {
+            origin: format!("env variable {name:?} points to"),
+            uri: Some(path.to_owned()),
+            cause: Box::new(err),
+        }),
     }
 }
@@ -1,6 +1,7 @@
 use std::error::Error;
 use std::fmt;
 use std::result;
+use std::sync::Arc;
 
 use serde_core::de;
 use serde_core::ser;
@@ -37,6 +38,33 @@ impl fmt::Display for Unexpected {
     }
 }
 
+/// Broad category of a [`ConfigError`], as returned by [`ConfigError::kind`].
+///
+/// Categories are stable across the variants added to [`ConfigError`], so applications can map
+/// them to exit codes or metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A requested key, or a field required by the deserialized type, is missing.
+    NotFound,
+
+    /// A source or a key path could not be parsed.
+    Parse,
+
+    /// A value could not be converted into the requested type.
+    Type,
+
+    /// A source could not be read.
+    Io,
+
+    /// An error returned by a custom source or format.
+    Foreign,
+
+    /// The configuration is well formed but rejected, e.g. a merge conflict, a change to a
+    /// frozen configuration or a custom message.
+    Validation,
+}
+
 /// Represents all possible errors that can occur when working with
 /// configuration.
 #[non_exhaustive]
@@ -47,6 +75,15 @@ pub enum ConfigError {
     /// Configuration property was not found
     NotFound(String),
 
+    /// Configuration property was not found, but keys with a similar spelling exist
+    NotFoundWithSuggestions {
+        /// The key that was looked up
+        key: String,
+
+        /// Existing keys with a similar spelling, closest first
+        suggestions: Vec<String>,
+    },
+
     /// Configuration path could not be parsed.
     PathParse { cause: Box<dyn Error + Send + Sync> },
 
@@ -94,23 +131,100 @@ pub enum ConfigError {
         key: Option<String>,
     },
 
+    /// A source changed the shape of a value set by an earlier source or default.
+    ///
+    /// Only reported when [`ConfigBuilder::detect_merge_conflicts`] is enabled.
+    ///
+    /// [`ConfigBuilder::detect_merge_conflicts`]: crate::ConfigBuilder::detect_merge_conflicts
+    MergeConflict {
+        /// The key of the conflicting values.
+        key: String,
+
+        /// The URI that references the source of the value being replaced.
+        existing_origin: Option<String>,
+
+        /// The type of the value being replaced, e.g. `table`.
+        existing_type: &'static str,
+
+        /// The URI that references the source of the replacing value.
+        incoming_origin: Option<String>,
+
+        /// The type of the replacing value, e.g. `string`.
+        incoming_type: &'static str,
+    },
+
+    /// A source could not be read.
+    ///
+    /// The error reading it is returned by [`Error::source`], not included in the message.
+    SourceRead {
+        /// What was being read, like `sidecar configuration` or
+        /// `env variable "APP_KEY_FILE" points to`
+        origin: String,
+
+        /// Where it was read from, like a path or a socket.
+        uri: Option<String>,
+
+        /// The error reading it, usually an [`std::io::Error`].
+        cause: Box<dyn Error + Send + Sync>,
+    },
+
+    /// The content of a source could not be parsed in its format.
+    ///
+    /// The parse error is returned by [`Error::source`], not included in the message.
+    FormatParse {
+        /// The name of the format, like `JSON` or `dotenv`
+        format: String,
+
+        /// Where the content was read from, like a path or a socket.
+        uri: Option<String>,
+
+        /// The error returned by the parser.
+        cause: Box<dyn Error + Send + Sync>,
+    },
+
     /// Custom message
     Message(String),
 
     /// Unadorned error from a foreign origin.
     Foreign(Box<dyn Error + Send + Sync>),
+
+    /// Several errors reported together, like those recorded by a
+    /// [`Deferred`](crate::builder::Deferred) builder, in the order they occurred.
+    Multiple(Vec<ConfigError>),
 }
 
 impl ConfigError {
+    /// The category of this error.
+    ///
+    /// Errors extended with the key they occurred at are categorized by their cause, and I/O
+    /// errors are reported as [`ErrorKind::Io`] whichever variant carries them.
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            Self::NotFound(_) | Self::NotFoundWithSuggestions { .. } => ErrorKind::NotFound,
+            Self::PathParse { .. } => ErrorKind::Parse,
+            Self::FileParse { ref cause, .. } if cause.is::<std::io::Error>() => ErrorKind::Io,
+            Self::FileParse { .. } => ErrorKind::Parse,
+            Self::Type { .. } => ErrorKind::Type,
+            Self::At { ref error, .. } => error.kind(),
+            Self::SourceRead { ref cause, .. } if cause.is::<std::io::Error>() => ErrorKind::Io,
+            Self::SourceRead { .. } => ErrorKind::Foreign,
+            Self::FormatParse { .. } => ErrorKind::Parse,
+            Self::Foreign(ref cause) if cause.is::<std::io::Error>() => ErrorKind::Io,
+            Self::Foreign(_) => ErrorKind::Foreign,
+            Self::Multiple(ref errors) => errors.first().map_or(ErrorKind::Validation, Self::kind),
+            Self::Frozen | Self::MergeConflict { .. } | Self::Message(_) => ErrorKind::Validation,
+        }
+    }
+
     // FIXME: pub(crate)
     #[doc(hidden)]
     pub fn invalid_type(
-        origin: Option<String>,
+        origin: Option<Arc<str>>,
         unexpected: Unexpected,
         expected: &'static str,
     ) -> Self {
         Self::Type {
-            origin,
+            origin: origin.as_deref().map(Into::into),
             unexpected,
             expected,
             key: None,
@@ -129,6 +243,48 @@ impl ConfigError {
         })
     }
 
+    /// Attribute an error collected from a source to its `uri`, unless it already names one
+    #[cfg(feature = "async")]
+    #[must_use]
+    pub(crate) fn with_uri(self, uri: String) -> Self {
+        match self {
+            Self::Foreign(cause) | Self::FileParse { uri: None, cause } => Self::FileParse {
+                uri: Some(uri),
+                cause,
+            },
+            Self::FormatParse {
+                format,
+                uri: None,
+                cause,
+            } => Self::FormatParse {
+                format,
+                uri: Some(uri),
+                cause,
+            },
+            Self::Type {
+                origin: None,
+                unexpected,
+                expected,
+                key,
+            } => Self::Type {
+                origin: Some(uri),
+                unexpected,
+                expected,
+                key,
+            },
+            other => other,
+        }
+    }
+
+    /// The key this error occurred at, if known
+    #[cfg(feature = "path-to-error")]
+    pub(crate) fn key(&self) -> Option<&str> {
+        match *self {
+            Self::Type { ref key, .. } | Self::At { ref key, .. } => key.as_deref(),
+            _ => None,
+        }
+    }
+
     // FIXME: pub(crate)
     #[doc(hidden)]
     #[must_use]
@@ -161,10 +317,10 @@ impl ConfigError {
     }
 
     #[must_use]
-    fn prepend(self, segment: &str, add_dot: bool) -> Self {
+    fn prepend(self, segment: &str) -> Self {
         let concat = |key: Option<String>| {
             let key = key.unwrap_or_default();
-            let dot = if add_dot && key.as_bytes().first().unwrap_or(&b'[') != &b'[' {
+            let dot = if key.as_bytes().first().unwrap_or(&b'[') != &b'[' {
                 "."
             } else {
                 ""
@@ -189,6 +345,13 @@ impl ConfigError {
                 key: Some(concat(key)),
             },
             Self::NotFound(key) => Self::NotFound(concat(Some(key))),
+            Self::NotFoundWithSuggestions { key, suggestions } => Self::NotFoundWithSuggestions {
+                key: concat(Some(key)),
+                suggestions: suggestions
+                    .into_iter()
+                    .map(|suggestion| concat(Some(suggestion)))
+                    .collect(),
+            },
             other => Self::At {
                 error: Box::new(other),
                 origin: None,
@@ -197,17 +360,99 @@ impl ConfigError {
         }
     }
 
+    pub(crate) fn not_found(key: &str) -> Self {
+        Self::NotFound(key.into())
+    }
+
+    /// A missing `key`, suggesting the similar existing keys, if any
+    pub(crate) fn not_found_similar(key: &str, suggestions: Vec<String>) -> Self {
+        match suggestions.is_empty() {
+            true => Self::not_found(key),
+            false => Self::NotFoundWithSuggestions {
+                key: key.into(),
+                suggestions,
+            },
+        }
+    }
+
+    /// Suggest similar keys for a field missing from a table with the `available` keys
+    #[must_use]
+    pub(crate) fn suggest_from<'a>(self, available: impl Iterator<Item = &'a str>) -> Self {
+        match self {
+            Self::NotFound(key) => {
+                let suggestions = similar_keys(&key, available);
+                Self::not_found_similar(&key, suggestions)
+            }
+            other => other,
+        }
+    }
+
+    /// Existing keys with a similar spelling to the missing one, closest first
+    ///
+    /// Empty unless this is a [`ConfigError::NotFoundWithSuggestions`].
+    pub fn suggestions(&self) -> &[String] {
+        match *self {
+            Self::NotFoundWithSuggestions {
+                ref suggestions, ..
+            } => suggestions,
+            _ => &[],
+        }
+    }
+
     #[must_use]
     pub(crate) fn prepend_key(self, key: &str) -> Self {
-        self.prepend(key, true)
+        self.prepend(key)
     }
 
     #[must_use]
     pub(crate) fn prepend_index(self, idx: usize) -> Self {
-        self.prepend(&format!("[{idx}]"), false)
+        self.prepend(&format!("[{idx}]"))
     }
 }
 
+/// Keys from `available` within a small edit distance of `key`, closest first.
+///
+/// Case is ignored when measuring the distance, so a differently cased key is always suggested.
+pub(crate) fn similar_keys<'a>(key: &str, available: impl Iterator<Item = &'a str>) -> Vec<String> {
+    const MAX_SUGGESTIONS: usize = 3;
+
+    let key = key.to_lowercase();
+    let max_distance = (key.chars().count() / 3).max(1);
+
+    let mut candidates: Vec<(usize, &str)> = available
+        .filter_map(|candidate| {
+            let distance = edit_distance(&key, &candidate.to_lowercase());
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    candidates.sort_unstable();
+
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_owned())
+        .collect()
+}
+
+/// Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
 /// Alias for a `Result` with the error type set to `ConfigError`.
 pub(crate) type Result<T, E = ConfigError> = result::Result<T, E>;
 
@@ -233,6 +478,23 @@ impl fmt::Display for ConfigError {
                 write!(f, "missing configuration field {key:?}")
             }
 
+            ConfigError::NotFoundWithSuggestions {
+                ref key,
+                ref suggestions,
+            } => {
+                write!(f, "missing configuration field {key:?}")?;
+
+                for (i, suggestion) in suggestions.iter().enumerate() {
+                    let separator = if i == 0 { ", did you mean" } else { " or" };
+                    write!(f, "{separator} {suggestion:?}")?;
+                }
+                if !suggestions.is_empty() {
+                    write!(f, "?")?;
+                }
+
+                Ok(())
+            }
+
             ConfigError::Type {
                 ref origin,
                 ref unexpected,
@@ -270,6 +532,25 @@ impl fmt::Display for ConfigError {
                 Ok(())
             }
 
+            ConfigError::MergeConflict {
+                ref key,
+                ref existing_origin,
+                existing_type,
+                ref incoming_origin,
+                incoming_type,
+            } => {
+                write!(f, "merge conflict for key `{key}`: {incoming_type}")?;
+                if let Some(ref origin) = *incoming_origin {
+                    write!(f, " from {origin}")?;
+                }
+                write!(f, " would replace {existing_type}")?;
+                if let Some(ref origin) = *existing_origin {
+                    write!(f, " from {origin}")?;
+                }
+
+                Ok(())
+            }
+
             ConfigError::FileParse { ref cause, ref uri } => {
                 write!(f, "{cause}")?;
 
@@ -279,11 +560,62 @@ impl fmt::Display for ConfigError {
 
                 Ok(())
             }
+
+            ConfigError::SourceRead {
+                ref origin,
+                ref uri,
+                ..
+            } => {
+                write!(f, "{origin}")?;
+                if let Some(ref uri) = *uri {
+                    write!(f, " {uri:?}")?;
+                }
+                write!(f, ", which could not be read")
+            }
+
+            ConfigError::FormatParse {
+                ref format,
+                ref uri,
+                ..
+            } => {
+                write!(f, "invalid {format}")?;
+                if let Some(ref uri) = *uri {
+                    write!(f, " in {uri:?}")?;
+                }
+                Ok(())
+            }
+
+            ConfigError::Multiple(ref errors) => {
+                write!(f, "{} configuration errors", errors.len())?;
+                for error in errors {
+                    write!(f, "\n\n{error}")?;
+                }
+
+                Ok(())
+            }
         }
     }
 }
 
-impl Error for ConfigError {}
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            Self::PathParse { ref cause }
+            | Self::FileParse { ref cause, .. }
+            | Self::SourceRead { ref cause, .. }
+            | Self::FormatParse { ref cause, .. }
+            | Self::Foreign(ref cause) => Some(&**cause),
+            Self::At { ref error, .. } => Some(&**error),
+            Self::Frozen
+            | Self::NotFound(_)
+            | Self::NotFoundWithSuggestions { .. }
+            | Self::Type { .. }
+            | Self::MergeConflict { .. }
+            | Self::Message(_)
+            | Self::Multiple(_) => None,
+        }
+    }
+}
 
 impl de::Error for ConfigError {
     fn custom<T: fmt::Display>(msg: T) -> Self {
@@ -291,7 +623,7 @@ impl de::Error for ConfigError {
     }
 
     fn missing_field(field: &'static str) -> Self {
-        Self::NotFound(field.into())
+        Self::not_found(field)
     }
 }
 
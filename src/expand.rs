@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use serde_core::de::{Deserialize, Deserializer, Error as _};
+
+use crate::error::{ConfigError, Result};
+
+/// Maximum depth of nested references, guarding against values that grow on each expansion
+const MAX_DEPTH: usize = 32;
+
+/// Expand the references to environment variables in `text`, looking them up in `vars`
+///
+/// Follows the shell syntax:
+///
+/// - `$VAR` and `${VAR}` expand to the value of `VAR`, or to nothing if it is unset
+/// - `${VAR:-default}` expands to `default` if `VAR` is unset or empty
+/// - `${VAR:?message}` fails with `message` if `VAR` is unset or empty
+/// - `${VAR:+alternative}` expands to `alternative` if `VAR` is set and not empty
+/// - `$$` expands to `$`
+///
+/// Values of variables and the words after `:-`, `:?` and `:+` are expanded in turn.
+pub(crate) fn expand(text: &str, vars: &HashMap<&str, &str>) -> Result<String> {
+    Expander {
+        vars,
+        stack: Vec::new(),
+    }
+    .expand(text)
+}
+
+struct Expander<'a> {
+    vars: &'a HashMap<&'a str, &'a str>,
+    /// Variables whose value is being expanded, to detect cycles
+    stack: Vec<&'a str>,
+}
+
+impl<'a> Expander<'a> {
+    fn expand(&mut self, text: &str) -> Result<String> {
+        if self.stack.len() > MAX_DEPTH {
+            return Err(ConfigError::Message(format!(
+                "environment variable references are nested more than {MAX_DEPTH} levels deep"
+            )));
+        }
+
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+
+            if let Some(tail) = rest.strip_prefix('$') {
+                expanded.push('$');
+                rest = tail;
+            } else if let Some(tail) = rest.strip_prefix('{') {
+                let end = closing_brace(tail).ok_or_else(|| {
+                    ConfigError::Message(format!("unterminated `${{` in {text:?}"))
+                })?;
+                expanded.push_str(&self.braced(&tail[..end])?);
+                rest = &tail[end + 1..];
+            } else {
+                let len = name_len(rest);
+                if len == 0 {
+                    // Not a reference, like `$1` or a trailing `$`
+                    expanded.push('$');
+                } else {
+                    expanded.push_str(&self.var(&rest[..len])?.unwrap_or_default());
+                    rest = &rest[len..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+
+    /// Expand the reference between `${` and `}`
+    fn braced(&mut self, reference: &str) -> Result<String> {
+        let len = name_len(reference);
+        let (name, operator) = reference.split_at(len);
+        if name.is_empty() {
+            return Err(ConfigError::Message(format!(
+                "invalid environment variable reference `${{{reference}}}`"
+            )));
+        }
+
+        let value = self.var(name)?.filter(|value| !value.is_empty());
+        if operator.is_empty() {
+            return Ok(value.unwrap_or_default());
+        }
+        let (operator, word) = operator.split_at(operator.len().min(2));
+        match (operator, value) {
+            (":-", Some(value)) => Ok(value),
+            (":-", None) => self.expand(word),
+            (":?", Some(value)) => Ok(value),
+            (":?", None) => {
+                let message = match self.expand(word)? {
+                    message if message.is_empty() => "not set".to_owned(),
+                    message => message,
+                };
+                Err(ConfigError::Message(format!(
+                    "environment variable `{name}` is {message}"
+                )))
+            }
+            (":+", Some(_)) => self.expand(word),
+            (":+", None) => Ok(String::new()),
+            _ => Err(ConfigError::Message(format!(
+                "invalid environment variable reference `${{{reference}}}`"
+            ))),
+        }
+    }
+
+    /// The expanded value of the variable `name`, if it is set
+    fn var(&mut self, name: &str) -> Result<Option<String>> {
+        let Some((&name, value)) = self.vars.get_key_value(name) else {
+            return Ok(None);
+        };
+
+        if self.stack.contains(&name) {
+            let cycle = self
+                .stack
+                .iter()
+                .skip_while(|outer| **outer != name)
+                .chain([&name])
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(ConfigError::Message(format!(
+                "environment variables reference each other in a cycle: {cycle}"
+            )));
+        }
+
+        self.stack.push(name);
+        let value = self.expand(value);
+        self.stack.pop();
+        value.map(Some)
+    }
+}
+
+/// The length of the variable name at the start of `text`
+fn name_len(text: &str) -> usize {
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        return 0;
+    }
+    text.find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(text.len())
+}
+
+/// The index of the `}` closing a reference whose contents start `text`, skipping nested ones
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, byte) in text.bytes().enumerate() {
+        match byte {
+            b'{' => depth += 1,
+            b'}' if depth == 0 => return Some(index),
+            b'}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A path deserialized with a leading `~` and references to environment variables expanded
+///
+/// `~` and `~/...` start from the home directory, taken from `HOME`, or `USERPROFILE` on
+/// Windows. References are [expanded like the shell does](crate::Source::expand_env_vars), and
+/// on Windows `%VAR%` is also replaced by the value of `VAR` if it is set. Variables are read
+/// when the path is deserialized.
+///
+/// ```rust
+/// # use config::*;
+/// #[derive(serde::Deserialize)]
+/// struct Settings {
+///     cache_dir: ExpandedPath,
+/// }
+///
+/// # unsafe { std::env::set_var("EXPANDED_PATH_DOC_APP", "demo") };
+/// let settings: Settings = Config::builder()
+///     .set_default("cache_dir", "/var/cache/${EXPANDED_PATH_DOC_APP}")?
+///     .build()?
+///     .try_deserialize()?;
+///
+/// assert_eq!(*settings.cache_dir, *"/var/cache/demo");
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExpandedPath(PathBuf);
+
+impl ExpandedPath {
+    /// The expanded path
+    pub fn into_path_buf(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl Deref for ExpandedPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for ExpandedPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<ExpandedPath> for PathBuf {
+    fn from(path: ExpandedPath) -> Self {
+        path.0
+    }
+}
+
+impl<'de> Deserialize<'de> for ExpandedPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let vars = crate::env::vars();
+        let vars: HashMap<&str, &str> = vars
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_str()?, value.to_str()?)))
+            .collect();
+        expand_path(&text, &vars)
+            .map(Self)
+            .map_err(|error| D::Error::custom(format!("cannot expand path {text:?}: {error}")))
+    }
+}
+
+/// Expand the leading `~` and the references to environment variables of `text`
+fn expand_path(text: &str, vars: &HashMap<&str, &str>) -> Result<PathBuf> {
+    let text = if cfg!(windows) {
+        expand_percent(text, vars)
+    } else {
+        text.to_owned()
+    };
+
+    let home_relative = match text.strip_prefix('~') {
+        Some("") => Some(""),
+        Some(rest) if rest.starts_with('/') || cfg!(windows) && rest.starts_with('\\') => {
+            Some(&rest[1..])
+        }
+        _ => None,
+    };
+    let Some(rest) = home_relative else {
+        return expand(&text, vars).map(PathBuf::from);
+    };
+
+    let home_var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    let home = vars
+        .get(home_var)
+        .filter(|home| !home.is_empty())
+        .ok_or_else(|| {
+            ConfigError::Message(format!(
+                "`~` needs the home directory, but {home_var} is not set"
+            ))
+        })?;
+    let mut path = PathBuf::from(OsString::from(*home));
+    if !rest.is_empty() {
+        path.push(expand(rest, vars)?);
+    }
+    Ok(path)
+}
+
+/// Replace the `%VAR%` references to variables that are set by their value
+fn expand_percent(text: &str, vars: &HashMap<&str, &str>) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        let tail = &rest[start + 1..];
+        match tail
+            .find('%')
+            .and_then(|end| Some((end, vars.get(&tail[..end])?)))
+        {
+            Some((end, value)) => {
+                expanded.push_str(value);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                expanded.push('%');
+                rest = tail;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
@@ -1,22 +1,24 @@
 use crate::value::{Value, ValueKind};
 use crate::{Map, format};
 use std::error::Error;
+use std::sync::Arc;
 
 pub(crate) fn parse(
     uri: Option<&String>,
     text: &str,
 ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
-    let value = from_corn_value(uri, &corn::parse(text)?);
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
+    let value = from_corn_value(origin.as_ref(), &corn::parse(text)?);
     format::extract_root_table(uri, value)
 }
 
-fn from_corn_value(uri: Option<&String>, value: &corn::Value<'_>) -> Value {
+fn from_corn_value(uri: Option<&Arc<str>>, value: &corn::Value<'_>) -> Value {
     match value {
-        corn::Value::String(value) => Value::new(uri, ValueKind::String(value.to_string())),
-        corn::Value::Integer(value) => Value::new(uri, ValueKind::I64(*value)),
-        corn::Value::Float(value) => Value::new(uri, ValueKind::Float(*value)),
-        corn::Value::Boolean(value) => Value::new(uri, ValueKind::Boolean(*value)),
-        corn::Value::Object(value) => Value::new(
+        corn::Value::String(value) => Value::with_origin(uri, ValueKind::String(value.to_string())),
+        corn::Value::Integer(value) => Value::with_origin(uri, ValueKind::I64(*value)),
+        corn::Value::Float(value) => Value::with_origin(uri, ValueKind::Float(*value)),
+        corn::Value::Boolean(value) => Value::with_origin(uri, ValueKind::Boolean(*value)),
+        corn::Value::Object(value) => Value::with_origin(
             uri,
             ValueKind::Table(
                 value
@@ -25,7 +27,7 @@ fn from_corn_value(uri: Option<&String>, value: &corn::Value<'_>) -> Value {
                     .collect(),
             ),
         ),
-        corn::Value::Array(value) => Value::new(
+        corn::Value::Array(value) => Value::with_origin(
             uri,
             ValueKind::Array(
                 value
@@ -34,6 +36,6 @@ fn from_corn_value(uri: Option<&String>, value: &corn::Value<'_>) -> Value {
                     .collect(),
             ),
         ),
-        corn::Value::Null(_) => Value::new(uri, ValueKind::Nil),
+        corn::Value::Null(_) => Value::with_origin(uri, ValueKind::Nil),
     }
 }
@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use super::ParseOptions;
+use crate::map::Map;
+use crate::value::{Value, ValueKind};
+
+/// Parse the `NAME=value` lines of a dotenv file into a table of strings keyed by variable name
+///
+/// Lines may start with `export`, and `#` starts a comment, at the start of a line or after
+/// whitespace in an unquoted value. Values in single quotes are taken literally, and those in
+/// double quotes may contain the escapes `\n`, `\r`, `\t`, `\"`, `\\` and `\$`. Quoted values
+/// may span several lines.
+pub(crate) fn parse(
+    uri: Option<&String>,
+    text: &str,
+    options: &ParseOptions,
+) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
+    let mut parser = Parser {
+        text: text.strip_prefix('\u{feff}').unwrap_or(text),
+        offset: 0,
+    };
+    let mut map = Map::new();
+    loop {
+        parser.skip(|c| c.is_whitespace());
+        match parser.peek() {
+            None => return Ok(map),
+            Some('#') => parser.skip(|c| c != '\n'),
+            Some(_) => {
+                let start = parser.offset;
+                let (name, value) = parser.assignment()?;
+                let value = Value::with_origin(origin.as_ref(), ValueKind::String(value));
+                if map.insert(name, value).is_some() && options.deny_duplicate_keys {
+                    parser.offset = start;
+                    return Err(Box::new(parser.error("duplicate variable")));
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip(&mut self, skipped: impl Fn(char) -> bool) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start_matches(skipped).len();
+    }
+
+    fn error(&self, reason: &'static str) -> DotenvError {
+        let before = &self.text[..self.offset];
+        DotenvError {
+            line: before.matches('\n').count() + 1,
+            column: before[before.rfind('\n').map_or(0, |index| index + 1)..]
+                .chars()
+                .count()
+                + 1,
+            reason,
+        }
+    }
+
+    /// Parse a `NAME=value` line
+    fn assignment(&mut self) -> Result<(String, String), DotenvError> {
+        if let Some(rest) = self.rest().strip_prefix("export") {
+            if rest.starts_with([' ', '\t']) {
+                self.offset += "export".len();
+                self.skip(|c| c == ' ' || c == '\t');
+            }
+        }
+
+        let rest = self.rest();
+        let end = rest.find(['=', '\n']).unwrap_or(rest.len());
+        let name = rest[..end].trim_end();
+        let valid = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-');
+        if name.is_empty() || !name.chars().all(valid) {
+            return Err(self.error("invalid variable name"));
+        }
+        self.offset += end;
+        if self.peek() != Some('=') {
+            return Err(self.error("expected `=`"));
+        }
+        self.offset += 1;
+        self.skip(|c| c == ' ' || c == '\t');
+
+        let value = match self.peek() {
+            Some('\'') => self.single_quoted()?,
+            Some('"') => self.double_quoted()?,
+            _ => {
+                let rest = self.rest();
+                let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                self.offset += line.len();
+                let comment = line
+                    .char_indices()
+                    .find(|&(index, c)| {
+                        c == '#' && (index == 0 || line[..index].ends_with([' ', '\t']))
+                    })
+                    .map_or(line.len(), |(index, _)| index);
+                return Ok((name.to_owned(), line[..comment].trim_end().to_owned()));
+            }
+        };
+
+        self.skip(|c| c == ' ' || c == '\t' || c == '\r');
+        match self.peek() {
+            None | Some('\n') => {}
+            Some('#') => self.skip(|c| c != '\n'),
+            Some(_) => return Err(self.error("unexpected characters after the closing quote")),
+        }
+        Ok((name.to_owned(), value))
+    }
+
+    fn single_quoted(&mut self) -> Result<String, DotenvError> {
+        self.offset += 1;
+        let rest = self.rest();
+        let Some(end) = rest.find('\'') else {
+            return Err(self.error("unterminated single quote"));
+        };
+        self.offset += end + 1;
+        Ok(rest[..end].to_owned())
+    }
+
+    fn double_quoted(&mut self) -> Result<String, DotenvError> {
+        let start = self.offset;
+        self.offset += 1;
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.offset += index + 1;
+                    return Ok(value);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('"' | '\\' | '$')) => value.push(c),
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        self.offset = start;
+        Err(self.error("unterminated double quote"))
+    }
+}
+
+/// A line of a dotenv file that could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DotenvError {
+    line: usize,
+    column: usize,
+    reason: &'static str,
+}
+
+impl fmt::Display for DotenvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.reason, self.line, self.column
+        )
+    }
+}
+
+impl Error for DotenvError {}
@@ -1,37 +1,143 @@
 use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
 
-use ini::Ini;
+use ini::{Ini, ParseOption};
 
+use super::ParseOptions;
 use crate::map::Map;
-use crate::value::{Value, ValueKind};
+use crate::value::{Notations, Value, ValueKind};
 
 pub(crate) fn parse(
     uri: Option<&String>,
     text: &str,
+    options: &ParseOptions,
 ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
     let mut map: Map<String, Value> = Map::new();
-    let i = Ini::load_from_str(text)?;
+    let i = Ini::load_from_str_opt(
+        text,
+        ParseOption {
+            enabled_quote: options.ini_quote,
+            enabled_escape: options.ini_escape,
+            ..ParseOption::default()
+        },
+    )?;
+    let value = |v: &str| {
+        let kind = match options.ini_typed_values {
+            true => Notations::default().parse(v),
+            false => None,
+        };
+        Value::with_origin(
+            origin.as_ref(),
+            kind.unwrap_or_else(|| ValueKind::String(v.to_owned())),
+        )
+    };
+
     for (sec, prop) in i.iter() {
-        match sec {
-            Some(sec) => {
-                let mut sec_map: Map<String, Value> = Map::new();
-                for (k, v) in prop.iter() {
-                    sec_map.insert(
-                        k.to_owned(),
-                        Value::new(uri, ValueKind::String(v.to_owned())),
-                    );
-                }
-                map.insert(sec.to_owned(), Value::new(uri, ValueKind::Table(sec_map)));
-            }
-            None => {
-                for (k, v) in prop.iter() {
-                    map.insert(
-                        k.to_owned(),
-                        Value::new(uri, ValueKind::String(v.to_owned())),
-                    );
-                }
+        let table = match sec {
+            Some(sec) => section(&mut map, section_path(sec), origin.as_ref()),
+            None => &mut map,
+        };
+        for (k, v) in prop.iter() {
+            if table.insert(k.to_owned(), value(v)).is_some() && options.deny_duplicate_keys {
+                return Err(Box::new(DuplicateKeyError {
+                    section: sec.map(str::to_owned),
+                    key: k.to_owned(),
+                    line: duplicate_line(text, sec, k),
+                }));
             }
         }
     }
     Ok(map)
 }
+
+/// A key repeated within a section, when [`ParseOptions::deny_duplicate_keys`]
+#[derive(Debug)]
+struct DuplicateKeyError {
+    section: Option<String>,
+    key: String,
+    /// Line of the second occurrence of the key, counting from 1
+    line: Option<usize>,
+}
+
+impl fmt::Display for DuplicateKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate key `{}`", self.key)?;
+        match self.section {
+            Some(ref section) => write!(f, " in section `{section}`")?,
+            None => write!(f, " outside of sections")?,
+        }
+        match self.line {
+            Some(line) => write!(f, " at line {line}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Error for DuplicateKeyError {}
+
+/// The line of the second occurrence of `key` in the section named `sec` of `text`
+///
+/// The parser doesn't keep track of lines, so they are found again by scanning section headers
+/// and `key = value` lines, which misses keys written with quotes or escapes.
+fn duplicate_line(text: &str, sec: Option<&str>, key: &str) -> Option<usize> {
+    let mut section = None;
+    let mut seen = false;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            section = header.split_once(']').map(|(name, _)| name.trim());
+        } else if section == sec
+            && line
+                .split_once(['=', ':'])
+                .is_some_and(|(name, _)| name.trim() == key)
+        {
+            if seen {
+                return Some(index + 1);
+            }
+            seen = true;
+        }
+    }
+    None
+}
+
+/// The path of the table of the section named `sec`
+///
+/// Dotted names like `[server.tls]` are nested tables, and so are git-style subsections like
+/// `[remote "origin"]`, whose name is kept whole even if it contains dots.
+fn section_path(sec: &str) -> impl Iterator<Item = &str> {
+    let (name, subsection) = match sec.split_once([' ', '\t']) {
+        Some((name, subsection)) => match subsection
+            .trim_start()
+            .strip_prefix('"')
+            .and_then(|subsection| subsection.strip_suffix('"'))
+        {
+            Some(subsection) => (name, Some(subsection)),
+            None => (sec, None),
+        },
+        None => (sec, None),
+    };
+    name.split('.').chain(subsection)
+}
+
+/// The table at `path` in `table`, replacing values that are not tables on the way
+fn section<'a, 'p>(
+    mut table: &'a mut Map<String, Value>,
+    path: impl Iterator<Item = &'p str>,
+    origin: Option<&Arc<str>>,
+) -> &'a mut Map<String, Value> {
+    for name in path {
+        let child = table
+            .entry(name.to_owned())
+            .or_insert_with(|| Value::with_origin(origin, ValueKind::Table(Map::new())));
+        if !matches!(child.kind, ValueKind::Table(_)) {
+            *child = Value::with_origin(origin, ValueKind::Table(Map::new()));
+        }
+        let ValueKind::Table(ref mut child) = child.kind else {
+            unreachable!("replaced by a table above");
+        };
+        table = child;
+    }
+    table
+}
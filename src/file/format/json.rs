@@ -1,33 +1,237 @@
 use std::error::Error;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::ops::Range;
+use std::sync::Arc;
 
-use crate::format;
+use serde_core::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use super::ParseOptions;
+use crate::format::{self, LineIndex};
 use crate::map::Map;
 use crate::value::{Value, ValueKind};
 
 pub(crate) fn parse(
     uri: Option<&String>,
     text: &str,
+    options: &ParseOptions,
 ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
     // Parse a JSON object value from the text
-    let value = from_json_value(uri, &serde_json::from_str(text)?);
+    let json = match options.deny_duplicate_keys {
+        true => serde_json::from_str::<Unique>(text)?.0,
+        false => serde_json::from_str(text)?,
+    };
+    let mut value = from_json_value(origin.as_ref(), &json);
+    if options.record_spans {
+        Scanner {
+            text,
+            offset: 0,
+            index: LineIndex::new(text),
+        }
+        .value(Some(&mut value));
+    }
     format::extract_root_table(uri, value)
 }
 
-fn from_json_value(uri: Option<&String>, value: &serde_json::Value) -> Value {
+/// Parse a JSON document as it is read from `reader`, without holding its text
+pub(crate) fn parse_reader(
+    uri: Option<&String>,
+    reader: &mut dyn Read,
+    options: &ParseOptions,
+) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
+    let reader = BufReader::new(reader);
+    let json = match options.deny_duplicate_keys {
+        true => serde_json::from_reader::<_, Unique>(reader)?.0,
+        false => serde_json::from_reader(reader)?,
+    };
+    format::extract_root_table(uri, from_json_value(origin.as_ref(), &json))
+}
+
+/// Records the spans of values while going over the text of a valid JSON document again
+struct Scanner<'a> {
+    text: &'a str,
+    offset: usize,
+    index: LineIndex<'a>,
+}
+
+impl Scanner<'_> {
+    /// Go over the value at the offset, recording its span and those of its children in `value`
+    fn value(&mut self, mut value: Option<&mut Value>) {
+        self.skip_whitespace();
+        let start = self.offset;
+        match self.peek() {
+            Some(b'{') => {
+                self.offset += 1;
+                while self.skip_whitespace() != Some(b'}') {
+                    let key = self.string();
+                    let key: Option<String> = serde_json::from_str(&self.text[key]).ok();
+                    self.skip_whitespace();
+                    self.offset += 1; // `:`
+
+                    let child = match value.as_deref_mut().map(|value| &mut value.kind) {
+                        Some(ValueKind::Table(table)) => key.and_then(|key| table.get_mut(&key)),
+                        _ => None,
+                    };
+                    self.value(child);
+                    if self.skip_whitespace() == Some(b',') {
+                        self.offset += 1;
+                    }
+                }
+                self.offset += 1;
+            }
+            Some(b'[') => {
+                self.offset += 1;
+                let mut index = 0;
+                while self.skip_whitespace() != Some(b']') {
+                    let child = match value.as_deref_mut().map(|value| &mut value.kind) {
+                        Some(ValueKind::Array(array)) => array.get_mut(index),
+                        _ => None,
+                    };
+                    self.value(child);
+                    index += 1;
+                    if self.skip_whitespace() == Some(b',') {
+                        self.offset += 1;
+                    }
+                }
+                self.offset += 1;
+            }
+            Some(b'"') => {
+                self.string();
+            }
+            Some(_) => {
+                let len = self.text[self.offset..]
+                    .find([',', ']', '}', ' ', '\t', '\n', '\r'])
+                    .unwrap_or(self.text.len() - self.offset);
+                self.offset += len;
+            }
+            None => return,
+        }
+
+        if let Some(value) = value {
+            value.set_span(self.index.span(start..self.offset));
+        }
+    }
+
+    /// Go over the string at the offset, returning its range including quotes
+    fn string(&mut self) -> Range<usize> {
+        let start = self.offset;
+        self.offset += 1;
+        while let Some(byte) = self.peek() {
+            self.offset += match byte {
+                b'\\' => 2,
+                b'"' => {
+                    self.offset += 1;
+                    break;
+                }
+                _ => 1,
+            };
+        }
+        start..self.offset
+    }
+
+    /// Skip whitespace, returning the byte following it
+    fn skip_whitespace(&mut self) -> Option<u8> {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.offset += 1;
+        }
+        self.peek()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+}
+
+/// A JSON value whose objects have unique keys
+///
+/// Duplicates are reported while deserializing, so that `serde_json` adds their position.
+struct Unique(serde_json::Value);
+
+impl<'de> Deserialize<'de> for Unique {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UniqueVisitor).map(Unique)
+    }
+}
+
+struct UniqueVisitor;
+
+impl<'de> Visitor<'de> for UniqueVisitor {
+    type Value = serde_json::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(serde_json::Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut array = Vec::new();
+        while let Some(Unique(value)) = seq.next_element()? {
+            array.push(value);
+        }
+        Ok(serde_json::Value::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut object = serde_json::Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if object.contains_key(&key) {
+                return Err(de::Error::custom(format_args!("duplicate key `{key}`")));
+            }
+            let Unique(value) = map.next_value()?;
+            object.insert(key, value);
+        }
+        Ok(serde_json::Value::Object(object))
+    }
+}
+
+fn from_json_value(uri: Option<&Arc<str>>, value: &serde_json::Value) -> Value {
     match *value {
-        serde_json::Value::String(ref value) => Value::new(uri, ValueKind::String(value.clone())),
+        serde_json::Value::String(ref value) => {
+            Value::with_origin(uri, ValueKind::String(value.clone()))
+        }
 
         serde_json::Value::Number(ref value) => {
             if let Some(value) = value.as_i64() {
-                Value::new(uri, ValueKind::I64(value))
+                Value::with_origin(uri, ValueKind::I64(value))
+            } else if let Some(value) = value.as_u64() {
+                Value::with_origin(uri, ValueKind::U64(value))
             } else if let Some(value) = value.as_f64() {
-                Value::new(uri, ValueKind::Float(value))
+                Value::with_origin(uri, ValueKind::Float(value))
             } else {
                 unreachable!();
             }
         }
 
-        serde_json::Value::Bool(value) => Value::new(uri, ValueKind::Boolean(value)),
+        serde_json::Value::Bool(value) => Value::with_origin(uri, ValueKind::Boolean(value)),
 
         serde_json::Value::Object(ref table) => {
             let mut m = Map::new();
@@ -36,7 +240,7 @@ fn from_json_value(uri: Option<&String>, value: &serde_json::Value) -> Value {
                 m.insert(key.clone(), from_json_value(uri, value));
             }
 
-            Value::new(uri, ValueKind::Table(m))
+            Value::with_origin(uri, ValueKind::Table(m))
         }
 
         serde_json::Value::Array(ref array) => {
@@ -46,9 +250,9 @@ fn from_json_value(uri: Option<&String>, value: &serde_json::Value) -> Value {
                 l.push(from_json_value(uri, value));
             }
 
-            Value::new(uri, ValueKind::Array(l))
+            Value::with_origin(uri, ValueKind::Array(l))
         }
 
-        serde_json::Value::Null => Value::new(uri, ValueKind::Nil),
+        serde_json::Value::Null => Value::with_origin(uri, ValueKind::Nil),
     }
 }
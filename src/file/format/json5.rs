@@ -1,4 +1,5 @@
 use std::error::Error;
+use std::sync::Arc;
 
 use crate::format;
 use crate::map::Map;
@@ -36,11 +37,12 @@ pub(crate) fn parse(
     uri: Option<&String>,
     text: &str,
 ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
-    let value = from_json5_value(uri, json5::from_str::<Val>(text)?);
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
+    let value = from_json5_value(origin.as_ref(), json5::from_str::<Val>(text)?);
     format::extract_root_table(uri, value)
 }
 
-fn from_json5_value(uri: Option<&String>, value: Val) -> Value {
+fn from_json5_value(uri: Option<&Arc<str>>, value: Val) -> Value {
     let vk = match value {
         Val::Null => ValueKind::Nil,
         Val::String(v) => ValueKind::String(v),
@@ -66,5 +68,5 @@ fn from_json5_value(uri: Option<&String>, value: Val) -> Value {
         }
     };
 
-    Value::new(uri, vk)
+    Value::with_origin(uri, vk)
 }
@@ -1,17 +1,26 @@
 use std::error::Error;
+use std::fmt;
+use std::io::Read;
 
 use crate::map::Map;
+use crate::value::ValueKind;
 use crate::{Format, file::FileStoredFormat, value::Value};
 
 #[cfg(feature = "toml")]
 mod toml;
 
+#[cfg(feature = "toml")]
+pub(crate) use self::toml::render as render_toml;
+
 #[cfg(feature = "json")]
 mod json;
 
 #[cfg(feature = "yaml")]
 mod yaml;
 
+#[cfg(feature = "yaml")]
+pub(crate) use self::yaml::render as render_yaml;
+
 #[cfg(feature = "ini")]
 mod ini;
 
@@ -24,9 +33,166 @@ mod json5;
 #[cfg(feature = "corn")]
 mod corn;
 
+#[cfg(feature = "dotenv")]
+mod dotenv;
+
+/// Options controlling how a [`FileFormat`] parses text.
+///
+/// Options that do not apply to a format are ignored by it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Maximum nesting depth of tables and arrays; the root table is at depth 0.
+    max_depth: Option<usize>,
+
+    /// Whether keys repeated within a table are rejected.
+    deny_duplicate_keys: bool,
+
+    /// Whether quotes around INI values are stripped.
+    ini_quote: bool,
+
+    /// Whether `\` is interpreted as an escape character in INI values.
+    ini_escape: bool,
+
+    /// Whether INI values that look like booleans or numbers are parsed as such.
+    ini_typed_values: bool,
+
+    /// How YAML streams with several documents are loaded.
+    yaml_documents: YamlDocuments,
+
+    /// Whether RON enum variants are kept as externally tagged values.
+    ron_enum_tags: bool,
+
+    /// Whether the span of each value is recorded.
+    record_spans: bool,
+}
+
+/// How a YAML stream containing several `---` separated documents is loaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum YamlDocuments {
+    /// Reject streams with more than one document.
+    #[default]
+    Single,
+
+    /// Merge the documents in order, later documents overriding earlier ones.
+    Merge,
+
+    /// Load every document into an array under the given key.
+    Array(String),
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            deny_duplicate_keys: false,
+            ini_quote: true,
+            ini_escape: true,
+            ini_typed_values: false,
+            yaml_documents: YamlDocuments::Single,
+            ron_enum_tags: false,
+            record_spans: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Reject documents with tables or arrays nested deeper than `depth`.
+    ///
+    /// The root table is at depth 0, so a depth of 1 allows `a.b` but not `a.b.c`.
+    ///
+    /// The limit is checked once the document is parsed, before it is merged, so it keeps deep
+    /// values out of the configuration but does not bound the work of the parser itself: a
+    /// document nested too deeply for the parser still fails with the parser's own error.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Reject documents repeating a key within a table, rather than keeping its last value.
+    ///
+    /// Duplicates are almost always mistakes, e.g. a key added twice to a long file. JSON and
+    /// dotenv errors name the line and column of the duplicate, INI errors its section. YAML and TOML always
+    /// reject duplicate keys.
+    ///
+    /// Disabled by default.
+    pub fn deny_duplicate_keys(mut self, enabled: bool) -> Self {
+        self.deny_duplicate_keys = enabled;
+        self
+    }
+
+    /// Strip quotes around INI values, e.g. `key = "value"` becomes `value`.
+    ///
+    /// Enabled by default.
+    pub fn ini_quote(mut self, enabled: bool) -> Self {
+        self.ini_quote = enabled;
+        self
+    }
+
+    /// Interpret `\` as an escape character in INI values.
+    ///
+    /// Enabled by default. Disable it for values like Windows paths.
+    pub fn ini_escape(mut self, enabled: bool) -> Self {
+        self.ini_escape = enabled;
+        self
+    }
+
+    /// Parse INI values that look like booleans, integers or floats, e.g. `port = 8080`, the same
+    /// way as [`Environment::try_parsing`](crate::Environment::try_parsing) does.
+    ///
+    /// Disabled by default, in which case every value is a string. Quoted values are still
+    /// parsed, as quotes are stripped by the INI parser.
+    pub fn ini_typed_values(mut self, enabled: bool) -> Self {
+        self.ini_typed_values = enabled;
+        self
+    }
+
+    /// Choose how YAML streams with several documents are loaded.
+    ///
+    /// Defaults to [`YamlDocuments::Single`].
+    pub fn yaml_documents(mut self, documents: YamlDocuments) -> Self {
+        self.yaml_documents = documents;
+        self
+    }
+
+    /// Keep RON enum variants in serde's externally tagged representation.
+    ///
+    /// Unit variants like `Fast` load as the string `"Fast"` rather than nil, and variants with
+    /// data like `Cluster(size: 3)` or `Port(80)` load as a single-key table `{ Cluster = { size
+    /// = 3 } }`, so they deserialize into enums the same way JSON does. As RON struct names can't
+    /// be told apart from variant names, nested structs must then be written without their name.
+    ///
+    /// Disabled by default, in which case variant and struct names are dropped.
+    pub fn ron_enum_tags(mut self, enabled: bool) -> Self {
+        self.ron_enum_tags = enabled;
+        self
+    }
+
+    /// Record where each value is written, available from [`Value::span`], for tools pointing
+    /// users at the line that set a value.
+    ///
+    /// Supported by TOML and JSON, other formats ignore it. Disabled by default, as it takes
+    /// another pass over the text and memory for each value.
+    pub fn record_spans(mut self, enabled: bool) -> Self {
+        self.record_spans = enabled;
+        self
+    }
+
+    /// Whether [spans should be recorded](Self::record_spans), for custom formats supporting it.
+    pub fn records_spans(&self) -> bool {
+        self.record_spans
+    }
+}
+
 /// File formats provided by the library.
 ///
 /// Although it is possible to define custom formats using [`Format`] trait it is recommended to use `FileFormat` if possible.
+///
+/// Tables are keyed by strings. Formats allowing other map keys, like YAML and RON, stringify
+/// scalar keys: `1`, `true` and `1.5` become `"1"`, `"true"` and `"1.5"`, and a null key becomes
+/// `"null"`, so they still deserialize into maps keyed by numbers or booleans. Parsing fails on
+/// keys that are tables or arrays.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 #[non_exhaustive]
 pub enum FileFormat {
@@ -57,9 +223,15 @@ pub enum FileFormat {
     /// Corn (parsed with `libcorn`)
     #[cfg(feature = "corn")]
     Corn,
+
+    /// dotenv, the `NAME=value` lines of `.env` files, as read by
+    /// [`DotenvStack`](crate::DotenvStack), into a table of strings keyed by variable name
+    #[cfg(feature = "dotenv")]
+    Dotenv,
 }
 
 impl FileFormat {
+    #[cfg(feature = "fs")]
     pub(crate) fn all() -> &'static [FileFormat] {
         &[
             #[cfg(feature = "toml")]
@@ -76,6 +248,8 @@ impl FileFormat {
             FileFormat::Json5,
             #[cfg(feature = "corn")]
             FileFormat::Corn,
+            #[cfg(feature = "dotenv")]
+            FileFormat::Dotenv,
         ]
     }
 
@@ -102,6 +276,9 @@ impl FileFormat {
             #[cfg(feature = "corn")]
             FileFormat::Corn => &["corn"],
 
+            #[cfg(feature = "dotenv")]
+            FileFormat::Dotenv => &["env"],
+
             #[cfg(all(
                 not(feature = "toml"),
                 not(feature = "json"),
@@ -109,6 +286,7 @@ impl FileFormat {
                 not(feature = "ini"),
                 not(feature = "ron"),
                 not(feature = "json5"),
+                not(feature = "dotenv"),
             ))]
             _ => unreachable!("No features are enabled, this library won't work without features"),
         }
@@ -119,21 +297,44 @@ impl FileFormat {
         uri: Option<&String>,
         text: &str,
     ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
-        match self {
+        self.parse_with_options(uri, text, &ParseOptions::default())
+    }
+
+    /// Parses `text` in this format, configuring the parser with `options`.
+    #[cfg_attr(
+        not(any(
+            feature = "toml",
+            feature = "json",
+            feature = "yaml",
+            feature = "ini",
+            feature = "ron",
+            feature = "json5",
+            feature = "corn",
+            feature = "dotenv",
+        )),
+        allow(unused_variables, unreachable_code)
+    )]
+    pub fn parse_with_options(
+        &self,
+        uri: Option<&String>,
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+        let map: Result<Map<String, Value>, Box<dyn Error + Send + Sync>> = match self {
             #[cfg(feature = "toml")]
-            FileFormat::Toml => toml::parse(uri, text),
+            FileFormat::Toml => toml::parse(uri, text, options),
 
             #[cfg(feature = "json")]
-            FileFormat::Json => json::parse(uri, text),
+            FileFormat::Json => json::parse(uri, text, options),
 
             #[cfg(feature = "yaml")]
-            FileFormat::Yaml => yaml::parse(uri, text),
+            FileFormat::Yaml => yaml::parse(uri, text, options),
 
             #[cfg(feature = "ini")]
-            FileFormat::Ini => ini::parse(uri, text),
+            FileFormat::Ini => ini::parse(uri, text, options),
 
             #[cfg(feature = "ron")]
-            FileFormat::Ron => ron::parse(uri, text),
+            FileFormat::Ron => ron::parse(uri, text, options),
 
             #[cfg(feature = "json5")]
             FileFormat::Json5 => json5::parse(uri, text),
@@ -141,6 +342,9 @@ impl FileFormat {
             #[cfg(feature = "corn")]
             FileFormat::Corn => corn::parse(uri, text),
 
+            #[cfg(feature = "dotenv")]
+            FileFormat::Dotenv => dotenv::parse(uri, text, options),
+
             #[cfg(all(
                 not(feature = "toml"),
                 not(feature = "json"),
@@ -148,9 +352,15 @@ impl FileFormat {
                 not(feature = "ini"),
                 not(feature = "ron"),
                 not(feature = "json5"),
+                not(feature = "dotenv"),
             ))]
             _ => unreachable!("No features are enabled, this library won't work without features"),
-        }
+        };
+        let map = map?;
+
+        check_depth(&map, options)?;
+
+        Ok(map)
     }
 }
 
@@ -162,6 +372,37 @@ impl Format for FileFormat {
     ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
         self.parse(uri, text)
     }
+
+    fn parse_with_options(
+        &self,
+        uri: Option<&String>,
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+        self.parse_with_options(uri, text, options)
+    }
+
+    fn parse_reader(
+        &self,
+        uri: Option<&String>,
+        reader: &mut dyn Read,
+        options: &ParseOptions,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+        match self {
+            // Spans are found in the text, which is then needed
+            #[cfg(feature = "json")]
+            FileFormat::Json if !options.record_spans => {
+                let map = json::parse_reader(uri, reader, options)?;
+                check_depth(&map, options)?;
+                Ok(map)
+            }
+            _ => {
+                let mut buf = Vec::new();
+                reader.read_to_end(&mut buf)?;
+                self.parse_with_options(uri, &super::decode(buf), options)
+            }
+        }
+    }
 }
 
 impl FileStoredFormat for FileFormat {
@@ -169,3 +410,48 @@ impl FileStoredFormat for FileFormat {
         self.extensions()
     }
 }
+
+/// Fail if the tables or arrays of `table`, the root of a document, are nested deeper than
+/// [`ParseOptions::max_depth`] allows
+///
+/// Applied by [`FileFormat`] after parsing, custom formats call it from
+/// [`Format::parse_with_options`].
+pub fn check_depth(
+    table: &Map<String, Value>,
+    options: &ParseOptions,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match options.max_depth {
+        Some(max_depth) => Ok(check_nesting(table.values(), 1, max_depth)?),
+        None => Ok(()),
+    }
+}
+
+fn check_nesting<'a>(
+    values: impl Iterator<Item = &'a Value>,
+    depth: usize,
+    max_depth: usize,
+) -> Result<(), DepthLimitError> {
+    for value in values {
+        match value.kind {
+            ValueKind::Table(_) | ValueKind::Array(_) if depth > max_depth => {
+                return Err(DepthLimitError(max_depth));
+            }
+            ValueKind::Table(ref table) => check_nesting(table.values(), depth + 1, max_depth)?,
+            ValueKind::Array(ref array) => check_nesting(array.iter(), depth + 1, max_depth)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone)]
+struct DepthLimitError(usize);
+
+impl fmt::Display for DepthLimitError {
+    fn fmt(&self, format: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(format, "nesting exceeds the maximum depth of {}", self.0)
+    }
+}
+
+impl Error for DepthLimitError {}
@@ -1,20 +1,66 @@
-use std::convert::TryInto as _;
 use std::error::Error;
+use std::sync::Arc;
 
-use crate::format;
+use serde::Deserialize;
+
+use crate::ParseOptions;
+use crate::format::{self, Segment};
 use crate::map::Map;
 use crate::value::{Value, ValueKind};
 
 pub(crate) fn parse(
     uri: Option<&String>,
     text: &str,
+    options: &ParseOptions,
 ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
-    let value = from_ron_value(uri, ron::from_str(text)?)?;
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
+    let value = if options.ron_enum_tags {
+        let mut deserializer = ron::Deserializer::from_str(text)?;
+        let Tagged::Value(value) = Tagged::deserialize(&mut deserializer)
+            .and_then(|value| deserializer.end().map(|()| value))
+            .map_err(|err| deserializer.span_error(err))?;
+        let value = from_ron_value(origin.as_ref(), value)?;
+        // The root is never tagged, its struct name if any is dropped
+        match value.kind {
+            ValueKind::Table(table) if starts_with_name(text) && table.len() == 1 => {
+                let (_, value) = table.into_iter().next().expect("the table has one entry");
+                value
+            }
+            kind => Value::with_origin(origin.as_ref(), kind),
+        }
+    } else {
+        from_ron_value(origin.as_ref(), ron::from_str(text)?)?
+    };
     format::extract_root_table(uri, value)
 }
 
+/// A RON value buffered by serde, for which RON keeps enum variants externally tagged: unit
+/// variants are strings and others single-key maps, like serde's own representation
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Tagged {
+    Value(ron::Value),
+}
+
+/// Whether the document starts with a struct name, skipping whitespace and comments
+fn starts_with_name(text: &str) -> bool {
+    let mut text = text;
+    loop {
+        text = text.trim_start();
+        if let Some(rest) = text.strip_prefix("//") {
+            text = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(rest) = text.strip_prefix("/*") {
+            text = rest.split_once("*/").map_or("", |(_, rest)| rest);
+        } else if text.starts_with("#!") {
+            text = text.split_once(']').map_or("", |(_, rest)| rest);
+        } else {
+            return text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        }
+    }
+}
+
 fn from_ron_value(
-    uri: Option<&String>,
+    uri: Option<&Arc<str>>,
     value: ron::Value,
 ) -> Result<Value, Box<dyn Error + Send + Sync>> {
     let kind = match value {
@@ -37,7 +83,10 @@ fn from_ron_value(
             ron::Number::U8(value) => ValueKind::I64(value.into()),
             ron::Number::U16(value) => ValueKind::I64(value.into()),
             ron::Number::U32(value) => ValueKind::I64(value.into()),
-            ron::Number::U64(value) => ValueKind::I64(value.try_into()?),
+            ron::Number::U64(value) => match i64::try_from(value) {
+                Ok(value) => ValueKind::I64(value),
+                Err(_) => ValueKind::U64(value),
+            },
             _ => Err(crate::ConfigError::Message(
                 "unsupported numeric type".to_owned(),
             ))?,
@@ -52,7 +101,11 @@ fn from_ron_value(
         ron::Value::Seq(values) => {
             let array = values
                 .into_iter()
-                .map(|value| from_ron_value(uri, value))
+                .enumerate()
+                .map(|(index, value)| {
+                    from_ron_value(uri, value)
+                        .map_err(|err| format::within(err, Segment::Index(index)))
+                })
                 .collect::<Result<Vec<_>, _>>()?;
 
             ValueKind::Array(array)
@@ -62,8 +115,9 @@ fn from_ron_value(
             let map = values
                 .iter()
                 .map(|(key, value)| -> Result<_, Box<dyn Error + Send + Sync>> {
-                    let key = key.clone().into_rust::<String>()?;
-                    let value = from_ron_value(uri, value.clone())?;
+                    let key = format::scalar_key(from_ron_value(uri, key.clone())?)?;
+                    let value = from_ron_value(uri, value.clone())
+                        .map_err(|err| format::within(err, Segment::Key(&key)))?;
 
                     Ok((key, value))
                 })
@@ -73,5 +127,5 @@ fn from_ron_value(
         }
     };
 
-    Ok(Value::new(uri, kind))
+    Ok(Value::with_origin(uri, kind))
 }
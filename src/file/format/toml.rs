@@ -1,18 +1,71 @@
 use std::error::Error;
+use std::sync::Arc;
 
+use toml::Spanned;
+use toml::de::{DeTable, DeValue};
+
+use super::ParseOptions;
+use crate::format::LineIndex;
 use crate::map::Map;
-use crate::value::Value;
+use crate::value::{Value, ValueKind};
 
 pub(crate) fn parse(
     uri: Option<&String>,
     text: &str,
+    options: &ParseOptions,
 ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
     // Parse a TOML value from the provided text
-    let table = from_toml_table(uri, toml::from_str(text)?);
+    let mut table = from_toml_table(origin.as_ref(), toml::from_str(text)?);
+    if options.record_spans {
+        let index = LineIndex::new(text);
+        attach_table(&mut table, DeTable::parse(text)?.get_ref(), &index);
+    }
     Ok(table)
 }
 
-fn from_toml_table(uri: Option<&String>, table: toml::Table) -> Map<String, Value> {
+/// Render `value` as a TOML document if it is a table, as an inline value otherwise
+///
+/// Nil values are left out, as TOML has no null, and integers that don't fit in an `i64` are
+/// written as floats.
+pub(crate) fn render(value: &Value) -> String {
+    match to_toml_value(value) {
+        Some(toml::Value::Table(table)) => table.to_string(),
+        Some(value) => value.to_string(),
+        None => String::new(),
+    }
+}
+
+fn to_toml_value(value: &Value) -> Option<toml::Value> {
+    let value = match value.kind {
+        ValueKind::Nil => return None,
+        ValueKind::Boolean(value) => toml::Value::Boolean(value),
+        ValueKind::I64(value) => toml::Value::Integer(value),
+        ValueKind::I128(value) => {
+            i64::try_from(value).map_or(toml::Value::Float(value as f64), toml::Value::Integer)
+        }
+        ValueKind::U64(value) => {
+            i64::try_from(value).map_or(toml::Value::Float(value as f64), toml::Value::Integer)
+        }
+        ValueKind::U128(value) => {
+            i64::try_from(value).map_or(toml::Value::Float(value as f64), toml::Value::Integer)
+        }
+        ValueKind::Float(value) => toml::Value::Float(value),
+        ValueKind::String(ref value) => toml::Value::String(value.clone()),
+        ValueKind::Table(ref table) => toml::Value::Table(
+            table
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), to_toml_value(value)?)))
+                .collect(),
+        ),
+        ValueKind::Array(ref array) => {
+            toml::Value::Array(array.iter().filter_map(to_toml_value).collect())
+        }
+    };
+    Some(value)
+}
+
+fn from_toml_table(uri: Option<&Arc<str>>, table: toml::Table) -> Map<String, Value> {
     let mut m = Map::new();
 
     for (key, value) in table {
@@ -22,16 +75,16 @@ fn from_toml_table(uri: Option<&String>, table: toml::Table) -> Map<String, Valu
     m
 }
 
-fn from_toml_value(uri: Option<&String>, value: toml::Value) -> Value {
+fn from_toml_value(uri: Option<&Arc<str>>, value: toml::Value) -> Value {
     match value {
-        toml::Value::String(value) => Value::new(uri, value),
-        toml::Value::Float(value) => Value::new(uri, value),
-        toml::Value::Integer(value) => Value::new(uri, value),
-        toml::Value::Boolean(value) => Value::new(uri, value),
+        toml::Value::String(value) => Value::with_origin(uri, value),
+        toml::Value::Float(value) => Value::with_origin(uri, value),
+        toml::Value::Integer(value) => Value::with_origin(uri, value),
+        toml::Value::Boolean(value) => Value::with_origin(uri, value),
 
         toml::Value::Table(table) => {
             let m = from_toml_table(uri, table);
-            Value::new(uri, m)
+            Value::with_origin(uri, m)
         }
 
         toml::Value::Array(array) => {
@@ -41,9 +94,31 @@ fn from_toml_value(uri: Option<&String>, value: toml::Value) -> Value {
                 l.push(from_toml_value(uri, value));
             }
 
-            Value::new(uri, l)
+            Value::with_origin(uri, l)
+        }
+
+        toml::Value::Datetime(datetime) => Value::with_origin(uri, datetime.to_string()),
+    }
+}
+
+fn attach_table(table: &mut Map<String, Value>, spans: &DeTable<'_>, index: &LineIndex<'_>) {
+    for (key, spans) in spans {
+        if let Some(value) = table.get_mut(key.get_ref().as_ref()) {
+            attach(value, spans, index);
         }
+    }
+}
 
-        toml::Value::Datetime(datetime) => Value::new(uri, datetime.to_string()),
+/// Record the spans of `value` and its children
+fn attach(value: &mut Value, spans: &Spanned<DeValue<'_>>, index: &LineIndex<'_>) {
+    value.set_span(index.span(spans.span()));
+    match (&mut value.kind, spans.get_ref()) {
+        (ValueKind::Table(table), DeValue::Table(spans)) => attach_table(table, spans, index),
+        (ValueKind::Array(array), DeValue::Array(spans)) => {
+            for (value, spans) in array.iter_mut().zip(spans.iter()) {
+                attach(value, spans, index);
+            }
+        }
+        _ => {}
     }
 }
@@ -1,19 +1,42 @@
 use std::error::Error;
 use std::fmt;
 use std::mem;
+use std::sync::Arc;
 
 use yaml_rust2 as yaml;
 
-use crate::format;
+use crate::file::{ParseOptions, YamlDocuments};
+use crate::format::{self, Segment};
 use crate::map::Map;
+use crate::path::Expression;
 use crate::value::{Value, ValueKind};
 
 pub(crate) fn parse(
     uri: Option<&String>,
     text: &str,
+    options: &ParseOptions,
 ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let origin = uri.map(|uri| Arc::from(uri.as_str()));
     // Parse a YAML object from file
     let mut docs = yaml::YamlLoader::load_from_str(text)?;
+    if docs.len() > 1 {
+        match options.yaml_documents {
+            YamlDocuments::Single => {}
+            YamlDocuments::Merge => return merge_documents(uri, origin.as_ref(), &docs),
+            YamlDocuments::Array(ref key) => {
+                let mut documents = Vec::with_capacity(docs.len());
+                for doc in &docs {
+                    documents.push(from_yaml_value(origin.as_ref(), doc)?);
+                }
+                let mut map = Map::new();
+                map.insert(
+                    key.clone(),
+                    Value::with_origin(origin.as_ref(), ValueKind::Array(documents)),
+                );
+                return Ok(map);
+            }
+        }
+    }
     let root = match docs.len() {
         0 => yaml::Yaml::Hash(yaml::yaml::Hash::new()),
         1 => mem::replace(&mut docs[0], yaml::Yaml::Null),
@@ -22,17 +45,39 @@ pub(crate) fn parse(
         }
     };
 
-    let value = from_yaml_value(uri, &root)?;
+    let value = from_yaml_value(origin.as_ref(), &root)?;
     format::extract_root_table(uri, value)
 }
 
-fn from_yaml_value(
+/// Merge the root tables of `docs` in order, skipping empty documents
+fn merge_documents(
     uri: Option<&String>,
+    origin: Option<&Arc<str>>,
+    docs: &[yaml::Yaml],
+) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let mut merged: Value = Map::<String, Value>::new().into();
+    for doc in docs.iter().filter(|doc| !doc.is_null()) {
+        let value = from_yaml_value(origin, doc)?;
+        for (key, value) in format::extract_root_table(uri, value)? {
+            Expression::root(key).set(&mut merged, value);
+        }
+    }
+    format::extract_root_table(uri, merged)
+}
+
+fn from_yaml_value(
+    uri: Option<&Arc<str>>,
     value: &yaml::Yaml,
 ) -> Result<Value, Box<dyn Error + Send + Sync>> {
     match *value {
-        yaml::Yaml::String(ref value) => Ok(Value::new(uri, ValueKind::String(value.clone()))),
+        yaml::Yaml::String(ref value) => {
+            Ok(Value::with_origin(uri, ValueKind::String(value.clone())))
+        }
         yaml::Yaml::Real(ref value) => {
+            // Integers larger than `i64::MAX` are parsed as reals
+            if let Ok(value) = value.parse::<u64>() {
+                return Ok(Value::with_origin(uri, ValueKind::U64(value)));
+            }
             // TODO: Figure out in what cases this can panic?
             value
                 .parse::<f64>()
@@ -40,31 +85,38 @@ fn from_yaml_value(
                     Box::new(FloatParsingError(value.clone())) as Box<dyn Error + Send + Sync>
                 })
                 .map(ValueKind::Float)
-                .map(|f| Value::new(uri, f))
+                .map(|f| Value::with_origin(uri, f))
         }
-        yaml::Yaml::Integer(value) => Ok(Value::new(uri, ValueKind::I64(value))),
-        yaml::Yaml::Boolean(value) => Ok(Value::new(uri, ValueKind::Boolean(value))),
+        yaml::Yaml::Integer(value) => Ok(Value::with_origin(uri, ValueKind::I64(value))),
+        yaml::Yaml::Boolean(value) => Ok(Value::with_origin(uri, ValueKind::Boolean(value))),
         yaml::Yaml::Hash(ref table) => {
             let mut m = Map::new();
             for (key, value) in table {
-                match key {
-                    yaml::Yaml::String(k) => m.insert(k.to_owned(), from_yaml_value(uri, value)?),
-                    yaml::Yaml::Integer(k) => m.insert(k.to_string(), from_yaml_value(uri, value)?),
-                    yaml::Yaml::Boolean(k) => m.insert(k.to_string(), from_yaml_value(uri, value)?),
-                    yaml::Yaml::Real(k) => m.insert(k.to_owned(), from_yaml_value(uri, value)?),
+                let key = match key {
+                    yaml::Yaml::String(k) => k.to_owned(),
+                    yaml::Yaml::Integer(k) => k.to_string(),
+                    yaml::Yaml::Boolean(k) => k.to_string(),
+                    yaml::Yaml::Real(k) => k.to_owned(),
+                    yaml::Yaml::Null => "null".to_owned(),
                     other => Err(Box::new(UnsupportedHashKeyError(format!("{other:?}"))))?,
                 };
+                let value = from_yaml_value(uri, value)
+                    .map_err(|err| format::within(err, Segment::Key(&key)))?;
+                m.insert(key, value);
             }
-            Ok(Value::new(uri, ValueKind::Table(m)))
+            Ok(Value::with_origin(uri, ValueKind::Table(m)))
         }
         yaml::Yaml::Array(ref array) => {
             let mut l = Vec::new();
 
-            for value in array {
-                l.push(from_yaml_value(uri, value)?);
+            for (index, value) in array.iter().enumerate() {
+                l.push(
+                    from_yaml_value(uri, value)
+                        .map_err(|err| format::within(err, Segment::Index(index)))?,
+                );
             }
 
-            Ok(Value::new(uri, ValueKind::Array(l)))
+            Ok(Value::with_origin(uri, ValueKind::Array(l)))
         }
 
         // 1. Yaml NULL
@@ -72,7 +124,61 @@ fn from_yaml_value(
         //               using the index trait badly or on a type error but we send back nil.
         // 3. Alias – No idea what to do with this and there is a note in the lib that its
         //            not fully supported yet anyway
-        _ => Ok(Value::new(uri, ValueKind::Nil)),
+        _ => Ok(Value::with_origin(uri, ValueKind::Nil)),
+    }
+}
+
+/// Render `value` as a YAML document, without the `---` marker
+pub(crate) fn render(value: &Value) -> String {
+    let mut output = String::new();
+    yaml::YamlEmitter::new(&mut output)
+        .dump(&to_yaml_value(value))
+        .expect("writing to a string does not fail");
+    output
+        .strip_prefix("---")
+        .unwrap_or(&output)
+        .trim_start()
+        .to_owned()
+}
+
+fn to_yaml_value(value: &Value) -> yaml::Yaml {
+    match value.kind {
+        ValueKind::Nil => yaml::Yaml::Null,
+        ValueKind::Boolean(value) => yaml::Yaml::Boolean(value),
+        ValueKind::I64(value) => yaml::Yaml::Integer(value),
+        // Integers that don't fit in an `i64` are written as reals, as they are parsed
+        ValueKind::I128(value) => match i64::try_from(value) {
+            Ok(value) => yaml::Yaml::Integer(value),
+            Err(_) => yaml::Yaml::Real(value.to_string()),
+        },
+        ValueKind::U64(value) => match i64::try_from(value) {
+            Ok(value) => yaml::Yaml::Integer(value),
+            Err(_) => yaml::Yaml::Real(value.to_string()),
+        },
+        ValueKind::U128(value) => match i64::try_from(value) {
+            Ok(value) => yaml::Yaml::Integer(value),
+            Err(_) => yaml::Yaml::Real(value.to_string()),
+        },
+        ValueKind::Float(value) if value.is_nan() => yaml::Yaml::Real(".nan".to_owned()),
+        ValueKind::Float(value) if value.is_infinite() => {
+            let sign = if value < 0.0 { "-" } else { "" };
+            yaml::Yaml::Real(format!("{sign}.inf"))
+        }
+        ValueKind::Float(value) => yaml::Yaml::Real(format!("{value:?}")),
+        ValueKind::String(ref value) => yaml::Yaml::String(value.clone()),
+        ValueKind::Table(ref table) => {
+            let mut entries: Vec<_> = table.iter().collect();
+            if !cfg!(feature = "preserve_order") {
+                entries.sort_by_key(|(key, _)| *key);
+            }
+            yaml::Yaml::Hash(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (yaml::Yaml::String(key.clone()), to_yaml_value(value)))
+                    .collect(),
+            )
+        }
+        ValueKind::Array(ref array) => yaml::Yaml::Array(array.iter().map(to_yaml_value).collect()),
     }
 }
 
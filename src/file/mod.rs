@@ -1,18 +1,35 @@
 mod format;
 pub(crate) mod source;
 
+use std::error::Error;
 use std::fmt::Debug;
+#[cfg(feature = "fs")]
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
 
-use self::source::FileSource;
+#[cfg(feature = "fs")]
+use self::source::file::{AmbiguousFileError, UnsafeFileError};
+use self::source::{FileSource, StreamError, TooLargeError};
 use crate::Format;
 use crate::error::{ConfigError, Result};
 use crate::map::Map;
-use crate::source::Source;
-use crate::value::Value;
+use crate::path::Expression;
+use crate::source::{Source, SourceInfo, record_loaded};
+use crate::value::{Value, ValueKind};
 
-pub use self::format::FileFormat;
+pub use self::format::check_depth;
+#[cfg(feature = "toml")]
+pub(crate) use self::format::render_toml;
+#[cfg(feature = "yaml")]
+pub(crate) use self::format::render_yaml;
+pub use self::format::{FileFormat, ParseOptions, YamlDocuments};
+pub(crate) use self::source::decode;
+#[cfg(feature = "fs")]
 pub use self::source::file::FileSourceFile;
+#[cfg(feature = "fs")]
+pub(crate) use self::source::file::{Extensions, uri};
+pub use self::source::reader::FileSourceReader;
 pub use self::source::string::FileSourceString;
 
 /// An extension of [`Format`] trait.
@@ -23,6 +40,65 @@ pub trait FileStoredFormat: Format {
     fn file_extensions(&self) -> &'static [&'static str];
 }
 
+/// Decrypts file contents before they are parsed.
+///
+/// This is only a hook: like [`AsyncSource`](crate::AsyncSource), this library ships no
+/// implementation, and no `age` or `sops` backend. Secret-at-rest tools are plugged in through
+/// [`File::decrypt`], with their own crate or command line tool.
+///
+/// The contents of a file are decrypted whole, as text, and the plain text is then parsed in the
+/// format of the file. Formats encrypting values one by one, like sops does, are decrypted by
+/// the tool into a whole plain file too. For instance, with the `sops` command:
+///
+/// ```rust,no_run
+/// use std::error::Error;
+/// use std::io::Write as _;
+/// use std::process::{Command, Stdio};
+///
+/// use config::*;
+///
+/// # #[cfg(all(feature = "fs", feature = "yaml"))]
+/// # {
+/// #[derive(Debug)]
+/// struct Sops;
+///
+/// impl Decryptor for Sops {
+///     fn decrypt(
+///         &self,
+///         _uri: Option<&String>,
+///         content: &str,
+///     ) -> Result<String, Box<dyn Error + Send + Sync>> {
+///         let mut sops = Command::new("sops")
+///             .args(["--decrypt", "--input-type", "yaml", "--output-type", "yaml"])
+///             .arg("/dev/stdin")
+///             .stdin(Stdio::piped())
+///             .stdout(Stdio::piped())
+///             .stderr(Stdio::piped())
+///             .spawn()?;
+///         sops.stdin.take().unwrap().write_all(content.as_bytes())?;
+///         let output = sops.wait_with_output()?;
+///         if !output.status.success() {
+///             return Err(String::from_utf8_lossy(&output.stderr).trim().into());
+///         }
+///         Ok(String::from_utf8(output.stdout)?)
+///     }
+/// }
+///
+/// let config = Config::builder()
+///     .add_source(File::with_name("secrets.yaml").decrypt(Sops))
+///     .build()?;
+/// # }
+/// # Ok::<(), ConfigError>(())
+/// ```
+pub trait Decryptor: Debug {
+    /// Returns the plain text of `content`, read from the file at `uri`.
+    fn decrypt(
+        &self,
+        uri: Option<&String>,
+        content: &str,
+    ) -> Result<String, Box<dyn Error + Send + Sync>>;
+}
+
 /// A configuration source backed up by a file.
 ///
 /// It supports optional automatic file format discovery.
@@ -36,6 +112,15 @@ pub struct File<T, F> {
 
     /// A required File will error if it cannot be found
     required: bool,
+
+    /// Options passed to the format when parsing
+    options: ParseOptions,
+
+    /// Decrypts the contents before they are parsed
+    decryptor: Option<Arc<dyn Decryptor + Send + Sync>>,
+
+    /// Path of the table used as the root of the source
+    fragment: Option<String>,
 }
 
 impl<F> File<FileSourceString, F>
@@ -46,11 +131,59 @@ where
         Self {
             format: Some(format),
             required: true,
+            options: ParseOptions::default(),
+            decryptor: None,
+            fragment: None,
             source: s.into(),
         }
     }
 }
 
+impl<F> File<FileSourceReader, F>
+where
+    F: FileStoredFormat + 'static,
+{
+    /// Read the standard input, e.g. for a tool called with `--config - < settings.yaml`.
+    ///
+    /// Standard input is read once, when the source is first collected, and parsed as it is
+    /// read by formats able to, like JSON. Its values are reused by later builds. A UTF-8 BOM is
+    /// skipped like for files.
+    pub fn from_stdin(format: F) -> Self {
+        Self::from_source(
+            FileSourceReader::new(Some("<stdin>"), Box::new(std::io::stdin())),
+            format,
+        )
+    }
+
+    /// Read the stream `reader`, like [`from_stdin`](Self::from_stdin).
+    pub fn from_reader<R>(reader: R, format: F) -> Self
+    where
+        R: std::io::Read + Send + 'static,
+    {
+        Self::from_source(FileSourceReader::new(None, Box::new(reader)), format)
+    }
+
+    /// Refuse to read more than `max_size` bytes from the stream.
+    ///
+    /// Building fails when the stream is longer, even when it is not [required](Self::required).
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.source.max_size(max_size);
+        self
+    }
+
+    fn from_source(source: FileSourceReader, format: F) -> Self {
+        Self {
+            format: Some(format),
+            required: true,
+            options: ParseOptions::default(),
+            decryptor: None,
+            fragment: None,
+            source,
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
 impl<F> File<FileSourceFile, F>
 where
     F: FileStoredFormat + 'static,
@@ -59,21 +192,135 @@ where
         Self {
             format: Some(format),
             required: true,
+            options: ParseOptions::default(),
+            decryptor: None,
+            fragment: None,
             source: FileSourceFile::new(name.into()),
         }
     }
 }
 
+#[cfg(feature = "fs")]
+impl<F> File<FileSourceFile, F>
+where
+    F: FileStoredFormat + 'static,
+{
+    /// Refuse to read the file when its path is a symbolic link.
+    ///
+    /// Only the last component of the path is checked, directories may still be links. Building
+    /// fails even when the file is not [required](Self::required).
+    pub fn refuse_symlinks(mut self, refuse: bool) -> Self {
+        self.source.refuse_symlinks(refuse);
+        self
+    }
+
+    /// Refuse to read the file when any user can write to it.
+    ///
+    /// This is only checked on Unix. Building fails even when the file is not
+    /// [required](Self::required).
+    pub fn refuse_world_writable(mut self, refuse: bool) -> Self {
+        self.source.refuse_world_writable(refuse);
+        self
+    }
+
+    /// Refuse to read the file when it is larger than `max_size` bytes.
+    ///
+    /// This guards against loading a file that grew by mistake, e.g. a generated configuration,
+    /// into memory. Building fails even when the file is not [required](Self::required).
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.source.max_size(max_size);
+        self
+    }
+}
+
+#[cfg(feature = "fs")]
 impl File<FileSourceFile, FileFormat> {
     /// Given the basename of a file, will attempt to locate a file by setting its
     /// extension to a registered format.
+    ///
+    /// Building fails if files with more than one registered extension exist, even when the file
+    /// is not [required](Self::required), as the intended one cannot be told apart.
     pub fn with_name(base_name: &str) -> Self {
         Self {
             format: None,
             required: true,
+            options: ParseOptions::default(),
+            decryptor: None,
+            fragment: None,
             source: FileSourceFile::new(base_name.into()),
         }
     }
+
+    /// Like [`with_name`](Self::with_name), selecting the [fragment](Self::fragment) at the path
+    /// after the last `#`, like in `app.yaml#server`
+    ///
+    /// A name without `#` loads the whole file.
+    pub fn with_name_and_fragment(name: &str) -> Self {
+        match name.rsplit_once('#') {
+            Some((base_name, fragment)) => Self::with_name(base_name).fragment(fragment),
+            None => Self::with_name(name),
+        }
+    }
+
+    /// Like [`with_name`](Self::with_name), for the basename rendered from `template` by
+    /// replacing each `{name}` with the variable `name` in `vars`, e.g. `conf/{tenant}`.
+    ///
+    /// Use `{{` and `}}` for literal braces. Rendering fails if a variable is missing, or if its
+    /// value is empty, `.`, `..` or contains a path separator, so that a variable taken from a
+    /// request cannot point outside of the templated directory.
+    pub fn with_name_tpl(template: &str, vars: &Map<String, String>) -> Result<Self> {
+        Ok(Self::with_name(&render_template(template, vars)?))
+    }
+
+    /// Parse the file as `format` when its extension is not registered by any format, e.g. for a
+    /// legacy `/etc/app/config` file.
+    ///
+    /// Files with a registered extension are still parsed in the format it stands for, and a
+    /// name without extension is still first looked up with each registered extension.
+    pub fn default_format(mut self, format: FileFormat) -> Self {
+        self.source.default_format(format);
+        self
+    }
+}
+
+#[cfg(feature = "fs")]
+fn render_template(template: &str, vars: &Map<String, String>) -> Result<String> {
+    let invalid = |reason: &str| {
+        ConfigError::Message(format!("invalid file name template {template:?}: {reason}"))
+    };
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        rendered.push_str(&rest[..start]);
+        let brace = &rest[start..];
+        if brace.starts_with("{{") || brace.starts_with("}}") {
+            rendered.push_str(&brace[..1]);
+            rest = &brace[2..];
+            continue;
+        }
+
+        let end = match (brace.starts_with('{'), brace.find('}')) {
+            (true, Some(end)) => end,
+            _ => return Err(invalid("unmatched brace")),
+        };
+        let name = &brace[1..end];
+        let value = vars
+            .get(name)
+            .ok_or_else(|| invalid(&format!("missing variable {name:?}")))?;
+        if matches!(value.as_str(), "" | "." | "..")
+            || value.contains(|c| c == '/' || std::path::is_separator(c))
+        {
+            return Err(invalid(&format!(
+                "variable {name:?} is not a single path component: {value:?}"
+            )));
+        }
+        rendered.push_str(value);
+        rest = &brace[end + 1..];
+    }
+    rendered.push_str(rest);
+
+    Ok(rendered)
 }
 
 impl<T, F> File<T, F>
@@ -91,23 +338,60 @@ where
         self.required = required;
         self
     }
+
+    /// Set the options used when parsing the file contents.
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Use the table at the path `fragment` as the root of the source, ignoring the rest.
+    ///
+    /// The file must still parse, but the other tables can have any shape. Building fails if
+    /// the fragment is missing, unless the file is not [required](Self::required), or if it is
+    /// not a table.
+    pub fn fragment(mut self, fragment: &str) -> Self {
+        self.fragment = Some(fragment.into());
+        self
+    }
+
+    /// Decrypt the file contents with `decryptor` before parsing them.
+    ///
+    /// A stream is then read to its end before being decrypted. Decryption errors are reported
+    /// like parse errors, along with the URI of the file, and the
+    /// [hash of the contents](crate::SourceInfo::content_hash) is that of the encrypted text.
+    pub fn decrypt<D>(mut self, decryptor: D) -> Self
+    where
+        D: Decryptor + Send + Sync + 'static,
+    {
+        self.decryptor = Some(Arc::new(decryptor));
+        self
+    }
 }
 
+#[cfg(feature = "fs")]
 impl<'a> From<&'a Path> for File<FileSourceFile, FileFormat> {
     fn from(path: &'a Path) -> Self {
         Self {
             format: None,
             required: true,
+            options: ParseOptions::default(),
+            decryptor: None,
+            fragment: None,
             source: FileSourceFile::new(path.to_path_buf()),
         }
     }
 }
 
+#[cfg(feature = "fs")]
 impl From<PathBuf> for File<FileSourceFile, FileFormat> {
     fn from(path: PathBuf) -> Self {
         Self {
             format: None,
             required: true,
+            options: ParseOptions::default(),
+            decryptor: None,
+            fragment: None,
             source: FileSourceFile::new(path),
         }
     }
@@ -123,26 +407,115 @@ where
     }
 
     fn collect(&self) -> Result<Map<String, Value>> {
+        // Parse streams as they are read, unless their text has to be decrypted first
+        if self.decryptor.is_none() {
+            if let Some(parsed) = self
+                .source
+                .parse_stream(self.format.as_ref(), &self.options)
+            {
+                return match parsed {
+                    Ok((info, map)) => {
+                        let uri = info.uri().map(Into::into);
+                        record_loaded(info);
+                        self.select_fragment(uri, map)
+                    }
+                    Err(StreamError::Read(cause)) => self.unreadable(ConfigError::Foreign(cause)),
+                    Err(StreamError::Parse { uri, cause }) => {
+                        Err(ConfigError::FileParse { uri, cause })
+                    }
+                };
+            }
+        }
+
         // Coerce the file contents to a string
         let (uri, contents, format) = match self
             .source
             .resolve(self.format.clone())
             .map_err(ConfigError::Foreign)
         {
-            Ok(result) => (result.uri, result.content, result.format),
+            Ok(result) => {
+                record_loaded(SourceInfo::new(
+                    result.uri.clone(),
+                    result.modified,
+                    &result.content,
+                ));
+                (result.uri, result.content, result.format)
+            }
 
-            Err(error) => {
-                if !self.required {
-                    return Ok(Map::new());
-                }
+            Err(error) => return self.unreadable(error),
+        };
 
-                return Err(error);
-            }
+        let contents = match self.decryptor {
+            Some(ref decryptor) => decryptor
+                .decrypt(uri.as_ref(), &contents)
+                .map_err(|cause| ConfigError::FileParse {
+                    uri: uri.clone(),
+                    cause,
+                })?,
+            None => contents,
         };
 
         // Parse the string using the given format
-        format
-            .parse(uri.as_ref(), &contents)
-            .map_err(|cause| ConfigError::FileParse { uri, cause })
+        let map = format
+            .parse_with_options(uri.as_ref(), &contents, &self.options)
+            .map_err(|cause| ConfigError::FileParse {
+                uri: uri.clone(),
+                cause,
+            })?;
+
+        self.select_fragment(uri, map)
+    }
+}
+
+impl<T, F> File<T, F>
+where
+    F: FileStoredFormat + 'static,
+    T: FileSource<F>,
+{
+    /// The values of a file that could not be read: none if it is optional and merely missing
+    fn unreadable(&self, error: ConfigError) -> Result<Map<String, Value>> {
+        // An ambiguous name, an unsafe or a too large file is a mistake in the
+        // configuration layout, not a missing file
+        let refused = matches!(
+            error,
+            ConfigError::Foreign(ref cause) if cause.is::<TooLargeError>()
+        );
+        #[cfg(feature = "fs")]
+        let refused = refused
+            || matches!(
+                error,
+                ConfigError::Foreign(ref cause)
+                    if cause.is::<AmbiguousFileError>() || cause.is::<UnsafeFileError>()
+            );
+        if !self.required && !refused {
+            return Ok(Map::new());
+        }
+
+        Err(error)
+    }
+
+    /// The table at the [fragment](File::with_name_and_fragment) of `map`, if any, parsed from
+    /// the file at `uri`
+    fn select_fragment(
+        &self,
+        uri: Option<String>,
+        map: Map<String, Value>,
+    ) -> Result<Map<String, Value>> {
+        let Some(ref fragment) = self.fragment else {
+            return Ok(map);
+        };
+        let root = Value::new(uri.as_ref(), ValueKind::Table(map));
+        match Expression::from_str(fragment)?.get(&root) {
+            Some(table) => table
+                .clone()
+                .into_table()
+                .map_err(|e| e.extend_with_key(fragment)),
+            None if !self.required => Ok(Map::new()),
+            None => Err(ConfigError::At {
+                error: Box::new(ConfigError::not_found(fragment)),
+                origin: uri,
+                key: None,
+            }),
+        }
     }
 }
@@ -1,21 +1,184 @@
+use std::cell::RefCell;
 use std::env;
 use std::error::Error;
+use std::fmt;
 use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use crate::file::{FileFormat, FileSource, FileStoredFormat, Format, source::FileSourceResult};
+use crate::file::source::{FileSourceResult, decode, read_capped};
+use crate::file::{FileFormat, FileSource, FileStoredFormat, Format};
+
+thread_local! {
+    /// The extensions registered on the builder whose sources are being collected
+    static EXTENSIONS: RefCell<Extensions> = RefCell::new(Extensions::default());
+}
+
+/// Formats of file extensions registered on a builder, `None` for a forbidden extension
+///
+/// They take precedence over the extensions of [`FileFormat`] when a file is given without a
+/// format.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Extensions(Vec<(String, Option<FileFormat>)>);
+
+impl Extensions {
+    pub(crate) fn set(&mut self, extension: &str, format: Option<FileFormat>) {
+        let extension = extension.strip_prefix('.').unwrap_or(extension);
+        self.0.retain(|(registered, _)| registered != extension);
+        self.0.push((extension.to_owned(), format));
+    }
+
+    /// Run `collect` with these extensions in effect for the files it resolves
+    pub(crate) fn scope<T>(&self, collect: impl FnOnce() -> T) -> T {
+        if self.0.is_empty() {
+            return collect();
+        }
+        let outer = EXTENSIONS.with(|extensions| extensions.replace(self.clone()));
+        let result = collect();
+        EXTENSIONS.with(|extensions| extensions.replace(outer));
+        result
+    }
+
+    pub(crate) fn current() -> Self {
+        EXTENSIONS.with(|extensions| extensions.borrow().clone())
+    }
+
+    /// The format registered for `extension`, `Some(None)` if it is forbidden
+    fn get(&self, extension: &str) -> Option<Option<FileFormat>> {
+        self.0
+            .iter()
+            .find(|(registered, _)| registered == extension)
+            .map(|(_, format)| *format)
+    }
+
+    /// The format of a file with `extension`, if it is registered here or by a [`FileFormat`]
+    pub(crate) fn format(&self, extension: &str) -> Option<FileFormat> {
+        self.get(extension).unwrap_or_else(|| {
+            FileFormat::all()
+                .iter()
+                .find(|format| format.extensions().contains(&extension))
+                .copied()
+        })
+    }
+
+    /// The extensions a name given without one is looked up with, along with their format
+    fn candidates(&self) -> Vec<(&str, FileFormat)> {
+        let builtin = FileFormat::all().iter().flat_map(|format| {
+            format
+                .extensions()
+                .iter()
+                .map(move |extension| (*extension, *format))
+        });
+        builtin
+            .filter(|(extension, _)| self.get(extension).is_none())
+            .chain(
+                self.0
+                    .iter()
+                    .filter_map(|(extension, format)| Some((extension.as_str(), (*format)?))),
+            )
+            .collect()
+    }
+}
 
 /// Describes a file sourced from a file
 #[derive(Clone, Debug)]
 pub struct FileSourceFile {
     /// Path of configuration file
     name: PathBuf,
+
+    /// Refuse to read the file through a symbolic link
+    refuse_symlinks: bool,
+
+    /// Refuse to read a file any user can write to
+    refuse_world_writable: bool,
+
+    /// Refuse to read a file larger than this many bytes
+    max_size: Option<u64>,
+
+    /// Format of a file whose extension is not registered by any format
+    default_format: Option<FileFormat>,
 }
 
 impl FileSourceFile {
     pub fn new(name: PathBuf) -> Self {
-        Self { name }
+        Self {
+            name,
+            refuse_symlinks: false,
+            refuse_world_writable: false,
+            max_size: None,
+            default_format: None,
+        }
+    }
+
+    pub(crate) fn refuse_symlinks(&mut self, refuse: bool) {
+        self.refuse_symlinks = refuse;
+    }
+
+    pub(crate) fn refuse_world_writable(&mut self, refuse: bool) {
+        self.refuse_world_writable = refuse;
+    }
+
+    pub(crate) fn max_size(&mut self, max_size: u64) {
+        self.max_size = Some(max_size);
+    }
+
+    pub(crate) fn default_format(&mut self, format: FileFormat) {
+        self.default_format = Some(format);
+    }
+
+    /// Read the file at `path`, checking the opened file rather than the path so it can't be
+    /// swapped between the checks and the read
+    fn read(
+        &self,
+        path: &Path,
+        uri: &str,
+    ) -> Result<(Vec<u8>, Option<SystemTime>), Box<dyn Error + Send + Sync>> {
+        let unsafe_file = |reason| UnsafeFileError {
+            path: path.to_path_buf(),
+            reason,
+        };
+
+        let link = if self.refuse_symlinks {
+            let link = fs::symlink_metadata(path)?;
+            if link.file_type().is_symlink() {
+                return Err(Box::new(unsafe_file(UnsafeFileReason::Symlink)));
+            }
+            Some(link)
+        } else {
+            None
+        };
+
+        let file = fs::File::open(path)?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() {
+            return Err(Box::new(unsafe_file(UnsafeFileReason::NotAFile)));
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt as _;
+
+            // The path was replaced after it was checked not to be a symlink
+            if let Some(link) = link {
+                if (link.dev(), link.ino()) != (metadata.dev(), metadata.ino()) {
+                    return Err(Box::new(unsafe_file(UnsafeFileReason::Symlink)));
+                }
+            }
+            if self.refuse_world_writable && metadata.mode() & 0o002 != 0 {
+                return Err(Box::new(unsafe_file(UnsafeFileReason::WorldWritable)));
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = link;
+
+        let buf = read_capped(
+            file,
+            Some(uri),
+            self.max_size,
+            metadata.len().try_into().unwrap_or(0),
+        )?;
+        Ok((buf, metadata.modified().ok()))
     }
 
     fn find_file<F>(
@@ -31,16 +194,25 @@ impl FileSourceFile {
             env::current_dir()?.as_path().join(&self.name)
         };
 
+        let extensions = Extensions::current();
+
         // First check for an _exact_ match
         if path.is_file() {
             if let Some(format) = format_hint {
                 return Ok((path, Box::new(format)));
             } else {
                 let ext = path.extension().unwrap_or_default().to_string_lossy();
-                for format in FileFormat::all() {
-                    if format.extensions().contains(&ext.as_ref()) {
-                        return Ok((path, Box::new(*format)));
-                    }
+                if matches!(extensions.get(&ext), Some(None)) {
+                    return Err(Box::new(UnsafeFileError {
+                        path,
+                        reason: UnsafeFileReason::ForbiddenExtension,
+                    }));
+                }
+                if let Some(format) = extensions.format(&ext) {
+                    return Ok((path, Box::new(format)));
+                }
+                if let Some(format) = self.default_format {
+                    return Ok((path, Box::new(format)));
                 }
                 return Err(Box::new(io::Error::new(
                     io::ErrorKind::NotFound,
@@ -69,13 +241,30 @@ impl FileSourceFile {
                 }
             }
             None => {
-                for format in FileFormat::all() {
-                    for ext in format.extensions() {
-                        path.set_extension(ext);
+                let mut candidates = Vec::new();
+                for (ext, format) in extensions.candidates() {
+                    path.set_extension(ext);
 
-                        if path.is_file() {
-                            return Ok((path, Box::new(*format)));
-                        }
+                    if path.is_file() {
+                        candidates.push((path.clone(), format));
+                    }
+                }
+
+                // Without format features, the candidates are uninhabited, so they are matched
+                // rather than indexed into
+                let mut candidates = candidates.into_iter();
+                match (candidates.next(), candidates.next()) {
+                    (None, _) => {}
+                    (Some((path, format)), None) => return Ok((path, Box::new(format))),
+                    (Some(first), Some(second)) => {
+                        return Err(Box::new(AmbiguousFileError {
+                            name: self.name.clone(),
+                            candidates: [first, second]
+                                .into_iter()
+                                .chain(candidates)
+                                .map(|(path, _)| path)
+                                .collect(),
+                        }));
                     }
                 }
             }
@@ -101,29 +290,85 @@ where
         // Find file
         let (filename, format) = self.find_file(format_hint)?;
 
-        // Attempt to use a relative path for the URI
-        let uri = env::current_dir()
-            .ok()
-            .and_then(|base| pathdiff::diff_paths(&filename, base))
-            .unwrap_or_else(|| filename.clone());
-
         // Read contents from file
-        let buf = fs::read(filename)?;
-
-        // If it exists, skip the UTF-8 BOM byte sequence: EF BB BF
-        let buf = if buf.len() >= 3 && &buf[0..3] == b"\xef\xbb\xbf" {
-            &buf[3..]
-        } else {
-            &buf
-        };
-
-        let c = String::from_utf8_lossy(buf);
-        let text = c.into_owned();
+        let uri = uri(&filename);
+        let (buf, modified) = self.read(&filename, &uri)?;
 
         Ok(FileSourceResult {
-            uri: Some(uri.to_string_lossy().into_owned()),
-            content: text,
+            uri: Some(uri),
+            content: decode(buf),
+            modified,
             format,
         })
     }
 }
+
+/// The URI of the file at `path`, relative to the current directory when possible
+pub(crate) fn uri(path: &Path) -> String {
+    env::current_dir()
+        .ok()
+        .and_then(|base| pathdiff::diff_paths(path, base))
+        .unwrap_or_else(|| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// More than one file matches a name given without an extension.
+#[derive(Debug)]
+pub(crate) struct AmbiguousFileError {
+    name: PathBuf,
+    candidates: Vec<PathBuf>,
+}
+
+impl fmt::Display for AmbiguousFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "configuration file \"{}\" is ambiguous, found",
+            self.name.to_string_lossy()
+        )?;
+
+        for (i, candidate) in self.candidates.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            let file_name = candidate.file_name().unwrap_or_default().to_string_lossy();
+            write!(f, "{separator} \"{file_name}\"")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for AmbiguousFileError {}
+
+/// A file refused by the checks enabled on its [`File`](crate::File).
+#[derive(Debug)]
+pub(crate) struct UnsafeFileError {
+    path: PathBuf,
+    reason: UnsafeFileReason,
+}
+
+#[derive(Debug)]
+enum UnsafeFileReason {
+    Symlink,
+    WorldWritable,
+    NotAFile,
+    ForbiddenExtension,
+}
+
+impl fmt::Display for UnsafeFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.reason {
+            UnsafeFileReason::Symlink => "is a symbolic link",
+            UnsafeFileReason::WorldWritable => "is writable by any user",
+            UnsafeFileReason::NotAFile => "is not a regular file",
+            UnsafeFileReason::ForbiddenExtension => "has a forbidden extension",
+        };
+        write!(
+            f,
+            "configuration file \"{}\" {reason}",
+            self.path.to_string_lossy()
+        )
+    }
+}
+
+impl Error for UnsafeFileError {}
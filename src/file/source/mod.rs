@@ -1,10 +1,20 @@
+#[cfg(feature = "fs")]
 pub(crate) mod file;
+pub(crate) mod reader;
 pub(crate) mod string;
 
 use std::error::Error;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::io::Read;
+use std::time::SystemTime;
 
-use crate::{Format, file::FileStoredFormat};
+use crate::map::Map;
+use crate::source::SourceInfo;
+use crate::value::Value;
+use crate::{
+    Format,
+    file::{FileStoredFormat, ParseOptions},
+};
 
 /// Describes where the [`File`][super::File] is sourced
 pub trait FileSource<T>: Debug + Clone
@@ -15,6 +25,36 @@ where
         &self,
         format_hint: Option<T>,
     ) -> Result<FileSourceResult, Box<dyn Error + Send + Sync>>;
+
+    /// Parse this source while reading it, for sources like streams that are not worth holding
+    /// in memory as text, or `None` to [resolve](Self::resolve) it instead
+    #[doc(hidden)]
+    fn parse_stream(
+        &self,
+        format_hint: Option<&T>,
+        options: &ParseOptions,
+    ) -> Option<ParsedStream> {
+        let _ = (format_hint, options);
+        None
+    }
+}
+
+/// The values of a [parsed stream](FileSource::parse_stream), along with what was read
+pub(crate) type ParsedStream = Result<(SourceInfo, Map<String, Value>), StreamError>;
+
+/// Why [parsing a stream](FileSource::parse_stream) failed
+#[doc(hidden)]
+#[allow(unnameable_types)]
+#[derive(Debug)]
+pub enum StreamError {
+    /// Reading the stream failed, like [resolving](FileSource::resolve) a file
+    Read(Box<dyn Error + Send + Sync>),
+
+    /// The stream is not valid in its format
+    Parse {
+        uri: Option<String>,
+        cause: Box<dyn Error + Send + Sync>,
+    },
 }
 
 #[allow(unnameable_types)] // Unsure if/how to expose this
@@ -22,6 +62,7 @@ pub struct FileSourceResult {
     pub(crate) uri: Option<String>,
     pub(crate) content: String,
     pub(crate) format: Box<dyn Format>,
+    pub(crate) modified: Option<SystemTime>,
 }
 
 impl FileSourceResult {
@@ -37,3 +78,64 @@ impl FileSourceResult {
         self.format.as_ref()
     }
 }
+
+/// Decode the contents of a file as UTF-8, skipping the BOM byte sequence `EF BB BF` if it exists
+///
+/// Valid UTF-8 is decoded in place, without copying the contents.
+pub(crate) fn decode(mut buf: Vec<u8>) -> String {
+    if buf.starts_with(b"\xef\xbb\xbf") {
+        buf.drain(..3);
+    }
+    String::from_utf8(buf)
+        .unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned())
+}
+
+/// Read `reader` to its end, failing if it holds more than `max_size` bytes
+///
+/// `size_hint` is the expected size, used to allocate the buffer once.
+pub(crate) fn read_capped(
+    reader: impl Read,
+    uri: Option<&str>,
+    max_size: Option<u64>,
+    size_hint: usize,
+) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let too_large = |limit| {
+        Box::new(TooLargeError {
+            uri: uri.map(Into::into),
+            limit,
+        })
+    };
+
+    if let Some(limit) = max_size {
+        if size_hint as u64 > limit {
+            return Err(too_large(limit));
+        }
+    }
+
+    let mut buf = Vec::with_capacity(size_hint);
+    let cap = max_size.map_or(u64::MAX, |limit| limit.saturating_add(1));
+    reader.take(cap).read_to_end(&mut buf)?;
+    match max_size {
+        Some(limit) if buf.len() as u64 > limit => Err(too_large(limit)),
+        _ => Ok(buf),
+    }
+}
+
+/// Contents larger than the limit set with [`File::max_size`](super::File::max_size).
+#[derive(Debug)]
+pub(crate) struct TooLargeError {
+    uri: Option<String>,
+    limit: u64,
+}
+
+impl fmt::Display for TooLargeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.uri {
+            Some(ref uri) => write!(f, "configuration file \"{uri}\"")?,
+            None => write!(f, "configuration")?,
+        }
+        write!(f, " is larger than the limit of {} bytes", self.limit)
+    }
+}
+
+impl Error for TooLargeError {}
@@ -0,0 +1,251 @@
+use std::error::Error;
+use std::fmt;
+use std::hash::{DefaultHasher, Hasher as _};
+use std::io::{self, BufRead as _, BufReader, Read};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::map::Map;
+use crate::source::SourceInfo;
+use crate::value::Value;
+use crate::{
+    Format,
+    file::source::{
+        FileSourceResult, ParsedStream, StreamError, TooLargeError, decode, read_capped,
+    },
+    file::{FileSource, FileStoredFormat, ParseOptions},
+};
+
+/// Describes a file sourced from a stream, like the standard input
+///
+/// The stream is parsed as it is read, the first time the source is collected, and the parsed
+/// values are kept for later builds, e.g. on refresh, as the stream can't be read again. Clones
+/// share them. A stream of a [decrypted](crate::File::decrypt) file is read to its end first.
+#[derive(Clone)]
+pub struct FileSourceReader {
+    uri: Option<&'static str>,
+    max_size: Option<u64>,
+    stream: Arc<Mutex<Stream>>,
+}
+
+enum Stream {
+    Unread(Box<dyn Read + Send>),
+    /// Read to its end, to be [resolved](FileSource::resolve)
+    Read(String),
+    /// Parsed as it was read
+    Parsed(Map<String, Value>, SourceInfo),
+    /// Reading the stream, or parsing it if `read` is false, failed with `cause`
+    Failed {
+        read: bool,
+        cause: Arc<dyn Error + Send + Sync>,
+    },
+}
+
+impl FileSourceReader {
+    pub(crate) fn new(uri: Option<&'static str>, reader: Box<dyn Read + Send>) -> Self {
+        Self {
+            uri,
+            max_size: None,
+            stream: Arc::new(Mutex::new(Stream::Unread(reader))),
+        }
+    }
+
+    pub(crate) fn max_size(&mut self, max_size: u64) {
+        self.max_size = Some(max_size);
+    }
+
+    fn content(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let mut stream = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Stream::Unread(ref mut reader) = *stream {
+            *stream = match read_capped(reader, self.uri, self.max_size, 0) {
+                Ok(buf) => Stream::Read(decode(buf)),
+                Err(cause) => Stream::Failed {
+                    read: true,
+                    cause: cause.into(),
+                },
+            };
+        }
+
+        match *stream {
+            Stream::Read(ref content) => Ok(content.clone()),
+            Stream::Parsed(..) => Err(Box::new(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the stream was parsed as it was read, its contents were not kept",
+            ))),
+            Stream::Failed { ref cause, .. } => Err(self.failure(cause)),
+            Stream::Unread(_) => unreachable!("the stream was just read"),
+        }
+    }
+
+    /// An error of the stream, returned again on each build
+    ///
+    /// I/O errors are returned as an [`io::Error`] of the same kind, wrapping the original one.
+    fn failure(&self, cause: &Arc<dyn Error + Send + Sync>) -> Box<dyn Error + Send + Sync> {
+        if let Some(too_large) = cause.downcast_ref::<TooLargeError>() {
+            return Box::new(TooLargeError {
+                uri: too_large.uri.clone(),
+                limit: too_large.limit,
+            });
+        }
+        match cause.downcast_ref::<io::Error>() {
+            Some(error) => Box::new(io::Error::new(error.kind(), Arc::clone(cause))),
+            None => Box::new(Arc::clone(cause)),
+        }
+    }
+
+    fn parse(&self, format: &dyn Format, options: &ParseOptions) -> ParsedStream {
+        let uri = self.uri.map(String::from);
+        let mut stream = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+        match *stream {
+            Stream::Unread(ref mut reader) => {
+                let mut tracked = Tracked {
+                    reader,
+                    limit: self.max_size,
+                    read: 0,
+                    error: None,
+                    hasher: DefaultHasher::new(),
+                };
+                let parsed = parse_tracked(&mut tracked, uri.as_ref(), format, options);
+                *stream = match (tracked.finish(self.uri), parsed) {
+                    (Err(cause), _) => Stream::Failed {
+                        read: true,
+                        cause: cause.into(),
+                    },
+                    (Ok(_), Err(cause)) => Stream::Failed {
+                        read: false,
+                        cause: cause.into(),
+                    },
+                    (Ok(hash), Ok(map)) => {
+                        Stream::Parsed(map, SourceInfo::with_hash(uri.clone(), None, hash))
+                    }
+                };
+            }
+            Stream::Read(ref content) => {
+                let map = format
+                    .parse_with_options(uri.as_ref(), content, options)
+                    .map_err(|cause| StreamError::Parse { uri, cause })?;
+                return Ok((
+                    SourceInfo::new(self.uri.map(Into::into), None, content),
+                    map,
+                ));
+            }
+            Stream::Parsed(..) | Stream::Failed { .. } => {}
+        }
+
+        match *stream {
+            Stream::Parsed(ref map, ref info) => Ok((info.clone(), map.clone())),
+            Stream::Failed {
+                read: true,
+                ref cause,
+            } => Err(StreamError::Read(self.failure(cause))),
+            Stream::Failed {
+                read: false,
+                ref cause,
+            } => Err(StreamError::Parse {
+                uri,
+                cause: self.failure(cause),
+            }),
+            Stream::Unread(_) | Stream::Read(_) => unreachable!("the stream was just parsed"),
+        }
+    }
+}
+
+/// Parse the stream read through `tracked`, skipping a UTF-8 BOM
+fn parse_tracked(
+    tracked: &mut Tracked<'_>,
+    uri: Option<&String>,
+    format: &dyn Format,
+    options: &ParseOptions,
+) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let mut reader = BufReader::new(tracked);
+    if reader.fill_buf()?.starts_with(b"\xef\xbb\xbf") {
+        reader.consume(3);
+    }
+    format.parse_reader(uri, &mut reader, options)
+}
+
+/// A stream being parsed, hashing what is read and reading at most one byte past its limit
+struct Tracked<'a> {
+    reader: &'a mut Box<dyn Read + Send>,
+    limit: Option<u64>,
+    read: u64,
+    /// The first error of `reader`, kept as the parser is handed a copy
+    error: Option<io::Error>,
+    hasher: DefaultHasher,
+}
+
+impl Tracked<'_> {
+    /// The hash of the stream, or why reading it failed
+    fn finish(self, uri: Option<&str>) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        match (self.limit, self.error) {
+            (Some(limit), _) if self.read > limit => Err(Box::new(TooLargeError {
+                uri: uri.map(Into::into),
+                limit,
+            })),
+            (_, Some(error)) => Err(Box::new(error)),
+            (_, None) => {
+                let mut hasher = self.hasher;
+                // Like hashing the contents as a `str`
+                hasher.write_u8(0xff);
+                Ok(hasher.finish())
+            }
+        }
+    }
+}
+
+impl Read for Tracked<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self
+            .limit
+            .map_or(u64::MAX, |limit| (limit + 1).saturating_sub(self.read));
+        let len = buf
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        match self.reader.read(&mut buf[..len]) {
+            Ok(read) => {
+                self.hasher.write(&buf[..read]);
+                self.read += read as u64;
+                Ok(read)
+            }
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => Err(error),
+            Err(error) => {
+                let kind = error.kind();
+                self.error.get_or_insert(error);
+                Err(kind.into())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for FileSourceReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileSourceReader")
+            .field("uri", &self.uri)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> FileSource<F> for FileSourceReader
+where
+    F: Format + FileStoredFormat + 'static,
+{
+    fn resolve(
+        &self,
+        format_hint: Option<F>,
+    ) -> Result<FileSourceResult, Box<dyn Error + Send + Sync>> {
+        Ok(FileSourceResult {
+            uri: self.uri.map(Into::into),
+            content: self.content()?,
+            modified: None,
+            format: Box::new(format_hint.expect("from_reader requires a set file format")),
+        })
+    }
+
+    fn parse_stream(
+        &self,
+        format_hint: Option<&F>,
+        options: &ParseOptions,
+    ) -> Option<ParsedStream> {
+        let format = format_hint.expect("from_reader requires a set file format");
+        Some(self.parse(format, options))
+    }
+}
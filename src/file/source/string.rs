@@ -27,6 +27,7 @@ where
         Ok(FileSourceResult {
             uri: None,
             content: self.0.clone(),
+            modified: None,
             format: Box::new(format_hint.expect("from_str requires a set file format")),
         })
     }
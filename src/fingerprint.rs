@@ -0,0 +1,57 @@
+use std::fmt::Write as _;
+
+use sha2::{Digest as _, Sha256};
+
+use crate::value::{Value, ValueKind};
+
+/// SHA-256 of the canonical rendering of `value`, as lowercase hex
+pub(crate) fn fingerprint(value: &Value) -> String {
+    let mut rendering = String::new();
+    render(&mut rendering, value);
+
+    Sha256::digest(rendering.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Render `value` so that no two trees render the same
+///
+/// Tables and arrays are prefixed with their length, table keys are sorted, and strings are
+/// prefixed with their length. Integers are rendered regardless of their width and floats use
+/// their shortest round-trip representation, with `-0.0` and NaNs made canonical. Origins are
+/// not part of the rendering.
+fn render(output: &mut String, value: &Value) {
+    let _ = match value.kind {
+        ValueKind::Nil => writeln!(output, "nil"),
+        ValueKind::Boolean(value) => writeln!(output, "bool {value}"),
+        ValueKind::I64(value) => writeln!(output, "int {value}"),
+        ValueKind::I128(value) => writeln!(output, "int {value}"),
+        ValueKind::U64(value) => writeln!(output, "int {value}"),
+        ValueKind::U128(value) => writeln!(output, "int {value}"),
+        ValueKind::Float(value) if value.is_nan() => writeln!(output, "float NaN"),
+        // Also matches `-0.0`
+        ValueKind::Float(0.0) => writeln!(output, "float 0.0"),
+        ValueKind::Float(value) => writeln!(output, "float {value:?}"),
+        ValueKind::String(ref value) => writeln!(output, "string {}:{value}", value.len()),
+        ValueKind::Table(ref table) => {
+            let _ = writeln!(output, "table {}", table.len());
+            let mut entries: Vec<_> = table.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            for (key, child) in entries {
+                let _ = writeln!(output, "{}:{key}", key.len());
+                render(output, child);
+            }
+            Ok(())
+        }
+        ValueKind::Array(ref array) => {
+            let _ = writeln!(output, "array {}", array.len());
+            for child in array {
+                render(output, child);
+            }
+            Ok(())
+        }
+    };
+}
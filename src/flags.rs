@@ -0,0 +1,191 @@
+use std::fmt;
+
+use serde_core::de::Deserialize;
+
+use crate::config::Config;
+use crate::notify::leaves;
+use crate::value::{Value, ValueKind};
+
+type Callback = Box<dyn Fn(&FeatureFlags) + Send + Sync>;
+
+/// Feature flags read from a table of a [`Config`], `features` unless [set](Self::table)
+/// otherwise
+///
+/// A flag is off unless the [`Config`] turns it on, so a missing or mistyped flag never enables
+/// a feature. Each flag is either a boolean, written like the values of
+/// [`Config::get_bool`], or a table with these keys:
+///
+/// - `enabled`, a boolean, `true` by default, to turn the flag off whatever its rollout
+/// - `rollout`, the percentage of subjects the flag is on for, `100` by default, like `25` or
+///   `"25%"`
+///
+/// ```rust
+/// # use config::*;
+/// let config = Config::builder()
+///     .set_default("features.search", true)?
+///     .set_default("features.checkout.rollout", 25)?
+///     .set_default("features.checkout.variant", "compact")?
+///     .build()?;
+/// let flags = FeatureFlags::new(config);
+///
+/// assert!(flags.is_enabled("search"));
+/// assert!(!flags.is_enabled("checkout"));
+/// assert!(!flags.is_enabled("unknown"));
+/// assert_eq!(flags.get::<String>("checkout.variant"), "compact");
+///
+/// let users = (0..1000).map(|user| format!("user-{user}"));
+/// let enabled = users.filter(|user| flags.is_enabled_for("checkout", user)).count();
+/// assert!((200..300).contains(&enabled));
+/// # Ok::<(), ConfigError>(())
+/// ```
+///
+/// Feed each reloaded [`Config`], e.g. yielded by
+/// [`ConfigBuilder::watch`](crate::ConfigBuilder::watch), to [`update`](Self::update) to notify
+/// the [subscribers](Self::subscribe) of the flags that changed.
+pub struct FeatureFlags {
+    config: Config,
+    table: String,
+    subscribers: Vec<(String, Callback)>,
+}
+
+impl FeatureFlags {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            table: "features".to_owned(),
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Read the flags from the table at `key` instead of `features`
+    #[must_use]
+    pub fn table(mut self, key: &str) -> Self {
+        self.table = key.to_owned();
+        self
+    }
+
+    /// The [`Config`] the flags are read from
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Whether `flag` is on for every subject, i.e. not rolled out to only a percentage of them
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.rollout(flag) >= 100.0
+    }
+
+    /// Whether `flag` is on for `subject`, e.g. a user or tenant ID
+    ///
+    /// Each subject is assigned a bucket from a hash of the flag and the subject, which is stable
+    /// across processes and releases: a subject keeps its assignment as long as the rollout
+    /// does not change, and raising the rollout only turns the flag on for more subjects.
+    pub fn is_enabled_for(&self, flag: &str, subject: &str) -> bool {
+        let rollout = self.rollout(flag);
+        rollout >= 100.0 || (bucket(flag, subject) as f64) < rollout * 100.0
+    }
+
+    /// Deserialize the value at `key` in the table of the flags, like the variant of a flag at
+    /// `checkout.variant`, or `T::default()` if it is missing or not a `T`
+    pub fn get<'de, T>(&self, key: &str) -> T
+    where
+        T: Deserialize<'de> + Default,
+    {
+        self.config.get(&self.key(key)).unwrap_or_default()
+    }
+
+    /// Call `callback` with the updated flags when the value of `flag` changes
+    pub fn subscribe<F>(&mut self, flag: &str, callback: F) -> &mut Self
+    where
+        F: Fn(&Self) + Send + Sync + 'static,
+    {
+        self.subscribers.push((flag.to_owned(), Box::new(callback)));
+        self
+    }
+
+    /// Read the flags from `config` from now on, and notify the subscribers of the flags whose
+    /// value changed
+    ///
+    /// Values are compared regardless of their origin.
+    pub fn update(&mut self, config: Config) {
+        let previous = std::mem::replace(&mut self.config, config);
+        for (flag, callback) in &self.subscribers {
+            let key = self.key(flag);
+            if !same_values(
+                previous.get::<Value>(&key).ok(),
+                self.config.get::<Value>(&key).ok(),
+            ) {
+                callback(self);
+            }
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}.{key}", self.table)
+    }
+
+    /// The percentage of subjects `flag` is on for, 0 if it is missing or invalid
+    fn rollout(&self, flag: &str) -> f64 {
+        let Ok(value) = self.config.get::<Value>(&self.key(flag)) else {
+            return 0.0;
+        };
+        let ValueKind::Table(table) = value.kind else {
+            return match value.into_bool() {
+                Ok(true) => 100.0,
+                _ => 0.0,
+            };
+        };
+
+        let enabled = table
+            .get("enabled")
+            .cloned()
+            .map_or(Ok(true), Value::into_bool);
+        let rollout = match table.get("rollout").cloned() {
+            None => Some(100.0),
+            Some(Value {
+                kind: ValueKind::String(text),
+                ..
+            }) => text.trim().trim_end_matches('%').trim_end().parse().ok(),
+            Some(value) => value.into_float().ok(),
+        };
+        match (enabled, rollout) {
+            (Ok(true), Some(rollout)) if rollout.is_finite() => rollout.clamp(0.0, 100.0),
+            _ => 0.0,
+        }
+    }
+}
+
+impl fmt::Debug for FeatureFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags: Vec<&str> = self
+            .subscribers
+            .iter()
+            .map(|(flag, _)| flag.as_str())
+            .collect();
+        f.debug_struct("FeatureFlags")
+            .field("table", &self.table)
+            .field("subscribed", &flags)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether `a` and `b` hold the same values, regardless of their origin
+fn same_values(a: Option<Value>, b: Option<Value>) -> bool {
+    let kinds = |value: &Option<Value>| -> Vec<(String, ValueKind)> {
+        value.as_ref().map_or_else(Vec::new, |value| {
+            leaves(value)
+                .into_iter()
+                .map(|(key, leaf)| (key, leaf.kind.clone()))
+                .collect()
+        })
+    };
+    kinds(&a) == kinds(&b)
+}
+
+/// The bucket of `subject` for `flag`, from 0 to 9999, from their 64 bit FNV-1a hash
+fn bucket(flag: &str, subject: &str) -> u64 {
+    let bytes = flag.bytes().chain([0]).chain(subject.bytes());
+    let hash = bytes.fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    hash % 10_000
+}
@@ -1,8 +1,12 @@
 use std::error::Error;
+use std::fmt;
+use std::io::Read;
+use std::ops::Range;
 
 use crate::error::{ConfigError, Unexpected};
+use crate::file::ParseOptions;
 use crate::map::Map;
-use crate::value::{Value, ValueKind};
+use crate::value::{Position, Span, Value, ValueKind};
 
 /// Describes a format of configuration source data
 ///
@@ -13,19 +17,60 @@ use crate::value::{Value, ValueKind};
 /// the need to alter library sources.
 ///
 /// What is more, it is recommended to use this trait with custom [`Source`](crate::Source)s and their async counterparts.
+///
+/// The [`format_support`](crate::format_support) module walks through an implementation and
+/// provides the helpers the built-in formats use, so custom formats report errors the same way.
 pub trait Format {
     /// Parses provided content into configuration values understood by the library.
     ///
     /// It also allows specifying optional URI of the source associated with format instance that can facilitate debugging.
+    ///
+    /// The returned map is the root table of the document. Values should carry `uri` as their
+    /// origin, see [`Value::new`]. Errors are wrapped by the caller, e.g. into
+    /// [`ConfigError::FileParse`] naming the file, so they only need to describe the problem.
     fn parse(
         &self,
         uri: Option<&String>,
         text: &str,
     ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>>;
+
+    /// Parses provided content, configuring the parser with `options`.
+    ///
+    /// Formats without configurable behavior can rely on the default, which ignores `options` and
+    /// calls [`parse`](Self::parse).
+    fn parse_with_options(
+        &self,
+        uri: Option<&String>,
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+        let _ = options;
+        self.parse(uri, text)
+    }
+
+    /// Parses the content read from `reader`, configuring the parser with `options`.
+    ///
+    /// Used for [streams](crate::File::from_reader), which are parsed as they are read. Formats
+    /// whose parser reads incrementally, like JSON, override it so that the text of a large
+    /// document is never held in memory. The default reads `reader` to its end and calls
+    /// [`parse_with_options`](Self::parse_with_options).
+    fn parse_reader(
+        &self,
+        uri: Option<&String>,
+        reader: &mut dyn Read,
+        options: &ParseOptions,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        self.parse_with_options(uri, &crate::file::decode(buf), options)
+    }
 }
 
-// Have a proper error fire if the root of a file is ever not a Table
-pub(crate) fn extract_root_table(
+/// Unwrap the root table of a document, failing like the built-in formats if `value` is not a
+/// table
+///
+/// Formats whose parser returns an arbitrary value, like JSON, call it on the parsed document.
+pub fn extract_root_table(
     uri: Option<&String>,
     value: Value,
 ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
@@ -44,3 +89,116 @@ pub(crate) fn extract_root_table(
     .map_err(|err| ConfigError::invalid_root(uri, err))
     .map_err(|err| Box::new(err) as Box<dyn Error + Send + Sync>)
 }
+
+/// Converts byte offsets in a text to [`Position`]s
+///
+/// Built once per document, for parsers reporting byte ranges, to record [`Span`]s on values
+/// with [`Value::set_span`].
+#[derive(Debug)]
+pub struct LineIndex<'a> {
+    text: &'a str,
+    /// Offset of the first byte of each line
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index the lines of `text`
+    pub fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { text, starts }
+    }
+
+    /// The span covering the bytes of `range`
+    pub fn span(&self, range: Range<usize>) -> Span {
+        Span::new(self.position(range.start), self.position(range.end))
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let column = self.text[self.starts[line]..offset].chars().count();
+        Position::new(line + 1, column + 1)
+    }
+}
+
+/// Convert a map key to a table key: scalars are stringified, tables and arrays are rejected
+///
+/// Null keys become `"null"`. Formats allowing other keys than strings, like YAML and RON, use it
+/// so maps keyed by numbers or booleans load the same way from all of them.
+pub fn scalar_key(key: Value) -> Result<String, Box<dyn Error + Send + Sync>> {
+    match key.kind {
+        ValueKind::String(key) => Ok(key),
+        ValueKind::Nil => Ok("null".to_owned()),
+        ValueKind::Table(_) | ValueKind::Array(_) => {
+            Err(Box::new(UnsupportedKeyError(key.kind.type_name())))
+        }
+        kind => Ok(kind.to_string()),
+    }
+}
+
+#[derive(Debug)]
+struct UnsupportedKeyError(&'static str);
+
+impl fmt::Display for UnsupportedKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot use {} key, only scalar keys are supported",
+            self.0
+        )
+    }
+}
+
+impl Error for UnsupportedKeyError {}
+
+/// The key or index of a child value in a parsed document
+#[derive(Debug, Clone, Copy)]
+pub enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Attribute `error`, raised while converting a child value, to the key or index of that child
+///
+/// Applied at each level while unwinding, errors read like ``cannot use array key, only scalar
+/// keys are supported at `limits[1].max` ``.
+pub fn within(
+    error: Box<dyn Error + Send + Sync>,
+    segment: Segment<'_>,
+) -> Box<dyn Error + Send + Sync> {
+    let segment = match segment {
+        Segment::Key(key) => key.to_owned(),
+        Segment::Index(index) => format!("[{index}]"),
+    };
+    match error.downcast::<KeyPathError>() {
+        Ok(mut error) => {
+            let dot = if error.path.starts_with('[') { "" } else { "." };
+            error.path = format!("{segment}{dot}{}", error.path);
+            error
+        }
+        Err(cause) => Box::new(KeyPathError {
+            path: segment,
+            cause,
+        }),
+    }
+}
+
+/// An error converting the value at `path` of a parsed document
+#[derive(Debug)]
+struct KeyPathError {
+    path: String,
+    cause: Box<dyn Error + Send + Sync>,
+}
+
+impl fmt::Display for KeyPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at `{}`", self.cause, self.path)
+    }
+}
+
+impl Error for KeyPathError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.cause.as_ref())
+    }
+}
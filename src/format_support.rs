@@ -0,0 +1,107 @@
+//! Helpers for implementing [`Format`](crate::Format) outside of this crate
+//!
+//! The built-in formats follow the same conventions, which a format shipped by another crate,
+//! like XML or KDL, should keep so it can be swapped in without surprises:
+//!
+//! - [`Format::parse`](crate::Format::parse) returns the root table of the document. Parsers
+//!   returning any value check it with [`extract_root_table`], so a document holding e.g. a
+//!   bare list fails with `invalid type: sequence, expected a map`.
+//! - Each value carries the URI of the document as its origin, by building it with
+//!   [`Value::new`](crate::Value::new).
+//! - Keys that are not strings are stringified with [`scalar_key`], so `1` and `true` load as
+//!   `"1"` and `"true"`, and deserialize into maps keyed by numbers or booleans.
+//! - Errors only describe the problem: [`File`](crate::File) wraps them into
+//!   [`ConfigError::FileParse`](crate::ConfigError::FileParse), naming the file. Errors about a
+//!   nested value are attributed to its key path with [`within`], while unwinding.
+//! - [`Format::parse_with_options`](crate::Format::parse_with_options) honors
+//!   [`ParseOptions::max_depth`](crate::ParseOptions::max_depth) with [`check_depth`], and
+//!   records spans with a [`LineIndex`] when
+//!   [`ParseOptions::records_spans`](crate::ParseOptions::records_spans) is set.
+//!
+//! A format of `key = value` lines, where keys may be nested with `.`, goes as follows:
+//!
+//! ```rust
+//! use std::error::Error;
+//!
+//! use config::format_support::{LineIndex, Segment, check_depth, within};
+//! use config::{Config, File, FileStoredFormat, Format, Map, ParseOptions, Value};
+//!
+//! #[derive(Debug, Clone)]
+//! struct Lines;
+//!
+//! impl Format for Lines {
+//!     fn parse(
+//!         &self,
+//!         uri: Option<&String>,
+//!         text: &str,
+//!     ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+//!         self.parse_with_options(uri, text, &ParseOptions::default())
+//!     }
+//!
+//!     fn parse_with_options(
+//!         &self,
+//!         uri: Option<&String>,
+//!         text: &str,
+//!         options: &ParseOptions,
+//!     ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+//!         let index = LineIndex::new(text);
+//!         let mut root = Map::new();
+//!         let mut offset = 0;
+//!         for line in text.split_inclusive('\n') {
+//!             let start = offset;
+//!             offset += line.len();
+//!             let Some((key, value)) = line.split_once('=') else {
+//!                 continue;
+//!             };
+//!             let mut value = Value::new(uri, value.trim());
+//!             if options.records_spans() {
+//!                 value.set_span(index.span(start..start + line.trim_end().len()));
+//!             }
+//!             insert(&mut root, key.trim(), value, uri)?;
+//!         }
+//!         check_depth(&root, options)?;
+//!         Ok(root)
+//!     }
+//! }
+//!
+//! fn insert(
+//!     table: &mut Map<String, Value>,
+//!     key: &str,
+//!     value: Value,
+//!     uri: Option<&String>,
+//! ) -> Result<(), Box<dyn Error + Send + Sync>> {
+//!     let Some((head, rest)) = key.split_once('.') else {
+//!         table.insert(key.to_owned(), value);
+//!         return Ok(());
+//!     };
+//!     let child = table
+//!         .entry(head.to_owned())
+//!         .or_insert_with(|| Value::new(uri, Map::<String, Value>::new()));
+//!     let child = match child.kind {
+//!         config::ValueKind::Table(ref mut child) => child,
+//!         _ => return Err(within(format!("`{rest}` is set on a scalar").into(), Segment::Key(head))),
+//!     };
+//!     insert(child, rest, value, uri).map_err(|err| within(err, Segment::Key(head)))
+//! }
+//!
+//! impl FileStoredFormat for Lines {
+//!     fn file_extensions(&self) -> &'static [&'static str] {
+//!         &["lines"]
+//!     }
+//! }
+//!
+//! let config = Config::builder()
+//!     .add_source(File::from_str("server.host = localhost\nserver.port = 8080", Lines))
+//!     .build()?;
+//! assert_eq!(config.get_int("server.port")?, 8080);
+//!
+//! let error = Config::builder()
+//!     .add_source(File::from_str("a.b = 1\na.b.c = 2", Lines))
+//!     .build()
+//!     .unwrap_err();
+//! assert_eq!(error.to_string(), "`c` is set on a scalar at `a.b`");
+//! # Ok::<(), config::ConfigError>(())
+//! ```
+
+pub use crate::file::check_depth;
+pub use crate::format::{LineIndex, Segment, extract_root_table, scalar_key, within};
@@ -0,0 +1,240 @@
+//! Deserializers for values commonly written loosely in configuration files
+//!
+//! Use them on fields with `#[serde(deserialize_with = "...")]`:
+//!
+//! ```rust
+//! # use config::*;
+//! #[derive(serde::Deserialize)]
+//! struct Settings {
+//!     #[serde(deserialize_with = "config::helpers::comma_separated")]
+//!     hosts: Vec<String>,
+//!     #[serde(deserialize_with = "config::helpers::flexible_bool")]
+//!     verbose: bool,
+//! }
+//!
+//! let settings: Settings = Config::builder()
+//!     .set_default("hosts", "a.example.com, b.example.com")?
+//!     .set_default("verbose", "Off")?
+//!     .build()?
+//!     .try_deserialize()?;
+//!
+//! assert_eq!(settings.hosts, ["a.example.com", "b.example.com"]);
+//! assert!(!settings.verbose);
+//! # Ok::<(), ConfigError>(())
+//! ```
+//!
+//! They work with any [`Deserializer`], not only that of [`Config`](crate::Config). The helpers
+//! for types of other crates need the feature of the same name, `log` or `url`.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use serde_core::Deserializer;
+use serde_core::de::{self, Deserialize, Unexpected};
+
+/// Deserialize a list from a string of items separated by commas, like `"80, 443"`, or from a
+/// sequence
+///
+/// Items are trimmed and parsed with [`FromStr`], and empty ones are skipped, so an empty
+/// string is an empty list. This lets a list be set from a single environment variable.
+pub fn comma_separated<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr + Deserialize<'de>,
+    T::Err: fmt::Display,
+{
+    struct Visitor<T>(PhantomData<T>);
+
+    impl<'de, T> de::Visitor<'de> for Visitor<T>
+    where
+        T: FromStr + Deserialize<'de>,
+        T::Err: fmt::Display,
+    {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a list, or a string of items separated by commas")
+        }
+
+        fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+            text.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| {
+                    item.parse()
+                        .map_err(|err| E::custom(format_args!("invalid item `{item}`: {err}")))
+                })
+                .collect()
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(item) = seq.next_element()? {
+                items.push(item);
+            }
+            Ok(items)
+        }
+    }
+
+    deserializer.deserialize_any(Visitor(PhantomData))
+}
+
+/// Deserialize a value of any type, converting strings to numbers and booleans and back as
+/// [`Config`](crate::Config) does
+///
+/// Serde reads the fields of internally tagged and untagged enums before knowing their types,
+/// and then no longer converts strings, like those of environment variables, to the type of a
+/// field. Use this helper on such fields, or convert them all with
+/// [`ConfigBuilder::coerce_strings`](crate::builder::ConfigBuilder::coerce_strings):
+///
+/// ```rust
+/// # use config::*;
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(tag = "type", rename_all = "lowercase")]
+/// enum Listener {
+///     Tcp {
+///         #[serde(deserialize_with = "config::helpers::coerced")]
+///         port: u16,
+///     },
+///     Unix {
+///         path: String,
+///     },
+/// }
+///
+/// let listener: Listener = Config::builder()
+///     .set_default("type", "tcp")?
+///     .set_default("port", "8080")?
+///     .build()?
+///     .try_deserialize()?;
+///
+/// assert_eq!(listener, Listener::Tcp { port: 8080 });
+/// # Ok::<(), ConfigError>(())
+/// ```
+pub fn coerced<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let value = crate::Value::deserialize(deserializer)?;
+    T::deserialize(value).map_err(de::Error::custom)
+}
+
+/// Deserialize a boolean from `true`, `on`, `yes` or `1`, or from `false`, `off`, `no` or `0`,
+/// whatever their case, as well as from a boolean or the integers 0 and 1
+pub fn flexible_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct Visitor;
+
+    impl de::Visitor<'_> for Visitor {
+        type Value = bool;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a boolean like `true`, `on`, `yes` or `1`")
+        }
+
+        fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+            Ok(value)
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            match value {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(E::invalid_value(Unexpected::Signed(value), &self)),
+            }
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+            match value {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(E::invalid_value(Unexpected::Unsigned(value), &self)),
+            }
+        }
+
+        fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+            match text.trim().to_lowercase().as_str() {
+                "true" | "on" | "yes" | "1" => Ok(true),
+                "false" | "off" | "no" | "0" => Ok(false),
+                _ => Err(E::invalid_value(Unexpected::Str(text), &self)),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
+
+/// Deserialize a [`log::Level`] from its name, whatever its case, like `warn` or `INFO`
+///
+/// `warning` is accepted for [`log::Level::Warn`].
+#[cfg(feature = "log")]
+pub fn log_level<'de, D>(deserializer: D) -> Result<log::Level, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    match text.trim() {
+        level if level.eq_ignore_ascii_case("warning") => Ok(log::Level::Warn),
+        level => level.parse().map_err(|_| {
+            de::Error::invalid_value(
+                Unexpected::Str(&text),
+                &"a log level among `error`, `warn`, `info`, `debug` and `trace`",
+            )
+        }),
+    }
+}
+
+/// Deserialize a [`log::LevelFilter`] from its name, whatever its case, like `off` or `INFO`
+///
+/// `warning` is accepted for [`log::LevelFilter::Warn`].
+#[cfg(feature = "log")]
+pub fn log_level_filter<'de, D>(deserializer: D) -> Result<log::LevelFilter, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    match text.trim() {
+        level if level.eq_ignore_ascii_case("warning") => Ok(log::LevelFilter::Warn),
+        level => level.parse().map_err(|_| {
+            de::Error::invalid_value(
+                Unexpected::Str(&text),
+                &"a log level among `off`, `error`, `warn`, `info`, `debug` and `trace`",
+            )
+        }),
+    }
+}
+
+/// Deserialize an absolute [`url::Url`], failing with the reason a string is not one
+///
+/// ```rust
+/// # use config::*;
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Settings {
+///     #[serde(deserialize_with = "config::helpers::url")]
+///     endpoint: url::Url,
+/// }
+///
+/// let error = Config::builder()
+///     .set_default("endpoint", "http://localhost:99999")?
+///     .build()?
+///     .try_deserialize::<Settings>()
+///     .unwrap_err();
+///
+/// assert_eq!(
+///     error.to_string(),
+///     "invalid URL `http://localhost:99999`: invalid port number for key `endpoint`"
+/// );
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[cfg(feature = "url")]
+pub fn url<'de, D>(deserializer: D) -> Result<url::Url, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    url::Url::parse(text.trim())
+        .map_err(|err| de::Error::custom(format_args!("invalid URL `{text}`: {err}")))
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::map::Map;
+use crate::nested_json;
+use crate::notify::leaves;
+use crate::path;
+use crate::redact::{self, RedactionRules};
+use crate::value::Value;
+
+type Load = dyn Fn() -> Arc<Config> + Send + Sync;
+
+/// Requests whose headers are larger than this are rejected
+const MAX_REQUEST: usize = 8 * 1024;
+
+/// How long a connection may take to send its request and receive the response
+const DEADLINE: Duration = Duration::from_secs(5);
+
+/// A minimal HTTP server exposing the current [`Config`] of a live process as JSON, to debug
+/// which values it runs with and where they came from
+///
+/// It answers `GET` requests, one at a time, on these paths:
+///
+/// - `/config`, the merged configuration, with the values selected by the
+///   [redaction rules](Self::redaction) masked
+/// - `/origins`, an object mapping the path of each value, like `servers[0].port`, to the
+///   origin of the value, or `null` if it has none, like a default
+/// - `/sources`, the [`SourceReport`](crate::SourceReport) of the configuration, as an object
+///   with the URIs of the `loaded` and `skipped` files and the `failed` ones along with their
+///   `error`
+/// - `/diff`, an object mapping the path of each value that changed since the server was
+///   created to its value `before` and `after`, the former missing for added values and the
+///   latter for removed ones
+///
+/// The configuration is loaded for each request, e.g. from a [`ConfigCell`](crate::ConfigCell)
+/// swapped on reload. Unless other rules are set, the values whose key contains `password`,
+/// `secret` or `token` are masked, along with those that
+/// [look like secrets](RedactionRules::detect_secret_values). The user information of the URLs
+/// in origins, sources and errors, like `user:password@`, is masked too. The server has no
+/// authentication: bind it to a loopback address, or [handle](Self::handle) the connections of
+/// a Unix domain socket only the operators can reach.
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use config::*;
+/// let cell = Arc::new(ConfigCell::new(Config::builder().build()?));
+/// let server = IntrospectionServer::new({
+///     let cell = cell.clone();
+///     move || cell.load()
+/// })
+/// .redaction(RedactionRules::new().key("*.password").detect_secret_values());
+///
+/// let listener = std::net::TcpListener::bind("127.0.0.1:9898").unwrap();
+/// std::thread::spawn(move || server.serve(listener));
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[derive(Clone)]
+#[must_use]
+pub struct IntrospectionServer {
+    load: Arc<Load>,
+    baseline: Arc<Config>,
+    rules: RedactionRules,
+}
+
+impl IntrospectionServer {
+    /// Serve the [`Config`] returned by `load` when each request is received
+    ///
+    /// `load` is called right away too, for the configuration `/diff` compares against.
+    pub fn new<F>(load: F) -> Self
+    where
+        F: Fn() -> Arc<Config> + Send + Sync + 'static,
+    {
+        Self {
+            baseline: load(),
+            load: Arc::new(load),
+            rules: RedactionRules::new()
+                .key("*password*")
+                .key("*secret*")
+                .key("*token*")
+                .detect_secret_values(),
+        }
+    }
+
+    /// Mask the values selected by `rules` instead of the default ones
+    pub fn redaction(mut self, rules: RedactionRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Answer the connections of `listener` on the current thread, until the process exits
+    ///
+    /// A connection that fails, e.g. because the client hangs up or takes more than 5 seconds
+    /// to send its request and read the response, is dropped without stopping the server.
+    pub fn serve(&self, listener: TcpListener) {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let _ = self.handle(Deadline::new(stream));
+        }
+    }
+
+    /// Read a request from `stream` and write the response, e.g. for the connections of a Unix
+    /// domain socket
+    ///
+    /// # Errors
+    ///
+    /// Fails if reading from or writing to `stream` fails.
+    pub fn handle<S: Read + Write>(&self, mut stream: S) -> io::Result<()> {
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|end| end == b"\r\n\r\n") && request.len() <= MAX_REQUEST {
+            let read = stream.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+
+        let line = request
+            .split(|&byte| byte == b'\n')
+            .next()
+            .unwrap_or_default();
+        let line = String::from_utf8_lossy(line);
+        let mut parts = line.split_whitespace();
+        let (status, body) = match (parts.next(), parts.next()) {
+            _ if request.len() > MAX_REQUEST => error("431 Request Header Fields Too Large"),
+            (Some("GET"), Some(target)) => {
+                let path = target.split(['?', '#']).next().unwrap_or_default();
+                self.respond(path)
+            }
+            (Some(_), Some(_)) => error("405 Method Not Allowed"),
+            _ => error("400 Bad Request"),
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n",
+            body.len()
+        )?;
+        stream.write_all(body.as_bytes())?;
+        stream.flush()
+    }
+
+    /// The status and body of the response to a `GET` request of `path`
+    fn respond(&self, path: &str) -> (&'static str, String) {
+        let config = (self.load)();
+        let body = match path.trim_end_matches('/') {
+            "/config" => {
+                let mut cache = config.cache.clone();
+                redact::redact(&mut cache, &self.rules);
+                cache
+            }
+            "/origins" => {
+                let mut origins = Map::new();
+                path::visit_leaves(&config.cache, &mut |key, value| {
+                    let origin = value
+                        .origin()
+                        .map(|origin| redact::redact_credentials(origin, &self.rules));
+                    origins.insert(key.to_owned(), Value::from(origin));
+                });
+                origins.into()
+            }
+            "/sources" => {
+                let report = config.source_report();
+                let uri = |uri: &str| redact::redact_credentials(uri, &self.rules);
+                let loaded: Vec<_> = report
+                    .loaded()
+                    .iter()
+                    .map(|info| info.uri().map(uri))
+                    .collect();
+                let skipped: Vec<_> = report
+                    .skipped()
+                    .iter()
+                    .map(|skipped| uri(skipped))
+                    .collect();
+                let failed: Vec<_> = report
+                    .failed()
+                    .iter()
+                    .map(|(failed, error)| {
+                        Map::from_iter([
+                            ("uri".to_owned(), uri(failed)),
+                            ("error".to_owned(), uri(error)),
+                        ])
+                    })
+                    .collect();
+                let mut sources = Map::new();
+                sources.insert("loaded".to_owned(), Value::from(loaded));
+                sources.insert("skipped".to_owned(), Value::from(skipped));
+                sources.insert("failed".to_owned(), Value::from(failed));
+                sources.into()
+            }
+            "/diff" => self.diff(&config),
+            _ => return error("404 Not Found"),
+        };
+        ("200 OK", nested_json::render(&body))
+    }
+
+    /// The redacted values of `config` that differ from those of the baseline
+    fn diff(&self, config: &Config) -> Value {
+        let before = leaves(&self.baseline.cache);
+        let after = leaves(&config.cache);
+
+        let mut diff = Map::new();
+        let added = after.keys().filter(|key| !before.contains_key(*key));
+        for key in before.keys().chain(added) {
+            let (old, new) = (before.get(key).copied(), after.get(key).copied());
+            if old.map(|value| &value.kind) == new.map(|value| &value.kind) {
+                continue;
+            }
+
+            let mut change = Map::new();
+            for (name, value) in [("before", old), ("after", new)] {
+                if let Some(value) = value {
+                    let mut value = value.clone();
+                    redact::redact_leaf(key, &mut value, &self.rules);
+                    change.insert(name.to_owned(), value);
+                }
+            }
+            diff.insert(key.clone(), Value::from(change));
+        }
+        diff.into()
+    }
+}
+
+impl fmt::Debug for IntrospectionServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntrospectionServer")
+            .field("rules", &self.rules)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A response with `status`, whose body names the error
+fn error(status: &'static str) -> (&'static str, String) {
+    let message = status
+        .split_once(' ')
+        .map_or(status, |(_, message)| message);
+    let body = Map::from_iter([("error".to_owned(), message.to_lowercase())]);
+    (status, nested_json::render(&body.into()))
+}
+
+/// A TCP connection failing with [`io::ErrorKind::TimedOut`] once the [`DEADLINE`] of its
+/// request has passed
+struct Deadline {
+    stream: TcpStream,
+    end: Instant,
+}
+
+impl Deadline {
+    fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            end: Instant::now() + DEADLINE,
+        }
+    }
+
+    /// The time left before the deadline, as a socket timeout
+    fn remaining(&self) -> io::Result<Option<Duration>> {
+        match self.end.checked_duration_since(Instant::now()) {
+            Some(remaining) if !remaining.is_zero() => Ok(Some(remaining)),
+            _ => Err(io::ErrorKind::TimedOut.into()),
+        }
+    }
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.set_read_timeout(self.remaining()?)?;
+        self.stream.read(buf)
+    }
+}
+
+impl Write for Deadline {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.set_write_timeout(self.remaining()?)?;
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
@@ -4,9 +4,15 @@
 //! configuration from a variety of sources:
 //!
 //!  - [Environment variables][Environment]
+//!  - [`.env` files][DotenvStack], layered by mode like dotenv tooling does
 //!  - [String literals][FileSourceString] in [well-known formats][FileFormat]
-//!  - Another [`Config`] instance
-//!  - [Files][FileSourceFile] in [well known formats][FileFormat] and custom ones defined with [`Format`] trait
+//!  - [Streams][FileSourceReader] like the standard input, in [well-known formats][FileFormat]
+//!  - [Sidecar processes][Sidecar], through shared memory or a Unix domain socket
+//!  - Another [`Config`] instance, see [composing configurations](Config#composing-configurations)
+//!  - [Files][FileSourceFile] in [well known formats][FileFormat] and custom ones defined with [`Format`] trait,
+//!    see [`format_support`]
+//!  - [Directories][Directory] of such files, like `conf.d`
+//!  - [Files looked up in a directory and its parents][HierarchicalFile], like `.cargo/config.toml`
 //!  - Manual, programmatic [overrides][ConfigBuilder::set_override]
 //!
 //! Additionally, [`Config`] supports:
@@ -14,6 +20,24 @@
 //!  - Live watching and re-reading of configuration files
 //!  - Deep access into the merged configuration via a path syntax
 //!  - Deserialization via `serde` of the configuration or any subset defined via a path
+//!  - [Feature flags][FeatureFlags] with percentage rollouts
+//!
+//! Access to the file system and to environment variables is provided by the default `fs` and
+//! `env` features. Disabling them leaves a core suitable for targets like
+//! `wasm32-unknown-unknown`, where configuration arrives as strings.
+//!
+//! With the `tracing` feature, building a [`Config`] emits [`tracing`](https://docs.rs/tracing)
+//! events telling which sources were collected, which environment variables were skipped and
+//! where each key was set from, e.g. to find why a variable did not apply. Sources are
+//! reported at the `debug` level, and keys and variables at the `trace` level.
+//!
+//! With the `perf-stats` feature, [`PerfStats`] counts the configurations built, the keys looked
+//! up and the values deserialized across the process, to profile an application's use of the
+//! crate. The benchmarks of the crate itself are run with `cargo bench`.
+//!
+//! With the `introspect` feature, an [`IntrospectionServer`] serves the redacted configuration
+//! of a live process and the origins of its values as JSON over HTTP, without pulling in an
+//! HTTP stack.
 //!
 //! # Example
 //!
@@ -30,35 +54,109 @@
 #![warn(clippy::print_stderr)]
 #![warn(clippy::print_stdout)]
 
+mod adapter;
 pub mod builder;
+mod cell;
 mod config;
+mod config_struct;
 mod de;
+#[cfg(feature = "fs")]
+mod dir;
+#[cfg(feature = "fs")]
+mod discovery;
+#[cfg(all(feature = "fs", feature = "env", feature = "dotenv"))]
+mod dotenv;
+#[cfg(feature = "env")]
 mod env;
 mod error;
+#[cfg(feature = "env")]
+mod expand;
 mod file;
+#[cfg(feature = "fingerprint")]
+mod fingerprint;
+mod flags;
 mod format;
+pub mod format_support;
+#[cfg(feature = "helpers")]
+pub mod helpers;
+#[cfg(feature = "introspect")]
+mod introspect;
 mod map;
+mod nested_json;
+mod normalize;
+mod notify;
 mod path;
+mod perf;
+mod redact;
+mod render;
 mod ser;
+#[cfg(all(feature = "fs", feature = "json"))]
+mod sidecar;
 mod source;
+mod stats;
+mod tenant;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod time;
 mod value;
 
 // Re-export
+#[cfg(feature = "derive")]
+pub use config_derive::ConfigStruct;
 #[cfg(feature = "convert-case")]
 pub use convert_case::Case;
 
+#[cfg(feature = "env")]
+pub use crate::adapter::ExpandEnvVars;
+pub use crate::adapter::{FilterKeys, MapKeys, Prefixed};
 pub use crate::builder::ConfigBuilder;
-pub use crate::config::Config;
-pub use crate::env::Environment;
-pub use crate::error::ConfigError;
+#[cfg(feature = "tokio")]
+pub use crate::cell::AsyncConfigCell;
+pub use crate::cell::ConfigCell;
+pub use crate::config::{Config, SharedConfig};
+#[doc(hidden)]
+pub use crate::config_struct::__private;
+pub use crate::config_struct::ConfigStruct;
+#[cfg(feature = "fs")]
+pub use crate::dir::Directory;
+#[cfg(feature = "fs")]
+pub use crate::discovery::{Discovery, HierarchicalFile};
+#[cfg(all(feature = "fs", feature = "env", feature = "dotenv"))]
+pub use crate::dotenv::DotenvStack;
+#[cfg(feature = "env")]
+pub use crate::env::{Environment, KeyCollision};
+pub use crate::error::{ConfigError, ErrorKind};
+#[cfg(feature = "env")]
+pub use crate::expand::ExpandedPath;
+#[cfg(feature = "fs")]
+pub use crate::file::FileSourceFile;
 pub use crate::file::source::FileSource;
-pub use crate::file::{File, FileFormat, FileSourceFile, FileSourceString, FileStoredFormat};
+pub use crate::file::{
+    Decryptor, File, FileFormat, FileSourceReader, FileSourceString, FileStoredFormat,
+    ParseOptions, YamlDocuments,
+};
+pub use crate::flags::FeatureFlags;
 pub use crate::format::Format;
+#[cfg(feature = "introspect")]
+pub use crate::introspect::IntrospectionServer;
 pub use crate::map::Map;
+pub use crate::notify::Subscriptions;
+#[cfg(feature = "perf-stats")]
+pub use crate::perf::PerfStats;
+pub use crate::redact::RedactionRules;
+pub use crate::render::RenderFormat;
+#[cfg(all(feature = "fs", feature = "json"))]
+pub use crate::sidecar::Sidecar;
 #[cfg(feature = "async")]
 pub use crate::source::AsyncSource;
+#[cfg(feature = "tokio")]
+pub use crate::source::BlockingSource;
 pub use crate::source::Source;
-pub use crate::value::{Value, ValueKind};
+pub use crate::source::SourceInfo;
+pub use crate::source::SourceReport;
+pub use crate::stats::ConfigStats;
+pub use crate::tenant::TenantConfigFactory;
+pub use crate::value::{Position, Span, Value, ValueKind};
 
 #[doc = include_str!("../README.md")]
 #[cfg(doctest)]
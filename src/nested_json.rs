@@ -0,0 +1,334 @@
+use std::fmt::Write as _;
+
+use crate::error::{ConfigError, Result};
+use crate::map::Map;
+use crate::value::{Value, ValueKind};
+
+/// Arrays and objects may not be nested deeper than this, guarding against a stack overflow
+const MAX_DEPTH: usize = 128;
+
+/// Render `value` as compact JSON
+///
+/// Nil is `null`, and floats that JSON can't represent, infinities and NaN, are `null` too.
+/// Keys are sorted, unless the `preserve_order` feature keeps tables in insertion order.
+pub(crate) fn render(value: &Value) -> String {
+    let mut output = String::new();
+    write(&mut output, value);
+    output
+}
+
+/// Append `value` to `output` as compact JSON
+fn write(output: &mut String, value: &Value) {
+    match value.kind {
+        ValueKind::Nil => output.push_str("null"),
+        ValueKind::Boolean(value) => {
+            let _ = write!(output, "{value}");
+        }
+        ValueKind::I64(value) => {
+            let _ = write!(output, "{value}");
+        }
+        ValueKind::I128(value) => {
+            let _ = write!(output, "{value}");
+        }
+        ValueKind::U64(value) => {
+            let _ = write!(output, "{value}");
+        }
+        ValueKind::U128(value) => {
+            let _ = write!(output, "{value}");
+        }
+        ValueKind::Float(value) if value.is_finite() => {
+            let _ = write!(output, "{value:?}");
+        }
+        ValueKind::Float(_) => output.push_str("null"),
+        ValueKind::String(ref value) => render_string(output, value),
+        ValueKind::Table(ref table) => {
+            let mut entries: Vec<_> = table.iter().collect();
+            if !cfg!(feature = "preserve_order") {
+                entries.sort_by_key(|(key, _)| *key);
+            }
+            output.push('{');
+            for (index, (key, child)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                render_string(output, key);
+                output.push(':');
+                write(output, child);
+            }
+            output.push('}');
+        }
+        ValueKind::Array(ref array) => {
+            output.push('[');
+            for (index, child) in array.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write(output, child);
+            }
+            output.push(']');
+        }
+    }
+}
+
+fn render_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Parse the JSON document `text` into a value without origin
+pub(crate) fn parse(text: &str) -> Result<Value> {
+    let mut parser = Parser { text, offset: 0 };
+    let value = parser.value(0)?;
+    parser.whitespace();
+    if parser.offset < text.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.offset..]
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    fn whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    /// Skip `token` if the text continues with it
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.offset += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        self.whitespace();
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.error(&format!("expected `{token}`"))),
+        }
+    }
+
+    fn error(&self, reason: &str) -> ConfigError {
+        let before = &self.text[..self.offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |index| index + 1)..]
+            .chars()
+            .count()
+            + 1;
+        ConfigError::Message(format!(
+            "invalid JSON at line {line} column {column}: {reason}"
+        ))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value> {
+        self.whitespace();
+        let kind = match self.peek() {
+            Some(b'{' | b'[') if depth == MAX_DEPTH => {
+                return Err(self.error(&format!("nested more than {MAX_DEPTH} levels deep")));
+            }
+            Some(b'{') => {
+                self.offset += 1;
+                ValueKind::Table(self.object(depth)?)
+            }
+            Some(b'[') => {
+                self.offset += 1;
+                ValueKind::Array(self.array(depth)?)
+            }
+            Some(b'"') => ValueKind::String(self.string()?),
+            Some(b'-' | b'0'..=b'9') => self.number()?,
+            _ if self.eat("null") => ValueKind::Nil,
+            _ if self.eat("true") => ValueKind::Boolean(true),
+            _ if self.eat("false") => ValueKind::Boolean(false),
+            None => return Err(self.error("unexpected end of input")),
+            Some(_) => return Err(self.error("expected a value")),
+        };
+        Ok(Value::new(None, kind))
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Map<String, Value>> {
+        let mut table = Map::new();
+        self.whitespace();
+        if self.eat("}") {
+            return Ok(table);
+        }
+        loop {
+            self.whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key = self.string()?;
+            self.expect(":")?;
+            let value = self.value(depth + 1)?;
+            table.insert(key, value);
+
+            self.whitespace();
+            if self.eat("}") {
+                return Ok(table);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Vec<Value>> {
+        let mut array = Vec::new();
+        self.whitespace();
+        if self.eat("]") {
+            return Ok(array);
+        }
+        loop {
+            array.push(self.value(depth + 1)?);
+
+            self.whitespace();
+            if self.eat("]") {
+                return Ok(array);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    /// Parse the string starting at the current `"`
+    fn string(&mut self) -> Result<String> {
+        self.offset += 1;
+        let mut string = String::new();
+        loop {
+            let rest = self.rest();
+            let Some(end) = rest.find(['"', '\\']) else {
+                self.offset = self.text.len();
+                return Err(self.error("unterminated string"));
+            };
+            if let Some(control) = rest[..end].find(|c: char| c < ' ') {
+                self.offset += control;
+                return Err(self.error("control character in string"));
+            }
+            string.push_str(&rest[..end]);
+            self.offset += end + 1;
+            if rest.as_bytes()[end] == b'"' {
+                return Ok(string);
+            }
+
+            let escaped = match self.peek() {
+                Some(b'"') => '"',
+                Some(b'\\') => '\\',
+                Some(b'/') => '/',
+                Some(b'b') => '\u{8}',
+                Some(b'f') => '\u{c}',
+                Some(b'n') => '\n',
+                Some(b'r') => '\r',
+                Some(b't') => '\t',
+                Some(b'u') => {
+                    self.offset += 1;
+                    let unit = self.hex()?;
+                    let code = if (0xD800..0xDC00).contains(&unit) && self.eat("\\u") {
+                        let low = self.hex()?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            return Err(self.error("invalid surrogate pair"));
+                        }
+                        0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                    } else {
+                        unit
+                    };
+                    string.push(
+                        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))?,
+                    );
+                    continue;
+                }
+                _ => return Err(self.error("invalid escape")),
+            };
+            string.push(escaped);
+            self.offset += 1;
+        }
+    }
+
+    /// Parse the 4 hexadecimal digits of a `\u` escape
+    fn hex(&mut self) -> Result<u32> {
+        let digits = self.rest().get(..4).unwrap_or_default();
+        if digits.len() < 4 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(self.error("invalid unicode escape"));
+        }
+        self.offset += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("digits are hexadecimal"))
+    }
+
+    /// Parse a number, as the narrowest integer that holds it, or as a float
+    fn number(&mut self) -> Result<ValueKind> {
+        let rest = self.rest();
+        let digits =
+            |text: &str| text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+        let mut len = usize::from(rest.starts_with('-'));
+        let int_len = digits(&rest[len..]);
+        if int_len == 0 || int_len > 1 && rest[len..].starts_with('0') {
+            return Err(self.error("invalid number"));
+        }
+        len += int_len;
+        let mut integer = true;
+        if rest[len..].starts_with('.') {
+            let fraction = digits(&rest[len + 1..]);
+            if fraction == 0 {
+                return Err(self.error("invalid number"));
+            }
+            len += 1 + fraction;
+            integer = false;
+        }
+        if rest[len..].starts_with(['e', 'E']) {
+            len += 1;
+            if rest[len..].starts_with(['+', '-']) {
+                len += 1;
+            }
+            let exponent = digits(&rest[len..]);
+            if exponent == 0 {
+                return Err(self.error("invalid number"));
+            }
+            len += exponent;
+            integer = false;
+        }
+
+        let number = &rest[..len];
+        self.offset += len;
+        if integer {
+            if let Ok(value) = number.parse::<i64>() {
+                return Ok(ValueKind::I64(value));
+            }
+            if let Ok(value) = number.parse::<u64>() {
+                return Ok(ValueKind::U64(value));
+            }
+            if let Ok(value) = number.parse::<i128>() {
+                return Ok(ValueKind::I128(value));
+            }
+            if let Ok(value) = number.parse::<u128>() {
+                return Ok(ValueKind::U128(value));
+            }
+        }
+        number
+            .parse()
+            .map(ValueKind::Float)
+            .map_err(|_| self.error("invalid number"))
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::map::Map;
+use crate::path::Expression;
+use crate::value::{Value, ValueKind};
+
+/// Rewrites every table key, e.g. to compare keys case-insensitively
+#[derive(Clone)]
+pub(crate) struct KeyNormalizer(Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl KeyNormalizer {
+    pub(crate) fn new<F>(normalize: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        Self(Arc::new(normalize))
+    }
+
+    pub(crate) fn key(&self, key: &str) -> String {
+        (self.0)(key)
+    }
+
+    /// Normalize all keys of the tree rooted at `value`.
+    ///
+    /// Tables whose keys collide once normalized are merged as if they were set in turn.
+    pub(crate) fn value(&self, value: &mut Value) {
+        match value.kind {
+            ValueKind::Table(ref mut table) => {
+                let mut normalized: Value = Map::<String, Value>::new().into();
+                for (key, mut child) in std::mem::take(table) {
+                    self.value(&mut child);
+                    Expression::root(self.key(&key)).set(&mut normalized, child);
+                }
+                if let ValueKind::Table(normalized) = normalized.kind {
+                    *table = normalized;
+                }
+            }
+            ValueKind::Array(ref mut array) => array.iter_mut().for_each(|child| self.value(child)),
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Debug for KeyNormalizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("KeyNormalizer")
+    }
+}
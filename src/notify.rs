@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::config::Config;
+use crate::path;
+use crate::redact::glob_match;
+use crate::value::Value;
+
+type Callback = Box<dyn Fn(&str, Option<&Value>, Option<&Value>) + Send + Sync>;
+
+/// Callbacks notified about the keys that changed between successive [`Config`]s
+///
+/// Each callback is registered for a glob pattern and called with the changed key, its previous
+/// value and its new value, the former being `None` for added keys and the latter for removed
+/// ones. Keys are rendered as paths like `servers[0].port`, where `*` matches any sequence of
+/// characters, including `.`, and `?` matches a single character.
+///
+/// Feed each reloaded [`Config`], e.g. yielded by
+/// [`ConfigBuilder::watch`](crate::ConfigBuilder::watch), to [`update`](Self::update).
+///
+/// ```rust
+/// # use config::*;
+/// # use std::sync::{Arc, Mutex};
+/// let changed = Arc::new(Mutex::new(Vec::new()));
+/// let mut subscriptions = Subscriptions::new();
+/// let log = changed.clone();
+/// subscriptions.subscribe("server.*", move |key, _old, new| {
+///     log.lock().unwrap().push(format!("{key} = {}", new.unwrap()));
+/// });
+///
+/// let before = Config::builder().set_default("server.port", 80)?.build()?;
+/// let after = Config::builder()
+///     .set_default("server.port", 8080)?
+///     .set_default("debug", true)?
+///     .build()?;
+///
+/// subscriptions.update(&before);
+/// subscriptions.update(&after);
+/// assert_eq!(*changed.lock().unwrap(), ["server.port = 8080"]);
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[derive(Default)]
+pub struct Subscriptions {
+    subscribers: Vec<(String, Callback)>,
+    last: Option<Config>,
+}
+
+impl Subscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `callback` for each changed key matching the glob `pattern`
+    pub fn subscribe<F>(&mut self, pattern: &str, callback: F) -> &mut Self
+    where
+        F: Fn(&str, Option<&Value>, Option<&Value>) + Send + Sync + 'static,
+    {
+        self.subscribers.push((pattern.into(), Box::new(callback)));
+        self
+    }
+
+    /// Notify subscribers of the changes from the previously updated [`Config`] to `config`
+    ///
+    /// The first update only records `config`, without notifying anything.
+    pub fn update(&mut self, config: &Config) {
+        if let Some(ref last) = self.last {
+            self.notify(last, config);
+        }
+        self.last = Some(config.clone());
+    }
+
+    /// Notify subscribers of the changes from `old` to `new`
+    ///
+    /// Keys are notified in sorted order. Values are compared regardless of their origin.
+    pub fn notify(&self, old: &Config, new: &Config) {
+        let old = leaves(&old.cache);
+        let new = leaves(&new.cache);
+
+        let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            let (old, new) = (old.get(key).copied(), new.get(key).copied());
+            if old.map(|value| &value.kind) == new.map(|value| &value.kind) {
+                continue;
+            }
+
+            for (pattern, callback) in &self.subscribers {
+                if glob_match(pattern, key) {
+                    callback(key, old, new);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Subscriptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let patterns: Vec<&str> = self.subscribers.iter().map(|(p, _)| p.as_str()).collect();
+        f.debug_struct("Subscriptions")
+            .field("patterns", &patterns)
+            .finish_non_exhaustive()
+    }
+}
+
+pub(crate) fn leaves(value: &Value) -> BTreeMap<String, &Value> {
+    let mut leaves = BTreeMap::new();
+    path::visit_leaves(value, &mut |key, leaf| {
+        leaves.insert(key.to_owned(), leaf);
+    });
+    leaves
+}
@@ -1,7 +1,8 @@
 use std::str::FromStr;
 
-use crate::error::{ConfigError, Result};
+use crate::error::{ConfigError, Result, similar_keys};
 use crate::map::Map;
+use crate::normalize::KeyNormalizer;
 use crate::value::{Value, ValueKind};
 
 mod parser;
@@ -19,6 +20,54 @@ impl Expression {
             postfix: Vec::new(),
         }
     }
+
+    /// The path to `key` in the table at this path
+    pub(crate) fn child(mut self, key: String) -> Self {
+        self.postfix.push(Postfix::Key(key));
+        self
+    }
+
+    /// Normalize each key of the path
+    pub(crate) fn normalize(mut self, normalizer: &KeyNormalizer) -> Self {
+        self.root = normalizer.key(&self.root);
+        for postfix in &mut self.postfix {
+            if let Postfix::Key(key) = postfix {
+                *key = normalizer.key(key);
+            }
+        }
+        self
+    }
+
+    /// Turn the all-digit keys after the first one into array indices, e.g. `list.0.name` into
+    /// `list[0].name`
+    pub(crate) fn numeric_keys_as_indices(mut self) -> Self {
+        for postfix in &mut self.postfix {
+            let index = match postfix {
+                Postfix::Key(key) if !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit()) => {
+                    key.parse().ok()
+                }
+                _ => None,
+            };
+            if let Some(index) = index {
+                *postfix = Postfix::Index(index);
+            }
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut path = String::new();
+        let root = Postfix::Key(self.root.clone());
+        render(
+            &mut path,
+            &std::iter::once(&root)
+                .chain(&self.postfix)
+                .collect::<Vec<_>>(),
+        );
+        f.write_str(&path)
+    }
 }
 
 impl FromStr for Expression {
@@ -35,6 +84,12 @@ impl FromStr for Expression {
 enum Postfix {
     Key(String),
     Index(isize),
+    /// `[+]`, a new element at the end of the array, numbered by [`insert`] to tell apart
+    /// successive appends to the same array
+    Append(usize),
+    /// `[++]`, the elements of an array value at the end of the array, or a new element like
+    /// [`Append`](Self::Append) for other values, numbered the same way
+    Extend(usize),
 }
 
 #[derive(Debug)]
@@ -54,6 +109,105 @@ impl std::fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
+/// Render path segments back into their textual form, appending them to `path`
+fn render(path: &mut String, segments: &[&Postfix]) {
+    for segment in segments {
+        match segment {
+            Postfix::Key(key) if path.is_empty() => path.push_str(key),
+            Postfix::Key(key) => {
+                path.push('.');
+                path.push_str(key);
+            }
+            Postfix::Index(index) => path.push_str(&format!("[{index}]")),
+            Postfix::Append(_) => path.push_str("[+]"),
+            Postfix::Extend(_) => path.push_str("[++]"),
+        }
+    }
+}
+
+/// Call `visit` with the path of each leaf of `value`, visiting table keys in sorted order
+///
+/// Scalars, empty tables and empty arrays are leaves.
+pub(crate) fn visit_leaves<'a>(value: &'a Value, visit: &mut dyn FnMut(&str, &'a Value)) {
+    fn walk<'a>(path: &mut String, value: &'a Value, visit: &mut dyn FnMut(&str, &'a Value)) {
+        let len = path.len();
+        match value.kind {
+            ValueKind::Table(ref table) if !table.is_empty() => {
+                let mut entries: Vec<_> = table.iter().collect();
+                entries.sort_by_key(|(key, _)| *key);
+                for (key, child) in entries {
+                    render(path, &[&Postfix::Key(key.clone())]);
+                    walk(path, child, visit);
+                    path.truncate(len);
+                }
+            }
+            ValueKind::Array(ref array) if !array.is_empty() => {
+                for (index, child) in array.iter().enumerate() {
+                    render(path, &[&Postfix::Index(index as isize)]);
+                    walk(path, child, visit);
+                    path.truncate(len);
+                }
+            }
+            _ => visit(path, value),
+        }
+    }
+
+    walk(&mut String::new(), value, visit);
+}
+
+/// Call `visit` with the path of each leaf of `value`, like [`visit_leaves`], letting it modify
+/// the leaf, in no particular order
+#[cfg(feature = "introspect")]
+pub(crate) fn visit_leaves_mut(value: &mut Value, visit: &mut dyn FnMut(&str, &mut Value)) {
+    fn walk(path: &mut String, value: &mut Value, visit: &mut dyn FnMut(&str, &mut Value)) {
+        let len = path.len();
+        match value.kind {
+            ValueKind::Table(ref mut table) if !table.is_empty() => {
+                for (key, child) in table.iter_mut() {
+                    render(path, &[&Postfix::Key(key.clone())]);
+                    walk(path, child, visit);
+                    path.truncate(len);
+                }
+            }
+            ValueKind::Array(ref mut array) if !array.is_empty() => {
+                for (index, child) in array.iter_mut().enumerate() {
+                    render(path, &[&Postfix::Index(index as isize)]);
+                    walk(path, child, visit);
+                    path.truncate(len);
+                }
+            }
+            _ => visit(path, value),
+        }
+    }
+
+    walk(&mut String::new(), value, visit);
+}
+
+/// Insert `value` at `key` in `values`, keeping each append, like `plugins[+]`, apart from the
+/// previous ones at the same key
+pub(crate) fn insert<V>(values: &mut Map<Expression, V>, mut key: Expression, value: V) {
+    let ordinal = values.len();
+    for postfix in &mut key.postfix {
+        if let Postfix::Append(ref mut append) | Postfix::Extend(ref mut append) = *postfix {
+            *append = ordinal;
+        }
+    }
+    values.insert(key, value);
+}
+
+/// The entries of `values` in the order to set them, with appends last in the order they were
+/// [inserted](insert)
+pub(crate) fn ordered<V>(values: &Map<Expression, V>) -> Vec<(&Expression, &V)> {
+    let mut entries: Vec<_> = values.iter().collect();
+    entries.sort_by_key(|(key, _)| {
+        key.postfix.iter().find_map(|postfix| match *postfix {
+            Postfix::Append(ordinal) | Postfix::Extend(ordinal) => Some(ordinal),
+            _ => None,
+        })
+    });
+    entries
+}
+
 /// Convert a relative index into an absolute index
 fn abs_index(index: isize, len: usize) -> Result<usize, usize> {
     if index >= 0 {
@@ -86,11 +240,105 @@ impl Expression {
                     let index = abs_index(*rel_index, array.len()).ok()?;
                     child = array.get(index)?;
                 }
+                Postfix::Append(_) | Postfix::Extend(_) => return None,
             }
         }
         Some(child)
     }
 
+    pub(crate) fn get_mut(self, root: &mut Value) -> Option<&mut Value> {
+        let ValueKind::Table(map) = &mut root.kind else {
+            return None;
+        };
+        let mut child = map.get_mut(&self.root)?;
+        for postfix in &self.postfix {
+            match postfix {
+                Postfix::Key(key) => {
+                    let ValueKind::Table(map) = &mut child.kind else {
+                        return None;
+                    };
+                    child = map.get_mut(key)?;
+                }
+                Postfix::Index(rel_index) => {
+                    let ValueKind::Array(array) = &mut child.kind else {
+                        return None;
+                    };
+                    let index = abs_index(*rel_index, array.len()).ok()?;
+                    child = array.get_mut(index)?;
+                }
+                Postfix::Append(_) | Postfix::Extend(_) => return None,
+            }
+        }
+        Some(child)
+    }
+
+    /// Whether setting this path in `root` would not replace any value but nil.
+    pub(crate) fn is_vacant(&self, root: &Value) -> bool {
+        let root_key = Postfix::Key(self.root.clone());
+        let mut current = root;
+        for segment in std::iter::once(&root_key).chain(&self.postfix) {
+            let child = match (segment, &current.kind) {
+                (_, ValueKind::Nil)
+                | (Postfix::Append(_) | Postfix::Extend(_), ValueKind::Array(_)) => return true,
+                (Postfix::Key(key), ValueKind::Table(map)) => map.get(key),
+                (Postfix::Index(rel_index), ValueKind::Array(array)) => {
+                    abs_index(*rel_index, array.len())
+                        .ok()
+                        .and_then(|index| array.get(index))
+                }
+                _ => return false,
+            };
+            match child {
+                Some(child) => current = child,
+                None => return true,
+            }
+        }
+        current.kind == ValueKind::Nil
+    }
+
+    /// Similarly spelled paths that exist in `root`, closest first.
+    ///
+    /// Only the first segment missing from `root` is corrected.
+    pub(crate) fn suggest(&self, root: &Value) -> Vec<String> {
+        let root_key = Postfix::Key(self.root.clone());
+        let segments: Vec<&Postfix> = std::iter::once(&root_key).chain(&self.postfix).collect();
+
+        let mut current = root;
+        for (i, segment) in segments.iter().enumerate() {
+            match (segment, &current.kind) {
+                (Postfix::Key(key), ValueKind::Table(map)) => match map.get(key) {
+                    Some(child) => current = child,
+                    None => {
+                        let mut prefix = String::new();
+                        render(&mut prefix, &segments[..i]);
+
+                        return similar_keys(key, map.keys().map(String::as_str))
+                            .into_iter()
+                            .map(|candidate| {
+                                let mut path = prefix.clone();
+                                render(&mut path, &[&Postfix::Key(candidate)]);
+                                render(&mut path, &segments[i + 1..]);
+                                path
+                            })
+                            .collect();
+                    }
+                },
+                (Postfix::Index(rel_index), ValueKind::Array(array)) => {
+                    match abs_index(*rel_index, array.len())
+                        .ok()
+                        .and_then(|index| array.get(index))
+                    {
+                        Some(child) => current = child,
+                        None => return Vec::new(),
+                    }
+                }
+                _ => return Vec::new(),
+            }
+        }
+
+        Vec::new()
+    }
+
     pub(crate) fn get_mut_forcibly<'a>(&self, root: &'a mut Value) -> &'a mut Value {
         if !matches!(root.kind, ValueKind::Table(_)) {
             *root = Map::<String, Value>::new().into();
@@ -141,12 +389,42 @@ impl Expression {
 
                     child = &mut array[uindex];
                 }
+                Postfix::Append(_) | Postfix::Extend(_) => {
+                    if !matches!(child.kind, ValueKind::Array(_)) {
+                        *child = Vec::<Value>::new().into();
+                    }
+                    let ValueKind::Array(ref mut array) = child.kind else {
+                        unreachable!()
+                    };
+
+                    array.push(Value::new(None, ValueKind::Nil));
+                    child = array.last_mut().expect("an element was pushed");
+                }
             }
         }
         child
     }
 
     pub(crate) fn set(&self, root: &mut Value, value: Value) {
+        if let (Some((Postfix::Extend(_), postfix)), ValueKind::Array(_)) =
+            (self.postfix.split_last(), &value.kind)
+        {
+            let array = Self {
+                root: self.root.clone(),
+                postfix: postfix.to_vec(),
+            };
+            let parent = array.get_mut_forcibly(root);
+            if !matches!(parent.kind, ValueKind::Array(_)) {
+                *parent = Vec::<Value>::new().into();
+            }
+            if let (ValueKind::Array(array), ValueKind::Array(elements)) =
+                (&mut parent.kind, value.kind)
+            {
+                array.extend(elements);
+            }
+            return;
+        }
+
         let parent = self.get_mut_forcibly(root);
         match value.kind {
             ValueKind::Table(ref incoming_map) => {
@@ -154,6 +432,9 @@ impl Expression {
                 // table
                 if !matches!(parent.kind, ValueKind::Table(_)) {
                     *parent = Map::<String, Value>::new().into();
+                    if let Some(span) = value.span() {
+                        parent.set_span(span);
+                    }
                 }
 
                 // Continue the deep merge
@@ -2,6 +2,7 @@ use std::str::FromStr;
 
 use winnow::ascii::digit1;
 use winnow::ascii::space0;
+use winnow::combinator::alt;
 use winnow::combinator::cut_err;
 use winnow::combinator::dispatch;
 use winnow::combinator::fail;
@@ -34,7 +35,7 @@ fn postfix(i: &mut &str) -> ModalResult<Postfix> {
     dispatch! {any;
         '[' => cut_err(
             seq!(
-                integer.map(Postfix::Index),
+                alt((extend, append, integer.map(Postfix::Index))),
                 _: ']'.context(StrContext::Expected(StrContextValue::CharLiteral(']'))),
             )
                 .map(|(i,)| i)
@@ -63,6 +64,18 @@ fn ident(i: &mut &str) -> ModalResult<String> {
         .parse_next(i)
 }
 
+fn extend(i: &mut &str) -> ModalResult<Postfix> {
+    (space0, "++", space0)
+        .value(Postfix::Extend(0))
+        .parse_next(i)
+}
+
+fn append(i: &mut &str) -> ModalResult<Postfix> {
+    (space0, '+', space0)
+        .value(Postfix::Append(0))
+        .parse_next(i)
+}
+
 fn integer(i: &mut &str) -> ModalResult<isize> {
     seq!(
         _: space0,
@@ -185,6 +198,44 @@ Expression {
     ],
 }
 
+"#]]
+        );
+    }
+
+    #[test]
+    fn test_subscript_append() {
+        let parsed: Expression = from_str("abcd[+]").unwrap();
+        assert_data_eq!(
+            parsed.to_debug(),
+            str![[r#"
+Expression {
+    root: "abcd",
+    postfix: [
+        Append(
+            0,
+        ),
+    ],
+}
+
+"#]]
+        );
+    }
+
+    #[test]
+    fn test_subscript_extend() {
+        let parsed: Expression = from_str("abcd[++]").unwrap();
+        assert_data_eq!(
+            parsed.to_debug(),
+            str![[r#"
+Expression {
+    root: "abcd",
+    postfix: [
+        Extend(
+            0,
+        ),
+    ],
+}
+
 "#]]
         );
     }
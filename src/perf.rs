@@ -0,0 +1,136 @@
+#[cfg(feature = "perf-stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "perf-stats")]
+use std::time::{Duration, Instant};
+
+/// A counter of [`PerfStats`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Counter {
+    Builds,
+    Sources,
+    CollectNanos,
+    Keys,
+    Lookups,
+    Deserializations,
+}
+
+#[cfg(feature = "perf-stats")]
+static COUNTERS: [AtomicU64; 6] = [const { AtomicU64::new(0) }; 6];
+
+/// Add `n` to `counter`, doing nothing without the `perf-stats` feature
+#[inline]
+#[cfg_attr(not(feature = "perf-stats"), allow(unused_variables))]
+pub(crate) fn add(counter: Counter, n: u64) {
+    #[cfg(feature = "perf-stats")]
+    COUNTERS[counter as usize].fetch_add(n, Ordering::Relaxed);
+}
+
+/// Run `f`, adding the nanoseconds it took to `counter`
+#[inline]
+#[cfg_attr(not(feature = "perf-stats"), allow(unused_variables))]
+pub(crate) fn timed<R>(counter: Counter, f: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "perf-stats")]
+    {
+        let start = Instant::now();
+        let result = f();
+        add(counter, start.elapsed().as_nanos() as u64);
+        result
+    }
+    #[cfg(not(feature = "perf-stats"))]
+    f()
+}
+
+/// Counters of the work done by this crate in the process, to profile how an application uses
+/// it, e.g. to find a loop reading keys where the configuration could be deserialized once
+///
+/// Counters are shared by all threads and only grow, so measure a piece of code with the
+/// [difference](Self::since) of the snapshots taken around it.
+///
+/// ```rust
+/// # use config::*;
+/// let before = PerfStats::snapshot();
+/// let config = Config::builder()
+///     .set_default("port", 8080)?
+///     .set_override("host", "localhost")?
+///     .build()?;
+/// config.get_int("port")?;
+///
+/// let stats = PerfStats::snapshot().since(&before);
+/// assert!(stats.builds() >= 1);
+/// assert!(stats.lookups() >= 1);
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[cfg(feature = "perf-stats")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PerfStats {
+    builds: u64,
+    sources: u64,
+    collect_time: Duration,
+    keys: u64,
+    lookups: u64,
+    deserializations: u64,
+}
+
+#[cfg(feature = "perf-stats")]
+impl PerfStats {
+    /// The counters of the process so far
+    pub fn snapshot() -> Self {
+        let get = |counter: Counter| COUNTERS[counter as usize].load(Ordering::Relaxed);
+        Self {
+            builds: get(Counter::Builds),
+            sources: get(Counter::Sources),
+            collect_time: Duration::from_nanos(get(Counter::CollectNanos)),
+            keys: get(Counter::Keys),
+            lookups: get(Counter::Lookups),
+            deserializations: get(Counter::Deserializations),
+        }
+    }
+
+    /// The work done between `earlier` and this snapshot
+    #[must_use]
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            builds: self.builds.saturating_sub(earlier.builds),
+            sources: self.sources.saturating_sub(earlier.sources),
+            collect_time: self.collect_time.saturating_sub(earlier.collect_time),
+            keys: self.keys.saturating_sub(earlier.keys),
+            lookups: self.lookups.saturating_sub(earlier.lookups),
+            deserializations: self
+                .deserializations
+                .saturating_sub(earlier.deserializations),
+        }
+    }
+
+    /// Number of configurations built or refreshed
+    pub fn builds(&self) -> u64 {
+        self.builds
+    }
+
+    /// Number of sources collected while building
+    pub fn sources(&self) -> u64 {
+        self.sources
+    }
+
+    /// Time spent collecting synchronous sources, summed across threads when they are collected
+    /// in parallel
+    pub fn collect_time(&self) -> Duration {
+        self.collect_time
+    }
+
+    /// Number of keys set by sources, counting a key again for each source setting it
+    pub fn keys(&self) -> u64 {
+        self.keys
+    }
+
+    /// Number of keys looked up in a [`Config`](crate::Config), like by
+    /// [`Config::get`](crate::Config::get)
+    pub fn lookups(&self) -> u64 {
+        self.lookups
+    }
+
+    /// Number of values deserialized from a [`Config`](crate::Config), whether a key or the
+    /// whole configuration
+    pub fn deserializations(&self) -> u64 {
+        self.deserializations
+    }
+}
@@ -0,0 +1,31 @@
+use crate::value::Value;
+
+/// Text format of a value rendered by [`Config::get_rendered`](crate::Config::get_rendered)
+///
+/// Tables are rendered with their keys sorted, unless the `preserve_order` feature keeps them in
+/// insertion order. Origins and spans are left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RenderFormat {
+    /// Compact JSON, as with [`Value::into_nested_json`], available without the `json` feature
+    Json,
+
+    /// A TOML document for tables, or an inline TOML value otherwise, leaving nil values out
+    #[cfg(feature = "toml")]
+    Toml,
+
+    /// A YAML document, without its `---` marker
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+/// Render `value` as text in `format`
+pub(crate) fn render(value: &Value, format: RenderFormat) -> String {
+    match format {
+        RenderFormat::Json => crate::nested_json::render(value),
+        #[cfg(feature = "toml")]
+        RenderFormat::Toml => crate::file::render_toml(value),
+        #[cfg(feature = "yaml")]
+        RenderFormat::Yaml => crate::file::render_yaml(value),
+    }
+}
@@ -0,0 +1,231 @@
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use async_trait::async_trait;
+
+use crate::error::{ConfigError, Result};
+use crate::file::FileFormat;
+use crate::map::Map;
+#[cfg(feature = "tokio")]
+use crate::source::{AsyncSource, collect_blocking};
+use crate::source::{Source, SourceInfo, record_loaded};
+use crate::value::Value;
+
+/// Frames larger than this are refused unless [`Sidecar::max_size`] raises the limit
+const DEFAULT_MAX_SIZE: u32 = 16 * 1024 * 1024;
+
+/// How long a request to a sidecar socket may block, unless changed with [`Sidecar::timeout`]
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A [`Source`] reading the configuration published by another process, like a sidecar
+/// container, without going through configuration files.
+///
+/// The configuration is exchanged as a single frame: the length of the payload as a big-endian
+/// `u32`, followed by the payload, a JSON object. It is read either
+///
+/// - from [shared memory](Self::shared_memory), a file under `/dev/shm` or a `memfd` passed
+///   down as `/proc/self/fd/N`, which the sidecar rewrites in place, or
+/// - from a [Unix domain socket](Self::socket), by sending an empty frame as the request, to
+///   which the sidecar answers with the frame of the configuration.
+///
+/// The configuration is read again each time the source is collected, so
+/// [`Config::refresh_source`](crate::Config::refresh_source) picks up what the sidecar published
+/// since.
+///
+/// Shared memory is read with plain reads rather than mapped: the sidecar rewrites the file in
+/// place, which is undefined behavior for a mapping the process reads from, and the frame is
+/// parsed into owned values right away, so a mapping would not save the copy either.
+///
+/// ```rust
+/// # use config::*;
+/// # let dir = std::env::temp_dir().join("config-sidecar-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let path = dir.join("app.config");
+/// let mut blob = Vec::new();
+/// Sidecar::write_frame(&mut blob, br#"{"port": 8080}"#).unwrap();
+/// std::fs::write(&path, blob).unwrap();
+///
+/// let config = Config::builder()
+///     .add_source(Sidecar::shared_memory(&path))
+///     .build()?;
+///
+/// assert_eq!(config.get_int("port")?, 8080);
+/// # Ok::<(), ConfigError>(())
+/// ```
+///
+/// With the `tokio` feature, it is also an [`AsyncSource`](crate::AsyncSource), reading on a blocking thread.
+#[derive(Debug, Clone)]
+pub struct Sidecar {
+    transport: Transport,
+    required: bool,
+    max_size: u32,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+enum Transport {
+    SharedMemory(PathBuf),
+    #[cfg(unix)]
+    Socket(PathBuf),
+}
+
+impl Sidecar {
+    /// Read the frame at the start of the file at `path`, like `/dev/shm/app.config`
+    pub fn shared_memory(path: impl AsRef<Path>) -> Self {
+        Self::new(Transport::SharedMemory(path.as_ref().to_path_buf()))
+    }
+
+    /// Request the frame from the Unix domain socket at `path`
+    #[cfg(unix)]
+    pub fn socket(path: impl AsRef<Path>) -> Self {
+        Self::new(Transport::Socket(path.as_ref().to_path_buf()))
+    }
+
+    fn new(transport: Transport) -> Self {
+        Self {
+            transport,
+            required: true,
+            max_size: DEFAULT_MAX_SIZE,
+            timeout: Some(DEFAULT_TIMEOUT),
+        }
+    }
+
+    /// Whether a sidecar that has not published anything yet is an error.
+    ///
+    /// Enabled by default. When disabled, a missing file or socket, or a socket nobody listens
+    /// on, yields no values.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Refuse frames with a payload larger than `max_size` bytes, 16 MiB by default.
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// How long reading from or writing to the socket may block, 5 seconds by default.
+    ///
+    /// `None` waits for the sidecar indefinitely.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Write `payload` as a frame, for the sidecar publishing the configuration
+    pub fn write_frame(writer: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(payload.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame payload is larger than 4 GiB",
+            )
+        })?;
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(payload)?;
+        writer.flush()
+    }
+
+    fn path(&self) -> &Path {
+        match self.transport {
+            Transport::SharedMemory(ref path) => path,
+            #[cfg(unix)]
+            Transport::Socket(ref path) => path,
+        }
+    }
+
+    fn read(&self) -> io::Result<Vec<u8>> {
+        match self.transport {
+            Transport::SharedMemory(ref path) => {
+                read_frame(&mut std::fs::File::open(path)?, self.max_size)
+            }
+            #[cfg(unix)]
+            Transport::Socket(ref path) => {
+                let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+                stream.set_read_timeout(self.timeout)?;
+                stream.set_write_timeout(self.timeout)?;
+                Self::write_frame(&mut stream, &[])?;
+                read_frame(&mut stream, self.max_size)
+            }
+        }
+    }
+}
+
+/// Read one frame from `reader`, failing if its payload is larger than `max_size` bytes
+fn read_frame(reader: &mut impl Read, max_size: u32) -> io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len > max_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes is larger than the limit of {max_size} bytes"),
+        ));
+    }
+
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+impl Source for Sidecar {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>> {
+        let uri = self.path().display().to_string();
+        let payload = match self.read() {
+            Ok(payload) => payload,
+            Err(err)
+                if !self.required
+                    && matches!(
+                        err.kind(),
+                        io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+                    ) =>
+            {
+                return Ok(Map::new());
+            }
+            Err(err) => {
+                return Err(ConfigError::SourceRead {
+                    origin: "sidecar configuration".to_owned(),
+                    uri: Some(uri),
+                    cause: Box::new(err),
+                });
+            }
+        };
+
+        let text = String::from_utf8(payload).map_err(|err| ConfigError::FormatParse {
+            format: "JSON".to_owned(),
+            uri: Some(uri.clone()),
+            cause: Box::new(err),
+        })?;
+        record_loaded(SourceInfo::new(Some(uri.clone()), None, &text));
+        let uri = Some(uri);
+        FileFormat::Json
+            .parse(uri.as_ref(), &text)
+            .map_err(|cause| ConfigError::FormatParse {
+                format: "JSON".to_owned(),
+                uri,
+                cause,
+            })
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[async_trait]
+impl AsyncSource for Sidecar {
+    async fn collect(&self) -> Result<Map<String, Value>> {
+        let source = self.clone();
+
+        collect_blocking(move || Source::collect(&source))
+            .await
+            .map_err(|e| ConfigError::SourceRead {
+                origin: "sidecar configuration".to_owned(),
+                uri: Some(self.path().display().to_string()),
+                cause: Box::new(e),
+            })?
+    }
+}
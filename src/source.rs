@@ -1,12 +1,21 @@
+use std::cell::RefCell;
 use std::fmt::Debug;
+use std::hash::{DefaultHasher, Hash as _, Hasher as _};
 use std::str::FromStr;
+use std::time::SystemTime;
 
 #[cfg(feature = "async")]
 use async_trait::async_trait;
 
+#[cfg(feature = "env")]
+use crate::adapter::ExpandEnvVars;
+use crate::adapter::{FilterKeys, MapKeys, Prefixed};
+#[cfg(feature = "tokio")]
+use crate::error::ConfigError;
 use crate::error::Result;
 use crate::map::Map;
 use crate::path;
+use crate::perf::{self, Counter};
 use crate::value::{Value, ValueKind};
 
 /// Describes a generic _source_ of configuration properties.
@@ -25,16 +34,270 @@ pub trait Source: Debug {
 
         Ok(())
     }
+
+    /// Whether the strings of this source take the type of the scalar they override when
+    /// building, like `"8080"` overriding an integer becoming an integer.
+    ///
+    /// Building fails, naming the key, when such a string does not convert. Disabled unless
+    /// the source opts in, like an [`Environment`](crate::Environment) with
+    /// [`keep_existing_types`](crate::Environment::keep_existing_types).
+    fn keeps_existing_types(&self) -> bool {
+        false
+    }
+
+    /// Mount this source under the table at the path `prefix`, e.g. `database`.
+    fn with_prefix(self, prefix: &str) -> Prefixed<Self>
+    where
+        Self: Sized,
+    {
+        Prefixed::new(self, prefix)
+    }
+
+    /// Keep the values of this source whose key satisfies `filter`.
+    ///
+    /// Keys are the paths of the leaves of the collected tree, like `database.pool.size` or
+    /// `servers[0]`, whether the source nests tables or collects dotted keys.
+    fn filter_keys<F>(self, filter: F) -> FilterKeys<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&str) -> bool,
+    {
+        FilterKeys::new(self, filter)
+    }
+
+    /// Move each value of this source to the key returned by `map`.
+    ///
+    /// Keys are paths, like for [`filter_keys`](Self::filter_keys). When several keys are mapped
+    /// to the same one, the last in sorted order wins.
+    fn map_keys<F>(self, map: F) -> MapKeys<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&str) -> String,
+    {
+        MapKeys::new(self, map)
+    }
+
+    /// Expand references to environment variables in the strings of this source, like
+    /// `${HOME}/.cache` or `${PORT:-8080}`.
+    ///
+    /// The shell syntax is followed:
+    ///
+    /// - `$VAR` and `${VAR}` expand to the value of `VAR`, or to nothing if it is unset
+    /// - `${VAR:-default}` expands to `default` if `VAR` is unset or empty
+    /// - `${VAR:?message}` fails collecting with `message` if `VAR` is unset or empty
+    /// - `${VAR:+alternative}` expands to `alternative` if `VAR` is set and not empty
+    /// - `$$` expands to `$`
+    ///
+    /// Values of variables that reference other variables are expanded in turn, and collecting
+    /// fails if they reference each other in a cycle. Only strings are expanded, and they stay
+    /// strings.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// # unsafe { std::env::set_var("EXPAND_DOC_USER", "app") };
+    /// let toml = r#"url = "postgres://${EXPAND_DOC_USER}@${EXPAND_DOC_HOST:-localhost}/db""#;
+    /// let config = Config::builder()
+    ///     .add_source(File::from_str(toml, FileFormat::Toml).expand_env_vars())
+    ///     .build()?;
+    ///
+    /// assert_eq!(config.get_string("url")?, "postgres://app@localhost/db");
+    /// # }
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    #[cfg(feature = "env")]
+    fn expand_env_vars(self) -> ExpandEnvVars<Self>
+    where
+        Self: Sized,
+    {
+        ExpandEnvVars::new(self)
+    }
+}
+
+/// What was read by a [`Source`] when a [`Config`](crate::Config) was built, returned by
+/// [`Config::sources`](crate::Config::sources)
+///
+/// Only [`File`](crate::File) sources report what they read, wrapped or not in an adapter like
+/// [`Source::with_prefix`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceInfo {
+    uri: Option<String>,
+    modified: Option<SystemTime>,
+    content_hash: u64,
+}
+
+impl SourceInfo {
+    pub(crate) fn new(uri: Option<String>, modified: Option<SystemTime>, content: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Self::with_hash(uri, modified, hasher.finish())
+    }
+
+    /// Contents whose hash was computed while reading them
+    pub(crate) fn with_hash(
+        uri: Option<String>,
+        modified: Option<SystemTime>,
+        content_hash: u64,
+    ) -> Self {
+        Self {
+            uri,
+            modified,
+            content_hash,
+        }
+    }
+
+    /// Where the contents were read from, e.g. the path of a file, relative to the current
+    /// directory when possible
+    pub fn uri(&self) -> Option<&str> {
+        self.uri.as_deref()
+    }
+
+    /// When the file was last modified, if it was read from the file system and the platform
+    /// reports it
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// A hash of the contents as read, before [decryption](crate::File::decrypt)
+    ///
+    /// Hashes are only meant to be compared within the same build of an application, e.g. to
+    /// skip a reload when a file was touched but not changed.
+    pub fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+}
+
+/// What the sources read and left out when a [`Config`](crate::Config) was built, returned by
+/// [`Config::source_report`](crate::Config::source_report)
+///
+/// Sources that look for several files, like a [`Directory`](crate::Directory), report the
+/// files they skipped and, when they are not required, the files that failed to load, so they
+/// can be logged or exported as metrics rather than going unnoticed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceReport {
+    loaded: Vec<SourceInfo>,
+    skipped: Vec<String>,
+    failed: Vec<(String, String)>,
 }
 
-fn set_value(cache: &mut Value, key: String, value: Value) {
-    match path::Expression::from_str(key.as_str()) {
+impl SourceReport {
+    /// What was read, in the order the sources were added, like [`Config::sources`](crate::Config::sources)
+    pub fn loaded(&self) -> &[SourceInfo] {
+        &self.loaded
+    }
+
+    /// URIs of the files left out because no format is registered for their extension
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// URIs of the files that failed to load, along with the error, when their source is not
+    /// required
+    pub fn failed(&self) -> &[(String, String)] {
+        &self.failed
+    }
+
+    pub(crate) fn append(&mut self, mut other: Self) {
+        self.loaded.append(&mut other.loaded);
+        self.skipped.append(&mut other.skipped);
+        self.failed.append(&mut other.failed);
+    }
+}
+
+thread_local! {
+    /// What the sources collected by [`track_loaded`] reported, or `None` outside of it
+    static LOADED: RefCell<Option<SourceReport>> = const { RefCell::new(None) };
+}
+
+/// Run `collect`, also returning what the sources it collects [reported](record_loaded)
+pub(crate) fn track_loaded<R>(collect: impl FnOnce() -> R) -> (R, SourceReport) {
+    let outer = LOADED.with(|loaded| loaded.borrow_mut().replace(SourceReport::default()));
+    let result = collect();
+    let loaded = LOADED.with(|loaded| std::mem::replace(&mut *loaded.borrow_mut(), outer));
+    (result, loaded.unwrap_or_default())
+}
+
+/// Add to the report of the source being collected by [`track_loaded`]
+fn record(update: impl FnOnce(&mut SourceReport)) {
+    LOADED.with(|loaded| {
+        if let Some(ref mut loaded) = *loaded.borrow_mut() {
+            update(loaded);
+        }
+    });
+}
+
+/// Report what a source read, when collected by [`track_loaded`]
+pub(crate) fn record_loaded(info: SourceInfo) {
+    record(|report| report.loaded.push(info));
+}
+
+/// Report what a source collected within another one read, when collected by [`track_loaded`]
+#[cfg(any(feature = "fs", feature = "tokio"))]
+pub(crate) fn record_report(report: SourceReport) {
+    record(|outer| outer.append(report));
+}
+
+/// Run `collect` on a blocking thread, with the extensions registered on the builder whose source
+/// is being collected in effect for the files it resolves, and reporting what it read as if it
+/// ran on this thread
+#[cfg(feature = "tokio")]
+pub(crate) async fn collect_blocking<T: Send + 'static>(
+    collect: impl FnOnce() -> T + Send + 'static,
+) -> std::result::Result<T, tokio::task::JoinError> {
+    #[cfg(feature = "fs")]
+    let extensions = crate::file::Extensions::current();
+    let (collected, report) = tokio::task::spawn_blocking(move || {
+        #[cfg(feature = "fs")]
+        {
+            extensions.scope(|| track_loaded(collect))
+        }
+        #[cfg(not(feature = "fs"))]
+        {
+            track_loaded(collect)
+        }
+    })
+    .await?;
+    record_report(report);
+    Ok(collected)
+}
+
+/// Report a file a source left out for its extension, when collected by [`track_loaded`]
+#[cfg(feature = "fs")]
+pub(crate) fn record_skipped(uri: String) {
+    record(|report| report.skipped.push(uri));
+}
+
+/// Report a file a source failed to load but did not require, when collected by
+/// [`track_loaded`]
+#[cfg(feature = "fs")]
+pub(crate) fn record_failed(uri: String, error: String) {
+    record(|report| report.failed.push((uri, error)));
+}
+
+pub(crate) fn set_value(cache: &mut Value, key: String, value: Value) {
+    let expr = match path::Expression::from_str(key.as_str()) {
         // Set using the path
-        Ok(expr) => expr.set(cache, value),
+        Ok(expr) => expr,
 
         // Set directly anyway
-        _ => path::Expression::root(key).set(cache, value),
+        _ => path::Expression::root(key),
+    };
+
+    #[cfg(feature = "tracing")]
+    if tracing::enabled!(tracing::Level::TRACE) {
+        match expr.clone().get(cache) {
+            Some(previous) => tracing::trace!(
+                key = %expr,
+                origin = value.origin(),
+                previous = previous.origin(),
+                "overriding key"
+            ),
+            None => tracing::trace!(key = %expr, origin = value.origin(), "setting key"),
+        }
     }
+    perf::add(Counter::Keys, 1);
+    expr.set(cache, value);
 }
 
 /// Describes a generic _source_ of configuration properties capable of using an async runtime.
@@ -60,6 +323,16 @@ pub trait AsyncSource: Debug + Sync {
     /// a Map as an async operations.
     async fn collect(&self) -> Result<Map<String, Value>>;
 
+    /// The location this source is read from, e.g. the URL of an HTTP endpoint.
+    ///
+    /// When set, errors returned while collecting that do not already name their origin
+    /// are reported as [`ConfigError::FileParse`] for this URI.
+    ///
+    /// [`ConfigError::FileParse`]: crate::ConfigError::FileParse
+    fn uri(&self) -> Option<String> {
+        None
+    }
+
     /// Collects all configuration properties to a provided cache.
     async fn collect_to(&self, cache: &mut Value) -> Result<()> {
         self.collect()
@@ -71,6 +344,44 @@ pub trait AsyncSource: Debug + Sync {
     }
 }
 
+/// Adapts a [`Source`] into an [`AsyncSource`] by running its collection on a blocking thread.
+///
+/// Sources such as [`Environment`](crate::Environment) or [`File`](crate::File) perform blocking
+/// I/O. Wrapping them allows an async builder to load them without stalling the executor.
+/// Collection is delegated to [`tokio::task::spawn_blocking`], so it must be awaited within a
+/// Tokio runtime.
+///
+/// Usually created through [`ConfigBuilder::add_source_blocking`](crate::ConfigBuilder::add_source_blocking).
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone)]
+pub struct BlockingSource(Box<dyn Source + Send + Sync>);
+
+#[cfg(feature = "tokio")]
+impl BlockingSource {
+    pub fn new<T>(source: T) -> Self
+    where
+        T: Source + Send + Sync + 'static,
+    {
+        Self(Box::new(source))
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[async_trait]
+impl AsyncSource for BlockingSource {
+    async fn collect(&self) -> Result<Map<String, Value>> {
+        let source = self.0.clone();
+
+        collect_blocking(move || source.collect())
+            .await
+            .map_err(|e| ConfigError::SourceRead {
+                origin: "blocking source".to_owned(),
+                uri: None,
+                cause: Box::new(e),
+            })?
+    }
+}
+
 #[cfg(feature = "async")]
 impl Clone for Box<dyn AsyncSource + Send + Sync> {
     fn clone(&self) -> Self {
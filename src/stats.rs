@@ -0,0 +1,86 @@
+use std::mem::size_of;
+
+use crate::value::{Span, Value, ValueKind};
+
+/// Size of a [`Config`](crate::Config), as returned by [`Config::stats`](crate::Config::stats)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConfigStats {
+    keys: usize,
+    depth: usize,
+    memory: usize,
+}
+
+impl ConfigStats {
+    /// Number of keys, counting those of nested tables but not array indices
+    pub fn keys(&self) -> usize {
+        self.keys
+    }
+
+    /// How deep tables and arrays are nested, where one under the root table is at depth 1, as
+    /// limited by [`ParseOptions::max_depth`](crate::ParseOptions::max_depth)
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Approximate number of bytes held by the values, including the unused capacity of
+    /// strings, tables and arrays
+    ///
+    /// Origins are shared between the values of a source, so they are not counted.
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+}
+
+/// The statistics of `value`, the root of a configuration
+pub(crate) fn stats(value: &Value) -> ConfigStats {
+    let mut stats = ConfigStats {
+        memory: size_of::<Value>(),
+        ..ConfigStats::default()
+    };
+    visit(&mut stats, value, 0);
+    stats
+}
+
+/// Account for the heap memory of `value`, at `depth`, and for its children
+fn visit(stats: &mut ConfigStats, value: &Value, depth: usize) {
+    if value.span().is_some() {
+        stats.memory += size_of::<Span>();
+    }
+
+    match value.kind {
+        ValueKind::String(ref string) => stats.memory += string.capacity(),
+        ValueKind::Table(ref table) => {
+            stats.depth = stats.depth.max(depth);
+            stats.keys += table.len();
+            stats.memory += table.capacity() * (size_of::<String>() + size_of::<Value>());
+            for (key, child) in table {
+                stats.memory += key.capacity();
+                visit(stats, child, depth + 1);
+            }
+        }
+        ValueKind::Array(ref array) => {
+            stats.depth = stats.depth.max(depth);
+            stats.memory += array.capacity() * size_of::<Value>();
+            for child in array {
+                visit(stats, child, depth + 1);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Release the unused capacity of the strings, tables and arrays of `value`
+pub(crate) fn shrink_to_fit(value: &mut Value) {
+    match value.kind {
+        ValueKind::String(ref mut string) => string.shrink_to_fit(),
+        ValueKind::Table(ref mut table) => {
+            table.shrink_to_fit();
+            table.values_mut().for_each(shrink_to_fit);
+        }
+        ValueKind::Array(ref mut array) => {
+            array.shrink_to_fit();
+            array.iter_mut().for_each(shrink_to_fit);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::config::Config;
+use crate::error::Result;
+
+type Build = Box<dyn Fn(&str) -> Result<Config> + Send + Sync>;
+
+/// Builds and caches a [`Config`] per tenant
+///
+/// The first [`get`](Self::get) for a tenant builds its [`Config`] with the given function, and
+/// later calls share it until the tenant is [invalidated](Self::invalidate). Failed builds are
+/// not cached. Combine it with [`File::with_name_tpl`](crate::File::with_name_tpl) to layer
+/// per-tenant overrides on top of shared files.
+///
+/// ```rust
+/// # use config::*;
+/// let tenants = TenantConfigFactory::new(|tenant| {
+///     Config::builder()
+///         .set_default("limits.requests", 100)?
+///         .set_override("tenant", tenant)?
+///         .build()
+/// });
+///
+/// let acme = tenants.get("acme")?;
+/// assert_eq!(acme.get_string("tenant")?, "acme");
+/// assert!(std::sync::Arc::ptr_eq(&acme, &tenants.get("acme")?));
+///
+/// tenants.invalidate("acme");
+/// assert!(!std::sync::Arc::ptr_eq(&acme, &tenants.get("acme")?));
+/// # Ok::<(), ConfigError>(())
+/// ```
+pub struct TenantConfigFactory {
+    build: Build,
+    configs: Mutex<HashMap<String, Arc<Config>>>,
+}
+
+impl TenantConfigFactory {
+    pub fn new<F>(build: F) -> Self
+    where
+        F: Fn(&str) -> Result<Config> + Send + Sync + 'static,
+    {
+        Self {
+            build: Box::new(build),
+            configs: Mutex::default(),
+        }
+    }
+
+    /// Get the [`Config`] of `tenant`, building it unless it is cached
+    ///
+    /// Builds run without holding the cache, so that tenants don't wait on each other. Should two
+    /// threads build the same tenant at once, the first [`Config`] cached is kept.
+    pub fn get(&self, tenant: &str) -> Result<Arc<Config>> {
+        if let Some(config) = self.configs().get(tenant) {
+            return Ok(config.clone());
+        }
+
+        let config = Arc::new((self.build)(tenant)?);
+        Ok(self
+            .configs()
+            .entry(tenant.to_owned())
+            .or_insert(config)
+            .clone())
+    }
+
+    /// Drop the cached [`Config`] of `tenant`, to be built again by the next [`get`](Self::get)
+    ///
+    /// Returns whether a [`Config`] was cached. Clones handed out before keep their values.
+    pub fn invalidate(&self, tenant: &str) -> bool {
+        self.configs().remove(tenant).is_some()
+    }
+
+    /// Drop the cached [`Config`]s of all tenants
+    pub fn invalidate_all(&self) {
+        self.configs().clear();
+    }
+
+    /// The tenants whose [`Config`] is cached, in no particular order
+    pub fn tenants(&self) -> Vec<String> {
+        self.configs().keys().cloned().collect()
+    }
+
+    fn configs(&self) -> MutexGuard<'_, HashMap<String, Arc<Config>>> {
+        // The cache is consistent whatever panicked while holding it
+        self.configs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for TenantConfigFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantConfigFactory")
+            .field("tenants", &self.tenants())
+            .finish_non_exhaustive()
+    }
+}
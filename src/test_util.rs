@@ -0,0 +1,120 @@
+//! Helpers comparing [`Config`]s in tests
+//!
+//! Failed assertions list the key paths whose values differ, instead of dumping both trees.
+//!
+//! ```rust
+//! # use config::*;
+//! use config::test_util::{assert_config_eq, assert_config_matches};
+//!
+//! let config = Config::builder()
+//!     .set_default("server.port", 8080)?
+//!     .set_default("server.host", "localhost")?
+//!     .build()?;
+//!
+//! let expected = Config::builder()
+//!     .set_override("server.host", "localhost")?
+//!     .set_override("server.port", 8080)?
+//!     .build()?;
+//! assert_config_eq(&config, &expected);
+//!
+//! #[derive(serde::Serialize)]
+//! struct Server {
+//!     host: &'static str,
+//!     port: u16,
+//! }
+//!
+//! #[derive(serde::Serialize)]
+//! struct Settings {
+//!     server: Server,
+//! }
+//!
+//! let server = Server { host: "localhost", port: 8080 };
+//! assert_config_matches(&config, &Settings { server });
+//! # Ok::<(), ConfigError>(())
+//! ```
+
+use std::fmt::Write;
+
+use serde_core::ser::Serialize;
+
+use crate::config::Config;
+use crate::notify::leaves;
+use crate::value::{Value, ValueKind};
+
+/// Render the differences between `actual` and `expected`, or `None` if they are equal
+///
+/// Each differing leaf is rendered on its own line, as `- key = value` for `expected` and
+/// `+ key = value` for `actual`, with keys in sorted order. Values are compared regardless of
+/// their origin, integers regardless of their width, and strings are quoted so that `"8080"` and
+/// `8080` can be told apart.
+pub fn diff(actual: &Config, expected: &Config) -> Option<String> {
+    let actual = leaves(&actual.cache);
+    let expected = leaves(&expected.cache);
+
+    let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut diff = String::new();
+    for key in keys {
+        let (expected, actual) = (expected.get(key), actual.get(key));
+        if let (Some(expected), Some(actual)) = (expected, actual) {
+            if same(&expected.kind, &actual.kind) {
+                continue;
+            }
+        }
+
+        for (sign, value) in [('-', expected), ('+', actual)] {
+            if let Some(value) = value {
+                let _ = writeln!(diff, "{sign} {key} = {}", render(value));
+            }
+        }
+    }
+
+    (!diff.is_empty()).then_some(diff)
+}
+
+/// Assert that `actual` equals `expected`, panicking with their [`diff`] otherwise
+#[track_caller]
+pub fn assert_config_eq(actual: &Config, expected: &Config) {
+    if let Some(diff) = diff(actual, expected) {
+        panic!("configs differ (- expected, + actual):\n{diff}");
+    }
+}
+
+/// Assert that `actual` equals the tree `expected`, serialized like with [`Config::try_from`]
+///
+/// `expected` can be a struct or a literal, e.g. built with `serde_json::json!`.
+#[track_caller]
+pub fn assert_config_matches<T: Serialize>(actual: &Config, expected: &T) {
+    match Config::try_from(expected) {
+        Ok(expected) => assert_config_eq(actual, &expected),
+        Err(error) => panic!("expected tree cannot be serialized: {error}"),
+    }
+}
+
+fn same(expected: &ValueKind, actual: &ValueKind) -> bool {
+    fn int(kind: &ValueKind) -> Option<String> {
+        match *kind {
+            ValueKind::I64(value) => Some(value.to_string()),
+            ValueKind::I128(value) => Some(value.to_string()),
+            ValueKind::U64(value) => Some(value.to_string()),
+            ValueKind::U128(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    match (int(expected), int(actual)) {
+        (Some(expected), Some(actual)) => expected == actual,
+        _ => expected == actual,
+    }
+}
+
+fn render(value: &Value) -> String {
+    match value.kind {
+        ValueKind::String(ref value) => format!("{value:?}"),
+        ValueKind::Table(_) => "{}".to_owned(),
+        ValueKind::Array(_) => "[]".to_owned(),
+        ref scalar => scalar.to_string(),
+    }
+}
@@ -0,0 +1,200 @@
+//! Parsing of durations written as text
+//!
+//! [`std::time::Duration`] fields are deserialized from strings like `"30s"`, `"1h 30m"` or
+//! `"PT30S"`, besides the `secs` and `nanos` table serde expects:
+//!
+//! ```rust
+//! # use config::*;
+//! use std::time::Duration;
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Settings {
+//!     timeout: Duration,
+//!     retry_after: Duration,
+//! }
+//!
+//! let settings: Settings = Config::builder()
+//!     .set_default("timeout", "1m30s")?
+//!     .set_default("retry_after", "PT0.5S")?
+//!     .build()?
+//!     .try_deserialize()?;
+//!
+//! assert_eq!(settings.timeout, Duration::from_secs(90));
+//! assert_eq!(settings.retry_after, Duration::from_millis(500));
+//! # Ok::<(), ConfigError>(())
+//! ```
+//!
+//! Dates and times of [`chrono`](https://docs.rs/chrono) deserialize from strings on their own,
+//! including the datetimes of TOML files. Its `TimeDelta` has no `Deserialize` implementation,
+//! use [`chrono_duration`] with the `chrono` feature.
+
+use std::time::Duration;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+const MINUTE: u128 = 60 * NANOS_PER_SEC;
+const HOUR: u128 = 60 * MINUTE;
+const DAY: u128 = 24 * HOUR;
+const WEEK: u128 = 7 * DAY;
+
+/// Parse a duration written as text, or `None` if it is invalid
+///
+/// Two notations are accepted:
+///
+/// - ISO 8601 durations of weeks, days, hours, minutes and seconds, like `P1DT12H` or `PT0.5S`.
+///   Years and months are rejected, as their length varies.
+/// - A sequence of numbers followed by a unit, optionally separated by spaces, like `1h 30m` or
+///   `1.5s`. Units are `w`, `d`, `h`, `m`, `s`, `ms`, `us` (or `µs`) and `ns`.
+///
+/// Numbers can have a fractional part, and durations are truncated to nanoseconds.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let nanos = match text.strip_prefix('P') {
+        Some(iso) => parse_iso(iso)?,
+        None => components(text)?
+            .into_iter()
+            .try_fold(0u128, |total, (number, unit)| {
+                let unit = match unit {
+                    "w" => WEEK,
+                    "d" => DAY,
+                    "h" => HOUR,
+                    "m" => MINUTE,
+                    "s" => NANOS_PER_SEC,
+                    "ms" => 1_000_000,
+                    "us" | "µs" => 1_000,
+                    "ns" => 1,
+                    _ => return None,
+                };
+                total.checked_add(nanos(number, unit)?)
+            })?,
+    };
+
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+    Some(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+/// Deserialize a [`chrono::TimeDelta`] from a duration [written as text](parse_duration),
+/// optionally negative like `-30s`
+///
+/// ```rust
+/// # use config::*;
+/// #[derive(serde::Deserialize)]
+/// struct Settings {
+///     #[serde(deserialize_with = "config::time::chrono_duration")]
+///     clock_skew: chrono::TimeDelta,
+/// }
+///
+/// let settings: Settings = Config::builder()
+///     .set_default("clock_skew", "-PT5S")?
+///     .build()?
+///     .try_deserialize()?;
+///
+/// assert_eq!(settings.clock_skew, chrono::TimeDelta::seconds(-5));
+/// # Ok::<(), ConfigError>(())
+/// ```
+#[cfg(feature = "chrono")]
+pub fn chrono_duration<'de, D>(deserializer: D) -> Result<chrono::TimeDelta, D::Error>
+where
+    D: serde_core::Deserializer<'de>,
+{
+    use serde_core::de::{self, Unexpected};
+
+    struct Visitor;
+
+    impl de::Visitor<'_> for Visitor {
+        type Value = chrono::TimeDelta;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            formatter.write_str("a duration like `30s` or `PT30S`")
+        }
+
+        fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+            let (negative, duration) = match text.trim().strip_prefix('-') {
+                Some(duration) => (true, duration),
+                None => (false, text),
+            };
+            let duration = parse_duration(duration)
+                .and_then(|duration| chrono::TimeDelta::from_std(duration).ok())
+                .ok_or_else(|| E::invalid_value(Unexpected::Str(text), &self))?;
+            Ok(if negative { -duration } else { duration })
+        }
+    }
+
+    deserializer.deserialize_str(Visitor)
+}
+
+/// Parse the part of an ISO 8601 duration after `P`
+fn parse_iso(text: &str) -> Option<u128> {
+    let (date, time) = match text.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+    if date.is_empty() && time.is_none() {
+        return None;
+    }
+
+    let mut total = 0u128;
+    if !date.is_empty() {
+        for (number, unit) in components(date)? {
+            let unit = match unit {
+                "W" => WEEK,
+                "D" => DAY,
+                _ => return None,
+            };
+            total = total.checked_add(nanos(number, unit)?)?;
+        }
+    }
+    if let Some(time) = time {
+        for (number, unit) in components(time)? {
+            let unit = match unit {
+                "H" => HOUR,
+                "M" => MINUTE,
+                "S" => NANOS_PER_SEC,
+                _ => return None,
+            };
+            total = total.checked_add(nanos(number, unit)?)?;
+        }
+    }
+    Some(total)
+}
+
+/// Split `text` into `(number, unit)` pairs, like `("1.5", "h")`, ignoring spaces between them
+fn components(text: &str) -> Option<Vec<(&str, &str)>> {
+    let mut components = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let (number, tail) = rest.split_at(
+            rest.find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len()),
+        );
+        let (unit, tail) = tail.split_at(
+            tail.find(|c: char| !c.is_alphabetic())
+                .unwrap_or(tail.len()),
+        );
+        if number.is_empty() || unit.is_empty() {
+            return None;
+        }
+        components.push((number, unit));
+        rest = tail.trim_start();
+    }
+    (!components.is_empty()).then_some(components)
+}
+
+/// The number of nanoseconds in `number` times `unit` nanoseconds
+fn nanos(number: &str, unit: u128) -> Option<u128> {
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if integer.is_empty() && fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let integer: u128 = match integer {
+        "" => 0,
+        integer => integer.parse().ok()?,
+    };
+    let mut total = integer.checked_mul(unit)?;
+    let mut scale = unit;
+    for digit in fraction.bytes() {
+        scale /= 10;
+        total = total.checked_add(u128::from(digit - b'0') * scale)?;
+    }
+    Some(total)
+}
@@ -1,6 +1,8 @@
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryInto;
 use std::fmt;
 use std::fmt::Display;
+use std::sync::Arc;
 
 use serde_core::de::{Deserialize, Deserializer, Visitor};
 
@@ -136,6 +138,17 @@ where
     }
 }
 
+/// Builds a table regardless of whether [`Map`] preserves insertion order.
+impl<T> From<BTreeMap<String, T>> for ValueKind
+where
+    T: Into<Value>,
+{
+    fn from(values: BTreeMap<String, T>) -> Self {
+        let t = values.into_iter().map(|(k, v)| (k, v.into())).collect();
+        Self::Table(t)
+    }
+}
+
 impl<T> From<Vec<T>> for ValueKind
 where
     T: Into<Value>,
@@ -145,6 +158,112 @@ where
     }
 }
 
+impl ValueKind {
+    /// Name of this kind of value, for error messages
+    pub(crate) fn type_name(&self) -> &'static str {
+        match *self {
+            Self::Nil => "nil",
+            Self::Boolean(_) => "boolean",
+            Self::I64(_) | Self::I128(_) | Self::U64(_) | Self::U128(_) => "integer",
+            Self::Float(_) => "float",
+            Self::String(_) => "string",
+            Self::Table(_) => "table",
+            Self::Array(_) => "array",
+        }
+    }
+}
+
+/// Number notations recognized when parsing strings into typed values, beyond plain decimals
+#[cfg(any(feature = "env", feature = "ini"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Notations {
+    pub(crate) hex: bool,
+    pub(crate) octal: bool,
+    pub(crate) underscores: bool,
+    pub(crate) no_scientific: bool,
+    /// Whether values written like zip codes or phone numbers are kept as strings
+    pub(crate) preserve_leading_zeros: bool,
+}
+
+#[cfg(any(feature = "env", feature = "ini"))]
+impl Notations {
+    /// Parse `value` as a boolean, an integer or a float, if it looks like one
+    pub(crate) fn parse(&self, value: &str) -> Option<ValueKind> {
+        // convert to lowercase because bool parsing expects all lowercase
+        if let Ok(parsed) = value.to_lowercase().parse::<bool>() {
+            return Some(ValueKind::Boolean(parsed));
+        }
+        self.parse_number(value)
+    }
+
+    /// Parse `value` as an integer or a float in the enabled notations
+    fn parse_number(&self, value: &str) -> Option<ValueKind> {
+        if self.preserve_leading_zeros && has_significant_prefix(value) {
+            return None;
+        }
+
+        let stripped;
+        let value = if self.underscores && value.contains('_') {
+            let bytes = value.as_bytes();
+            let separates_digits = bytes.iter().enumerate().all(|(i, b)| {
+                *b != b'_'
+                    || (i > 0
+                        && bytes[i - 1].is_ascii_hexdigit()
+                        && bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit))
+            });
+            if !separates_digits {
+                return None;
+            }
+            stripped = value.replace('_', "");
+            stripped.as_str()
+        } else {
+            value
+        };
+
+        if let Ok(parsed) = value.parse::<i64>() {
+            return Some(ValueKind::I64(parsed));
+        }
+
+        let (sign, unsigned) = match value.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", value.strip_prefix('+').unwrap_or(value)),
+        };
+        let radix = match unsigned.get(..2) {
+            Some("0x" | "0X") if self.hex => Some(16),
+            Some("0o" | "0O") if self.octal => Some(8),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            let digits = &unsigned[2..];
+            if !digits.starts_with(|c: char| c.is_digit(radix)) {
+                return None;
+            }
+            return i64::from_str_radix(&format!("{sign}{digits}"), radix)
+                .ok()
+                .map(ValueKind::I64);
+        }
+
+        if self.no_scientific && value.contains(['e', 'E']) {
+            return None;
+        }
+        value.parse::<f64>().ok().map(ValueKind::Float)
+    }
+}
+
+/// Whether `value` starts like a zip code or a phone number, as in `007` or `+4912345`, whose
+/// leading characters would be lost by parsing it as a number
+#[cfg(any(feature = "env", feature = "ini"))]
+fn has_significant_prefix(value: &str) -> bool {
+    if let Some(rest) = value.strip_prefix('+') {
+        return rest.starts_with(|c: char| c.is_ascii_digit());
+    }
+    let mut digits = value.strip_prefix('-').unwrap_or(value).bytes();
+    digits.next() == Some(b'0')
+        && digits
+            .next()
+            .is_some_and(|b| b.is_ascii_digit() || b == b'_')
+}
+
 impl Display for ValueKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use std::fmt::Write;
@@ -176,8 +295,66 @@ impl Display for ValueKind {
     }
 }
 
+/// A range of text that set a [`Value`], from its first character to the one following it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    start: Position,
+    end: Position,
+}
+
+impl Span {
+    pub(crate) fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    /// The position of the first character
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// The position following the last character
+    pub fn end(&self) -> Position {
+        self.end
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// A position in a text, displayed as `line:column`
+///
+/// Lines and columns start at 1, and columns count characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    pub(crate) fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 /// A configuration value.
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone)]
 pub struct Value {
     /// A description of the original location of the value.
     ///
@@ -195,27 +372,116 @@ pub struct Value {
     /// ```text
     /// etcd+http://127.0.0.1:2379
     /// ```
-    origin: Option<String>,
+    ///
+    /// Shared between all values parsed from the same source.
+    origin: Option<Arc<str>>,
+
+    /// Where the value was written in its source, when recorded.
+    span: Option<Box<Span>>,
 
     /// Underlying kind of the configuration value.
     pub kind: ValueKind,
 }
 
+/// Values are equal when they have the same origin and kind, wherever they were written in
+/// their source
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.origin == other.origin && self.kind == other.kind
+    }
+}
+
 impl Value {
     /// Create a new value instance that will remember its source uri.
     pub fn new<V>(origin: Option<&String>, kind: V) -> Self
+    where
+        V: Into<ValueKind>,
+    {
+        Self {
+            origin: origin.map(|origin| Arc::from(origin.as_str())),
+            span: None,
+            kind: kind.into(),
+        }
+    }
+
+    /// Create a new value instance sharing an already allocated source uri.
+    #[cfg(any(
+        feature = "env",
+        feature = "toml",
+        feature = "json",
+        feature = "yaml",
+        feature = "ini",
+        feature = "ron",
+        feature = "json5",
+        feature = "corn",
+        feature = "dotenv",
+    ))]
+    pub(crate) fn with_origin<V>(origin: Option<&Arc<str>>, kind: V) -> Self
     where
         V: Into<ValueKind>,
     {
         Self {
             origin: origin.cloned(),
+            span: None,
             kind: kind.into(),
         }
     }
 
     /// Get the description of the original location of the value.
     pub fn origin(&self) -> Option<&str> {
-        self.origin.as_ref().map(AsRef::as_ref)
+        self.origin.as_deref()
+    }
+
+    /// Where the value was written in its [origin](Self::origin), if the format recorded it.
+    ///
+    /// Only recorded by TOML and JSON files with [`ParseOptions::record_spans`]. A table merged
+    /// from several sources keeps the span of the first one.
+    ///
+    /// [`ParseOptions::record_spans`]: crate::ParseOptions::record_spans
+    pub fn span(&self) -> Option<Span> {
+        self.span.as_deref().copied()
+    }
+
+    /// Record where the value was written, for formats implementing
+    /// [`ParseOptions::record_spans`](crate::ParseOptions::record_spans).
+    pub fn set_span(&mut self, span: Span) {
+        self.span = Some(Box::new(span));
+    }
+
+    /// Render this value as a compact JSON document, without the `json` feature.
+    ///
+    /// Tables are objects, with their keys sorted unless the `preserve_order` feature is
+    /// enabled. Nil, and floats JSON can't represent like NaN, are `null`. Origins and spans are
+    /// left out.
+    ///
+    /// ```rust
+    /// # use config::*;
+    /// let config = Config::builder()
+    ///     .set_default("server.hosts", vec!["a", "b"])?
+    ///     .set_default("server.port", 8080)?
+    ///     .build()?;
+    ///
+    /// assert_eq!(
+    ///     config.cache.into_nested_json(),
+    ///     r#"{"server":{"hosts":["a","b"],"port":8080}}"#
+    /// );
+    /// # Ok::<(), ConfigError>(())
+    /// ```
+    pub fn into_nested_json(self) -> String {
+        crate::nested_json::render(&self)
+    }
+
+    /// Parse a JSON document, without the `json` feature.
+    ///
+    /// Any value can be the root of the document. Integers are kept as the narrowest of `i64`,
+    /// `u64`, `i128` and `u128` that holds them, other numbers are floats. Values have no
+    /// origin.
+    ///
+    /// # Errors
+    ///
+    /// Fails on invalid JSON, naming the line and column of the problem.
+    pub fn from_nested_json(text: &str) -> Result<Self> {
+        crate::nested_json::parse(text)
     }
 
     /// Attempt to deserialize this value into the requested type.
@@ -700,6 +966,22 @@ impl Value {
             )),
         }
     }
+
+    /// If the `Value` is a Table, returns its entries sorted by key.
+    ///
+    /// Unlike [`into_table`](Self::into_table), the resulting type does not depend on the
+    /// `preserve_order` feature.
+    pub fn into_btree_map(self) -> Result<BTreeMap<String, Self>> {
+        self.into_table().map(|table| table.into_iter().collect())
+    }
+
+    /// If the `Value` is a Table, returns its entries in a [`HashMap`].
+    ///
+    /// Unlike [`into_table`](Self::into_table), the resulting type does not depend on the
+    /// `preserve_order` feature.
+    pub fn into_hash_map(self) -> Result<HashMap<String, Self>> {
+        self.into_table().map(|table| table.into_iter().collect())
+    }
 }
 
 impl<'de> Deserialize<'de> for Value {
@@ -864,6 +1146,7 @@ where
     fn from(value: T) -> Self {
         Self {
             origin: None,
+            span: None,
             kind: value.into(),
         }
     }
@@ -908,4 +1191,17 @@ value = 120
             value.kind
         );
     }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn test_origin_is_shared() {
+        let uri = "settings.toml".to_owned();
+        let map = FileFormat::Toml
+            .parse(Some(&uri), "first = 1\nsecond = 2")
+            .unwrap();
+        let first = map["first"].origin.as_ref().unwrap();
+        let second = map["second"].origin.as_ref().unwrap();
+        assert_eq!(&**first, "settings.toml");
+        assert!(std::sync::Arc::ptr_eq(first, second));
+    }
 }
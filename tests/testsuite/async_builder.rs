@@ -2,6 +2,7 @@
 #![cfg(feature = "json")]
 
 use async_trait::async_trait;
+use snapbox::{assert_data_eq, str};
 
 use config::{AsyncSource, Config, ConfigError, FileFormat, Format, Map, Value};
 
@@ -19,6 +20,20 @@ impl AsyncSource for AsyncJson {
     }
 }
 
+#[derive(Debug)]
+struct AsyncRemoteJson(&'static str);
+
+#[async_trait]
+impl AsyncSource for AsyncRemoteJson {
+    async fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        AsyncJson(self.0).collect().await
+    }
+
+    fn uri(&self) -> Option<String> {
+        Some("https://example.com/config.json".into())
+    }
+}
+
 #[tokio::test]
 async fn test_single_async_file_source() {
     let config = Config::builder()
@@ -183,3 +198,209 @@ async fn test_async_file_sources_with_overrides() {
     );
     assert_eq!(config.get::<i32>("place.number").unwrap(), 1);
 }
+
+#[tokio::test]
+async fn test_async_sources_with_priority() {
+    let config = Config::builder()
+        .add_async_source_with_priority(AsyncJson(r#"{ "debug": true, "port": 1 }"#), 1)
+        .add_async_source(AsyncJson(r#"{ "debug": false, "port": 2 }"#))
+        .add_source_with_priority(
+            config::File::from_str(r#"{ "port": 3 }"#, FileFormat::Json),
+            2,
+        )
+        .build()
+        .await
+        .unwrap();
+
+    assert!(config.get::<bool>("debug").unwrap());
+    assert_eq!(config.get::<i64>("port").unwrap(), 3);
+}
+
+#[derive(Debug, Clone, Default)]
+struct Generation(std::sync::Arc<std::sync::atomic::AtomicI64>);
+
+impl config::Source for Generation {
+    fn clone_into_box(&self) -> Box<dyn config::Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let generation = self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Ok(Map::from_iter([(
+            "generation".to_owned(),
+            generation.into(),
+        )]))
+    }
+}
+
+#[tokio::test]
+async fn test_refresh_source_after_async_build() {
+    let generation = Generation::default();
+    let mut config = Config::builder()
+        .add_async_source(AsyncJson(r#"{ "generation": -1, "port": 1 }"#))
+        .add_source(generation.clone())
+        .refreshable(true)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(config.get::<i64>("generation").unwrap(), 0);
+
+    config.refresh_source::<Generation>().unwrap();
+    assert_eq!(config.get::<i64>("generation").unwrap(), 1);
+    assert_eq!(config.get::<i64>("port").unwrap(), 1);
+
+    let mut config = Config::builder()
+        .add_async_source(AsyncJson(r#"{ "port": 1 }"#))
+        .add_source(generation)
+        .build()
+        .await
+        .unwrap();
+    assert!(config.refresh_source::<Generation>().is_err());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[cfg(feature = "env")]
+async fn test_blocking_source() {
+    let config = Config::builder()
+        .add_source_blocking(config::Environment::default().source(Some({
+            let mut env = Map::new();
+            env.insert("PLACE".into(), "Torre di Pisa".into());
+            env
+        })))
+        .add_async_source(AsyncJson(
+            r#"
+{
+  "number": 1
+}
+"#,
+        ))
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(config.get::<String>("place").unwrap(), "Torre di Pisa");
+    assert_eq!(config.get::<i32>("number").unwrap(), 1);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[cfg(feature = "fs")]
+async fn test_file_extensions_in_async_sources() {
+    use config::{File, Source};
+
+    #[derive(Debug)]
+    struct AsyncFile(String);
+
+    #[async_trait]
+    impl AsyncSource for AsyncFile {
+        async fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+            File::with_name(&self.0).collect()
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("config-async-extensions-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("app.conf"), r#"{ "debug": true }"#).unwrap();
+    std::fs::write(dir.join("other.json"), r#"{ "port": 8080 }"#).unwrap();
+    let app = dir.join("app");
+    let app = app.to_str().unwrap();
+    let other = dir.join("other");
+    let other = other.to_str().unwrap();
+
+    let config = Config::builder()
+        .register_extension("conf", FileFormat::Json)
+        .add_source_blocking(File::with_name(app))
+        .add_async_source(AsyncFile(app.to_owned()))
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(config.get("debug").ok(), Some(true));
+    let loaded: Vec<_> = config
+        .source_report()
+        .loaded()
+        .iter()
+        .map(|info| info.uri().unwrap().to_owned())
+        .collect();
+    assert_eq!(loaded.len(), 2);
+    assert!(loaded.iter().all(|uri| uri.ends_with("app.conf")));
+
+    let res = Config::builder()
+        .forbid_extension("json")
+        .add_source_blocking(File::with_name(other))
+        .build()
+        .await;
+    assert!(res.unwrap_err().to_string().ends_with("other\" not found"));
+
+    let res = Config::builder()
+        .forbid_extension("json")
+        .add_async_source(AsyncFile(other.to_owned()))
+        .build()
+        .await;
+    assert!(res.unwrap_err().to_string().ends_with("other\" not found"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_watch_yields_changed_configs() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::time::Duration;
+
+    use config::Source;
+    use futures::StreamExt as _;
+
+    #[derive(Debug, Clone)]
+    struct Counter(Arc<AtomicI64>);
+
+    impl Source for Counter {
+        fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+            Box::new(self.clone())
+        }
+
+        fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+            // Only change every other collection so unchanged rebuilds are skipped
+            let count = self.0.fetch_add(1, Ordering::SeqCst) / 2;
+            let mut m = Map::new();
+            m.insert("count".into(), count.into());
+            Ok(m)
+        }
+    }
+
+    let counter = Arc::new(AtomicI64::new(0));
+    let stream = Config::builder()
+        .add_source_blocking(Counter(counter.clone()))
+        .watch(Duration::from_millis(5));
+    let configs: Vec<_> = stream.take(3).collect().await;
+
+    let counts: Vec<i64> = configs
+        .into_iter()
+        .map(|config| config.unwrap().get::<i64>("count").unwrap())
+        .collect();
+    assert_eq!(counts, vec![0, 1, 2]);
+    assert!(counter.load(Ordering::SeqCst) >= 5);
+}
+
+#[tokio::test]
+async fn test_async_source_uri_in_errors() {
+    let res = Config::builder()
+        .add_async_source(AsyncRemoteJson("{ \"debug\": "))
+        .build()
+        .await;
+
+    let err = res.unwrap_err();
+    assert!(matches!(err, ConfigError::FileParse { uri: Some(_), .. }));
+    assert_data_eq!(
+        err.to_string(),
+        str!["EOF while parsing a value at line 1 column 11 in https://example.com/config.json"]
+    );
+
+    let res = Config::builder()
+        .add_async_source(AsyncJson("{ \"debug\": "))
+        .build()
+        .await;
+
+    assert!(matches!(res.unwrap_err(), ConfigError::Foreign(_)));
+}
@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-use config::{Config, File, FileFormat};
+use config::{Config, File, FileFormat, Map, Value};
 
 #[test]
 #[cfg(feature = "json")]
@@ -76,3 +76,95 @@ fn respect_path_case() {
 
     c.get_string("Student[0].Name").unwrap();
 }
+
+#[test]
+#[cfg(feature = "json")]
+fn normalize_keys_merges_sources() {
+    let c = Config::builder()
+        .normalize_keys(|key| key.to_lowercase())
+        .set_default("Server.Port", 80)
+        .unwrap()
+        .add_source(File::from_str(
+            r#"
+{
+  "server": { "Host": "localhost", "PORT": 8080 },
+  "Users": [
+    { "Name": "1" }
+  ]
+}
+"#,
+            FileFormat::Json,
+        ))
+        .add_source(File::from_str(
+            r#"
+{
+  "SERVER": { "host": "example.com" }
+}
+"#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_string("Server.HOST").unwrap(), "example.com");
+    assert_eq!(c.get_int("server.port").unwrap(), 8080);
+    assert_eq!(c.get_string("USERS[0].name").unwrap(), "1");
+    assert_eq!(c.sections(), vec!["server"]);
+}
+
+#[test]
+fn normalize_keys_in_table_values() {
+    let table = |key: &str, value: i64| Map::from_iter([(key.to_owned(), Value::from(value))]);
+    let c = Config::builder()
+        .set_default("Server", table("Port", 80))
+        .unwrap()
+        .set_override(
+            "SERVER",
+            Map::from_iter([("Limits".to_owned(), table("MAX", 10))]),
+        )
+        .unwrap()
+        .normalize_keys(|key| key.to_lowercase())
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_int("server.port").unwrap(), 80);
+    assert_eq!(c.get_int("server.limits.max").unwrap(), 10);
+    let server = c.get_table("server").unwrap();
+    let mut keys: Vec<_> = server.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["limits", "port"]);
+}
+
+#[test]
+#[cfg(all(feature = "json", feature = "convert-case"))]
+fn convert_case_unifies_conventions() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Server {
+        max_connections: u64,
+        request_timeout: u64,
+    }
+
+    let c = Config::builder()
+        .convert_case(config::Case::Snake)
+        .add_source(File::from_str(
+            r#"
+{
+  "server": { "maxConnections": 8, "requestTimeout": 30 }
+}
+"#,
+            FileFormat::Json,
+        ))
+        .set_override("server.max-connections", 16)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_int("server.maxConnections").unwrap(), 16);
+    assert_eq!(
+        c.get::<Server>("server").unwrap(),
+        Server {
+            max_connections: 16,
+            request_timeout: 30,
+        }
+    );
+}
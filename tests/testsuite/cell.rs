@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use config::{Config, ConfigCell};
+
+fn workers(workers: i64) -> Config {
+    Config::builder()
+        .set_default("workers", workers)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_config_cell() {
+    let cell = Arc::new(ConfigCell::new(workers(1)));
+    assert_eq!(cell.generation(), 0);
+
+    let (config, generation) = cell.load_with_generation();
+    assert_eq!(config.get_int("workers").unwrap(), 1);
+
+    let reloader = {
+        let cell = cell.clone();
+        std::thread::spawn(move || {
+            for n in 2..=10 {
+                cell.swap(workers(n));
+            }
+        })
+    };
+    reloader.join().unwrap();
+
+    assert_eq!(cell.generation(), generation + 9);
+    assert_eq!(config.get_int("workers").unwrap(), 1);
+    let (config, generation) = cell.load_with_generation();
+    assert_eq!(config.get_int("workers").unwrap(), 10);
+
+    let previous = cell.swap(workers(11));
+    assert!(Arc::ptr_eq(&previous, &config));
+    assert!(cell.generation() > generation);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_async_config_cell() {
+    use config::{AsyncConfigCell, ConfigError};
+
+    let cell = AsyncConfigCell::new(workers(1));
+    let mut updates = cell.subscribe();
+    assert!(!updates.has_changed().unwrap());
+
+    let previous = cell.swap(workers(2));
+    assert_eq!(previous.get_int("workers").unwrap(), 1);
+    updates.changed().await.unwrap();
+    assert_eq!(updates.borrow_and_update().get_int("workers").unwrap(), 2);
+
+    let mut errors = Vec::new();
+    let stream = futures::stream::iter(vec![
+        Ok(workers(3)),
+        Err(ConfigError::Message("unavailable".into())),
+        Ok(workers(4)),
+    ]);
+    cell.clone()
+        .follow(stream, |error| errors.push(error.to_string()))
+        .await;
+
+    assert_eq!(errors, ["unavailable"]);
+    assert!(updates.has_changed().unwrap());
+    assert_eq!(updates.borrow_and_update().get_int("workers").unwrap(), 4);
+    assert_eq!(cell.load().get_int("workers").unwrap(), 4);
+}
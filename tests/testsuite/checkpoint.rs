@@ -0,0 +1,57 @@
+use config::builder::AsyncState;
+use config::{Config, ConfigBuilder, File, FileFormat};
+
+#[test]
+#[cfg(feature = "json")]
+fn test_reset_to_checkpoint() {
+    let builder = Config::builder()
+        .set_default("name", "app")
+        .unwrap()
+        .add_source(File::from_str(r#"{ "port": 80 }"#, FileFormat::Json));
+    let checkpoint = builder.checkpoint();
+
+    let builder = builder
+        .set_override("name", "tentative")
+        .unwrap()
+        .add_source(File::from_str("{ port: ", FileFormat::Json));
+    assert!(builder.build_cloned().is_err());
+
+    // A checkpoint can be rolled back to repeatedly
+    for _ in 0..2 {
+        let builder = builder.clone().reset_to(&checkpoint);
+        let config = builder.build().unwrap();
+        assert_eq!(config.get_string("name").unwrap(), "app");
+        assert_eq!(config.get_int("port").unwrap(), 80);
+        assert_eq!(config.sources().len(), 1);
+    }
+}
+
+#[test]
+fn test_checkpoint_keeps_options() {
+    let checkpoint = Config::builder()
+        .normalize_keys(|key| key.to_lowercase())
+        .checkpoint();
+
+    let config = Config::builder()
+        .reset_to(&checkpoint)
+        .set_default("Server.Port", 80)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("server.port").unwrap(), 80);
+}
+
+#[tokio::test]
+#[cfg(all(feature = "async", feature = "json"))]
+async fn test_reset_to_checkpoint_async() {
+    let checkpoint = ConfigBuilder::<AsyncState>::default()
+        .set_default("port", 80)
+        .unwrap()
+        .checkpoint();
+
+    let builder = ConfigBuilder::<AsyncState>::default()
+        .add_source(File::from_str("{ port: ", FileFormat::Json))
+        .reset_to(&checkpoint);
+    let config = builder.build().await.unwrap();
+    assert_eq!(config.get_int("port").unwrap(), 80);
+}
@@ -1,5 +1,6 @@
 use config::Config;
 use serde::{Deserialize, Serialize};
+use snapbox::{assert_data_eq, str};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(default)]
@@ -29,3 +30,149 @@ fn try_from_defaults() {
     let s: Settings = c.try_deserialize().expect("Deserialization failed");
     assert_eq!(s.db_host, "default");
 }
+
+#[test]
+fn set_lazy_defaults() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let builder = Config::builder()
+        .set_default("db.host", "localhost")
+        .unwrap()
+        .set_default_with("db.host", || -> String { unreachable!() })
+        .unwrap()
+        .set_default_with("db.port", move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            5432
+        })
+        .unwrap()
+        .set_override("cache", "disabled")
+        .unwrap()
+        .set_default_with("cache.dir", || "/tmp")
+        .unwrap();
+
+    let c = builder.build_cloned().unwrap();
+    assert_eq!(c.get_string("db.host").unwrap(), "localhost");
+    assert_eq!(c.get_int("db.port").unwrap(), 5432);
+    assert_eq!(c.get_string("cache").unwrap(), "disabled");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let c = builder
+        .set_override("db.port", 6543)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(c.get_int("db.port").unwrap(), 6543);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn set_defaults_from_struct() {
+    use config::{File, FileFormat};
+
+    #[derive(Serialize)]
+    struct Defaults {
+        db: Db,
+        workers: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Db {
+        host: String,
+        port: u16,
+    }
+
+    let defaults = Defaults {
+        db: Db {
+            host: "localhost".into(),
+            port: 5432,
+        },
+        workers: 4,
+    };
+    let c = Config::builder()
+        .set_defaults(&defaults)
+        .unwrap()
+        .add_source(File::from_str(
+            r#"{ "db": { "port": 6543 } }"#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_string("db.host").unwrap(), "localhost");
+    assert_eq!(c.get_int("db.port").unwrap(), 6543);
+    assert_eq!(c.get_int("workers").unwrap(), 4);
+
+    assert!(Config::builder().set_defaults(&42).is_err());
+}
+
+#[test]
+fn set_defaults_not_a_table() {
+    let res = Config::builder().set_defaults(&vec![1, 2]);
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["top level is not a struct"]
+    );
+
+    let res = Config::builder().set_defaults(&"localhost");
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["top level is not a struct"]
+    );
+}
+
+#[test]
+fn set_defaults_keeps_other_defaults() {
+    #[derive(Serialize)]
+    struct Defaults {
+        db: Db,
+    }
+
+    #[derive(Serialize)]
+    struct Db {
+        host: String,
+        port: u16,
+    }
+
+    let defaults = Defaults {
+        db: Db {
+            host: "localhost".into(),
+            port: 5432,
+        },
+    };
+    let c = Config::builder()
+        .set_default("db.user", "admin")
+        .unwrap()
+        .set_default("db.port", 6543)
+        .unwrap()
+        .set_defaults(&defaults)
+        .unwrap()
+        .set_default("db.host", "db.internal")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_string("db.user").unwrap(), "admin");
+    assert_eq!(c.get_int("db.port").unwrap(), 5432);
+    assert_eq!(c.get_string("db.host").unwrap(), "db.internal");
+}
+
+#[test]
+fn set_default_option() {
+    let workers: Option<i64> = None;
+    let c = Config::builder()
+        .set_default_option("db_host", Some("localhost"))
+        .unwrap()
+        .set_default_option("workers", workers)
+        .unwrap()
+        .set_override_option("workers", workers)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_string("db_host").unwrap(), "localhost");
+    assert!(c.get_int("workers").is_err());
+}
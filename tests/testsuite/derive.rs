@@ -0,0 +1,164 @@
+#![cfg(all(feature = "derive", feature = "env"))]
+
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, ConfigStruct};
+
+fn non_empty(value: &str) -> Result<(), &'static str> {
+    match value.is_empty() {
+        true => Err("must not be empty"),
+        false => Ok(()),
+    }
+}
+
+#[derive(Debug, Deserialize, ConfigStruct)]
+struct Database {
+    #[config(default = "postgres://localhost", validate = non_empty)]
+    url: String,
+    #[config(key = "pool", default = 4, validate = range(1..=64))]
+    #[serde(rename = "pool")]
+    pool_size: u8,
+}
+
+#[derive(Debug, Deserialize, ConfigStruct)]
+struct Settings {
+    #[config(env = "DERIVE_PORT", default = 8080, validate = range(1..65535))]
+    port: u16,
+    #[config(default = 0.5, validate = range(0.0..=1.0))]
+    ratio: f64,
+    debug: bool,
+    #[config(nested)]
+    database: Database,
+}
+
+#[test]
+fn test_derive_defaults_and_env() {
+    let builder = || Config::builder().set_override("debug", true).unwrap();
+
+    temp_env::with_var("DERIVE_PORT", None::<&str>, || {
+        let settings = Settings::load(builder()).unwrap();
+        assert_eq!(settings.port, 8080);
+        assert_eq!(settings.ratio, 0.5);
+        assert!(settings.debug);
+        assert_eq!(settings.database.url, "postgres://localhost");
+        assert_eq!(settings.database.pool_size, 4);
+    });
+
+    temp_env::with_var("DERIVE_PORT", Some("9090"), || {
+        let settings = Settings::load(builder().set_override("port", 1).unwrap()).unwrap();
+        assert_eq!(settings.port, 9090);
+    });
+}
+
+#[test]
+fn test_derive_validate() {
+    temp_env::with_var("DERIVE_PORT", None::<&str>, || {
+        let load = |key: &str, value: config::Value| {
+            let builder = Config::builder()
+                .set_override("debug", false)
+                .unwrap()
+                .set_override(key, value)
+                .unwrap();
+            Settings::load(builder).unwrap_err().to_string()
+        };
+
+        assert_data_eq!(
+            load("port", 0.into()),
+            str!["0 is out of range 1..65535 for key `port`"]
+        );
+        assert_data_eq!(
+            load("ratio", 1.5.into()),
+            str!["1.5 is out of range 0.0..=1.0 for key `ratio`"]
+        );
+        assert_data_eq!(
+            load("database.pool", 65.into()),
+            str!["65 is out of range 1..=64 for key `database.pool`"]
+        );
+        assert_data_eq!(
+            load("database.url", "".into()),
+            str!["must not be empty for key `database.url`"]
+        );
+    });
+}
+
+#[test]
+fn test_derive_try_from() {
+    let config = Config::try_from_iter([
+        ("port", config::Value::from(80)),
+        ("ratio", 0.1.into()),
+        ("debug", true.into()),
+        ("database.url", "sqlite://".into()),
+        ("database.pool", 0.into()),
+    ])
+    .unwrap();
+
+    let error = Settings::try_from(config).unwrap_err();
+    assert_data_eq!(
+        error.to_string(),
+        str!["0 is out of range 1..=64 for key `database.pool`"]
+    );
+
+    let config = Config::builder()
+        .set_override("port", 80)
+        .unwrap()
+        .set_override("ratio", 0.1)
+        .unwrap()
+        .set_override("debug", true)
+        .unwrap()
+        .set_override("database.url", "sqlite://")
+        .unwrap()
+        .set_override("database.pool", 2)
+        .unwrap()
+        .build()
+        .unwrap();
+    let settings: Settings = config.try_into().unwrap();
+    assert_eq!(settings.port, 80);
+    assert_eq!(settings.database.pool_size, 2);
+}
+
+#[derive(Debug, Deserialize, ConfigStruct)]
+struct Listener {
+    #[config(key = "listen_port", env = "DERIVE_LISTEN_PORT", default = 80)]
+    port: u16,
+    #[config(key = "tls", nested)]
+    tls_settings: Tls,
+}
+
+#[derive(Debug, Deserialize, ConfigStruct)]
+struct Tls {
+    #[config(key = "cert", default = "server.pem")]
+    cert_path: String,
+}
+
+#[test]
+fn test_derive_key_without_serde_rename() {
+    temp_env::with_var("DERIVE_LISTEN_PORT", None::<&str>, || {
+        let listener = Listener::load(Config::builder()).unwrap();
+        assert_eq!(listener.port, 80);
+        assert_eq!(listener.tls_settings.cert_path, "server.pem");
+
+        let builder = Config::builder()
+            .set_override("listen_port", 8443)
+            .unwrap()
+            .set_override("tls.cert", "tls.pem")
+            .unwrap();
+        let listener = Listener::load(builder).unwrap();
+        assert_eq!(listener.port, 8443);
+        assert_eq!(listener.tls_settings.cert_path, "tls.pem");
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn test_derive_env_not_unicode() {
+    use std::os::unix::ffi::OsStrExt as _;
+
+    let value = std::ffi::OsStr::from_bytes(b"80\xff");
+    temp_env::with_var("DERIVE_LISTEN_PORT", Some(value), || {
+        let error = Listener::load(Config::builder()).unwrap_err();
+        assert_data_eq!(
+            error.to_string(),
+            str![[r#"env variable "DERIVE_LISTEN_PORT" contains non-Unicode data: "80/xFF""#]]
+        );
+    });
+}
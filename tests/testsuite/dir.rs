@@ -0,0 +1,86 @@
+#![cfg(all(feature = "fs", feature = "json"))]
+
+use std::path::PathBuf;
+
+use config::{Config, Directory, FileFormat};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("config-dir-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_directory() {
+    let dir = scratch_dir("merge");
+    std::fs::write(dir.join("10-base.json"), r#"{"server": {"port": 80}}"#).unwrap();
+    std::fs::write(dir.join("20-host.json"), r#"{"server": {"host": "a"}}"#).unwrap();
+    std::fs::write(dir.join("30-port.json"), r#"{"server": {"port": 8080}}"#).unwrap();
+    std::fs::write(dir.join("notes.txt"), "not a configuration").unwrap();
+    std::fs::create_dir_all(dir.join("nested.json")).unwrap();
+
+    let config = Config::builder()
+        .add_source(Directory::new(&dir))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("server.port").unwrap(), 8080);
+    assert_eq!(config.get_string("server.host").unwrap(), "a");
+
+    let report = config.source_report();
+    assert_eq!(report.loaded().len(), 3);
+    assert!(report.loaded()[0].uri().unwrap().ends_with("10-base.json"));
+    assert_eq!(report.skipped().len(), 1);
+    assert!(report.skipped()[0].ends_with("notes.txt"));
+    assert!(report.failed().is_empty());
+    assert_eq!(config.sources(), report.loaded());
+
+    let config = Config::builder()
+        .add_source(Directory::new(&dir).pattern("?0-*.json"))
+        .register_extension("txt", FileFormat::Json)
+        .build()
+        .unwrap();
+    assert_eq!(config.source_report().loaded().len(), 3);
+    assert!(config.source_report().skipped().is_empty());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_directory_not_required() {
+    let dir = scratch_dir("not-required");
+    std::fs::write(dir.join("10-base.json"), r#"{"debug": true}"#).unwrap();
+    std::fs::write(dir.join("20-broken.json"), "{").unwrap();
+
+    let error = Config::builder()
+        .add_source(Directory::new(&dir))
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("20-broken.json"));
+
+    let config = Config::builder()
+        .add_source(Directory::new(&dir).required(false))
+        .build()
+        .unwrap();
+    assert!(config.get_bool("debug").unwrap());
+    let failed = config.source_report().failed();
+    assert_eq!(failed.len(), 1);
+    assert!(failed[0].0.ends_with("20-broken.json"));
+    assert!(failed[0].1.contains("EOF while parsing an object"));
+
+    let missing = dir.join("missing");
+    let config = Config::builder()
+        .add_source(Directory::new(&missing).required(false))
+        .build()
+        .unwrap();
+    assert!(config.cache.into_table().unwrap().is_empty());
+
+    let error = Config::builder()
+        .add_source(Directory::new(&missing))
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().starts_with("configuration directory"));
+    assert!(error.to_string().contains("which could not be read"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,111 @@
+#![cfg(all(feature = "fs", feature = "json"))]
+
+use std::path::{Path, PathBuf};
+
+use config::{Config, Discovery, FileFormat, HierarchicalFile};
+
+/// A project nested in a workspace, with a settings file in each, and the directory to start from
+fn scratch_tree(name: &str) -> (PathBuf, PathBuf) {
+    let root = std::env::temp_dir().join(format!("config-discovery-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let project = root.join("workspace/project");
+    std::fs::create_dir_all(project.join("src/bin")).unwrap();
+    std::fs::write(
+        root.join(".tool.json"),
+        r#"{"jobs": 1, "color": true, "registry": {"url": "a"}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        project.join(".tool.json"),
+        r#"{"jobs": 8, "registry": {"token": "t"}}"#,
+    )
+    .unwrap();
+    (root, project.join("src/bin"))
+}
+
+#[test]
+fn test_hierarchical_merge() {
+    let (root, start) = scratch_tree("merge");
+
+    let config = Config::builder()
+        .add_source(HierarchicalFile::new(".tool").start(&start).stop_at(&root))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("jobs").unwrap(), 8);
+    assert!(config.get_bool("color").unwrap());
+    assert_eq!(config.get_string("registry.url").unwrap(), "a");
+    assert_eq!(config.get_string("registry.token").unwrap(), "t");
+
+    let loaded = config.source_report().loaded().to_vec();
+    assert_eq!(loaded.len(), 2);
+    assert!(Path::new(loaded[0].uri().unwrap()).ends_with("project/.tool.json"));
+}
+
+#[test]
+fn test_hierarchical_nearest() {
+    let (root, start) = scratch_tree("nearest");
+
+    let config = Config::builder()
+        .add_source(
+            HierarchicalFile::new(".tool.json")
+                .start(&start)
+                .stop_at(&root)
+                .discovery(Discovery::Nearest),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("jobs").unwrap(), 8);
+    assert!(config.get_bool("color").is_err());
+
+    // The search stops at the boundary
+    let config = Config::builder()
+        .add_source(
+            HierarchicalFile::new(".tool")
+                .start(root.join("workspace"))
+                .stop_at(root.join("workspace")),
+        )
+        .build()
+        .unwrap();
+    assert!(config.get_int("jobs").is_err());
+}
+
+#[test]
+fn test_hierarchical_format_and_required() {
+    let (root, start) = scratch_tree("format");
+    std::fs::write(root.join("workspace/.toolrc"), r#"{"jobs": 2}"#).unwrap();
+
+    let config = Config::builder()
+        .add_source(
+            HierarchicalFile::new(".toolrc")
+                .start(&start)
+                .stop_at(&root)
+                .format(FileFormat::Json)
+                .required(true),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("jobs").unwrap(), 2);
+
+    let error = Config::builder()
+        .add_source(
+            HierarchicalFile::new(".missing")
+                .start(&start)
+                .stop_at(&root)
+                .required(true),
+        )
+        .build()
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with(r#"configuration file ".missing" not found in "#),
+        "{error}"
+    );
+
+    std::fs::write(root.join("workspace/.tool.json"), "{").unwrap();
+    let error = Config::builder()
+        .add_source(HierarchicalFile::new(".tool").start(&start).stop_at(&root))
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains(".tool.json"), "{error}");
+}
@@ -0,0 +1,150 @@
+#![cfg(all(feature = "fs", feature = "env", feature = "dotenv"))]
+
+use std::error::Error as _;
+use std::path::PathBuf;
+
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, DotenvStack, Environment};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("config-dotenv-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_dotenv_precedence() {
+    let dir = scratch_dir("precedence");
+    let files = [
+        (".env", "BASE=env\nLOCAL=env\nMODE=env\nMODE_LOCAL=env\n"),
+        (".env.local", "LOCAL=local\nMODE=local\nMODE_LOCAL=local\n"),
+        (
+            ".env.production",
+            "MODE=production\nMODE_LOCAL=production\n",
+        ),
+        (".env.production.local", "MODE_LOCAL=production.local\n"),
+        (".env.test", "MODE=test\n"),
+    ];
+    for (name, content) in files {
+        std::fs::write(dir.join(name), content).unwrap();
+    }
+
+    let config = Config::builder()
+        .add_source(DotenvStack::new(&dir).mode("production"))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_string("base").unwrap(), "env");
+    assert_eq!(config.get_string("local").unwrap(), "local");
+    assert_eq!(config.get_string("mode").unwrap(), "production");
+    assert_eq!(config.get_string("mode_local").unwrap(), "production.local");
+    assert_eq!(config.sources().len(), 4);
+
+    let config = Config::builder()
+        .add_source(DotenvStack::new(&dir))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_string("mode").unwrap(), "local");
+
+    // `.env.local` is left out in tests
+    let config = Config::builder()
+        .add_source(DotenvStack::new(&dir).mode("test"))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_string("local").unwrap(), "env");
+    assert_eq!(config.get_string("mode").unwrap(), "test");
+
+    let missing = Config::builder()
+        .add_source(DotenvStack::new(dir.join("missing")).mode("production"))
+        .build()
+        .unwrap();
+    assert!(missing.sources().is_empty());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_dotenv_syntax() {
+    let dir = scratch_dir("syntax");
+    std::fs::write(
+        dir.join(".env"),
+        r#"
+# A comment
+export APP_NAME=demo # trailing comment
+APP_URL=http://example.com/#anchor
+APP_EMPTY=
+APP_SINGLE='literal $HOME \n # kept'
+APP_DOUBLE="line\nbreak \"quoted\"" # comment
+APP_MULTILINE="first
+second"
+APP_SERVER__PORT = 8080
+OTHER=ignored
+"#,
+    )
+    .unwrap();
+
+    let config = Config::builder()
+        .add_source(
+            DotenvStack::new(&dir).environment(
+                Environment::with_prefix("APP")
+                    .separator("__")
+                    .prefix_separator("_")
+                    .try_parsing(true),
+            ),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(config.get_string("name").unwrap(), "demo");
+    assert_eq!(
+        config.get_string("url").unwrap(),
+        "http://example.com/#anchor"
+    );
+    assert_eq!(config.get_string("empty").unwrap(), "");
+    assert_eq!(
+        config.get_string("single").unwrap(),
+        r"literal $HOME \n # kept"
+    );
+    assert_eq!(
+        config.get_string("double").unwrap(),
+        "line\nbreak \"quoted\""
+    );
+    assert_eq!(config.get_string("multiline").unwrap(), "first\nsecond");
+    assert_eq!(config.get_int("server.port").unwrap(), 8080);
+    assert!(!config.contains_key("other"));
+
+    let server = config.get_table("server").unwrap();
+    assert!(server["port"].origin().unwrap().ends_with(".env"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_dotenv_errors() {
+    let dir = scratch_dir("errors");
+    let error = |content: &str| {
+        std::fs::write(dir.join(".env"), content).unwrap();
+        let error = Config::builder()
+            .add_source(DotenvStack::new(&dir))
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().ends_with(".env"), "{error}");
+        error.source().unwrap().to_string()
+    };
+
+    assert_data_eq!(error("A=1\nB\n"), str!["expected `=` at line 2 column 2"]);
+    assert_data_eq!(
+        error("A=1\nNOT VALID=2\n"),
+        str!["invalid variable name at line 2 column 1"]
+    );
+    assert_data_eq!(
+        error("A=\"open\n"),
+        str!["unterminated double quote at line 1 column 3"]
+    );
+    assert_data_eq!(
+        error("A='x' y\n"),
+        str!["unexpected characters after the closing quote at line 1 column 7"]
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -1,4 +1,4 @@
-use config::Config;
+use config::{Config, File, FileFormat};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,3 +17,45 @@ fn empty_deserializes() {
     assert_eq!(s.foo, 0);
     assert_eq!(s.bar, 0);
 }
+
+#[test]
+#[cfg(feature = "toml")]
+fn empty_tables_as_none() {
+    #[derive(Debug, Deserialize)]
+    struct Settings {
+        tls: Option<Tls>,
+        limits: Limits,
+        proxy: Option<Tls>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Tls {
+        cert: String,
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(default)]
+    struct Limits {
+        rate: u32,
+    }
+
+    let builder = Config::builder().add_source(File::from_str(
+        "[tls]\n[limits]\n[proxy]\ncert = \"proxy.pem\"",
+        FileFormat::Toml,
+    ));
+
+    let res = builder
+        .build_cloned()
+        .unwrap()
+        .try_deserialize::<Settings>();
+    assert!(res.is_err());
+
+    let config = builder.empty_tables_as_none(true).build().unwrap();
+    let tls: Option<Tls> = config.get("tls").unwrap();
+    assert!(tls.is_none());
+
+    let settings: Settings = config.try_deserialize().unwrap();
+    assert!(settings.tls.is_none());
+    assert_eq!(settings.limits.rate, 0);
+    assert_eq!(settings.proxy.unwrap().cert, "proxy.pem");
+}
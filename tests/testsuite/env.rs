@@ -1,7 +1,9 @@
+#![cfg(feature = "env")]
+
 use serde::Deserialize;
 use snapbox::{assert_data_eq, str};
 
-use config::{Config, Environment, Source};
+use config::{Config, ConfigError, Environment, KeyCollision, Source, ValueKind};
 
 /// Reminder that tests using env variables need to use different env variable names, since
 /// tests can be run in parallel
@@ -326,6 +328,63 @@ fn test_parse_off_bool() {
     });
 }
 
+#[test]
+fn test_parse_off_coerce_strings() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(tag = "type", rename_all = "lowercase")]
+    enum Listener {
+        Tcp { port: u16, tls: bool },
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Limit {
+        Enabled(bool),
+        Count(u32),
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Server {
+        listener: Listener,
+        limit: Limit,
+    }
+
+    let server = |port: &str, limit: &str| {
+        let vars = [
+            ("APP_LISTENER__TYPE", "tcp"),
+            ("APP_LISTENER__PORT", port),
+            ("APP_LISTENER__TLS", "true"),
+            ("APP_LIMIT", limit),
+        ];
+        Config::builder()
+            .add_source(
+                Environment::with_source_os(vars)
+                    .prefix("APP")
+                    .prefix_separator("_")
+                    .separator("__"),
+            )
+            .coerce_strings(true)
+            .build()?
+            .try_deserialize::<Server>()
+    };
+
+    let tcp = server("8080", "false").unwrap();
+    assert_eq!(
+        tcp.listener,
+        Listener::Tcp {
+            port: 8080,
+            tls: true
+        }
+    );
+    assert_eq!(tcp.limit, Limit::Enabled(false));
+    assert_eq!(server("8080", "16").unwrap().limit, Limit::Count(16));
+
+    assert_data_eq!(
+        server("http", "16").unwrap_err().to_string(),
+        str![[r#"invalid type: string "http", expected u16 for key `listener.port`"#]]
+    );
+}
+
 #[test]
 #[should_panic(expected = "invalid type: string \"not an int\", expected i32")]
 fn test_parse_int_fail() {
@@ -722,6 +781,424 @@ fn test_parse_uint_default() {
     assert_eq!(config.int_val, 42);
 }
 
+#[test]
+fn test_numeric_keys_as_map() {
+    #[derive(Deserialize, Debug)]
+    struct Settings {
+        divisors: config::Map<u32, u32>,
+    }
+
+    let source = || {
+        let mut env = config::Map::new();
+        env.insert("APP_DIVISORS_1".to_owned(), "1".to_owned());
+        env.insert("APP_DIVISORS_4".to_owned(), "3".to_owned());
+        env
+    };
+
+    let config = Config::builder()
+        .add_source(
+            Environment::with_prefix("APP")
+                .separator("_")
+                .try_parsing(true)
+                .numeric_keys_as_indices(false)
+                .source(Some(source())),
+        )
+        .build()
+        .unwrap();
+
+    let settings: Settings = config.try_deserialize().unwrap();
+    assert_eq!(settings.divisors[&1], 1);
+    assert_eq!(settings.divisors[&4], 3);
+}
+
+#[test]
+fn test_numeric_keys_as_index() {
+    #[derive(Deserialize, Debug)]
+    struct Settings {
+        servers: Vec<Server>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Server {
+        host: String,
+    }
+
+    let mut env = config::Map::new();
+    env.insert("APP_SERVERS_0_HOST".to_owned(), "alpha".to_owned());
+    env.insert("APP_SERVERS_1_HOST".to_owned(), "beta".to_owned());
+
+    let config = Config::builder()
+        .add_source(
+            Environment::with_prefix("APP")
+                .separator("_")
+                .numeric_keys_as_indices(true)
+                .source(Some(env)),
+        )
+        .build()
+        .unwrap();
+
+    let settings: Settings = config.try_deserialize().unwrap();
+    let hosts: Vec<_> = settings.servers.into_iter().map(|s| s.host).collect();
+    assert_eq!(hosts, vec!["alpha", "beta"]);
+}
+
+#[test]
+fn test_numeric_keys_match_builder_paths() {
+    let mut env = config::Map::new();
+    env.insert("A_B_0_C".to_owned(), "env".to_owned());
+
+    let from_env = Config::builder()
+        .numeric_keys_as_indices(true)
+        .add_source(
+            Environment::default()
+                .separator("_")
+                .numeric_keys_as_indices(true)
+                .source(Some(env)),
+        )
+        .build()
+        .unwrap();
+    let from_override = Config::builder()
+        .numeric_keys_as_indices(true)
+        .set_override("a.b.0.c", "env")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let b = |config: &Config| {
+        config
+            .get::<Vec<config::Map<String, String>>>("a.b")
+            .unwrap()
+    };
+    assert_eq!(b(&from_env), b(&from_override));
+    assert_eq!(b(&from_env)[0]["c"], "env");
+    assert_eq!(from_env.get_string("a.b[0].c").unwrap(), "env");
+    assert_eq!(from_override.get_string("a.b.0.c").unwrap(), "env");
+}
+
+#[test]
+fn test_parse_number_notations() {
+    let source = |value: &str| {
+        Environment::default()
+            .try_parsing(true)
+            .source(Some(config::Map::from([("VALUE".into(), value.into())])))
+    };
+    let parse = |environment: Environment| environment.collect().unwrap()["value"].clone();
+
+    assert_eq!(parse(source("0x1F")).into_string().unwrap(), "0x1F");
+    assert_eq!(
+        parse(source("0x1F").parse_hex(true)).into_int().unwrap(),
+        31
+    );
+    assert_eq!(
+        parse(source("-0X1f").parse_hex(true)).into_int().unwrap(),
+        -31
+    );
+    assert_eq!(parse(source("0o755")).into_string().unwrap(), "0o755");
+    assert_eq!(
+        parse(source("0o755").parse_octal(true)).into_int().unwrap(),
+        493
+    );
+    assert_eq!(
+        parse(source("0o9").parse_octal(true))
+            .into_string()
+            .unwrap(),
+        "0o9"
+    );
+
+    assert_eq!(
+        parse(source("1_000_000")).into_string().unwrap(),
+        "1_000_000"
+    );
+    let underscores = |value: &str| parse(source(value).parse_underscores(true).parse_hex(true));
+    assert_eq!(underscores("1_000_000").into_int().unwrap(), 1_000_000);
+    assert_eq!(underscores("0xFF_FF").into_int().unwrap(), 0xFFFF);
+    assert_eq!(underscores("1_000.5").into_float().unwrap(), 1000.5);
+    assert_eq!(underscores("1__000").into_string().unwrap(), "1__000");
+    assert_eq!(underscores("_1000").into_string().unwrap(), "_1000");
+    assert_eq!(underscores("in_f").into_string().unwrap(), "in_f");
+
+    assert_eq!(parse(source("1.5e3")).into_float().unwrap(), 1500.0);
+    assert_eq!(
+        parse(source("1.5e3").parse_scientific(false))
+            .into_string()
+            .unwrap(),
+        "1.5e3"
+    );
+    assert_eq!(
+        parse(source("1.5").parse_scientific(false))
+            .into_float()
+            .unwrap(),
+        1.5
+    );
+}
+
+#[test]
+fn test_preserve_leading_zeros() {
+    let parse = |value: &str, preserve: bool| {
+        Environment::default()
+            .try_parsing(true)
+            .preserve_leading_zeros(preserve)
+            .source(Some(config::Map::from([("VALUE".into(), value.into())])))
+            .collect()
+            .unwrap()["value"]
+            .kind
+            .clone()
+    };
+
+    for value in ["007", "+4912345", "-007", "00.5", "+1.5"] {
+        assert_eq!(
+            parse(value, true),
+            ValueKind::String(value.into()),
+            "{value}"
+        );
+    }
+    assert_eq!(parse("007", false), ValueKind::I64(7));
+    assert_eq!(parse("+4912345", false), ValueKind::I64(4912345));
+
+    assert_eq!(parse("0", true), ValueKind::I64(0));
+    assert_eq!(parse("-12", true), ValueKind::I64(-12));
+    assert_eq!(parse("0.5", true), ValueKind::Float(0.5));
+    assert_eq!(parse("true", true), ValueKind::Boolean(true));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_refresh_source() {
+    use config::{File, FileFormat};
+
+    temp_env::with_var("REFRESH__FLAGS__BETA", Some("false"), || {
+        let mut config = Config::builder()
+            .add_source(File::from_str(
+                r#"{ "flags": { "beta": true, "alpha": true } }"#,
+                FileFormat::Json,
+            ))
+            .add_source(Environment::with_prefix("REFRESH").separator("__"))
+            .set_override("flags.gamma", true)
+            .unwrap()
+            .refreshable(true)
+            .build()
+            .unwrap();
+        assert_eq!(config.get_string("flags.beta").unwrap(), "false");
+
+        temp_env::with_var("REFRESH__FLAGS__BETA", Some("true"), || {
+            config.refresh_source::<Environment>().unwrap();
+        });
+        assert_eq!(config.get_string("flags.beta").unwrap(), "true");
+        assert!(config.get_bool("flags.alpha").unwrap());
+        assert!(config.get_bool("flags.gamma").unwrap());
+
+        config.refresh_source::<Environment>().unwrap();
+        assert_eq!(config.get_string("flags.beta").unwrap(), "false");
+
+        let mut config = Config::builder()
+            .add_source(Environment::with_prefix("REFRESH").separator("__"))
+            .build()
+            .unwrap();
+        assert_data_eq!(
+            config
+                .refresh_source::<Environment>()
+                .unwrap_err()
+                .to_string(),
+            str!["configuration keeps no sources to refresh, build it with `refreshable(true)`"]
+        );
+    });
+}
+
+#[test]
+fn test_freeze_subtree() {
+    temp_env::with_vars([("FREEZE__SECURITY__TLS", Some("true"))], || {
+        let mut config = Config::builder()
+            .add_source(Environment::with_prefix("FREEZE").separator("__"))
+            .refreshable(true)
+            .build()
+            .unwrap();
+        config.freeze_subtree("security").unwrap();
+        config.freeze_subtree("security.audit").unwrap();
+
+        temp_env::with_vars(
+            [
+                ("FREEZE__SECURITY__TLS", Some("true")),
+                ("FREEZE__LOG__LEVEL", Some("debug")),
+            ],
+            || config.refresh_source::<Environment>().unwrap(),
+        );
+        assert_eq!(config.get_string("log.level").unwrap(), "debug");
+
+        let error = temp_env::with_var("FREEZE__SECURITY__TLS", Some("false"), || {
+            config.refresh_source::<Environment>().unwrap_err()
+        });
+        assert_data_eq!(
+            error.to_string(),
+            str!["configuration is frozen for key `security`"]
+        );
+        assert_eq!(config.get_string("security.tls").unwrap(), "true");
+        assert_eq!(config.get_string("log.level").unwrap(), "debug");
+
+        let error = temp_env::with_vars(
+            [
+                ("FREEZE__SECURITY__TLS", Some("true")),
+                ("FREEZE__SECURITY__AUDIT", Some("off")),
+            ],
+            || config.refresh_source::<Environment>().unwrap_err(),
+        );
+        assert_data_eq!(
+            error.to_string(),
+            str!["configuration is frozen for key `security`"]
+        );
+        assert!(!config.contains_key("security.audit"));
+    });
+}
+
+#[test]
+#[cfg(feature = "fs")]
+fn test_file_indirection() {
+    let dir = std::env::temp_dir().join(format!("config-file-indirection-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let secret = dir.join("db_password");
+    std::fs::write(&secret, "hunter2\n").unwrap();
+    let secret = secret.to_str().unwrap().to_owned();
+    let missing = dir.join("missing").to_str().unwrap().to_owned();
+
+    let environment = |vars: &[(&str, &str)]| {
+        Environment::with_prefix("APP")
+            .separator("__")
+            .file_indirection(true)
+            .source(Some(
+                vars.iter()
+                    .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect(),
+            ))
+    };
+
+    let config = Config::builder()
+        .add_source(environment(&[
+            ("APP__DB__PASSWORD", "ignored"),
+            ("APP__DB__PASSWORD_FILE", &secret),
+            ("APP__DB__USER", "admin"),
+        ]))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_string("db.password").unwrap(), "hunter2");
+    assert_eq!(config.get_string("db.user").unwrap(), "admin");
+
+    let error = Config::builder()
+        .add_source(environment(&[("APP__DB__PASSWORD_FILE", &missing)]))
+        .build()
+        .unwrap_err();
+    assert_eq!(error.kind(), config::ErrorKind::Io);
+    assert!(
+        error.to_string().starts_with(&format!(
+            "env variable \"APP__DB__PASSWORD_FILE\" points to {missing:?}, which could not be read"
+        )),
+        "{error}"
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_layered_prefixes_share_environment() {
+    temp_env::with_vars(
+        [
+            ("Layer_A__PORT", Some("1")),
+            ("LAYER_B__PORT", Some("2")),
+            ("LAYER_B__HOST", Some("b")),
+            ("LAYERED__PORT", Some("3")),
+        ],
+        || {
+            let config = Config::builder()
+                .add_source(Environment::with_prefix("layer_a").separator("__"))
+                .add_source(Environment::with_prefix("LAYER_B").separator("__"))
+                .add_source(
+                    Environment::with_prefix("LAYER_A")
+                        .separator("__")
+                        .keep_prefix(true),
+                )
+                .build()
+                .unwrap();
+
+            assert_eq!(config.get_string("port").unwrap(), "2");
+            assert_eq!(config.get_string("host").unwrap(), "b");
+            assert_eq!(config.get_string("layer_a.port").unwrap(), "1");
+            assert!(config.get_string("ed__port").is_err());
+        },
+    );
+}
+
+#[test]
+fn test_key_collision() {
+    let env = || {
+        Environment::from_closure(|| {
+            [
+                ("COLLIDE_LOG", "debug"),
+                ("COLLIDE_LOG_LEVEL", "info"),
+                ("COLLIDE_LOG_LEVEL_COLOR", "true"),
+                ("COLLIDE_PORT", "8080"),
+            ]
+        })
+        .prefix("COLLIDE")
+        .separator("_")
+    };
+
+    let vars = env().collect().unwrap();
+    let mut keys: Vec<_> = vars.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["log.level.color", "port"]);
+
+    let vars = env()
+        .key_collision(KeyCollision::PreferOuter)
+        .collect()
+        .unwrap();
+    let mut keys: Vec<_> = vars.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["log", "port"]);
+
+    let error = env()
+        .key_collision(KeyCollision::Error)
+        .collect()
+        .unwrap_err();
+    assert_data_eq!(
+        error.to_string(),
+        str![[
+            r#"env variable "COLLIDE_LOG" sets key "log", colliding with "log.level" set by "COLLIDE_LOG_LEVEL""#
+        ]]
+    );
+}
+
+#[test]
+fn test_require_keys() {
+    let env = || {
+        Environment::from_closure(|| {
+            [
+                ("REQUIRE_PORT", "8080"),
+                ("REQUIRE_TOKEN", ""),
+                ("REQUIRE_DB_URL", "postgres://localhost"),
+            ]
+        })
+        .prefix("REQUIRE")
+        .separator("_")
+    };
+
+    let vars = env()
+        .require_keys(["port", "DB_URL", "TOKEN"])
+        .collect()
+        .unwrap();
+    assert_eq!(
+        vars["db.url"].clone().into_string().unwrap(),
+        "postgres://localhost"
+    );
+
+    let error = env()
+        .ignore_empty(true)
+        .require_keys(["PORT", "TOKEN", "HOST"])
+        .collect()
+        .unwrap_err();
+    assert_data_eq!(
+        error.to_string(),
+        str!["missing required environment variables: REQUIRE_TOKEN, REQUIRE_HOST"]
+    );
+}
+
 #[cfg(any(unix, windows))]
 #[cfg(test)]
 mod unicode_tests {
@@ -803,4 +1280,333 @@ mod unicode_tests {
             },
         );
     }
+
+    #[test]
+    fn test_source_os() {
+        let mut invalid_name = OsString::from("APP_");
+        invalid_name.push(make_invalid_unicode_os_string());
+        let mut other_name = OsString::from("OTHER_");
+        other_name.push(make_invalid_unicode_os_string());
+        let vars = vec![
+            (OsString::from("APP_PORT"), OsString::from("8080")),
+            (other_name.clone(), OsString::from("skipped")),
+            (
+                OsString::from("OTHER_VALUE"),
+                make_invalid_unicode_os_string(),
+            ),
+        ];
+
+        let config = Config::builder()
+            .add_source(Environment::with_source_os(vars.clone()).prefix("APP"))
+            .build()
+            .unwrap();
+        assert_eq!(config.get_string("port").unwrap(), "8080");
+        assert!(config.get_table("other_value").is_err());
+
+        let mut vars = vars;
+        vars.push((invalid_name.clone(), OsString::from("1")));
+        let result = Environment::default()
+            .prefix("app")
+            .source_os(vars)
+            .collect();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!("env variable {invalid_name:?} has a name that is not valid Unicode")
+        );
+    }
+}
+
+#[test]
+fn test_snapshot() {
+    let env = temp_env::with_var("SNAPSHOT_PORT", Some("8080"), || {
+        Environment::snapshot().prefix("SNAPSHOT")
+    });
+
+    temp_env::with_var("SNAPSHOT_PORT", Some("9090"), || {
+        let config = Config::builder().add_source(env).build().unwrap();
+        assert_eq!(config.get_string("port").unwrap(), "8080");
+    });
+
+    let env = Environment::from_closure(|| [("SNAPSHOT_HOST", "localhost")]).prefix("SNAPSHOT");
+    let config = Config::builder().add_source(env).build().unwrap();
+    assert_eq!(config.get_string("host").unwrap(), "localhost");
+}
+
+#[test]
+fn test_parallel_collection() {
+    temp_env::with_vars(
+        [
+            ("PARALLEL_A_PORT", Some("1")),
+            ("PARALLEL_B_HOST", Some("b")),
+        ],
+        || {
+            let config = Config::builder()
+                .collect_in_parallel(true)
+                .add_source(Environment::with_prefix("PARALLEL_A"))
+                .add_source(Environment::with_prefix("PARALLEL_B"))
+                .build()
+                .unwrap();
+
+            assert_eq!(config.get_int("port").unwrap(), 1);
+            assert_eq!(config.get_string("host").unwrap(), "b");
+        },
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_expanded_path() {
+    #[derive(Deserialize, Debug)]
+    struct Settings {
+        data: config::ExpandedPath,
+        cache: config::ExpandedPath,
+        literal: config::ExpandedPath,
+    }
+
+    let load = || {
+        Config::builder()
+            .set_default("data", "~/.local/share/$EXPANDED_APP")
+            .unwrap()
+            .set_default("cache", "${EXPANDED_CACHE:-/tmp}/${EXPANDED_APP}")
+            .unwrap()
+            .set_default("literal", "~other/%EXPANDED_APP%")
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize::<Settings>()
+    };
+
+    temp_env::with_vars(
+        [
+            ("HOME", Some("/home/alice")),
+            ("EXPANDED_APP", Some("demo")),
+            ("EXPANDED_CACHE", None),
+        ],
+        || {
+            let settings = load().unwrap();
+            assert_eq!(
+                settings.data.into_path_buf(),
+                std::path::PathBuf::from("/home/alice/.local/share/demo")
+            );
+            assert_eq!(&*settings.cache, std::path::Path::new("/tmp/demo"));
+            assert_eq!(
+                &*settings.literal,
+                std::path::Path::new("~other/%EXPANDED_APP%")
+            );
+        },
+    );
+
+    temp_env::with_var("HOME", None::<&str>, || {
+        assert_data_eq!(
+            load().unwrap_err().to_string(),
+            str![[
+                r#"cannot expand path "~/.local/share/$EXPANDED_APP": `~` needs the home directory, but HOME is not set for key `data`"#
+            ]]
+        );
+    });
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_append_suffix() {
+    use config::{File, FileFormat};
+
+    let build = |env: &[(&str, &str)], environment: fn(Environment) -> Environment| {
+        let vars = env
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect();
+        Config::builder()
+            .add_source(File::from_str(
+                r#"{"plugins": ["auth", "cache"], "hosts": []}"#,
+                FileFormat::Json,
+            ))
+            .add_source(environment(
+                Environment::with_prefix("APP")
+                    .separator("__")
+                    .append_suffix("APPEND")
+                    .source(Some(vars)),
+            ))
+            .build()
+            .unwrap()
+    };
+
+    let config = build(
+        &[
+            ("APP__PLUGINS__APPEND", "metrics,tracing"),
+            ("APP__HOSTS__APPEND", "a"),
+        ],
+        |env| env,
+    );
+    let plugins: Vec<String> = config.get("plugins").unwrap();
+    assert_eq!(plugins, ["auth", "cache", "metrics,tracing"]);
+    let hosts: Vec<String> = config.get("hosts").unwrap();
+    assert_eq!(hosts, ["a"]);
+
+    let config = build(
+        &[
+            ("APP__PLUGINS__APPEND", "metrics,tracing"),
+            ("APP__NAME", "demo"),
+        ],
+        |env| {
+            env.try_parsing(true)
+                .list_separator(",")
+                .with_list_parse_key("plugins")
+        },
+    );
+    let plugins: Vec<String> = config.get("plugins").unwrap();
+    assert_eq!(plugins, ["auth", "cache", "metrics", "tracing"]);
+    assert_eq!(config.get_string("name").unwrap(), "demo");
+
+    let config = build(
+        &[
+            ("APP__PLUGINS__0", "sso"),
+            ("APP__PLUGINS__APPEND", "metrics"),
+        ],
+        |env| env.numeric_keys_as_indices(true),
+    );
+    let plugins: Vec<String> = config.get("plugins").unwrap();
+    assert_eq!(plugins, ["sso", "cache", "metrics"]);
+
+    // Without the suffix, the key is set like any other
+    let config = build(&[("APP__APPEND", "yes")], |env| env);
+    assert_eq!(config.get_string("append").unwrap(), "yes");
+}
+
+#[test]
+#[cfg(feature = "fs")]
+fn test_auto() {
+    temp_env::with_vars(
+        [("AUTO_APP__SERVER__PORT", Some("8080")), ("RUN_MODE", None)],
+        || {
+            let config = Config::auto("auto-app").unwrap();
+            assert_eq!(config.get_int("server.port").unwrap(), 8080);
+        },
+    );
+
+    temp_env::with_var("RUN_MODE", Some("../secrets"), || {
+        assert_data_eq!(
+            Config::auto("auto-app").unwrap_err().to_string(),
+            str![[
+                r#"invalid file name template "config/{mode}": variable "mode" is not a single path component: "../secrets""#
+            ]]
+        );
+    });
+}
+
+#[test]
+fn test_keep_existing_types() {
+    let vars = config::Map::from([
+        ("APP_PORT".to_owned(), "9090".to_owned()),
+        ("APP_RATIO".to_owned(), "0.5".to_owned()),
+        ("APP_TLS__ENABLED".to_owned(), "on".to_owned()),
+        ("APP_ZIP".to_owned(), "007".to_owned()),
+        ("APP_NAME".to_owned(), "app".to_owned()),
+    ]);
+    let build = |keep: bool| {
+        Config::builder()
+            .set_default("port", 8080)?
+            .set_default("ratio", 1.0)?
+            .set_default("tls.enabled", false)?
+            .set_default("zip", "12345")?
+            .add_source(
+                Environment::with_prefix("APP")
+                    .prefix_separator("_")
+                    .separator("__")
+                    .keep_existing_types(keep)
+                    .source(Some(vars.clone())),
+            )
+            .build()
+    };
+    let kind = |config: &Config, key: &str| config.get::<config::Value>(key).unwrap().kind;
+
+    let config = build(true).unwrap();
+    assert_eq!(kind(&config, "port"), ValueKind::I64(9090));
+    assert_eq!(kind(&config, "ratio"), ValueKind::Float(0.5));
+    assert_eq!(kind(&config, "tls.enabled"), ValueKind::Boolean(true));
+    assert_eq!(kind(&config, "zip"), ValueKind::String("007".into()));
+    assert_eq!(kind(&config, "name"), ValueKind::String("app".into()));
+
+    let config = build(false).unwrap();
+    assert_eq!(kind(&config, "port"), ValueKind::String("9090".into()));
+
+    let error = Config::builder()
+        .set_default("tls.enabled", false)
+        .unwrap()
+        .add_source(
+            Environment::with_prefix("APP")
+                .prefix_separator("_")
+                .separator("__")
+                .keep_existing_types(true)
+                .source(Some(config::Map::from([(
+                    "APP_TLS__ENABLED".to_owned(),
+                    "maybe".to_owned(),
+                )]))),
+        )
+        .build()
+        .unwrap_err();
+    assert_data_eq!(
+        error.to_string(),
+        str![[
+            r#"invalid type: string "maybe", expected a boolean for key `tls.enabled` in the environment"#
+        ]]
+    );
+}
+
+#[test]
+fn test_with_parser() {
+    let cidrs = |text: &str| -> Result<config::Value, ConfigError> {
+        text.split_whitespace()
+            .map(|block| match block.split_once('/') {
+                Some((addr, bits)) if bits.parse::<u8>().is_ok_and(|bits| bits <= 32) => {
+                    Ok(format!("{addr}/{bits}"))
+                }
+                _ => Err(ConfigError::Message(format!(
+                    "invalid CIDR block {block:?}"
+                ))),
+            })
+            .collect::<Result<Vec<String>, _>>()
+            .map(config::Value::from)
+    };
+    let env = |vars: &[(&str, &str)]| {
+        Environment::with_source_os(vars.iter().copied())
+            .prefix("APP")
+            .separator("__")
+            .try_parsing(true)
+            .with_parser("net.allow", |_| Ok(config::Value::from("replaced")))
+            .with_parser("net.allow", cidrs)
+            .with_parser("name", |text| Ok(config::Value::from(text.trim())))
+    };
+
+    let config = Config::builder()
+        .add_source(env(&[
+            ("APP__NET__ALLOW", "10.0.0.0/8 192.168.0.0/16"),
+            ("APP__NET__PORT", "8080"),
+            ("APP__NAME", " 42 "),
+        ]))
+        .build()
+        .unwrap();
+    assert_eq!(
+        config.get::<Vec<String>>("net.allow").unwrap(),
+        ["10.0.0.0/8", "192.168.0.0/16"]
+    );
+    assert_eq!(
+        config.get::<config::Value>("net.port").unwrap().kind,
+        ValueKind::I64(8080)
+    );
+    // The parser applies instead of `try_parsing`
+    assert_eq!(
+        config.get::<config::Value>("name").unwrap().kind,
+        ValueKind::String("42".to_owned())
+    );
+
+    let error = env(&[("APP__NET__ALLOW", "10.0.0.0/8 10.0.0.1")])
+        .collect()
+        .unwrap_err();
+    assert_data_eq!(
+        error.to_string(),
+        str![[
+            r#"invalid value for env variable "APP__NET__ALLOW": invalid CIDR block "10.0.0.1""#
+        ]]
+    );
 }
@@ -111,6 +111,7 @@ fn test_get_invalid_type() {
 }
 
 #[test]
+#[cfg(feature = "fs")]
 #[cfg(feature = "json")]
 fn test_get_invalid_type_file() {
     let c = Config::builder()
@@ -163,6 +164,7 @@ fn test_get_missing_field() {
 }
 
 #[test]
+#[cfg(feature = "fs")]
 #[cfg(feature = "json")]
 fn test_get_missing_field_file() {
     #[derive(Debug, Deserialize)]
@@ -299,7 +301,27 @@ fn test_value_deserialize_enum() {
     let on_d = on_v.try_deserialize::<Diode>();
     assert_data_eq!(
         on_d.unwrap_err().to_string(),
-        str!["enum Diode does not have variant constructor on"]
+        str![
+            "enum Diode does not have variant constructor on, expected one of `Off`, `Brightness`, `Blinking`, `Pattern`"
+        ]
+    );
+
+    let brightness_v: Value = "Brightness".into();
+    let brightness_d = brightness_v.try_deserialize::<Diode>();
+    assert_data_eq!(
+        brightness_d.unwrap_err().to_string(),
+        str![
+            "variant Brightness of enum Diode holds data, so it should be represented by a table with Brightness as its only key"
+        ]
+    );
+
+    let pattern_v: Value = "Pattern".into();
+    let pattern_d = pattern_v.try_deserialize::<Diode>();
+    assert_data_eq!(
+        pattern_d.unwrap_err().to_string(),
+        str![
+            "variant Pattern of enum Diode holds data, so it should be represented by a table with Pattern as its only key"
+        ]
     );
 
     let array_v: Value = vec![100, 100].into();
@@ -375,6 +397,62 @@ fn test_deserialize_invalid_type() {
 
 #[test]
 #[cfg(feature = "json")]
+fn test_deserialize_enum_invalid_type() {
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "kind", rename_all = "lowercase")]
+    enum Upstream {
+        Hosts {
+            #[allow(dead_code)]
+            hosts: Vec<Host>,
+        },
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Host {
+        #[allow(dead_code)]
+        port: u16,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Output {
+        #[allow(dead_code)]
+        upstream: Upstream,
+        #[serde(flatten)]
+        labels: Map<String, String>,
+    }
+
+    let output = |port: &str| {
+        let json = format!(
+            r#"
+{{
+  "upstream": {{
+    "kind": "hosts",
+    "hosts": [{{ "port": 8080 }}, {{ "port": {port} }}]
+  }},
+  "region": "1"
+}}
+"#
+        );
+        Config::builder()
+            .add_source(File::from_str(&json, FileFormat::Json))
+            .build()?
+            .try_deserialize::<Output>()
+    };
+
+    let labels = output("8081").unwrap().labels;
+    assert_eq!(labels.get("region").map(String::as_str), Some("1"));
+
+    let res = output(r#""http""#);
+    let e = res.unwrap_err();
+    assert_data_eq!(
+        e.to_string(),
+        str![[r#"invalid type: string "http", expected u16 for key `upstream.hosts[1].port`"#]]
+    );
+}
+
+#[test]
+#[cfg(feature = "fs")]
+#[cfg(feature = "json")]
 fn test_deserialize_invalid_type_file() {
     #[derive(Deserialize, Debug)]
     struct Place {
@@ -451,6 +529,7 @@ fn test_deserialize_missing_field() {
 }
 
 #[test]
+#[cfg(feature = "fs")]
 #[cfg(feature = "json")]
 fn test_deserialize_missing_field_file() {
     #[derive(Debug, Deserialize)]
@@ -481,3 +560,279 @@ fn test_deserialize_missing_field_file() {
         str![[r#"missing configuration field "inner.value2""#]]
     );
 }
+
+#[test]
+#[cfg(feature = "json")]
+fn test_get_not_found_suggestion() {
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+    "database": { "url": "postgres://localhost", "pool": [{ "size": 1 }] }
+}
+        "#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    let err = c.get::<String>("databse.url").unwrap_err();
+    assert_eq!(err.suggestions(), ["database.url"]);
+    assert_data_eq!(
+        err.to_string(),
+        str![[r#"missing configuration field "databse.url", did you mean "database.url"?"#]]
+    );
+
+    let res = c.get::<u32>("database.pool[0].sise");
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[
+            r#"missing configuration field "database.pool[0].sise", did you mean "database.pool[0].size"?"#
+        ]]
+    );
+
+    let res = c.get::<String>("cache.url");
+    assert!(matches!(res.unwrap_err(), ConfigError::NotFound(_)));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_deserialize_missing_field_suggestion() {
+    #[derive(Debug, Deserialize)]
+    struct Settings {
+        #[allow(dead_code)]
+        inner: InnerSettings,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct InnerSettings {
+        #[allow(dead_code)]
+        timeout: u32,
+    }
+
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+    "inner": { "timout": 42 }
+}
+        "#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    let res = c.try_deserialize::<Settings>();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[r#"missing configuration field "inner.timeout", did you mean "inner.timout"?"#]]
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_error_kind() {
+    use config::ErrorKind;
+
+    #[derive(Debug, Deserialize)]
+    struct Settings {
+        #[allow(dead_code)]
+        port: u16,
+        #[allow(dead_code)]
+        host: String,
+    }
+
+    let parse = Config::builder()
+        .add_source(File::from_str("{ ", FileFormat::Json))
+        .build()
+        .unwrap_err();
+    assert_eq!(parse.kind(), ErrorKind::Parse);
+
+    let c = Config::builder()
+        .add_source(File::from_str(r#"{ "port": "http" }"#, FileFormat::Json))
+        .build()
+        .unwrap();
+    assert_eq!(
+        c.get_int("missing").unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+    assert_eq!(c.get_int("port").unwrap_err().kind(), ErrorKind::Type);
+    assert_eq!(c.get_int("a[").unwrap_err().kind(), ErrorKind::Parse);
+    assert_eq!(
+        c.try_deserialize::<Settings>().unwrap_err().kind(),
+        ErrorKind::Type
+    );
+
+    let io = ConfigError::Foreign(Box::new(std::io::Error::other("denied")));
+    assert_eq!(io.kind(), ErrorKind::Io);
+    assert_eq!(
+        ConfigError::Message("invalid".into()).kind(),
+        ErrorKind::Validation
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_error_source_chain() {
+    use std::error::Error as _;
+
+    let parse = Config::builder()
+        .add_source(File::from_str("{ ", FileFormat::Json))
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        parse.source().unwrap().to_string(),
+        "EOF while parsing an object at line 1 column 2"
+    );
+
+    let c = Config::builder()
+        .add_source(File::from_str(r#"{ "port": "http" }"#, FileFormat::Json))
+        .build()
+        .unwrap();
+    assert!(c.get_int("port").unwrap_err().source().is_none());
+    let error = ConfigError::Message("invalid".into()).extend_with_key("port");
+    let cause = error.source().unwrap();
+    assert_eq!(cause.to_string(), "invalid");
+    assert!(cause.source().is_none());
+
+    let read = ConfigError::SourceRead {
+        origin: "sidecar configuration".into(),
+        uri: Some("/dev/shm/app".into()),
+        cause: Box::new(std::io::Error::from(std::io::ErrorKind::NotFound)),
+    };
+    assert_eq!(read.kind(), config::ErrorKind::Io);
+    assert_eq!(
+        read.source()
+            .unwrap()
+            .downcast_ref::<std::io::Error>()
+            .unwrap()
+            .kind(),
+        std::io::ErrorKind::NotFound
+    );
+    assert_data_eq!(
+        read.to_string(),
+        str![[r#"sidecar configuration "/dev/shm/app", which could not be read"#]]
+    );
+}
+
+#[test]
+#[cfg(all(feature = "fs", feature = "json"))]
+fn test_error_kind_missing_file() {
+    let res = Config::builder()
+        .add_source(File::new(
+            "tests/testsuite/file-nonexistent",
+            FileFormat::Json,
+        ))
+        .build();
+    assert_eq!(res.unwrap_err().kind(), config::ErrorKind::Io);
+}
+
+#[test]
+#[cfg(all(feature = "path-to-error", feature = "json"))]
+fn test_path_to_error() {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Server {
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    enum Mode {
+        Tcp(Server),
+    }
+
+    // Only one invalid field, as which of several fails first depends on the order of the table
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Settings {
+        mode: Mode,
+    }
+
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+  "mode": { "Tcp": { "port": "x" } },
+  "limits": [{ "1": "low" }, { "admin": "high" }]
+}
+"#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    let res = c.get::<Vec<HashMap<u32, String>>>("limits");
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[
+            r#"invalid map key "admin": expected an unsigned 32 bit integer for key `limits[1]`"#
+        ]]
+    );
+
+    let res = c.try_deserialize::<Settings>();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[r#"invalid type: string "x", expected an integer for key `mode.Tcp.port`"#]]
+    );
+}
+
+#[test]
+fn test_deferred_errors() {
+    let config = Config::builder()
+        .defer_errors()
+        .set_default("server.port", 80)
+        .set_default_option("server.host", Some("localhost"))
+        .set_override("server.port", 8080)
+        .map(|builder| builder.sort_keys(true))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("server.port").unwrap(), 8080);
+    assert_eq!(config.get_string("server.host").unwrap(), "localhost");
+
+    let error = Config::builder()
+        .defer_errors()
+        .set_default("a[", 1)
+        .set_default("valid", 1)
+        .set_override_option("b..c", Some(2))
+        .set_override_option("d..e", None::<i64>)
+        .with_overrides_from_pairs([("f", 3), ("g[", 4)])
+        .build()
+        .unwrap_err();
+    assert_eq!(error.kind(), config::ErrorKind::Parse);
+    assert_data_eq!(
+        error.to_string(),
+        str![[r#"
+3 configuration errors
+
+a[
+  ^
+invalid subscript
+expected integer
+
+b..c
+  ^
+invalid identifier
+expected ASCII alphanumeric, `_`, `-`
+
+g[
+  ^
+invalid subscript
+expected integer
+"#]]
+    );
+    let ConfigError::Multiple(errors) = error else {
+        panic!("expected several errors");
+    };
+    assert_eq!(errors.len(), 3);
+
+    // A single error is reported as is
+    let error = Config::builder()
+        .defer_errors()
+        .set_default("a[", 1)
+        .finish()
+        .unwrap_err();
+    assert!(matches!(error, ConfigError::PathParse { .. }));
+}
@@ -1,6 +1,9 @@
+#![cfg(feature = "fs")]
+
+use serde::Deserialize;
 use snapbox::{assert_data_eq, str};
 
-use config::{Config, File, FileFormat};
+use config::{Config, Decryptor, File, FileFormat, Source as _};
 
 #[test]
 #[cfg(feature = "json")]
@@ -90,3 +93,372 @@ fn test_file_second_ext() {
     assert_eq!(c.get("debug").ok(), Some(true));
     assert_eq!(c.get("production").ok(), Some(false));
 }
+
+#[derive(Debug)]
+struct Reversed;
+
+impl Decryptor for Reversed {
+    fn decrypt(
+        &self,
+        _uri: Option<&String>,
+        content: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        match content.strip_prefix("ENC:") {
+            Some(content) => Ok(content.chars().rev().collect()),
+            None => Err("content is not encrypted".into()),
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_decrypt() {
+    let c = Config::builder()
+        .add_source(File::from_str(r#"ENC:}eurt :"gubed"{"#, FileFormat::Json).decrypt(Reversed))
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get("debug").ok(), Some(true));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_decrypt_round_trip() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Secrets {
+        user: String,
+        password: String,
+        port: u16,
+    }
+
+    let plain = r#"{ "secrets": { "user": "admin", "password": "hunter2", "port": 5432 } }"#;
+    let dir = std::env::temp_dir().join(format!("config-decrypt-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let encrypted = format!("ENC:{}", plain.chars().rev().collect::<String>());
+    std::fs::write(dir.join("secrets.json"), &encrypted).unwrap();
+
+    let c = Config::builder()
+        .add_source(File::with_name(dir.join("secrets").to_str().unwrap()).decrypt(Reversed))
+        .build()
+        .unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+
+    let secrets: Secrets = c.get("secrets").unwrap();
+    assert_eq!(
+        secrets,
+        Secrets {
+            user: "admin".to_owned(),
+            password: "hunter2".to_owned(),
+            port: 5432,
+        }
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_decrypt_error() {
+    let res = Config::builder()
+        .add_source(File::new("tests/testsuite/file-auto", FileFormat::Json).decrypt(Reversed))
+        .build();
+
+    assert!(res.is_err());
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["content is not encrypted in tests/testsuite/file-auto.json"]
+    );
+}
+
+#[test]
+#[cfg(all(feature = "json", feature = "toml"))]
+fn test_file_auto_ambiguous() {
+    let res = Config::builder()
+        .add_source(File::with_name("tests/testsuite/file-ambiguous").required(false))
+        .build();
+
+    assert!(res.is_err());
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[
+            r#"configuration file "tests/testsuite/file-ambiguous" is ambiguous, found "file-ambiguous.toml", "file-ambiguous.json""#
+        ]]
+    );
+
+    let c = Config::builder()
+        .add_source(File::new(
+            "tests/testsuite/file-ambiguous",
+            FileFormat::Toml,
+        ))
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get("debug").ok(), Some(true));
+}
+
+#[test]
+#[cfg(all(unix, feature = "json"))]
+fn test_file_refuse_unsafe() {
+    use std::os::unix::fs::{PermissionsExt as _, symlink};
+
+    let dir = std::env::temp_dir().join(format!("config-refuse-unsafe-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("settings.json");
+    let link = dir.join("link.json");
+    std::fs::write(&path, r#"{ "debug": true }"#).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o666)).unwrap();
+    let _ = std::fs::remove_file(&link);
+    symlink(&path, &link).unwrap();
+
+    let build = |file: File<_, _>| Config::builder().add_source(file).build();
+    let path = path.to_str().unwrap();
+    let link = link.to_str().unwrap();
+
+    assert!(build(File::new(link, FileFormat::Json)).is_ok());
+    let res = build(
+        File::new(link, FileFormat::Json)
+            .refuse_symlinks(true)
+            .required(false),
+    );
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        format!("configuration file \"{link}\" is a symbolic link")
+    );
+
+    let res = build(File::new(path, FileFormat::Json).refuse_world_writable(true));
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        format!("configuration file \"{path}\" is writable by any user")
+    );
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).unwrap();
+    let c = build(
+        File::new(path, FileFormat::Json)
+            .refuse_symlinks(true)
+            .refuse_world_writable(true),
+    )
+    .unwrap();
+    assert_eq!(c.get("debug").ok(), Some(true));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_fragment() {
+    let c = Config::builder()
+        .add_source(File::with_name_and_fragment(
+            "tests/testsuite/file-fragment#server",
+        ))
+        .add_source(
+            File::with_name_and_fragment("tests/testsuite/file-fragment.json#server.tls")
+                .required(false),
+        )
+        .add_source(
+            File::with_name_and_fragment("tests/testsuite/file-fragment#missing").required(false),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(c.get_int("port").unwrap(), 8080);
+    assert!(c.get_bool("enabled").unwrap());
+    assert!(c.get_bool("tls.enabled").unwrap());
+    assert!(c.get_array("clients").is_err());
+
+    let res = Config::builder()
+        .add_source(File::with_name("tests/testsuite/file-fragment#server"))
+        .build();
+    assert!(res.is_err());
+
+    let res = Config::builder()
+        .add_source(File::with_name_and_fragment(
+            "tests/testsuite/file-fragment#missing",
+        ))
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[r#"missing configuration field "missing" in tests/testsuite/file-fragment.json"#]]
+    );
+
+    let res = Config::builder()
+        .add_source(
+            File::new("tests/testsuite/file-fragment", FileFormat::Json).fragment("clients"),
+        )
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[
+            r#"invalid type: sequence, expected a map for key `clients` in tests/testsuite/file-fragment.json"#
+        ]]
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_from_reader() {
+    let contents = b"\xef\xbb\xbf{ \"debug\": true, \"port\": 8080 }".as_slice();
+    let builder = Config::builder().add_source(File::from_reader(contents, FileFormat::Json));
+
+    let c = builder.build_cloned().unwrap();
+    assert_eq!(c.get("debug").ok(), Some(true));
+
+    // The stream was read to its end, later builds reuse its contents
+    let c = builder.build().unwrap();
+    assert_eq!(c.get("port").ok(), Some(8080));
+
+    let res = Config::builder()
+        .add_source(File::from_reader(b"{".as_slice(), FileFormat::Json))
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["EOF while parsing an object at line 1 column 1"]
+    );
+
+    use std::error::Error as _;
+
+    struct Broken;
+
+    impl std::io::Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    // Failures are reported as the I/O error of the stream, on each build
+    let builder = Config::builder().add_source(File::from_reader(Broken, FileFormat::Json));
+    for _ in 0..2 {
+        let error = builder.build_cloned().unwrap_err();
+        assert_eq!(error.kind(), config::ErrorKind::Io);
+        let cause = error.source().unwrap().downcast_ref::<std::io::Error>();
+        assert_eq!(cause.unwrap().kind(), std::io::ErrorKind::BrokenPipe);
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_sources() {
+    let builder = Config::builder()
+        .set_default("debug", false)
+        .unwrap()
+        .add_source(File::with_name("tests/testsuite/file-auto"))
+        .add_source(File::with_name("tests/testsuite/file-nonexistent").required(false))
+        .add_source(File::from_str(r#"{"port": 8080}"#, FileFormat::Json).with_prefix("server"));
+
+    let c = builder.build_cloned().unwrap();
+    let sources = c.sources();
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources[0].uri(), Some("tests/testsuite/file-auto.json"));
+    assert!(sources[0].modified().is_some());
+    assert_eq!(sources[1].uri(), None);
+    assert_eq!(sources[1].modified(), None);
+    assert_ne!(sources[0].content_hash(), sources[1].content_hash());
+
+    // Unchanged files are reported the same on rebuild
+    assert_eq!(builder.build().unwrap().sources(), sources);
+    assert!(Config::default().sources().is_empty());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_max_size() {
+    let c = Config::builder()
+        .add_source(File::with_name("tests/testsuite/file-auto").max_size(1024))
+        .build()
+        .unwrap();
+    assert_eq!(c.get("debug").ok(), Some(true));
+
+    let res = Config::builder()
+        .add_source(
+            File::with_name("tests/testsuite/file-auto")
+                .max_size(8)
+                .required(false),
+        )
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[
+            r#"configuration file "tests/testsuite/file-auto.json" is larger than the limit of 8 bytes"#
+        ]]
+    );
+
+    let builder = Config::builder().add_source(
+        File::from_reader(br#"{"debug": true}"#.as_slice(), FileFormat::Json).max_size(8),
+    );
+    let res = builder.build_cloned();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["configuration is larger than the limit of 8 bytes"]
+    );
+    assert!(builder.build().is_err());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_default_format() {
+    let res = Config::builder()
+        .add_source(File::with_name("tests/testsuite/file-extensionless"))
+        .build();
+    assert!(
+        res.unwrap_err()
+            .to_string()
+            .ends_with("file-extensionless\" is not of a supported file format")
+    );
+
+    let c = Config::builder()
+        .add_source(
+            File::with_name("tests/testsuite/file-extensionless").default_format(FileFormat::Json),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(c.get("debug").ok(), Some(true));
+
+    #[cfg(feature = "toml")]
+    {
+        let c = Config::builder()
+            .add_source(
+                File::with_name("tests/testsuite/file-ext").default_format(FileFormat::Toml),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(c.get("debug").ok(), Some(true));
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_file_extensions() {
+    let dir = std::env::temp_dir().join(format!("config-extensions-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("app.conf"), r#"{ "debug": true }"#).unwrap();
+    std::fs::write(dir.join("other.json"), r#"{ "debug": true }"#).unwrap();
+    let app = dir.join("app");
+    let app = app.to_str().unwrap();
+    let other = dir.join("other");
+    let other = other.to_str().unwrap();
+
+    let res = Config::builder().add_source(File::with_name(app)).build();
+    assert!(res.unwrap_err().to_string().ends_with("app\" not found"));
+
+    let c = Config::builder()
+        .register_extension(".conf", FileFormat::Json)
+        .add_source(File::with_name(app))
+        .add_source(File::with_name(&format!("{app}.conf")))
+        .collect_in_parallel(true)
+        .build()
+        .unwrap();
+    assert_eq!(c.get("debug").ok(), Some(true));
+
+    let res = Config::builder()
+        .forbid_extension("json")
+        .add_source(File::with_name(other))
+        .build();
+    assert!(res.unwrap_err().to_string().ends_with("other\" not found"));
+
+    let res = Config::builder()
+        .forbid_extension("json")
+        .add_source(File::with_name(&format!("{other}.json")).required(false))
+        .build();
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        format!("configuration file \"{other}.json\" has a forbidden extension")
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "dotenv")]
+
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, File, FileFormat, ParseOptions};
+
+#[test]
+fn test_file() {
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+# Not mapped to keys, unlike with `DotenvStack`
+export PORT=8080
+DATABASE__HOST='db.local'
+GREETING="hello\nworld" # comment
+"#,
+            FileFormat::Dotenv,
+        ))
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_string("PORT").unwrap(), "8080");
+    assert_eq!(c.get_int("PORT").unwrap(), 8080);
+    assert_eq!(c.get_string("DATABASE__HOST").unwrap(), "db.local");
+    assert_eq!(c.get_string("GREETING").unwrap(), "hello\nworld");
+}
+
+#[test]
+fn test_error() {
+    let res = Config::builder()
+        .add_source(File::from_str("PORT=8080\nHOST\n", FileFormat::Dotenv))
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["expected `=` at line 2 column 5"]
+    );
+}
+
+#[test]
+fn test_deny_duplicate_keys() {
+    let res = Config::builder()
+        .add_source(
+            File::from_str("PORT=80\nHOST=a\nexport PORT=8080\n", FileFormat::Dotenv)
+                .parse_options(ParseOptions::default().deny_duplicate_keys(true)),
+        )
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["duplicate variable at line 3 column 1"]
+    );
+}
@@ -4,7 +4,7 @@ use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
 use snapbox::{assert_data_eq, str};
 
-use config::{Config, File, FileFormat};
+use config::{Config, File, FileFormat, ParseOptions, Value, ValueKind};
 
 #[test]
 fn test_file() {
@@ -80,6 +80,7 @@ error
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -144,6 +145,7 @@ rating = 4.5
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -186,6 +188,7 @@ rating = 4.5
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -212,11 +215,14 @@ bar = "bar is a lowercase param"
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -243,7 +249,9 @@ bar = "bar is a lowercase param"
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
@@ -264,3 +272,155 @@ fn ini() {
     let date: DateTime<Utc> = s.get("ini_datetime").unwrap();
     assert_eq!(date, Utc.with_ymd_and_hms(2017, 5, 10, 2, 14, 53).unwrap());
 }
+
+#[test]
+fn test_parse_options_escape() {
+    let text = r#"
+path = C:\Windows
+name = "quoted"
+"#;
+
+    let cfg = Config::builder()
+        .add_source(File::from_str(text, FileFormat::Ini))
+        .build()
+        .unwrap();
+    assert_eq!(cfg.get_string("path").unwrap(), "C:Windows");
+    assert_eq!(cfg.get_string("name").unwrap(), "quoted");
+
+    let cfg = Config::builder()
+        .add_source(
+            File::from_str(text, FileFormat::Ini)
+                .parse_options(ParseOptions::default().ini_escape(false).ini_quote(false)),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(cfg.get_string("path").unwrap(), r"C:\Windows");
+    assert_eq!(cfg.get_string("name").unwrap(), r#""quoted""#);
+}
+
+#[test]
+fn test_parse_options_typed_values() {
+    let text = r#"
+debug = true
+
+[server]
+port = 8080
+ratio = 0.5
+name = web
+
+[server.tls]
+enabled = FALSE
+version = "1.3"
+"#;
+
+    let c = Config::builder()
+        .add_source(
+            File::from_str(text, FileFormat::Ini)
+                .parse_options(ParseOptions::default().ini_typed_values(true)),
+        )
+        .build()
+        .unwrap();
+    assert!(c.get::<bool>("debug").unwrap());
+    assert_eq!(c.get::<i64>("server.port").unwrap(), 8080);
+    assert_eq!(c.get::<f64>("server.ratio").unwrap(), 0.5);
+    assert_eq!(c.get_string("server.name").unwrap(), "web");
+    assert!(!c.get::<bool>("server.tls.enabled").unwrap());
+    assert_eq!(c.get::<f64>("server.tls.version").unwrap(), 1.3);
+
+    let c = Config::builder()
+        .add_source(File::from_str(text, FileFormat::Ini))
+        .build()
+        .unwrap();
+    assert_eq!(c.get_string("server.tls.enabled").unwrap(), "FALSE");
+    assert_eq!(
+        c.get::<Value>("server.port").unwrap().kind,
+        ValueKind::String("8080".into())
+    );
+}
+
+#[test]
+fn test_nested_sections() {
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+[server.tls]
+enabled = true
+
+[server]
+port = 8080
+
+[server.tls]
+version = 1.3
+"#,
+            FileFormat::Ini,
+        ))
+        .build()
+        .unwrap();
+    assert_eq!(c.get_string("server.port").unwrap(), "8080");
+    assert_eq!(c.get_string("server.tls.enabled").unwrap(), "true");
+    assert_eq!(c.get_string("server.tls.version").unwrap(), "1.3");
+}
+
+#[test]
+fn test_subsections() {
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+[core]
+editor = vim
+
+[remote "origin"]
+url = https://github.com/rust-cli/config-rs.git
+
+[branch "release/1.x"]
+remote = origin
+
+[includeIf "gitdir:~/work/"]
+path = .gitconfig-work
+
+[url "git@github.com:"]
+insteadOf = https://github.com/
+
+[alias]
+lg = log --graph "--format=%h %s"
+"#,
+            FileFormat::Ini,
+        ))
+        .build()
+        .unwrap();
+    assert_eq!(c.get_string("core.editor").unwrap(), "vim");
+    assert_eq!(
+        c.get_string("remote.origin.url").unwrap(),
+        "https://github.com/rust-cli/config-rs.git"
+    );
+
+    let branches = c.get_table("branch").unwrap();
+    assert_eq!(
+        branches["release/1.x"].clone().into_table().unwrap()["remote"]
+            .clone()
+            .into_string()
+            .unwrap(),
+        "origin"
+    );
+    let include = c.get_table("includeIf").unwrap();
+    assert!(include.contains_key("gitdir:~/work/"));
+    let urls = c.get_table("url").unwrap();
+    assert!(urls.contains_key("git@github.com:"));
+}
+
+#[test]
+fn test_deny_duplicate_keys() {
+    let res = Config::builder()
+        .add_source(
+            File::from_str(
+                "[server]\nport = 80\nhost = a\nport = 8080\n",
+                FileFormat::Ini,
+            )
+            .parse_options(ParseOptions::default().deny_duplicate_keys(true)),
+        )
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["duplicate key `port` in section `server` at line 4"]
+    );
+}
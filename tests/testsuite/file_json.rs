@@ -5,7 +5,7 @@ use float_cmp::ApproxEqUlps;
 use serde::Deserialize;
 use snapbox::{assert_data_eq, str};
 
-use config::{Config, File, FileFormat, Map, Value};
+use config::{Config, File, FileFormat, Map, ParseOptions, Value};
 
 #[test]
 fn test_file() {
@@ -119,6 +119,7 @@ fn test_error_parse() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -195,6 +196,7 @@ fn test_override_uppercase_value_for_struct() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -247,6 +249,7 @@ fn test_override_lowercase_value_for_struct() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -275,11 +278,14 @@ fn test_override_uppercase_value_for_enums() {
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -308,7 +314,9 @@ fn test_override_lowercase_value_for_enums() {
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
@@ -331,3 +339,106 @@ fn json() {
     let date: DateTime<Utc> = s.get("json_datetime").unwrap();
     assert_eq!(date, Utc.with_ymd_and_hms(2017, 5, 10, 2, 14, 53).unwrap());
 }
+
+#[test]
+fn test_parse_options_max_depth() {
+    let text = r#"
+{
+  "place": {
+    "name": "Torre di Pisa",
+    "tags": ["tower"]
+  }
+}
+"#;
+
+    let res = Config::builder()
+        .add_source(
+            File::from_str(text, FileFormat::Json)
+                .parse_options(ParseOptions::default().max_depth(2)),
+        )
+        .build();
+    assert!(res.is_ok());
+
+    let res = Config::builder()
+        .add_source(
+            File::from_str(text, FileFormat::Json)
+                .parse_options(ParseOptions::default().max_depth(1)),
+        )
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["nesting exceeds the maximum depth of 1"]
+    );
+}
+
+#[test]
+fn test_deny_duplicate_keys() {
+    let text = r#"
+{
+    "server": { "port": 80 },
+    "debug": false,
+    "server": {
+        "host": "localhost",
+        "host": "example.com"
+    }
+}
+"#;
+
+    let c = Config::builder()
+        .add_source(File::from_str(text, FileFormat::Json))
+        .build()
+        .unwrap();
+    assert_eq!(c.get_string("server.host").unwrap(), "example.com");
+
+    let res = Config::builder()
+        .add_source(
+            File::from_str(text, FileFormat::Json)
+                .parse_options(ParseOptions::default().deny_duplicate_keys(true)),
+        )
+        .build();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["duplicate key `server` at line 5 column 12"]
+    );
+}
+
+#[test]
+fn test_record_spans() {
+    let c = Config::builder()
+        .add_source(
+            File::from_str(
+                r#"{
+  "server": {
+    "host": "é\"x",
+    "ports": [80, 443]
+  },
+  "debug": true, "debug": false
+}"#,
+                FileFormat::Json,
+            )
+            .parse_options(ParseOptions::default().record_spans(true)),
+        )
+        .build()
+        .unwrap();
+
+    let root = c.cache.into_table().unwrap();
+    let server = root["server"].clone().into_table().unwrap();
+    let ports = server["ports"].clone().into_array().unwrap();
+    let span = |value: &Value| value.span().unwrap().to_string();
+    assert_eq!(span(&root["server"]), "2:13-5:4");
+    assert_eq!(span(&server["host"]), "3:13-3:19");
+    assert_eq!(span(&server["ports"]), "4:14-4:23");
+    assert_eq!(span(&ports[1]), "4:19-4:22");
+
+    let debug = root["debug"].span().unwrap();
+    assert_eq!((debug.start().line(), debug.start().column()), (6, 27));
+
+    let c = Config::builder()
+        .add_source(File::from_str(r#"{"debug": true}"#, FileFormat::Json))
+        .build()
+        .unwrap();
+    let other = c.cache.into_table().unwrap();
+    assert_eq!(other["debug"].span(), None);
+    assert_eq!(other["debug"], Value::new(None, true));
+    assert_eq!(root["debug"], Value::new(None, false));
+}
@@ -127,6 +127,7 @@ fn test_error_parse() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -203,6 +204,7 @@ fn test_override_uppercase_value_for_struct() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -256,6 +258,7 @@ fn test_override_lowercase_value_for_struct() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -284,11 +287,14 @@ fn test_override_uppercase_value_for_enums() {
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -317,7 +323,9 @@ fn test_override_lowercase_value_for_enums() {
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
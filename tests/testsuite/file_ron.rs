@@ -122,6 +122,7 @@ fn test_error_parse() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -198,6 +199,7 @@ fn test_override_uppercase_value_for_struct() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -251,6 +253,7 @@ fn test_override_lowercase_value_for_struct() {
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -279,11 +282,14 @@ fn test_override_uppercase_value_for_enums() {
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -312,7 +318,9 @@ fn test_override_lowercase_value_for_enums() {
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
@@ -335,3 +343,68 @@ fn ron() {
     let date: DateTime<Utc> = s.get("ron_datetime").unwrap();
     assert_eq!(date, Utc.with_ymd_and_hms(2021, 4, 19, 11, 33, 2).unwrap());
 }
+
+#[test]
+fn test_ron_parsing_scalar_keys() {
+    #[derive(Debug, Deserialize)]
+    struct Keys {
+        ints: std::collections::HashMap<u32, String>,
+        bools: std::collections::HashMap<bool, String>,
+        floats: std::collections::HashMap<String, String>,
+    }
+
+    let config = Config::builder()
+        .add_source(File::from_str(
+            r#"
+(
+    ints: { 1: "one", 2: "two" },
+    bools: { true: "yes", false: "no" },
+    floats: { 1.5: "one and a half" },
+)
+"#,
+            FileFormat::Ron,
+        ))
+        .build()
+        .unwrap()
+        .try_deserialize::<Keys>()
+        .unwrap();
+    assert_eq!(config.ints[&2], "two");
+    assert_eq!(config.bools[&false], "no");
+    assert_eq!(config.floats["1.5"], "one and a half");
+}
+
+#[test]
+fn test_ron_parsing_unsupported_key() {
+    let result = Config::builder()
+        .add_source(File::from_str(
+            r#"
+(
+    inner_vec: { [1, 2]: "unsupported" },
+)
+"#,
+            FileFormat::Ron,
+        ))
+        .build();
+    assert_data_eq!(
+        result.unwrap_err().to_string(),
+        str!["cannot use array key, only scalar keys are supported at `inner_vec`"]
+    );
+}
+
+#[test]
+fn test_ron_parsing_error_has_key_path() {
+    let result = Config::builder()
+        .add_source(File::from_str(
+            r#"
+(
+    limits: [(max: 1), (max: { [1]: 2 })],
+)
+"#,
+            FileFormat::Ron,
+        ))
+        .build();
+    assert_data_eq!(
+        result.unwrap_err().to_string(),
+        str!["cannot use array key, only scalar keys are supported at `limits[1].max`"]
+    );
+}
@@ -5,7 +5,7 @@ use float_cmp::ApproxEqUlps;
 use serde::Deserialize;
 use snapbox::{assert_data_eq, str};
 
-use config::{Config, File, FileFormat, Map, Value};
+use config::{Config, File, FileFormat, Map, ParseOptions, Value};
 
 #[test]
 fn test_file() {
@@ -169,6 +169,7 @@ invalid boolean, expected `true`
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -282,6 +283,7 @@ down = 1
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -372,6 +374,7 @@ down = 1
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -398,11 +401,14 @@ bar = "bar is a lowercase param"
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -429,7 +435,9 @@ bar = "bar is a lowercase param"
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
@@ -450,3 +458,33 @@ fn toml() {
     let date: DateTime<Utc> = s.get("toml_datetime").unwrap();
     assert_eq!(date, Utc.with_ymd_and_hms(2017, 5, 11, 14, 55, 15).unwrap());
 }
+
+#[test]
+fn test_record_spans() {
+    let c = Config::builder()
+        .add_source(
+            File::from_str(
+                r#"
+title = "é"
+released = 1979-05-27
+
+[server]
+ports = [80, 443]
+"#,
+                FileFormat::Toml,
+            )
+            .parse_options(ParseOptions::default().record_spans(true)),
+        )
+        .build()
+        .unwrap();
+
+    let root = c.cache.into_table().unwrap();
+    let span = |value: &Value| value.span().unwrap().to_string();
+    assert_eq!(span(&root["title"]), "2:9-2:12");
+    assert_eq!(span(&root["released"]), "3:12-3:22");
+
+    let server = root["server"].clone().into_table().unwrap();
+    assert_eq!(span(&server["ports"]), "6:9-6:18");
+    let ports = server["ports"].clone().into_array().unwrap();
+    assert_eq!(span(&ports[1]), "6:14-6:17");
+}
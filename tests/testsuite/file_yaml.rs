@@ -7,7 +7,7 @@ use float_cmp::ApproxEqUlps;
 use serde::Deserialize;
 use snapbox::{assert_data_eq, str};
 
-use config::{Config, File, FileFormat, Map, Value};
+use config::{Config, File, FileFormat, Map, ParseOptions, Value, YamlDocuments};
 
 #[test]
 fn test_file() {
@@ -155,6 +155,7 @@ inner_string:
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -227,6 +228,7 @@ bar: I am bar
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_struct() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct StructSettings {
@@ -275,6 +277,7 @@ bar: I am bar
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_uppercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -301,11 +304,14 @@ bar: bar is a lowercase param
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
 #[test]
+#[cfg(feature = "env")]
 fn test_override_lowercase_value_for_enums() {
     #[derive(Debug, Deserialize, PartialEq)]
     enum EnumSettings {
@@ -332,7 +338,9 @@ bar: bar is a lowercase param
     assert!(param.is_err());
     assert_data_eq!(
         param.unwrap_err().to_string(),
-        str!["enum EnumSettings does not have variant constructor bar"]
+        str![
+            "enum EnumSettings does not have variant constructor bar, expected one of `Bar` (variants are case-sensitive, did you mean `Bar`?)"
+        ]
     );
 }
 
@@ -371,7 +379,29 @@ inner_vec:
     assert_data_eq!(
         result.unwrap_err().to_string(),
         str![
-            "Cannot parse Array([Integer(1), Integer(2)]) because it is an unsupported hash key type"
+            "Cannot parse Array([Integer(1), Integer(2)]) because it is an unsupported hash key type at `inner_vec`"
+        ]
+    );
+}
+
+#[test]
+fn test_yaml_parsing_error_has_key_path() {
+    let result = Config::builder()
+        .add_source(File::from_str(
+            r#"
+servers:
+  - name: a
+  - name: b
+    labels:
+      [1, 2]: "unsupported"
+"#,
+            FileFormat::Yaml,
+        ))
+        .build();
+    assert_data_eq!(
+        result.unwrap_err().to_string(),
+        str![
+            "Cannot parse Array([Integer(1), Integer(2)]) because it is an unsupported hash key type at `servers[1].labels`"
         ]
     );
 }
@@ -423,3 +453,79 @@ inner_float:
     assert_eq!(config.inner_float.get("0.1").unwrap(), "float 0.1");
     assert_eq!(config.inner_float.get("0.2").unwrap(), "float 0.2");
 }
+
+const MULTI_DOCUMENT: &str = r#"
+kind: Service
+metadata:
+  name: web
+---
+---
+metadata:
+  labels:
+    app: web
+"#;
+
+#[test]
+fn test_yaml_multiple_documents_rejected() {
+    let res = Config::builder()
+        .add_source(File::from_str(MULTI_DOCUMENT, FileFormat::Yaml))
+        .build();
+
+    assert!(res.is_err());
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["Got 3 YAML documents, expected 1"]
+    );
+}
+
+#[test]
+fn test_yaml_multiple_documents_merged() {
+    let config = Config::builder()
+        .add_source(
+            File::from_str(MULTI_DOCUMENT, FileFormat::Yaml)
+                .parse_options(ParseOptions::default().yaml_documents(YamlDocuments::Merge)),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(config.get_string("kind").unwrap(), "Service");
+    assert_eq!(config.get_string("metadata.name").unwrap(), "web");
+    assert_eq!(config.get_string("metadata.labels.app").unwrap(), "web");
+}
+
+#[test]
+fn test_yaml_multiple_documents_array() {
+    let config = Config::builder()
+        .add_source(
+            File::from_str(MULTI_DOCUMENT, FileFormat::Yaml).parse_options(
+                ParseOptions::default().yaml_documents(YamlDocuments::Array("resources".into())),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(config.get_array("resources").unwrap().len(), 3);
+    assert_eq!(config.get_string("resources[0].kind").unwrap(), "Service");
+    assert!(config.get_table("resources[1]").is_err());
+    assert_eq!(
+        config
+            .get_string("resources[2].metadata.labels.app")
+            .unwrap(),
+        "web"
+    );
+}
+
+#[test]
+fn test_yaml_parsing_null_hash() {
+    let config = Config::builder()
+        .add_source(File::from_str(
+            r#"
+inner_null:
+    ~: "null key"
+"#,
+            FileFormat::Yaml,
+        ))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_string("inner_null.null").unwrap(), "null key");
+}
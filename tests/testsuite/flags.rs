@@ -0,0 +1,103 @@
+use std::sync::{Arc, Mutex};
+
+use config::{Config, FeatureFlags};
+
+fn flags(pairs: &[(&str, &str)]) -> FeatureFlags {
+    FeatureFlags::new(config(pairs))
+}
+
+fn config(pairs: &[(&str, &str)]) -> Config {
+    Config::builder()
+        .with_overrides_from_pairs(pairs.iter().copied())
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_flags_default_off() {
+    let flags = flags(&[
+        ("features.on", "on"),
+        ("features.off", "false"),
+        ("features.invalid", "maybe"),
+        ("features.disabled.enabled", "no"),
+        ("features.disabled.rollout", "100"),
+        ("features.full.rollout", "100%"),
+        ("features.bad_rollout.rollout", "most"),
+    ]);
+
+    assert!(flags.is_enabled("on"));
+    assert!(flags.is_enabled("full"));
+    for flag in ["off", "invalid", "disabled", "bad_rollout", "missing"] {
+        assert!(!flags.is_enabled(flag), "{flag}");
+        assert!(!flags.is_enabled_for(flag, "user-1"), "{flag}");
+    }
+}
+
+#[test]
+fn test_flags_rollout() {
+    let subjects: Vec<String> = (0..10_000).map(|user| format!("user-{user}")).collect();
+    let enabled = |rollout: &str| -> Vec<&String> {
+        let flags = flags(&[("features.beta.rollout", rollout)]);
+        assert!(!flags.is_enabled("beta"));
+        subjects
+            .iter()
+            .filter(|user| flags.is_enabled_for("beta", user))
+            .collect()
+    };
+
+    let some = enabled("10");
+    let more = enabled(" 50 % ");
+    assert!((900..1100).contains(&some.len()), "{}", some.len());
+    assert!((4800..5200).contains(&more.len()), "{}", more.len());
+    assert!(some.iter().all(|user| more.contains(user)));
+    assert!(enabled("0").is_empty());
+    assert!(enabled("-5").is_empty());
+
+    // Other flags are rolled out to other subjects
+    let flags = flags(&[
+        ("features.beta.rollout", "10"),
+        ("features.gamma.rollout", "10"),
+    ]);
+    let both = subjects
+        .iter()
+        .filter(|user| flags.is_enabled_for("beta", user) && flags.is_enabled_for("gamma", user))
+        .count();
+    assert!(both < 300, "{both}");
+}
+
+#[test]
+fn test_flags_get() {
+    let flags = flags(&[
+        ("settings.checkout.variant", "compact"),
+        ("settings.checkout.limit", "3"),
+    ])
+    .table("settings");
+
+    assert_eq!(flags.get::<String>("checkout.variant"), "compact");
+    assert_eq!(flags.get::<u32>("checkout.limit"), 3);
+    assert_eq!(flags.get::<u32>("checkout.variant"), 0);
+    assert_eq!(flags.get::<Option<String>>("missing"), None);
+    assert!(!flags.is_enabled("checkout.variant"));
+}
+
+#[test]
+fn test_flags_subscribe() {
+    let notified = Arc::new(Mutex::new(Vec::new()));
+    let mut flags = flags(&[("features.search", "false"), ("features.debug", "true")]);
+    let log = notified.clone();
+    flags.subscribe("search", move |flags| {
+        log.lock().unwrap().push(flags.is_enabled("search"));
+    });
+
+    flags.update(config(&[
+        ("features.search", "false"),
+        ("features.debug", "false"),
+    ]));
+    assert!(notified.lock().unwrap().is_empty());
+
+    flags.update(config(&[("features.search", "true")]));
+    flags.update(config(&[("features.search.rollout", "50")]));
+    flags.update(config(&[]));
+    assert_eq!(*notified.lock().unwrap(), [true, false, false]);
+}
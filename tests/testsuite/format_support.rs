@@ -0,0 +1,133 @@
+use std::error::Error;
+
+use snapbox::{assert_data_eq, str};
+
+use config::format_support::{
+    LineIndex, Segment, check_depth, extract_root_table, scalar_key, within,
+};
+use config::{Config, File, FileStoredFormat, Format, Map, ParseOptions, Value, ValueKind};
+
+/// A format whose documents are a single value: a list of `key=value` pairs separated by `;`,
+/// or a bare integer
+#[derive(Debug, Clone)]
+struct Pairs;
+
+impl Format for Pairs {
+    fn parse(
+        &self,
+        uri: Option<&String>,
+        text: &str,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+        self.parse_with_options(uri, text, &ParseOptions::default())
+    }
+
+    fn parse_with_options(
+        &self,
+        uri: Option<&String>,
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+        let root = match text.parse::<i64>() {
+            Ok(value) => Value::new(uri, value),
+            Err(_) => Value::new(uri, pairs(uri, text, options)?),
+        };
+        let root = extract_root_table(uri, root)?;
+        check_depth(&root, options)?;
+        Ok(root)
+    }
+}
+
+fn pairs(
+    uri: Option<&String>,
+    text: &str,
+    options: &ParseOptions,
+) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    let index = LineIndex::new(text);
+    let mut table = Map::new();
+    let mut offset = 0;
+    for pair in text.split(';') {
+        let (key, value) = pair.split_once('=').ok_or("missing `=`")?;
+        let key = match key.parse::<i64>() {
+            Ok(key) => scalar_key(Value::new(uri, key))?,
+            Err(_) if key.starts_with('[') => scalar_key(Value::new(uri, Vec::<Value>::new()))?,
+            Err(_) => key.to_owned(),
+        };
+        let mut value = match value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+            Some(inner) => Value::new(
+                uri,
+                pairs(uri, inner, options).map_err(|err| within(err, Segment::Key(&key)))?,
+            ),
+            None => Value::new(uri, value),
+        };
+        if options.records_spans() {
+            value.set_span(index.span(offset..offset + pair.len()));
+        }
+        offset += pair.len() + 1;
+        table.insert(key, value);
+    }
+    Ok(table)
+}
+
+impl FileStoredFormat for Pairs {
+    fn file_extensions(&self) -> &'static [&'static str] {
+        &["pairs"]
+    }
+}
+
+fn build(text: &str, options: ParseOptions) -> Result<Config, config::ConfigError> {
+    Config::builder()
+        .add_source(File::from_str(text, Pairs).parse_options(options))
+        .build()
+}
+
+#[test]
+fn test_custom_format() {
+    let config = build(
+        "name=app;1=one;db={host=localhost}",
+        ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(config.get_string("name").unwrap(), "app");
+    assert_eq!(config.get_string("1").unwrap(), "one");
+    assert_eq!(config.get_string("db.host").unwrap(), "localhost");
+}
+
+#[test]
+fn test_custom_format_errors() {
+    let error = build("42", ParseOptions::default()).unwrap_err();
+    assert_data_eq!(
+        error.to_string(),
+        str!["invalid type: 64-bit integer `42`, expected a map"]
+    );
+
+    let error = build("db={x={[k]=1}}", ParseOptions::default()).unwrap_err();
+    assert_data_eq!(
+        error.to_string(),
+        str!["cannot use array key, only scalar keys are supported at `db.x`"]
+    );
+
+    let error = build("a={b={c=1}}", ParseOptions::default().max_depth(1)).unwrap_err();
+    assert_data_eq!(
+        error.to_string(),
+        str!["nesting exceeds the maximum depth of 1"]
+    );
+}
+
+#[test]
+fn test_custom_format_spans() {
+    let config = build("a=1;bb=2", ParseOptions::default()).unwrap();
+    assert_eq!(config.cache.into_table().unwrap()["bb"].span(), None);
+
+    let config = build("a=1;bb=2", ParseOptions::default().record_spans(true)).unwrap();
+    let table = config.cache.into_table().unwrap();
+    let span = table["bb"].span().unwrap();
+    assert_eq!(
+        (
+            span.start().line(),
+            span.start().column(),
+            span.end().column()
+        ),
+        (1, 5, 9)
+    );
+    assert!(matches!(table["a"].kind, ValueKind::String(_)));
+}
@@ -551,3 +551,634 @@ fn test_int_key() {
     assert_eq!(s.divisors[&4], 3);
     assert_eq!(s.divisors.len(), 4);
 }
+
+#[test]
+#[cfg(feature = "json")]
+fn test_get_section() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+  "debug": true,
+  "server": {
+    "host": "localhost",
+    "port": 8080
+  },
+  "database": {
+    "url": "postgres://localhost"
+  },
+  "cache": null
+}
+"#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    let mut sections = c.sections();
+    sections.sort_unstable();
+    assert_eq!(sections, vec!["database", "server"]);
+
+    let server: Option<Server> = c.get_section("server").unwrap();
+    assert_eq!(
+        server,
+        Some(Server {
+            host: "localhost".to_owned(),
+            port: 8080,
+        })
+    );
+
+    assert_eq!(c.get_section::<Server>("metrics").unwrap(), None);
+    assert_eq!(c.get_section::<Server>("cache").unwrap(), None);
+
+    let res = c.get_section::<Server>("database");
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[r#"missing configuration field "database.host""#]]
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_get_section_with_options() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        port: u16,
+        tls: Option<Tls>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Tls {
+        cert: String,
+    }
+
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+  "server": {
+    "port": 8080,
+    "tls": {}
+  }
+}
+"#,
+            FileFormat::Json,
+        ))
+        .empty_tables_as_none(true)
+        .build()
+        .unwrap();
+
+    let server: Option<Server> = c.get_section("server").unwrap();
+    assert_eq!(
+        server,
+        Some(Server {
+            port: 8080,
+            tls: None,
+        })
+    );
+}
+
+#[test]
+#[cfg(all(feature = "json", feature = "yaml", feature = "ron"))]
+fn test_null_and_missing() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Settings {
+        server: Server,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        port: u16,
+        proxy: Option<String>,
+        #[serde(default)]
+        workers: u32,
+    }
+
+    let sources = [
+        (
+            r#"{"server": {"port": 80, "proxy": null, "workers": 4}}"#,
+            r#"{"server": {"port": 80}}"#,
+            FileFormat::Json,
+        ),
+        (
+            "server:\n  port: 80\n  proxy: ~\n  workers: 4",
+            "server:\n  port: 80",
+            FileFormat::Yaml,
+        ),
+        (
+            "(server: (port: 80, proxy: None, workers: 4))",
+            "(server: (port: 80))",
+            FileFormat::Ron,
+        ),
+    ];
+    for (null, missing, format) in sources {
+        let build = |text| {
+            Config::builder()
+                .add_source(File::from_str(text, format))
+                .build()
+                .unwrap()
+        };
+        let null = build(null);
+        let missing = build(missing);
+
+        // An explicit null is `None` either way
+        let expected = Settings {
+            server: Server {
+                port: 80,
+                proxy: None,
+                workers: 4,
+            },
+        };
+        assert_eq!(null.get::<Option<String>>("server.proxy").unwrap(), None);
+        assert_eq!(
+            null.get_strict::<Server>("server").unwrap(),
+            expected.server
+        );
+        assert_eq!(
+            null.clone().try_deserialize_strict::<Settings>().unwrap(),
+            expected
+        );
+        assert_eq!(null.try_deserialize::<Settings>().unwrap(), expected);
+
+        // A missing key defaults unless strict
+        assert!(missing.get::<Option<String>>("server.proxy").is_err());
+        let settings: Settings = missing.clone().try_deserialize().unwrap();
+        assert_eq!(settings.server.proxy, None);
+        assert_eq!(settings.server.workers, 0);
+        assert_data_eq!(
+            missing
+                .get_strict::<Server>("server")
+                .unwrap_err()
+                .to_string(),
+            str![[r#"missing configuration field "proxy" for key `server`"#]]
+        );
+        assert_data_eq!(
+            missing
+                .try_deserialize_strict::<Settings>()
+                .unwrap_err()
+                .to_string(),
+            str![[r#"missing configuration field "server.proxy""#]]
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_keys() {
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+  "debug": true,
+  "server": {
+    "host": "localhost",
+    "ports": [80, 443],
+    "tls": {}
+  },
+  "cache": null
+}
+"#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    assert!(c.contains_key("server.host"));
+    assert!(c.contains_key("server.ports[1]"));
+    assert!(c.contains_key("server.tls"));
+    assert!(c.contains_key("cache"));
+    assert!(!c.contains_key("server.port"));
+    assert!(!c.contains_key("server.ports[2]"));
+    assert!(!c.contains_key("debug.level"));
+    assert!(!c.contains_key("server..host"));
+
+    assert_eq!(
+        c.keys_with_prefix(""),
+        [
+            "cache",
+            "debug",
+            "server.host",
+            "server.ports[0]",
+            "server.ports[1]",
+            "server.tls"
+        ]
+    );
+    assert_eq!(
+        c.keys_with_prefix("server"),
+        [
+            "server.host",
+            "server.ports[0]",
+            "server.ports[1]",
+            "server.tls"
+        ]
+    );
+    assert_eq!(
+        c.keys_with_prefix("server.ports"),
+        ["server.ports[0]", "server.ports[1]"]
+    );
+    assert_eq!(c.keys_with_prefix("debug"), ["debug"]);
+    assert!(c.keys_with_prefix("client").is_empty());
+    assert!(c.keys_with_prefix("server..host").is_empty());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_map_conversions() {
+    use std::collections::BTreeMap;
+
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+  "place": {
+    "name": "Torre di Pisa",
+    "number": 1
+  }
+}
+"#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    let place = c.get::<Value>("place").unwrap();
+    let sorted = place.clone().into_btree_map().unwrap();
+    assert_eq!(
+        sorted.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec!["name", "number"]
+    );
+
+    let hashed = place.into_hash_map().unwrap();
+    assert_eq!(hashed["number"].clone().into_int().unwrap(), 1);
+
+    let mut defaults = BTreeMap::new();
+    defaults.insert("name".to_owned(), "Tower of London");
+    let c = Config::builder()
+        .set_default("place", defaults)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(c.get_string("place.name").unwrap(), "Tower of London");
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_get_unsigned_and_128_bit() {
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+    "port": 8080,
+    "negative": -1
+}
+"#,
+            FileFormat::Json,
+        ))
+        .set_override("big", u64::MAX)
+        .unwrap()
+        .set_override("huge", u128::MAX)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_uint("port").unwrap(), 8080);
+    assert_eq!(c.get_uint("big").unwrap(), u64::MAX);
+    assert_eq!(c.get_int128("big").unwrap(), i128::from(u64::MAX));
+    assert_eq!(c.get_int128("negative").unwrap(), -1);
+    assert_eq!(c.get_uint128("huge").unwrap(), u128::MAX);
+
+    assert_data_eq!(
+        c.get_uint("negative").unwrap_err().to_string(),
+        str![
+            "invalid type: 64-bit integer `-1`, expected an unsigned 64 bit or less integer for key `negative`"
+        ]
+    );
+    assert_data_eq!(
+        c.get_int128("huge").unwrap_err().to_string(),
+        str![
+            "invalid type: 128-bit unsigned integer `340282366920938463463374607431768211455`, expected an signed 128 bit integer for key `huge`"
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_try_deserialize_with_unused() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Settings {
+        debug: bool,
+        servers: Vec<Server>,
+        labels: Map<String, String>,
+        mode: Mode,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Server {
+        host: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    enum Mode {
+        Cluster { size: u32 },
+    }
+
+    let c = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+  "debug": true,
+  "verbose": true,
+  "servers": [
+    { "host": "a" },
+    { "host": "b", "prot": 80 }
+  ],
+  "labels": { "team": "core" },
+  "mode": { "Cluster": { "size": 3, "zone": "eu" } }
+}
+"#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    let (settings, unused) = c.try_deserialize_with_unused::<Settings>().unwrap();
+    assert!(settings.debug);
+    assert_eq!(
+        unused,
+        vec!["mode.Cluster.zone", "servers[1].prot", "verbose"]
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_try_deserialize_lenient() {
+    #[derive(Debug, Deserialize)]
+    struct Settings {
+        hosts: Vec<String>,
+        ports: Vec<u16>,
+        servers: Vec<Server>,
+        name: String,
+        retries: Option<u8>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host: String,
+    }
+
+    let c = || {
+        Config::builder()
+            .add_source(File::from_str(
+                r#"
+{
+  "hosts": "a",
+  "ports": [80, 443],
+  "servers": { "host": "b" },
+  "name": ["c"],
+  "retries": [3]
+}
+"#,
+                FileFormat::Json,
+            ))
+            .build()
+            .unwrap()
+    };
+
+    let settings: Settings = c().try_deserialize_lenient().unwrap();
+    assert_eq!(settings.hosts, ["a"]);
+    assert_eq!(settings.ports, [80, 443]);
+    assert_eq!(
+        settings.servers,
+        [Server {
+            host: "b".to_owned()
+        }]
+    );
+    assert_eq!(settings.name, "c");
+    assert_eq!(settings.retries, Some(3));
+
+    assert!(c().try_deserialize::<Settings>().is_err());
+
+    let res = Config::builder()
+        .set_default("name", vec!["a", "b"])
+        .unwrap()
+        .build()
+        .unwrap()
+        .try_deserialize_lenient::<Server>();
+    assert!(res.is_err());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_sub_config() {
+    let c = Config::builder()
+        .set_default("database.pool", 4)
+        .unwrap()
+        .add_source(File::from_str(
+            r#"
+{
+  "debug": true,
+  "database": { "url": "postgres://localhost", "replicas": [{ "url": "postgres://replica" }] }
+}
+"#,
+            FileFormat::Json,
+        ))
+        .build()
+        .unwrap();
+
+    let database = c.sub_config("database").unwrap();
+    assert_eq!(database.get_int("pool").unwrap(), 4);
+    assert_eq!(database.get_string("url").unwrap(), "postgres://localhost");
+    assert_eq!(
+        database.get_string("replicas[0].url").unwrap(),
+        "postgres://replica"
+    );
+    assert!(database.get_bool("debug").is_err());
+
+    let replica = c.sub_config("database.replicas[0]").unwrap();
+    assert_eq!(replica.get_string("url").unwrap(), "postgres://replica");
+
+    let res = c.sub_config("debug");
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["invalid type: boolean `true`, expected a map for key `debug`"]
+    );
+    let res = c.sub_config("cache");
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[r#"missing configuration field "cache""#]]
+    );
+}
+
+#[test]
+#[cfg(all(feature = "fingerprint", feature = "json"))]
+fn test_fingerprint() {
+    let json = |text| {
+        Config::builder()
+            .add_source(File::from_str(text, FileFormat::Json))
+            .build()
+            .unwrap()
+    };
+
+    let a = json(r#"{ "server": { "port": 8080, "hosts": ["a", "b"] }, "ratio": -0.0 }"#);
+    let b = json(r#"{ "ratio": 0.0, "server": { "hosts": ["a", "b"], "port": 8080 } }"#);
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_eq!(a.fingerprint().len(), 64);
+    assert_eq!(
+        json("{}").fingerprint(),
+        "2b742421630dcb7bd9bd60325db89733dcd96abdeb4c21ce547161f142a6394d"
+    );
+
+    for other in [
+        r#"{ "server": { "port": "8080", "hosts": ["a", "b"] }, "ratio": 0.0 }"#,
+        r#"{ "server": { "port": 8080, "hosts": ["b", "a"] }, "ratio": 0.0 }"#,
+        r#"{ "server": { "port": 8080, "hosts": ["a", "b"] }, "ratio": 0.1 }"#,
+        r#"{ "server": { "port": 8080, "hosts": ["a", "b"] } }"#,
+    ] {
+        assert_ne!(a.fingerprint(), json(other).fingerprint(), "{other}");
+    }
+}
+
+#[test]
+fn test_sort_keys() {
+    /// The keys of a map, in the order they are visited
+    struct Keys(Vec<String>);
+
+    impl<'de> Deserialize<'de> for Keys {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct Visitor;
+
+            impl<'de> serde::de::Visitor<'de> for Visitor {
+                type Value = Keys;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str("a map")
+                }
+
+                fn visit_map<A: serde::de::MapAccess<'de>>(
+                    self,
+                    mut map: A,
+                ) -> Result<Keys, A::Error> {
+                    let mut keys = Vec::new();
+                    while let Some((key, _)) = map.next_entry::<String, serde::de::IgnoredAny>()? {
+                        keys.push(key);
+                    }
+                    Ok(Keys(keys))
+                }
+            }
+
+            deserializer.deserialize_map(Visitor)
+        }
+    }
+
+    let mut builder = Config::builder().sort_keys(true);
+    for n in (0..32).rev() {
+        builder = builder.set_override(format!("servers.s{n:02}"), n).unwrap();
+        builder = builder.set_override(format!("t{n:02}.n"), n).unwrap();
+    }
+    let config = builder.build().unwrap();
+
+    let expected: Vec<_> = (0..32).map(|n| format!("s{n:02}")).collect();
+    assert_eq!(config.get::<Keys>("servers").unwrap().0, expected);
+    let sub = config.sub_config("servers").unwrap();
+    assert_eq!(sub.try_deserialize::<Keys>().unwrap().0, expected);
+
+    let mut sections = vec!["servers".to_owned()];
+    sections.extend((0..32).map(|n| format!("t{n:02}")));
+    assert_eq!(config.sections(), sections);
+
+    #[cfg(feature = "preserve_order")]
+    {
+        let table = config.get_table("servers").unwrap();
+        assert_eq!(table.keys().cloned().collect::<Vec<_>>(), expected);
+    }
+}
+
+#[test]
+fn test_get_rendered() {
+    let config = Config::builder()
+        .set_default("server.hosts", vec!["a", "b"])
+        .unwrap()
+        .set_default("server.name", "8080")
+        .unwrap()
+        .set_default("server.port", 8080)
+        .unwrap()
+        .set_default("server.tls", Map::<String, Value>::new())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        config
+            .get_rendered("server", config::RenderFormat::Json)
+            .unwrap(),
+        r#"{"hosts":["a","b"],"name":"8080","port":8080,"tls":{}}"#
+    );
+    assert_eq!(
+        config
+            .get_rendered("server.name", config::RenderFormat::Json)
+            .unwrap(),
+        r#""8080""#
+    );
+    assert_data_eq!(
+        config
+            .get_rendered("server.missing", config::RenderFormat::Json)
+            .unwrap_err()
+            .to_string(),
+        str![[r#"missing configuration field "server.missing""#]]
+    );
+
+    #[cfg(feature = "toml")]
+    {
+        assert_data_eq!(
+            config
+                .get_rendered("server", config::RenderFormat::Toml)
+                .unwrap(),
+            str![[r#"
+hosts = ["a", "b"]
+name = "8080"
+port = 8080
+
+[tls]
+
+"#]]
+        );
+        assert_eq!(
+            config
+                .get_rendered("server.hosts", config::RenderFormat::Toml)
+                .unwrap(),
+            r#"["a", "b"]"#
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    {
+        assert_data_eq!(
+            config
+                .get_rendered("server", config::RenderFormat::Yaml)
+                .unwrap(),
+            str![[r#"
+hosts:
+  - a
+  - b
+name: "8080"
+port: 8080
+tls: {}
+"#]]
+        );
+        assert_eq!(
+            config
+                .get_rendered("server.port", config::RenderFormat::Yaml)
+                .unwrap(),
+            "8080"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+#![cfg(feature = "helpers")]
+
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, ConfigError};
+
+#[derive(Debug, Deserialize)]
+struct Ports {
+    #[serde(deserialize_with = "config::helpers::comma_separated")]
+    ports: Vec<u16>,
+}
+
+fn ports(value: impl Into<config::Value>) -> Result<Vec<u16>, ConfigError> {
+    Config::builder()
+        .set_default("ports", value)?
+        .build()?
+        .try_deserialize::<Ports>()
+        .map(|settings| settings.ports)
+}
+
+#[test]
+fn test_comma_separated() {
+    assert_eq!(ports("80, 443,,8080 ").unwrap(), [80, 443, 8080]);
+    assert_eq!(ports("").unwrap(), Vec::<u16>::new());
+    assert_eq!(ports(vec![80, 443]).unwrap(), [80, 443]);
+    assert_data_eq!(
+        ports("80, https").unwrap_err().to_string(),
+        str!["invalid item `https`: invalid digit found in string for key `ports`"]
+    );
+}
+
+#[derive(Debug, Deserialize)]
+struct Verbose {
+    #[serde(deserialize_with = "config::helpers::flexible_bool")]
+    verbose: bool,
+}
+
+fn verbose(value: impl Into<config::Value>) -> Result<bool, ConfigError> {
+    Config::builder()
+        .set_default("verbose", value)?
+        .build()?
+        .try_deserialize::<Verbose>()
+        .map(|settings| settings.verbose)
+}
+
+#[test]
+fn test_flexible_bool() {
+    for text in ["true", "On", "YES", "1", " yes "] {
+        assert!(verbose(text).unwrap(), "{text:?}");
+    }
+    for text in ["false", "OFF", "No", "0"] {
+        assert!(!verbose(text).unwrap(), "{text:?}");
+    }
+    assert!(verbose(true).unwrap());
+    assert!(verbose(1).unwrap());
+    assert!(!verbose(0).unwrap());
+
+    assert_data_eq!(
+        verbose("maybe").unwrap_err().to_string(),
+        str![[
+            r#"invalid value: string "maybe", expected a boolean like `true`, `on`, `yes` or `1` for key `verbose`"#
+        ]]
+    );
+    assert_data_eq!(
+        verbose(2).unwrap_err().to_string(),
+        str![
+            "invalid value: integer `2`, expected a boolean like `true`, `on`, `yes` or `1` for key `verbose`"
+        ]
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Listener {
+    Tcp {
+        #[serde(deserialize_with = "config::helpers::coerced")]
+        port: u16,
+        #[serde(deserialize_with = "config::helpers::coerced")]
+        tls: bool,
+    },
+    Unix {
+        path: String,
+    },
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum Limit {
+    Enabled(#[serde(deserialize_with = "config::helpers::coerced")] bool),
+    Count(#[serde(deserialize_with = "config::helpers::coerced")] u32),
+}
+
+#[derive(Debug, Deserialize)]
+struct Server {
+    listener: Listener,
+    limit: Limit,
+}
+
+#[test]
+#[cfg(feature = "env")]
+fn test_coerced() {
+    let server = |vars: &[(&str, &str)]| {
+        let vars: Vec<_> = vars.iter().map(|&(name, value)| (name, value)).collect();
+        Config::builder()
+            .add_source(
+                config::Environment::with_source_os(vars)
+                    .prefix("APP")
+                    .prefix_separator("_")
+                    .separator("__"),
+            )
+            .build()?
+            .try_deserialize::<Server>()
+    };
+
+    let tcp = server(&[
+        ("APP_LISTENER__TYPE", "tcp"),
+        ("APP_LISTENER__PORT", "8080"),
+        ("APP_LISTENER__TLS", "on"),
+        ("APP_LIMIT", "100"),
+    ])
+    .unwrap();
+    assert_eq!(
+        tcp.listener,
+        Listener::Tcp {
+            port: 8080,
+            tls: true
+        }
+    );
+    assert_eq!(tcp.limit, Limit::Count(100));
+
+    let unix = server(&[
+        ("APP_LISTENER__TYPE", "unix"),
+        ("APP_LISTENER__PATH", "/run/app.sock"),
+        ("APP_LIMIT", "false"),
+    ])
+    .unwrap();
+    assert_eq!(
+        unix.listener,
+        Listener::Unix {
+            path: "/run/app.sock".to_owned()
+        }
+    );
+    assert_eq!(unix.limit, Limit::Enabled(false));
+
+    assert_data_eq!(
+        server(&[
+            ("APP_LISTENER__TYPE", "tcp"),
+            ("APP_LISTENER__PORT", "http"),
+            ("APP_LISTENER__TLS", "on"),
+            ("APP_LIMIT", "100"),
+        ])
+        .unwrap_err()
+        .to_string(),
+        str![[r#"invalid type: string "http", expected an integer for key `listener.port`"#]]
+    );
+}
+
+#[test]
+#[cfg(feature = "log")]
+fn test_log_level() {
+    #[derive(Debug, Deserialize)]
+    struct Logging {
+        #[serde(deserialize_with = "config::helpers::log_level")]
+        level: log::Level,
+        #[serde(deserialize_with = "config::helpers::log_level_filter")]
+        filter: log::LevelFilter,
+    }
+
+    let logging = |level: &str, filter: &str| {
+        Config::builder()
+            .set_default("filter", filter)?
+            .set_default("level", level)?
+            .build()?
+            .try_deserialize::<Logging>()
+    };
+
+    let settings = logging("Warning", "OFF").unwrap();
+    assert_eq!(settings.level, log::Level::Warn);
+    assert_eq!(settings.filter, log::LevelFilter::Off);
+    let settings = logging("debug", "Info").unwrap();
+    assert_eq!(settings.level, log::Level::Debug);
+    assert_eq!(settings.filter, log::LevelFilter::Info);
+
+    assert_data_eq!(
+        logging("off", "info").unwrap_err().to_string(),
+        str![[
+            r#"invalid value: string "off", expected a log level among `error`, `warn`, `info`, `debug` and `trace` for key `level`"#
+        ]]
+    );
+}
+
+#[test]
+#[cfg(feature = "url")]
+fn test_url() {
+    #[derive(Debug, Deserialize)]
+    struct Endpoint {
+        #[serde(deserialize_with = "config::helpers::url")]
+        endpoint: url::Url,
+    }
+
+    let endpoint = |text: &str| {
+        Config::builder()
+            .set_default("endpoint", text)?
+            .build()?
+            .try_deserialize::<Endpoint>()
+            .map(|settings| settings.endpoint)
+    };
+
+    assert_eq!(
+        endpoint(" https://example.com:8443/v1 ").unwrap().as_str(),
+        "https://example.com:8443/v1"
+    );
+    assert_data_eq!(
+        endpoint("example.com").unwrap_err().to_string(),
+        str!["invalid URL `example.com`: relative URL without a base for key `endpoint`"]
+    );
+}
@@ -68,3 +68,103 @@ fn invalid_signedness() {
 
     let _: u32 = c.get("settings.port").unwrap();
 }
+
+/// `u64::MAX` and `i64::MIN` written in `format`, with the keys `max` and `min`
+#[allow(dead_code)]
+fn extremes(text: &str, format: config::FileFormat) -> Config {
+    Config::builder()
+        .add_source(config::File::from_str(text, format))
+        .build()
+        .unwrap()
+}
+
+#[allow(dead_code)]
+fn assert_extremes(c: &Config) {
+    assert_eq!(c.get::<u64>("max").unwrap(), u64::MAX);
+    assert_eq!(c.get_uint("max").unwrap(), u64::MAX);
+    assert_eq!(c.get_uint128("max").unwrap(), u128::from(u64::MAX));
+    assert!(c.get::<i64>("max").is_err());
+    assert!(c.get::<u32>("max").is_err());
+
+    assert_eq!(c.get::<i64>("min").unwrap(), i64::MIN);
+    assert_eq!(c.get_int128("min").unwrap(), i128::from(i64::MIN));
+    assert!(c.get::<u64>("min").is_err());
+    assert!(c.get::<i32>("min").is_err());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn extremes_json() {
+    let c = extremes(
+        r#"{ "max": 18446744073709551615, "min": -9223372036854775808 }"#,
+        config::FileFormat::Json,
+    );
+    assert_extremes(&c);
+}
+
+#[test]
+#[cfg(feature = "json5")]
+fn extremes_json5() {
+    let c = extremes("{ min: -9223372036854775808 }", config::FileFormat::Json5);
+    assert_eq!(c.get::<i64>("min").unwrap(), i64::MIN);
+    assert!(c.get::<u64>("min").is_err());
+
+    // The JSON5 parser only reads signed 64 bit integers, larger ones fail rather than wrap
+    assert!(
+        Config::builder()
+            .add_source(config::File::from_str(
+                "{ max: 18446744073709551615 }",
+                config::FileFormat::Json5,
+            ))
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn extremes_yaml() {
+    let c = extremes(
+        "max: 18446744073709551615\nmin: -9223372036854775808\n",
+        config::FileFormat::Yaml,
+    );
+    assert_extremes(&c);
+}
+
+#[test]
+#[cfg(feature = "ron")]
+fn extremes_ron() {
+    let c = extremes(
+        "(max: 18446744073709551615, min: -9223372036854775808)",
+        config::FileFormat::Ron,
+    );
+    assert_extremes(&c);
+}
+
+#[test]
+#[cfg(feature = "ini")]
+fn extremes_ini() {
+    let c = extremes(
+        "max = 18446744073709551615\nmin = -9223372036854775808\n",
+        config::FileFormat::Ini,
+    );
+    assert_extremes(&c);
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn extremes_toml() {
+    // TOML integers are signed 64 bit, larger ones are rejected rather than losing precision
+    let c = extremes("min = -9223372036854775808", config::FileFormat::Toml);
+    assert_eq!(c.get::<i64>("min").unwrap(), i64::MIN);
+    assert!(c.get::<u64>("min").is_err());
+
+    let error = Config::builder()
+        .add_source(config::File::from_str(
+            "max = 18446744073709551615",
+            config::FileFormat::Toml,
+        ))
+        .build()
+        .unwrap_err();
+    assert_eq!(error.kind(), config::ErrorKind::Parse);
+}
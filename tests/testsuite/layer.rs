@@ -0,0 +1,80 @@
+use snapbox::{assert_data_eq, str};
+
+use config::builder::{AsyncState, BuilderState, ConfigLayer, DefaultState};
+use config::{Config, ConfigBuilder, ConfigError, File, FileFormat};
+
+struct Telemetry;
+
+impl<St: BuilderState> ConfigLayer<St> for Telemetry {
+    fn apply(&self, builder: ConfigBuilder<St>) -> Result<ConfigBuilder<St>, ConfigError> {
+        builder
+            .set_default("telemetry.enabled", true)?
+            .set_default("telemetry.endpoint", "localhost:4317")
+    }
+}
+
+#[cfg(feature = "json")]
+struct Bundled;
+
+#[cfg(feature = "json")]
+impl ConfigLayer for Bundled {
+    fn apply(
+        &self,
+        builder: ConfigBuilder<DefaultState>,
+    ) -> Result<ConfigBuilder<DefaultState>, ConfigError> {
+        Ok(builder.add_source(File::from_str(
+            r#"{"telemetry": {"endpoint": "collector:4317"}}"#,
+            FileFormat::Json,
+        )))
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_layers() {
+    let config = Config::builder()
+        .with_layer(Telemetry)
+        .unwrap()
+        .with_layer(Bundled)
+        .unwrap()
+        .with_layer(|builder: ConfigBuilder<DefaultState>| {
+            builder.set_override("telemetry.enabled", false)
+        })
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert!(!config.get_bool("telemetry.enabled").unwrap());
+    assert_eq!(
+        config.get_string("telemetry.endpoint").unwrap(),
+        "collector:4317"
+    );
+}
+
+#[tokio::test]
+async fn test_layers_async() {
+    let config = ConfigBuilder::<AsyncState>::default()
+        .with_layer(Telemetry)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+
+    assert!(config.get_bool("telemetry.enabled").unwrap());
+}
+
+#[test]
+fn test_layer_error() {
+    let res = Config::builder()
+        .with_layer(|builder: ConfigBuilder<DefaultState>| builder.set_default("a..b", 1));
+
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[r#"
+a..b
+  ^
+invalid identifier
+expected ASCII alphanumeric, `_`, `-`
+"#]]
+    );
+}
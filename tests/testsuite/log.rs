@@ -53,6 +53,8 @@ fn test_load_level_lowercase() {
     assert!(s.is_err());
     assert_data_eq!(
         s.unwrap_err().to_string(),
-        str!["enum Level does not have variant constructor error for key `log`"]
+        str![
+            "enum Level does not have variant constructor error, expected one of `ERROR`, `WARN`, `INFO`, `DEBUG`, `TRACE` (variants are case-sensitive, did you mean `ERROR`?) for key `log`"
+        ]
     );
 }
@@ -3,24 +3,50 @@ extern crate serde;
 
 pub mod async_builder;
 pub mod case;
+pub mod cell;
+pub mod checkpoint;
 pub mod defaults;
+pub mod derive;
+pub mod dir;
+pub mod discovery;
+pub mod dotenv;
 pub mod empty;
 pub mod env;
 pub mod errors;
 pub mod file;
 pub mod file_corn;
+pub mod file_dotenv;
 pub mod file_ini;
 pub mod file_json;
 pub mod file_json5;
 pub mod file_ron;
 pub mod file_toml;
 pub mod file_yaml;
+pub mod flags;
+pub mod format_support;
 pub mod get;
+pub mod helpers;
 pub mod integer_range;
+pub mod introspect;
+pub mod layer;
 pub mod log;
+pub mod map_key;
 pub mod merge;
+pub mod nested_json;
+pub mod parallel;
+pub mod perf;
+pub mod redact;
+pub mod resolver;
 pub mod ron_enum;
 pub mod set;
+pub mod sidecar;
+pub mod source_adapters;
+pub mod stats;
+pub mod subscriptions;
+pub mod tenant;
+pub mod test_util;
+pub mod time;
+pub mod tracing;
 pub mod unsigned_int;
 pub mod unsigned_int_hm;
 pub mod weird_keys;
@@ -0,0 +1,66 @@
+#![cfg(feature = "json")]
+
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, ConfigError, File, FileFormat, Value};
+
+fn trim(value: Value) -> Result<Value, ConfigError> {
+    Ok(Value::from(value.into_string()?.trim()))
+}
+
+#[test]
+fn test_map_key() {
+    let config = Config::builder()
+        .add_source(File::from_str(
+            r#"{ "server": { "host": " Example.COM ", "port": 80 }, "token": "secret://token" }"#,
+            FileFormat::Json,
+        ))
+        .map_key("server.host", trim)
+        .unwrap()
+        .map_key("server.host", |value: Value| {
+            Ok(Value::from(value.into_string()?.to_lowercase()))
+        })
+        .unwrap()
+        .map_key("token", trim)
+        .unwrap()
+        .map_key("missing", |_: Value| {
+            Err(ConfigError::Message("unreachable".into()))
+        })
+        .unwrap()
+        .register_resolver("secret", |_: &str| Ok(Value::from(" hunter2\n")))
+        .build()
+        .unwrap();
+
+    assert_eq!(config.get_string("server.host").unwrap(), "example.com");
+    assert_eq!(config.get_string("token").unwrap(), "hunter2");
+    assert!(!config.contains_key("missing"));
+}
+
+#[test]
+fn test_map_key_error() {
+    let error = Config::builder()
+        .add_source(File::from_str(
+            r#"{ "server": { "port": "http" } }"#,
+            FileFormat::Json,
+        ))
+        .map_key("server.port", |value: Value| {
+            let port = value.into_string()?;
+            match port.as_str() {
+                "http" => Ok(Value::from(80)),
+                _ => Err(ConfigError::Message(format!("unknown service {port:?}"))),
+            }
+        })
+        .unwrap()
+        .map_key("server.port", |value: Value| match value.into_int()? {
+            port if port < 1024 => Err(ConfigError::Message(format!("port {port} is privileged"))),
+            port => Ok(Value::from(port)),
+        })
+        .unwrap()
+        .build()
+        .unwrap_err();
+
+    assert_data_eq!(
+        error.to_string(),
+        str!["port 80 is privileged for key `server.port`"]
+    );
+}
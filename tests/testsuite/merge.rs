@@ -1,6 +1,8 @@
+use std::sync::Arc;
+
 use snapbox::{assert_data_eq, prelude::*, str};
 
-use config::{Config, File, FileFormat, Map};
+use config::{Config, File, FileFormat, Map, SharedConfig, Source};
 
 #[test]
 #[cfg(feature = "json")]
@@ -83,6 +85,42 @@ fn test_merge_whole_config() {
     assert_eq!(config3.get("y").ok(), Some(25));
 }
 
+#[test]
+fn test_merge_shared_config() {
+    let base = Arc::new(
+        Config::builder()
+            .set_default("server.port", 80)
+            .unwrap()
+            .set_default("server.host", "localhost")
+            .unwrap()
+            .build()
+            .unwrap(),
+    );
+
+    let builder = Config::builder()
+        .add_source(SharedConfig::from(base.clone()))
+        .set_override("server.port", 8080)
+        .unwrap();
+    let config = builder.build_cloned().unwrap();
+    assert_eq!(config.get_int("server.port").unwrap(), 8080);
+    assert_eq!(config.get_string("server.host").unwrap(), "localhost");
+
+    // Clones of the builder and the configurations it builds share the base
+    let shared = Arc::strong_count(&base);
+    let _clone = builder.clone();
+    assert_eq!(Arc::strong_count(&base), shared + 1);
+
+    let stage = Config::builder()
+        .add_source(config)
+        .set_override("server.host", "example.com")
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(stage.get_int("server.port").unwrap(), 8080);
+    assert_eq!(stage.get_string("server.host").unwrap(), "example.com");
+    assert_eq!(base.get_int("server.port").unwrap(), 80);
+}
+
 #[test]
 #[cfg(feature = "json")]
 /// Test a few scenarios with empty maps:
@@ -371,3 +409,127 @@ Settings {
         ]
     );
 }
+
+#[test]
+#[cfg(feature = "env")]
+#[cfg(feature = "json")]
+fn test_merge_conflict() {
+    let builder = Config::builder()
+        .add_source(File::from_str(
+            r#"{ "server": { "port": 80 }, "tags": ["a"], "workers": 4 }"#,
+            FileFormat::Json,
+        ))
+        .add_source(
+            config::Environment::default().source(Some(Map::from([("SERVER".into(), "x".into())]))),
+        );
+
+    assert_eq!(
+        builder
+            .build_cloned()
+            .unwrap()
+            .get_string("server")
+            .unwrap(),
+        "x"
+    );
+
+    let res = builder.detect_merge_conflicts(true).build();
+    assert!(matches!(
+        res,
+        Err(config::ConfigError::MergeConflict { .. })
+    ));
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["merge conflict for key `server`: string from the environment would replace table"]
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_merge_conflict_allows_compatible_values() {
+    let c = Config::builder()
+        .set_default("place.rating", 1)
+        .unwrap()
+        .add_source(File::from_str(
+            r#"{ "place": { "rating": "4.5", "tags": ["a"], "owner": null } }"#,
+            FileFormat::Json,
+        ))
+        .add_source(File::from_str(
+            r#"{ "place": { "tags": ["b", "c"], "owner": { "name": "John" } } }"#,
+            FileFormat::Json,
+        ))
+        .set_override("place", "closed")
+        .unwrap()
+        .detect_merge_conflicts(true)
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_string("place").unwrap(), "closed");
+
+    let res = Config::builder()
+        .add_source(File::from_str(
+            r#"{ "place": { "tags": ["a"] } }"#,
+            FileFormat::Json,
+        ))
+        .add_source(File::from_str(
+            r#"{ "place": { "tags": { "first": "a" } } }"#,
+            FileFormat::Json,
+        ))
+        .detect_merge_conflicts(true)
+        .build();
+
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str!["merge conflict for key `place.tags`: table would replace array"]
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_merge_by_priority() {
+    let json = |text| File::from_str(text, FileFormat::Json);
+    let c = Config::builder()
+        .add_source_with_priority(json(r#"{ "plugin": "high", "order": "high" }"#), 10)
+        .add_source(json(
+            r#"{ "plugin": "base", "order": "first", "base": true }"#,
+        ))
+        .add_source_with_priority(json(r#"{ "plugin": "low" }"#), -5)
+        .add_source(json(r#"{ "order": "second" }"#))
+        .add_source_with_priority(json(r#"{ "order": "last" }"#), 10)
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_string("plugin").unwrap(), "high");
+    assert_eq!(c.get_string("order").unwrap(), "last");
+    assert!(c.get_bool("base").unwrap());
+}
+
+#[test]
+#[cfg(all(feature = "json", feature = "env"))]
+fn test_with_sources() {
+    let json = |text| Box::new(File::from_str(text, FileFormat::Json));
+    let plugins: Vec<Box<dyn Source + Send + Sync>> = vec![
+        json(r#"{ "plugin": "cache", "cache": true }"#),
+        json(r#"{ "plugin": "metrics", "metrics": true }"#),
+    ];
+    let builder = Config::builder()
+        .add_source_with_priority(
+            File::from_str(r#"{ "plugin": "forced" }"#, FileFormat::Json),
+            10,
+        )
+        .with_sources(plugins)
+        .add_source(config::Environment::with_prefix("WITH_SOURCES"));
+    assert_eq!(builder.sources().len(), 4);
+
+    let c = builder.build_cloned().unwrap();
+    assert_eq!(c.get_string("plugin").unwrap(), "forced");
+    assert!(c.get_bool("cache").unwrap());
+    assert!(c.get_bool("metrics").unwrap());
+
+    let c = builder
+        .remove_sources::<config::Environment>()
+        .retain_sources(|source| !format!("{source:?}").contains("metrics"))
+        .build()
+        .unwrap();
+    assert!(c.get_bool("cache").unwrap());
+    assert!(c.get::<bool>("metrics").is_err());
+}
@@ -0,0 +1,116 @@
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, Map, Value, ValueKind};
+
+#[test]
+fn test_nested_json_round_trip() {
+    // Keys are inserted sorted, so they render in the same order with `preserve_order`
+    let server = Map::from_iter([
+        ("ports".to_owned(), Value::from(vec![80, 443])),
+        ("tls".to_owned(), Value::from(true)),
+    ]);
+    let value = Value::from(Map::from_iter([
+        ("big".to_owned(), Value::from(u64::MAX)),
+        ("huge".to_owned(), Value::from(i128::MIN)),
+        ("missing".to_owned(), Value::new(None, ValueKind::Nil)),
+        ("name".to_owned(), Value::from("a \"quoted\"\nline\u{1}")),
+        ("ratio".to_owned(), Value::from(0.5)),
+        ("server".to_owned(), Value::from(server)),
+    ]));
+
+    let json = value.clone().into_nested_json();
+    assert_eq!(
+        json,
+        r#"{"big":18446744073709551615,"huge":-170141183460469231731687303715884105728,"missing":null,"name":"a \"quoted\"\nline\u0001","ratio":0.5,"server":{"ports":[80,443],"tls":true}}"#
+    );
+
+    let parsed = Value::from_nested_json(&json).unwrap();
+    assert_eq!(parsed, value);
+    assert_eq!(parsed.origin(), None);
+
+    let config = Config::builder()
+        .set_default("server.port", 8080)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(
+        config.cache.into_nested_json(),
+        r#"{"server":{"port":8080}}"#
+    );
+}
+
+#[test]
+fn test_from_nested_json() {
+    let value =
+        Value::from_nested_json(r#" { "a" : [1, -2, 3.5e2, "é😀\/"], "b": {} , "c": [ ] } "#)
+            .unwrap();
+    let table = value.into_table().unwrap();
+    let a = table["a"].clone().into_array().unwrap();
+    assert_eq!(a[0].kind, ValueKind::I64(1));
+    assert_eq!(a[1].kind, ValueKind::I64(-2));
+    assert_eq!(a[2].kind, ValueKind::Float(350.0));
+    assert_eq!(a[3].kind, ValueKind::String("é😀/".into()));
+    assert_eq!(table["b"].kind, ValueKind::Table(Map::new()));
+    assert_eq!(table["c"].kind, ValueKind::Array(Vec::new()));
+
+    assert_eq!(
+        Value::from_nested_json("42").unwrap().kind,
+        ValueKind::I64(42)
+    );
+    assert_eq!(
+        Value::from_nested_json(&Value::from(f64::NAN).into_nested_json())
+            .unwrap()
+            .kind,
+        ValueKind::Nil
+    );
+}
+
+#[test]
+fn test_from_nested_json_errors() {
+    let error = |text: &str| Value::from_nested_json(text).unwrap_err().to_string();
+
+    assert_data_eq!(
+        error("{\n  \"a\": 1,\n  \"b\" 2\n}"),
+        str!["invalid JSON at line 3 column 7: expected `:`"]
+    );
+    assert_data_eq!(
+        error("[1, 2"),
+        str!["invalid JSON at line 1 column 6: expected `,`"]
+    );
+    assert_data_eq!(
+        error("{} {}"),
+        str!["invalid JSON at line 1 column 4: trailing characters"]
+    );
+    assert_data_eq!(
+        error("[01]"),
+        str!["invalid JSON at line 1 column 2: invalid number"]
+    );
+    assert_data_eq!(
+        error(r#""\x""#),
+        str!["invalid JSON at line 1 column 3: invalid escape"]
+    );
+    assert_data_eq!(
+        error(&"[".repeat(200)),
+        str!["invalid JSON at line 1 column 129: nested more than 128 levels deep"]
+    );
+}
+
+#[test]
+#[cfg(not(feature = "json"))]
+fn test_nested_json_without_json_feature() {
+    let config = Config::builder()
+        .set_default("server.hosts", vec!["a", "b"])
+        .unwrap()
+        .set_default("server.port", 8080)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let json = config
+        .get_rendered("server", config::RenderFormat::Json)
+        .unwrap();
+    assert_eq!(json, r#"{"hosts":["a","b"],"port":8080}"#);
+
+    let parsed = Value::from_nested_json(&json).unwrap();
+    assert_eq!(parsed, config.get::<Value>("server").unwrap());
+}
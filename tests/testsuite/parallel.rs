@@ -0,0 +1,152 @@
+#[cfg(feature = "async")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier, Mutex};
+#[cfg(feature = "async")]
+use std::task::Poll;
+use std::thread::ThreadId;
+
+use snapbox::{assert_data_eq, str};
+
+#[cfg(feature = "async")]
+use config::AsyncSource;
+use config::{Config, ConfigError, Map, Source, Value};
+
+/// A source collected only once `barrier` is reached by every source sharing it, which would
+/// never happen when collecting sequentially
+#[derive(Debug, Clone)]
+struct Rendezvous {
+    barrier: Arc<Barrier>,
+    key: &'static str,
+    value: Result<i64, &'static str>,
+}
+
+impl Source for Rendezvous {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        self.barrier.wait();
+        let value = self.value.map_err(|e| ConfigError::Message(e.into()))?;
+        Ok(Map::from_iter([(self.key.to_owned(), value.into())]))
+    }
+}
+
+/// A source recording the thread it is collected on
+#[derive(Debug, Clone)]
+struct Recorder {
+    threads: Arc<Mutex<Vec<ThreadId>>>,
+}
+
+impl Source for Recorder {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new((*self).clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        self.threads
+            .lock()
+            .unwrap()
+            .push(std::thread::current().id());
+        Ok(Map::new())
+    }
+}
+
+/// How many sources may be collected at once
+fn workers() -> usize {
+    std::thread::available_parallelism().map_or(1, usize::from)
+}
+
+#[test]
+fn test_parallel_sources() {
+    let barrier = Arc::new(Barrier::new(workers().min(3)));
+    let source = |key, value| Rendezvous {
+        barrier: barrier.clone(),
+        key,
+        value,
+    };
+
+    let config = Config::builder()
+        .collect_in_parallel(true)
+        .add_source(source("a", Ok(1)))
+        .add_source(source("shared", Ok(2)))
+        .add_source(source("shared", Ok(3)))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("a").unwrap(), 1);
+    assert_eq!(config.get_int("shared").unwrap(), 3);
+
+    let res = Config::builder()
+        .collect_in_parallel(true)
+        .add_source(source("a", Ok(1)))
+        .add_source(source("b", Err("first")))
+        .add_source(source("c", Err("second")))
+        .build();
+    assert_data_eq!(res.unwrap_err().to_string(), str!["first"]);
+}
+
+#[test]
+fn test_parallel_sources_bounded() {
+    let threads = Arc::new(Mutex::new(Vec::new()));
+    let mut builder = Config::builder().collect_in_parallel(true);
+    for _ in 0..workers() * 4 {
+        builder = builder.add_source(Recorder {
+            threads: threads.clone(),
+        });
+    }
+    builder.build().unwrap();
+
+    let mut threads = threads.lock().unwrap().clone();
+    assert_eq!(threads.len(), workers() * 4);
+    threads.sort_by_key(|id| format!("{id:?}"));
+    threads.dedup();
+    assert!(threads.len() <= workers());
+}
+
+#[cfg(feature = "async")]
+#[derive(Debug)]
+struct AsyncRendezvous {
+    arrived: Arc<AtomicUsize>,
+    expected: usize,
+    value: i64,
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncSource for AsyncRendezvous {
+    async fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        self.arrived.fetch_add(1, Ordering::SeqCst);
+        std::future::poll_fn(|cx| match self.arrived.load(Ordering::SeqCst) {
+            arrived if arrived < self.expected => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            _ => Poll::Ready(()),
+        })
+        .await;
+        Ok(Map::from_iter([("value".to_owned(), self.value.into())]))
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_parallel_async_sources() {
+    let arrived = Arc::new(AtomicUsize::new(0));
+    let source = |value| AsyncRendezvous {
+        arrived: arrived.clone(),
+        expected: 2,
+        value,
+    };
+
+    let config = Config::builder()
+        .set_default("default", true)
+        .unwrap()
+        .collect_in_parallel(true)
+        .add_async_source(source(1))
+        .add_async_source(source(2))
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(config.get_int("value").unwrap(), 2);
+    assert!(config.get_bool("default").unwrap());
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "perf-stats")]
+
+use config::{Config, PerfStats};
+
+#[test]
+fn test_perf_stats() {
+    let before = PerfStats::snapshot();
+
+    let config = Config::builder()
+        .set_default("server.port", 80)
+        .unwrap()
+        .add_source(Config::try_from_iter([("server.host", "a"), ("debug", "true")]).unwrap())
+        .add_source(Config::try_from_iter([("server.host", "b")]).unwrap())
+        .build()
+        .unwrap();
+    config.get_string("server.host").unwrap();
+    config.get::<u16>("server.port").unwrap();
+    assert_eq!(config.get_section::<bool>("missing").unwrap(), None);
+    let _: std::collections::BTreeMap<String, config::Value> = config.try_deserialize().unwrap();
+
+    // Other tests may run concurrently, adding to the counters
+    let stats = PerfStats::snapshot().since(&before);
+    assert!(stats.builds() >= 1, "{stats:?}");
+    assert!(stats.sources() >= 2, "{stats:?}");
+    assert!(stats.keys() >= 3, "{stats:?}");
+    assert!(stats.lookups() >= 3, "{stats:?}");
+    assert!(stats.deserializations() >= 2, "{stats:?}");
+
+    let later = PerfStats::snapshot();
+    assert_eq!(before.since(&later), PerfStats::default());
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "json")]
+
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, ConfigError, File, FileFormat, Value};
+
+fn secrets(location: &str) -> Result<Value, ConfigError> {
+    match location {
+        "vault/db#password" => Ok(Value::from("hunter2")),
+        "vault/db#port" => Ok(Value::from(5432)),
+        _ => Err(ConfigError::Message(format!("no secret at {location:?}"))),
+    }
+}
+
+#[test]
+fn test_resolver() {
+    let config = Config::builder()
+        .add_source(File::from_str(
+            r#"
+{
+  "database": {
+    "password": "secret://vault/db#password",
+    "port": "secret://vault/db#port",
+    "url": "https://secret://vault/db#password"
+  },
+  "replicas": ["secret://vault/db#port"],
+  "cache": "mem://local"
+}
+"#,
+            FileFormat::Json,
+        ))
+        .set_override("cache", "secret://vault/db#password")
+        .unwrap()
+        .register_resolver("secret", secrets)
+        .register_resolver("mem", |_: &str| Ok(Value::from("unused")))
+        .build()
+        .unwrap();
+
+    assert_eq!(config.get_string("database.password").unwrap(), "hunter2");
+    assert_eq!(config.get_int("database.port").unwrap(), 5432);
+    assert_eq!(
+        config.get_string("database.url").unwrap(),
+        "https://secret://vault/db#password"
+    );
+    assert_eq!(config.get_int("replicas[0]").unwrap(), 5432);
+    assert_eq!(config.get_string("cache").unwrap(), "hunter2");
+}
+
+#[test]
+fn test_resolver_error() {
+    let error = Config::builder()
+        .add_source(File::from_str(
+            r#"{ "database": { "password": "secret://vault/cache" } }"#,
+            FileFormat::Json,
+        ))
+        .register_resolver("secret", |_: &str| Ok(Value::from("stale")))
+        .register_resolver("secret", secrets)
+        .build()
+        .unwrap_err();
+
+    assert_data_eq!(
+        error.to_string(),
+        str![[r#"no secret at "vault/cache" for key `database.password`"#]]
+    );
+}
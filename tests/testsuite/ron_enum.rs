@@ -1,6 +1,6 @@
 #![cfg(feature = "ron")]
 
-use config::{Config, File, FileFormat};
+use config::{Config, File, FileFormat, ParseOptions};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -35,3 +35,78 @@ fn test_ron_enum() {
     let A::VariantA { port } = s.a;
     assert_eq!(port, 5000);
 }
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Mode {
+    Fast,
+    Cluster { size: u8 },
+    Port(u16),
+    Pair(String, bool),
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Tagged {
+    modes: Vec<Mode>,
+    fallback: Option<Mode>,
+}
+
+#[test]
+fn test_ron_enum_tags() {
+    let c = Config::builder()
+        .add_source(
+            File::from_str(
+                r#"
+                Settings(
+                    modes: [
+                        Fast,
+                        Cluster(size: 3),
+                        Port(8080),
+                        Pair("a", true),
+                    ],
+                    fallback: Some(Fast),
+                )
+                "#,
+                FileFormat::Ron,
+            )
+            .parse_options(ParseOptions::default().ron_enum_tags(true)),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(c.get_string("modes[0]").unwrap(), "Fast");
+    assert_eq!(c.get_int("modes[1].Cluster.size").unwrap(), 3);
+
+    let s: Tagged = c.try_deserialize().unwrap();
+    assert_eq!(
+        s,
+        Tagged {
+            modes: vec![
+                Mode::Fast,
+                Mode::Cluster { size: 3 },
+                Mode::Port(8080),
+                Mode::Pair("a".into(), true),
+            ],
+            fallback: Some(Mode::Fast),
+        }
+    );
+}
+
+#[test]
+fn test_ron_enum_tags_root() {
+    let load = |text: &str| {
+        Config::builder()
+            .add_source(
+                File::from_str(text, FileFormat::Ron)
+                    .parse_options(ParseOptions::default().ron_enum_tags(true)),
+            )
+            .build()
+            .unwrap()
+    };
+
+    // The name of the root struct is dropped, whatever its number of fields
+    let c = load("/* settings */ Settings(mode: Port(80))");
+    assert_eq!(c.get_int("mode.Port").unwrap(), 80);
+
+    let c = load("(mode: Fast)");
+    assert_eq!(c.get_string("mode").unwrap(), "Fast");
+}
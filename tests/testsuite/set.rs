@@ -1,4 +1,4 @@
-use config::{Config, File, FileFormat};
+use config::{Config, File, FileFormat, Map};
 
 #[test]
 fn test_set_override_scalar() {
@@ -140,3 +140,130 @@ fn test_set_capital() {
     assert_eq!(config.get::<bool>("ThAt").unwrap(), true);
     assert_eq!(config.get::<usize>("logLevel").unwrap(), 5);
 }
+
+#[test]
+#[cfg(feature = "json")]
+fn test_set_overrides_from_pairs() {
+    let config = Config::builder()
+        .add_source(File::from_str(
+            r#"{"place": {"name": "Torre di Pisa", "rating": 4.5}}"#,
+            FileFormat::Json,
+        ))
+        .with_overrides_from_pairs([
+            ("place.name", "Colosseum"),
+            ("place.tags[0]", "rome"),
+            ("place.tags[1]", "arena"),
+        ])
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(config.get_string("place.name").unwrap(), "Colosseum");
+    assert_eq!(config.get_float("place.rating").unwrap(), 4.5);
+    assert_eq!(
+        config.get::<Vec<String>>("place.tags").unwrap(),
+        vec!["rome".to_owned(), "arena".to_owned()]
+    );
+
+    let res = Config::builder().with_overrides_from_pairs([("place..name", 1)]);
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_from_iter() {
+    let mut pairs = std::collections::HashMap::new();
+    pairs.insert("place.name", config::Value::from("Torre di Pisa"));
+    pairs.insert("place.rating", 4.5.into());
+    pairs.insert("place.tags[0]", "tower".into());
+
+    let config = Config::try_from_iter(pairs).unwrap();
+    assert_eq!(config.get_string("place.name").unwrap(), "Torre di Pisa");
+    assert_eq!(config.get_float("place.rating").unwrap(), 4.5);
+    assert_eq!(config.get_string("place.tags[0]").unwrap(), "tower");
+
+    let config = Config::try_from_iter([("a.b", 1)]).unwrap();
+    assert_eq!(config.get_int("a.b").unwrap(), 1);
+
+    let res = Config::try_from_iter([("a.b", 1), ("a[b", 2)]);
+    assert!(res.is_err());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_set_append() {
+    let config = Config::builder()
+        .add_source(File::from_str(
+            r#"{"plugins": ["auth"], "servers": [{"host": "a"}]}"#,
+            FileFormat::Json,
+        ))
+        .set_default("defaults[+]", 1)
+        .unwrap()
+        .set_default("defaults[+]", 2)
+        .unwrap()
+        .set_override("plugins[+]", "metrics")
+        .unwrap()
+        .set_override("servers[+].host", "b")
+        .unwrap()
+        .set_override("plugins[+]", "tracing")
+        .unwrap()
+        .set_override("servers[0].port", 80)
+        .unwrap()
+        .set_override("empty[+]", true)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let plugins: Vec<String> = config.get("plugins").unwrap();
+    assert_eq!(plugins, ["auth", "metrics", "tracing"]);
+    let defaults: Vec<i32> = config.get("defaults").unwrap();
+    assert_eq!(defaults, [1, 2]);
+    assert_eq!(config.get_string("servers[0].host").unwrap(), "a");
+    assert_eq!(config.get_int("servers[0].port").unwrap(), 80);
+    assert_eq!(config.get_string("servers[1].host").unwrap(), "b");
+    let empty: Vec<bool> = config.get("empty").unwrap();
+    assert_eq!(empty, [true]);
+}
+
+#[test]
+fn test_set_append_separate_elements() {
+    let config = Config::builder()
+        .set_override("servers[+].host", "a")
+        .unwrap()
+        .set_override("servers[+].port", 80)
+        .unwrap()
+        .set_override(
+            "servers[+]",
+            Map::from_iter([("host".to_owned(), "b"), ("port".to_owned(), "81")]),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(config.get_string("servers[0].host").unwrap(), "a");
+    assert!(config.get_int("servers[0].port").is_err());
+    assert_eq!(config.get_int("servers[1].port").unwrap(), 80);
+    assert_eq!(config.get_string("servers[2].host").unwrap(), "b");
+    assert_eq!(config.get_int("servers[2].port").unwrap(), 81);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_set_extend() {
+    let config = Config::builder()
+        .add_source(File::from_str(r#"{"plugins": ["auth"]}"#, FileFormat::Json))
+        .set_override("plugins[++]", vec!["metrics", "tracing"])
+        .unwrap()
+        .set_override("plugins[++]", "audit")
+        .unwrap()
+        .set_override("matrix[+]", vec![1, 2])
+        .unwrap()
+        .set_override("matrix[++]", vec![vec![3]])
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let plugins: Vec<String> = config.get("plugins").unwrap();
+    assert_eq!(plugins, ["auth", "metrics", "tracing", "audit"]);
+    let matrix: Vec<Vec<i32>> = config.get("matrix").unwrap();
+    assert_eq!(matrix, [vec![1, 2], vec![3]]);
+}
@@ -0,0 +1,141 @@
+#![cfg(all(feature = "fs", feature = "json", unix))]
+
+use std::error::Error as _;
+use std::io::Read as _;
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+
+use config::{Config, Sidecar};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("config-sidecar-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn frame(payload: &str) -> Vec<u8> {
+    let mut frame = Vec::new();
+    Sidecar::write_frame(&mut frame, payload.as_bytes()).unwrap();
+    frame
+}
+
+#[test]
+fn test_shared_memory() {
+    let dir = scratch_dir("shm");
+    let path = dir.join("app.config");
+    std::fs::write(&path, frame(r#"{"server": {"port": 8080}}"#)).unwrap();
+
+    let mut config = Config::builder()
+        .add_source(Sidecar::shared_memory(&path))
+        .refreshable(true)
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("server.port").unwrap(), 8080);
+
+    // Bytes after the frame are ignored, as when a shorter configuration overwrites a longer one
+    let mut blob = frame(r#"{"server": {"port": 9090}}"#);
+    blob.extend_from_slice(b"stale");
+    std::fs::write(&path, blob).unwrap();
+    config.refresh_source::<Sidecar>().unwrap();
+    assert_eq!(config.get_int("server.port").unwrap(), 9090);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_socket() {
+    let dir = scratch_dir("socket");
+    let path = dir.join("app.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let sidecar = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 4];
+        stream.read_exact(&mut request).unwrap();
+        assert_eq!(request, [0; 4]);
+        Sidecar::write_frame(&mut stream, br#"{"debug": true}"#).unwrap();
+    });
+
+    let config = Config::builder()
+        .add_source(Sidecar::socket(&path))
+        .build()
+        .unwrap();
+    sidecar.join().unwrap();
+    assert!(config.get_bool("debug").unwrap());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_not_required() {
+    let dir = scratch_dir("missing");
+
+    let config = Config::builder()
+        .add_source(Sidecar::shared_memory(dir.join("app.config")).required(false))
+        .add_source(Sidecar::socket(dir.join("app.sock")).required(false))
+        .build()
+        .unwrap();
+    assert!(config.cache.into_table().unwrap().is_empty());
+
+    let error = Config::builder()
+        .add_source(Sidecar::shared_memory(dir.join("app.config")))
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("could not be read"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_invalid_frame() {
+    let dir = scratch_dir("invalid");
+    let path = dir.join("app.config");
+
+    std::fs::write(&path, frame(r#"{"name": "a long enough value"}"#)).unwrap();
+    let error = Config::builder()
+        .add_source(Sidecar::shared_memory(&path).max_size(8))
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("could not be read"));
+    assert!(
+        error
+            .source()
+            .unwrap()
+            .to_string()
+            .contains("larger than the limit of 8 bytes")
+    );
+
+    std::fs::write(&path, &frame("{}")[..5]).unwrap();
+    let error = Config::builder()
+        .add_source(Sidecar::shared_memory(&path).required(false))
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("could not be read"));
+
+    std::fs::write(&path, frame("[1, 2]")).unwrap();
+    assert!(
+        Config::builder()
+            .add_source(Sidecar::shared_memory(&path))
+            .build()
+            .is_err()
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_async_source() {
+    let dir = scratch_dir("async");
+    let path = dir.join("app.config");
+    std::fs::write(&path, frame(r#"{"workers": 4}"#)).unwrap();
+
+    let config = Config::builder()
+        .add_async_source(Sidecar::shared_memory(&path))
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(config.get_int("workers").unwrap(), 4);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,163 @@
+#![cfg(all(feature = "json", feature = "env"))]
+
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, Environment, File, FileFormat, Map, Source};
+
+fn env(vars: &[(&str, &str)]) -> Environment {
+    Environment::with_prefix("APP").separator("__").source(Some(
+        vars.iter()
+            .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+            .collect::<Map<_, _>>(),
+    ))
+}
+
+#[test]
+fn test_with_prefix() {
+    let c = Config::builder()
+        .add_source(
+            File::from_str(
+                r#"{ "url": "postgres://", "pool": { "size": 4 } }"#,
+                FileFormat::Json,
+            )
+            .with_prefix("services.database"),
+        )
+        .add_source(env(&[("APP__POOL__SIZE", "8")]).with_prefix("services.database"))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        c.get_string("services.database.url").unwrap(),
+        "postgres://"
+    );
+    assert_eq!(c.get_string("services.database.pool.size").unwrap(), "8");
+}
+
+#[test]
+fn test_filter_keys() {
+    let source = env(&[
+        ("APP__DATABASE__URL", "postgres://"),
+        ("APP__DATABASE__PASSWORD", "hunter2"),
+        ("APP__DEBUG", "true"),
+    ])
+    .filter_keys(|key| !key.ends_with("password"));
+    let c = Config::builder().add_source(source).build().unwrap();
+
+    assert_eq!(c.get_string("database.url").unwrap(), "postgres://");
+    assert!(c.get_string("database.password").is_err());
+    assert!(c.get_bool("debug").unwrap());
+
+    let servers = File::from_str(
+        r#"{ "servers": [{ "port": 80 }, { "port": 443 }], "other": 1 }"#,
+        FileFormat::Json,
+    )
+    .filter_keys(|key| key.starts_with("servers["));
+    let c = Config::builder().add_source(servers).build().unwrap();
+    assert_eq!(c.get_int("servers[1].port").unwrap(), 443);
+    assert!(c.get_int("other").is_err());
+}
+
+#[test]
+fn test_map_keys() {
+    let source = File::from_str(
+        r#"{ "db": { "host": "localhost" }, "port": 80 }"#,
+        FileFormat::Json,
+    )
+    .map_keys(|key| key.replacen("db.", "database.", 1))
+    .with_prefix("app");
+    let collected = source.collect().unwrap();
+    assert_eq!(collected.len(), 1);
+
+    let c = Config::builder().add_source(source).build().unwrap();
+    assert_eq!(c.get_string("app.database.host").unwrap(), "localhost");
+    assert_eq!(c.get_int("app.port").unwrap(), 80);
+    assert!(c.get_string("app.db.host").is_err());
+}
+
+#[test]
+fn test_expand_env_vars() {
+    let expand = |value: &str| {
+        Config::builder()
+            .add_source(
+                File::from_str(
+                    &serde_json::json!({ "value": value }).to_string(),
+                    FileFormat::Json,
+                )
+                .expand_env_vars(),
+            )
+            .build()
+            .and_then(|c| c.get_string("value"))
+    };
+
+    temp_env::with_vars(
+        [
+            ("EXPAND_USER", Some("app")),
+            ("EXPAND_EMPTY", Some("")),
+            ("EXPAND_HOME", Some("/home/${EXPAND_USER}")),
+            ("EXPAND_CACHE", Some("$EXPAND_HOME/.cache")),
+            ("EXPAND_PING", Some("${EXPAND_PONG}")),
+            ("EXPAND_PONG", Some("$EXPAND_PING")),
+            ("EXPAND_UNSET", None),
+        ],
+        || {
+            assert_eq!(expand("$EXPAND_USER@host").unwrap(), "app@host");
+            assert_eq!(expand("${EXPAND_USER}_1").unwrap(), "app_1");
+            assert_eq!(expand("[$EXPAND_UNSET]").unwrap(), "[]");
+            assert_eq!(
+                expand("$$EXPAND_USER costs $5").unwrap(),
+                "$EXPAND_USER costs $5"
+            );
+            assert_eq!(
+                expand("${EXPAND_EMPTY:-${EXPAND_UNSET:-fallback}}").unwrap(),
+                "fallback"
+            );
+            assert_eq!(expand("${EXPAND_USER:-fallback}").unwrap(), "app");
+            assert_eq!(
+                expand("${EXPAND_USER:+--user=$EXPAND_USER}").unwrap(),
+                "--user=app"
+            );
+            assert_eq!(expand("${EXPAND_UNSET:+--user}").unwrap(), "");
+            assert_eq!(expand("${EXPAND_USER:?is required}").unwrap(), "app");
+            assert_eq!(expand("$EXPAND_CACHE").unwrap(), "/home/app/.cache");
+
+            assert_data_eq!(
+                expand("${EXPAND_UNSET:?required to connect}")
+                    .unwrap_err()
+                    .to_string(),
+                str!["environment variable `EXPAND_UNSET` is required to connect for key `value`"]
+            );
+            assert_data_eq!(
+                expand("${EXPAND_EMPTY:?}").unwrap_err().to_string(),
+                str!["environment variable `EXPAND_EMPTY` is not set for key `value`"]
+            );
+            assert_data_eq!(
+                expand("$EXPAND_PING").unwrap_err().to_string(),
+                str![
+                    "environment variables reference each other in a cycle: EXPAND_PING -> EXPAND_PONG -> EXPAND_PING for key `value`"
+                ]
+            );
+            assert_data_eq!(
+                expand("${EXPAND_USER").unwrap_err().to_string(),
+                str![[r#"unterminated `${` in "${EXPAND_USER" for key `value`"#]]
+            );
+            assert_data_eq!(
+                expand("${EXPAND_USER-x}").unwrap_err().to_string(),
+                str!["invalid environment variable reference `${EXPAND_USER-x}` for key `value`"]
+            );
+        },
+    );
+
+    let c = Config::builder()
+        .add_source(
+            File::from_str(
+                r#"{ "list": ["$$", 1], "table": { "key": "$" } }"#,
+                FileFormat::Json,
+            )
+            .expand_env_vars(),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(c.get_string("list[0]").unwrap(), "$");
+    assert_eq!(c.get_int("list[1]").unwrap(), 1);
+    assert_eq!(c.get_string("table.key").unwrap(), "$");
+}
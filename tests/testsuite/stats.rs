@@ -0,0 +1,49 @@
+use config::{Config, Map, Value};
+
+#[test]
+fn test_stats() {
+    let config = Config::builder()
+        .set_default("name", "app")
+        .unwrap()
+        .set_default("server.tls.enabled", true)
+        .unwrap()
+        .set_default("server.hosts", vec!["a", "b"])
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let stats = config.stats();
+    assert_eq!(stats.keys(), 5);
+    assert_eq!(stats.depth(), 2);
+    assert!(stats.memory() > 0);
+
+    let empty = Config::default().stats();
+    assert_eq!((empty.keys(), empty.depth()), (0, 0));
+}
+
+#[test]
+fn test_shrink_to_fit() {
+    let mut value = String::with_capacity(256);
+    value.push_str("value");
+    let mut inner = Map::with_capacity(64);
+    inner.insert("key".to_owned(), Value::from(value));
+    let mut list = Vec::with_capacity(64);
+    list.push(Value::from(1));
+    let mut root = Map::with_capacity(64);
+    root.insert("inner".to_owned(), Value::from(inner));
+    root.insert("list".to_owned(), Value::from(list));
+
+    let mut config = Config::default();
+    config.cache = Value::from(root);
+    let before = config.stats();
+
+    config.shrink_to_fit();
+    let after = config.stats();
+    assert!(after.memory() < before.memory());
+    assert_eq!(
+        (after.keys(), after.depth()),
+        (before.keys(), before.depth())
+    );
+    assert_eq!(config.get_string("inner.key").unwrap(), "value");
+    assert_eq!(config.get::<Vec<i64>>("list").unwrap(), [1]);
+}
@@ -0,0 +1,53 @@
+use std::sync::{Arc, Mutex};
+
+use config::{Config, Subscriptions};
+
+fn config(pairs: &[(&str, i64)]) -> Config {
+    Config::builder()
+        .with_overrides_from_pairs(pairs.iter().copied())
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_subscriptions_notify_matching_changes() {
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let mut subscriptions = Subscriptions::new();
+    for pattern in ["server.*", "workers[?]"] {
+        let changes = changes.clone();
+        subscriptions.subscribe(pattern, move |key, old, new| {
+            changes.lock().unwrap().push(format!(
+                "{pattern}: {key} {:?} -> {:?}",
+                old.map(ToString::to_string),
+                new.map(ToString::to_string)
+            ));
+        });
+    }
+
+    subscriptions.update(&config(&[("server.port", 80), ("server.timeout", 5)]));
+    assert!(changes.lock().unwrap().is_empty());
+
+    subscriptions.update(&config(&[
+        ("server.port", 8080),
+        ("server.tls.port", 443),
+        ("workers[0]", 1),
+        ("debug", 1),
+    ]));
+    assert_eq!(
+        *changes.lock().unwrap(),
+        [
+            r#"server.*: server.port Some("80") -> Some("8080")"#,
+            r#"server.*: server.timeout Some("5") -> None"#,
+            r#"server.*: server.tls.port None -> Some("443")"#,
+            r#"workers[?]: workers[0] None -> Some("1")"#,
+        ]
+    );
+
+    changes.lock().unwrap().clear();
+    subscriptions.notify(
+        &config(&[("server.port", 80)]),
+        &config(&[("server.port", 80)]),
+    );
+    assert!(changes.lock().unwrap().is_empty());
+}
@@ -0,0 +1,78 @@
+#![cfg(all(feature = "fs", feature = "toml"))]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use snapbox::{assert_data_eq, str};
+
+use config::{Config, File, Map, TenantConfigFactory};
+
+fn vars(tenant: &str) -> Map<String, String> {
+    [("tenant".to_owned(), tenant.to_owned())]
+        .into_iter()
+        .collect()
+}
+
+#[test]
+fn test_with_name_tpl() {
+    let config = Config::builder()
+        .add_source(
+            File::with_name_tpl("tests/testsuite/tenant-{tenant}.toml", &vars("acme")).unwrap(),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(config.get_int("limits.requests").unwrap(), 500);
+
+    let res = File::with_name_tpl("conf/{tenant}", &vars("../secrets"));
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[
+            r#"invalid file name template "conf/{tenant}": variable "tenant" is not a single path component: "../secrets""#
+        ]]
+    );
+
+    let res = File::with_name_tpl("conf/{region}/{tenant}", &vars("acme"));
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[r#"invalid file name template "conf/{region}/{tenant}": missing variable "region""#]]
+    );
+}
+
+#[test]
+fn test_tenant_config_factory() {
+    let builds = Arc::new(AtomicUsize::new(0));
+    let counter = builds.clone();
+    let tenants = TenantConfigFactory::new(move |tenant| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Config::builder()
+            .set_default("limits.requests", 100)?
+            .add_source(
+                File::with_name_tpl("tests/testsuite/tenant-{tenant}", &vars(tenant))?
+                    .required(false),
+            )
+            .set_override("tenant", tenant)?
+            .build()
+    });
+
+    let acme = tenants.get("acme").unwrap();
+    assert_eq!(acme.get_string("tenant").unwrap(), "acme");
+    assert_eq!(acme.get_int("limits.requests").unwrap(), 500);
+    assert!(Arc::ptr_eq(&acme, &tenants.get("acme").unwrap()));
+    let globex = tenants.get("globex").unwrap();
+    assert_eq!(globex.get_int("limits.requests").unwrap(), 100);
+    assert_eq!(builds.load(Ordering::SeqCst), 2);
+
+    assert!(tenants.get("..").is_err());
+    assert_eq!(builds.load(Ordering::SeqCst), 3);
+    let mut cached = tenants.tenants();
+    cached.sort();
+    assert_eq!(cached, ["acme", "globex"]);
+
+    assert!(tenants.invalidate("acme"));
+    assert!(!tenants.invalidate("acme"));
+    assert!(!Arc::ptr_eq(&acme, &tenants.get("acme").unwrap()));
+    assert_eq!(builds.load(Ordering::SeqCst), 4);
+
+    tenants.invalidate_all();
+    assert!(tenants.tenants().is_empty());
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "test-util")]
+
+use snapbox::{assert_data_eq, str};
+
+use config::Config;
+use config::test_util::{assert_config_eq, assert_config_matches, diff};
+
+#[test]
+fn test_diff() {
+    let actual = Config::builder()
+        .set_default("server.port", "8080")
+        .unwrap()
+        .set_default("server.host", "localhost")
+        .unwrap()
+        .set_default("workers", 4)
+        .unwrap()
+        .build()
+        .unwrap();
+    let expected = Config::builder()
+        .set_default("server.port", 8080)
+        .unwrap()
+        .set_default("server.host", "localhost")
+        .unwrap()
+        .set_default("server.tls", true)
+        .unwrap()
+        .set_default("workers", 4u64)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_data_eq!(
+        diff(&actual, &expected).unwrap(),
+        str![[r#"
+- server.port = 8080
++ server.port = "8080"
+- server.tls = true
+
+"#]]
+    );
+    assert_eq!(diff(&expected, &expected), None);
+    assert_config_eq(&expected, &expected.clone());
+}
+
+#[test]
+fn test_assert_config_matches() {
+    #[derive(Serialize)]
+    struct Settings {
+        workers: u8,
+        tags: Vec<&'static str>,
+    }
+
+    let config = Config::builder()
+        .set_default("workers", 4)
+        .unwrap()
+        .set_default("tags", vec!["a", "b"])
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_config_matches(
+        &config,
+        &Settings {
+            workers: 4,
+            tags: vec!["a", "b"],
+        },
+    );
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        assert_config_matches(
+            &config,
+            &Settings {
+                workers: 8,
+                tags: vec!["a"],
+            },
+        );
+    }));
+    let message = res.unwrap_err();
+    assert_data_eq!(
+        message.downcast_ref::<String>().unwrap().as_str(),
+        str![[r#"
+configs differ (- expected, + actual):
++ tags[1] = "b"
+- workers = 8
++ workers = 4
+
+"#]]
+    );
+}
@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use snapbox::{assert_data_eq, str};
+
+use config::time::parse_duration;
+use config::{Config, File, FileFormat};
+
+#[test]
+fn test_parse_duration() {
+    let cases = [
+        ("30s", Some(Duration::from_secs(30))),
+        ("1h 30m", Some(Duration::from_secs(5400))),
+        ("1.5s", Some(Duration::from_millis(1500))),
+        ("2d12h", Some(Duration::from_secs(216_000))),
+        ("1w", Some(Duration::from_secs(604_800))),
+        ("250ms", Some(Duration::from_millis(250))),
+        ("10us", Some(Duration::from_micros(10))),
+        ("10µs", Some(Duration::from_micros(10))),
+        ("7ns", Some(Duration::from_nanos(7))),
+        ("PT30S", Some(Duration::from_secs(30))),
+        ("PT0.5S", Some(Duration::from_millis(500))),
+        ("P1DT12H", Some(Duration::from_secs(129_600))),
+        ("P2W", Some(Duration::from_secs(1_209_600))),
+        ("PT1H30M", Some(Duration::from_secs(5400))),
+        ("30", None),
+        ("s", None),
+        ("", None),
+        ("P", None),
+        ("P1M", None),
+        ("P1Y", None),
+        ("PT", None),
+        ("1.2.3s", None),
+        ("-1s", None),
+        ("1 fortnight", None),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(parse_duration(text), expected, "{text:?}");
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Timeouts {
+    connect: Duration,
+    idle: Duration,
+    native: Duration,
+}
+
+#[test]
+fn test_duration_fields() {
+    let timeouts: Timeouts = Config::builder()
+        .set_default("connect", "PT5S")
+        .unwrap()
+        .set_default("idle", "2m 30s")
+        .unwrap()
+        .set_default("native.secs", 3)
+        .unwrap()
+        .set_default("native.nanos", 0)
+        .unwrap()
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+
+    assert_eq!(timeouts.connect, Duration::from_secs(5));
+    assert_eq!(timeouts.idle, Duration::from_secs(150));
+    assert_eq!(timeouts.native, Duration::from_secs(3));
+
+    let res = Config::builder()
+        .set_default("connect", "5 seconds")
+        .unwrap()
+        .set_default("idle", "1s")
+        .unwrap()
+        .set_default("native", "1s")
+        .unwrap()
+        .build()
+        .unwrap()
+        .try_deserialize::<Timeouts>();
+    assert_data_eq!(
+        res.unwrap_err().to_string(),
+        str![[
+            r#"invalid type: string "5 seconds", expected a duration like `30s` or `PT30S` for key `connect`"#
+        ]]
+    );
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn test_chrono_dates() {
+    #[derive(Debug, Deserialize)]
+    struct Release {
+        date: NaiveDate,
+        local: NaiveDateTime,
+        published: DateTime<Utc>,
+        updated: DateTime<Utc>,
+    }
+
+    let release: Release = Config::builder()
+        .add_source(File::from_str(
+            r#"
+date = 1979-05-27
+local = 1979-05-27T07:32:00
+published = 1979-05-27T07:32:00Z
+updated = "1979-05-27 08:32:00+01:00"
+"#,
+            FileFormat::Toml,
+        ))
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+
+    assert_eq!(release.date, NaiveDate::from_ymd_opt(1979, 5, 27).unwrap());
+    assert_eq!(release.local, release.date.and_hms_opt(7, 32, 0).unwrap());
+    assert_eq!(release.published, release.local.and_utc());
+    assert_eq!(release.updated, release.published);
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn test_chrono_duration() {
+    #[derive(Debug, Deserialize)]
+    struct Clock {
+        #[serde(deserialize_with = "config::time::chrono_duration")]
+        skew: chrono::TimeDelta,
+        #[serde(deserialize_with = "config::time::chrono_duration")]
+        drift: chrono::TimeDelta,
+    }
+
+    let clock: Clock = Config::builder()
+        .set_default("skew", "-500ms")
+        .unwrap()
+        .set_default("drift", "PT1M")
+        .unwrap()
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+
+    assert_eq!(clock.skew, chrono::TimeDelta::milliseconds(-500));
+    assert_eq!(clock.drift, chrono::TimeDelta::minutes(1));
+}
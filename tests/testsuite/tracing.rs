@@ -0,0 +1,121 @@
+#![cfg(feature = "tracing")]
+
+use std::fmt::{self, Write as _};
+use std::sync::{Arc, Mutex};
+
+use ::tracing::field::{Field, Visit};
+use ::tracing::span::{Attributes, Id, Record};
+use ::tracing::{Event, Metadata, Subscriber};
+
+use config::{Config, Environment, FileFormat, Source};
+
+/// A subscriber recording each event as its message followed by its fields
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Recorder {
+    fn events(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.0.insert_str(0, &format!("{value:?}")),
+            name => write!(self.0, " {name}={value:?}").unwrap(),
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn recorded(build: impl FnOnce()) -> Vec<String> {
+    let recorder = Recorder::default();
+    ::tracing::subscriber::with_default(recorder.clone(), build);
+    recorder.events()
+}
+
+#[test]
+fn test_collected_sources() {
+    let events = recorded(|| {
+        Config::builder()
+            .set_default("port", 80)
+            .unwrap()
+            .add_source(config::File::from_str("port = 8080", FileFormat::Toml))
+            .add_source(config::File::with_name("missing.toml").required(false))
+            .build()
+            .unwrap();
+    });
+
+    assert_eq!(
+        events,
+        [
+            "applying default key=port",
+            "collecting source source=0",
+            "overriding key key=port",
+            "collected source source=0 loaded=[] skipped=[]",
+            "collecting source source=1",
+            "collected source source=1 loaded=[] skipped=[]",
+            "built configuration keys=1",
+        ]
+    );
+}
+
+#[test]
+fn test_failed_source() {
+    let events = recorded(|| {
+        Config::builder()
+            .add_source(config::File::from_str("port = ", FileFormat::Toml))
+            .build()
+            .unwrap_err();
+    });
+
+    assert_eq!(events.len(), 2);
+    assert!(events[1].starts_with("failed to collect source source=0 error="));
+}
+
+#[test]
+fn test_skipped_env_vars() {
+    let env = Environment::with_source_os([
+        ("APP_PORT", "8080"),
+        ("APP_HOST", ""),
+        ("HOME", "/home/user"),
+    ])
+    .prefix("APP")
+    .ignore_empty(true);
+
+    let events = recorded(|| {
+        env.collect().unwrap();
+    });
+
+    assert!(events.contains(&"skipping empty env variable name=\"APP_HOST\"".to_owned()));
+    assert!(events.contains(
+        &"skipping env variable without the prefix name=\"HOME\" prefix=\"app_\"".to_owned()
+    ));
+    assert_eq!(events.len(), 2);
+}
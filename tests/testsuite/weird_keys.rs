@@ -75,3 +75,80 @@ fn test_doublebackslash_key_json() {
     assert_eq!(cfg.foo, 8);
     assert_eq!(cfg.bar, 12);
 }
+
+#[test]
+#[cfg(feature = "json")]
+fn test_typed_map_keys_json() {
+    use std::collections::BTreeMap;
+    use std::net::IpAddr;
+
+    #[derive(Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+    enum Level {
+        Low,
+        High,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Settings {
+        flags: BTreeMap<bool, String>,
+        hosts: BTreeMap<IpAddr, String>,
+        ports: BTreeMap<u16, String>,
+        offsets: BTreeMap<i8, String>,
+        levels: BTreeMap<Level, u32>,
+    }
+
+    let settings: Settings = test_config_as(
+        r#"{
+            "flags": {"true": "on", "no": "off"},
+            "hosts": {"127.0.0.1": "local", "::1": "local6"},
+            "ports": {"8080": "http"},
+            "offsets": {"-3": "back"},
+            "levels": {"Low": 1, "High": 2}
+        }"#,
+        FileFormat::Json,
+    );
+    assert_eq!(settings.flags[&true], "on");
+    assert_eq!(settings.flags[&false], "off");
+    assert_eq!(settings.hosts[&"::1".parse::<IpAddr>().unwrap()], "local6");
+    assert_eq!(settings.ports[&8080], "http");
+    assert_eq!(settings.offsets[&-3], "back");
+    assert_eq!(settings.levels[&Level::High], 2);
+
+    let error = |text: &str| {
+        config::Config::builder()
+            .add_source(File::from_str(text, FileFormat::Json))
+            .build()
+            .unwrap()
+            .try_deserialize::<BTreeMap<String, BTreeMap<String, Settings>>>()
+            .unwrap_err()
+            .to_string()
+    };
+    let settings = |field: &str, key: &str| {
+        format!(
+            r#"{{"a": {{"b": {{"flags": {{}}, "hosts": {{}}, "ports": {{}}, "offsets": {{}}, "levels": {{}}, "{field}": {{"{key}": 1}}}}}}}}"#
+        )
+    };
+    // With `path-to-error`, the path also names the key
+    let assert_error = |field: &str, key: &str, expected: &str| {
+        let error = error(&settings(field, key));
+        assert!(
+            error.starts_with(&format!("{expected} for key `a.b.{field}")),
+            "{error}"
+        );
+    };
+    assert_error(
+        "flags",
+        "maybe",
+        r#"invalid map key "maybe": expected a boolean"#,
+    );
+    assert_error(
+        "hosts",
+        "300.0.0.1",
+        r#"invalid map key "300.0.0.1": invalid IP address syntax"#,
+    );
+    assert_error(
+        "ports",
+        "-1",
+        r#"invalid map key "-1": expected an unsigned 16 bit integer"#,
+    );
+}